@@ -28,6 +28,11 @@ static ARROW_LEFT_LINE_ICON: LazyLock<Handle> = LazyLock::new(|| Handle::from_me
 
 static ARROW_RIGHT_LINE_ICON: LazyLock<Handle> = LazyLock::new(|| Handle::from_memory(include_bytes!("../assets/arrow-right-from-line.svg")));
 
+static UPLOAD_ICON: LazyLock<Handle> = LazyLock::new(|| Handle::from_memory(include_bytes!("../assets/upload.svg")));
+
+static DATABASE_ICON: LazyLock<Handle> = LazyLock::new(|| Handle::from_memory(include_bytes!("../assets/database.svg")));
+
+#[allow(dead_code)]
 static APP_ICON: LazyLock<iced::widget::image::Handle> = LazyLock::new(|| iced::widget::image::Handle::from_bytes(APP_ICON_BUFFER));
 
 
@@ -40,6 +45,14 @@ pub fn file_icon() -> Svg<'static> {
     Svg::new(FILE_ICON.clone())
 }
 
+pub fn upload_icon() -> Svg<'static> {
+    Svg::new(UPLOAD_ICON.clone())
+}
+
+pub fn database_icon() -> Svg<'static> {
+    Svg::new(DATABASE_ICON.clone())
+}
+
 pub fn search_icon() -> Svg<'static> {
     Svg::new(SEARCH_ICON.clone())
 }
@@ -56,11 +69,11 @@ pub fn loading_icon() -> Svg<'static> {
     Svg::new(LOADING_ICON.clone())
 }
 
-pub fn chevron_down_icon() -> Svg<'static> {
+pub fn chevron_down_icon<'a>() -> Svg<'a> {
     Svg::new(CHEVRON_DOWN_ICON.clone())
 }
 
-pub fn chevron_right_icon() -> Svg<'static> {
+pub fn chevron_right_icon<'a>() -> Svg<'a> {
     Svg::new(CHEVRON_RIGHT_ICON.clone())
 }
 
@@ -80,6 +93,7 @@ pub fn arrow_right_line_icon() -> Svg<'static> {
     Svg::new(ARROW_RIGHT_LINE_ICON.clone())
 }
 
+#[allow(dead_code)]
 pub fn app_icon() -> iced::widget::Image {
     iced::widget::Image::new(APP_ICON.clone())
 }
\ No newline at end of file
@@ -0,0 +1,531 @@
+// Autocompletion of request paths and query DSL bodies.
+// Endpoints and DSL keys come from the bundled `spec`, index, alias and field names from the connected cluster.
+use crate::es;
+
+pub mod spec;
+
+const MAX_SUGGESTIONS: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SuggestionKind {
+    Endpoint,
+    Parameter,
+    Index,
+    Alias,
+    Key,
+    Field,
+}
+
+impl SuggestionKind {
+    pub fn as_str(&self) -> &str {
+        match self {
+            SuggestionKind::Endpoint => "endpoint",
+            SuggestionKind::Parameter => "param",
+            SuggestionKind::Index => "index",
+            SuggestionKind::Alias => "alias",
+            SuggestionKind::Key => "key",
+            SuggestionKind::Field => "field",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Suggestion {
+    pub label: String,
+    pub kind: SuggestionKind,
+    // Number of characters right before the cursor that the suggestion replaces
+    pub replace_len: usize,
+    pub insert: String,
+}
+
+impl Suggestion {
+    // Applies the suggestion to text whose cursor is at the end, such as a single line input
+    pub fn apply_to_end(&self, text: &str) -> String {
+        let keep = text.chars().count().saturating_sub(self.replace_len);
+        text.chars().take(keep).collect::<String>() + &self.insert
+    }
+}
+
+/**
+ * Names known about the connected cluster, used on top of the bundled spec
+ */
+#[derive(Debug, Clone, Default)]
+pub struct Completer {
+    pub indicies: Vec<String>,
+    pub aliases: Vec<String>,
+    pub fields: Vec<String>,
+}
+
+// Frame of the JSON being scanned
+enum Frame {
+    Object {
+        // Key whose value is being written
+        key: Option<String>,
+        expecting_key: bool,
+    },
+    Array,
+}
+
+#[derive(Debug, PartialEq)]
+struct BodyContext {
+    // Keys of the objects enclosing the cursor, outermost first. Arrays are skipped.
+    parents: Vec<String>,
+    expecting_key: bool,
+    // Key of the value at the cursor, when a value is expected
+    value_key: Option<String>,
+    partial: String,
+    in_string: bool,
+}
+
+fn is_placeholder(pattern_segment: &str) -> bool {
+    pattern_segment.starts_with('{')
+}
+
+fn segment_matches(pattern_segment: &str, segment: &str) -> bool {
+    if is_placeholder(pattern_segment) {
+        // Names starting with '_' are API endpoints rather than user values
+        !segment.is_empty() && !segment.starts_with('_')
+    } else {
+        pattern_segment == segment
+    }
+}
+
+fn endpoint_allows(endpoint: &spec::Endpoint, method: es::ElasticSearchMethodType) -> bool {
+    endpoint.methods.contains(&method.as_str())
+}
+
+fn starts_with_ignore_case(candidate: &str, partial: &str) -> bool {
+    candidate.to_lowercase().starts_with(&partial.to_lowercase())
+}
+
+// Trailing characters of a bare (unquoted) word being typed
+fn trailing_word(text: &str) -> &str {
+    let start = text
+        .rfind(|c: char| !(c.is_alphanumeric() || "_.-*".contains(c)))
+        .map(|idx| idx + text[idx..].chars().next().map(char::len_utf8).unwrap_or(1))
+        .unwrap_or(0);
+    &text[start..]
+}
+
+fn scan_body(text_before_cursor: &str) -> Option<BodyContext> {
+    let mut frames: Vec<Frame> = Vec::new();
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut string = String::new();
+
+    for c in text_before_cursor.chars() {
+        if in_string {
+            if escaped {
+                escaped = false;
+                string.push(c);
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_string = false;
+                if let Some(Frame::Object { key, expecting_key: true }) = frames.last_mut() {
+                    *key = Some(std::mem::take(&mut string));
+                }
+            } else {
+                string.push(c);
+            }
+            continue;
+        }
+
+        match c {
+            '"' => {
+                in_string = true;
+                string.clear();
+            },
+            '{' => frames.push(Frame::Object { key: None, expecting_key: true }),
+            '[' => frames.push(Frame::Array),
+            '}' | ']' => {
+                frames.pop();
+            },
+            ':' => {
+                if let Some(Frame::Object { expecting_key, .. }) = frames.last_mut() {
+                    *expecting_key = false;
+                }
+            },
+            ',' => {
+                if let Some(Frame::Object { key, expecting_key }) = frames.last_mut() {
+                    *key = None;
+                    *expecting_key = true;
+                }
+            },
+            _ => (),
+        }
+    }
+
+    let (top, enclosing) = frames.split_last()?;
+
+    let parents = enclosing.iter()
+        .filter_map(|frame| match frame {
+            Frame::Object { key, .. } => key.clone(),
+            Frame::Array => None,
+        })
+        .collect::<Vec<String>>();
+
+    let (expecting_key, value_key) = match top {
+        Frame::Object { expecting_key: true, .. } => (true, None),
+        Frame::Object { key, expecting_key: false } => (false, key.clone()),
+        Frame::Array => (false, parents.last().cloned()),
+    };
+
+    let partial = if in_string {
+        string
+    } else {
+        trailing_word(text_before_cursor).to_owned()
+    };
+
+    Some(BodyContext {
+        parents,
+        expecting_key,
+        value_key,
+        partial,
+        in_string,
+    })
+}
+
+impl Completer {
+    pub fn new(indicies: Vec<String>, aliases: Vec<String>, fields: Vec<String>) -> Self {
+        Self { indicies, aliases, fields }
+    }
+
+    /**
+     * Loads index, alias and field names from the cluster. Each list is loaded on its own so one
+     * that fails (e.g. aliases the user may not read) is left empty without losing the others,
+     * what failed is returned along with the completer.
+     */
+    pub async fn load(client: &es::ElasticsearchClient) -> (Self, Vec<String>) {
+        let mut errors = Vec::new();
+
+        let indicies = client.get_indicies().await
+            .map(|indicies| indicies.into_iter().map(|index| index.name).collect())
+            .unwrap_or_else(|err| {
+                errors.push(format!("index names: {}", err));
+                Vec::new()
+            });
+
+        let mut aliases = client.get_aliases().await
+            .map(|aliases| aliases.into_iter().map(|alias| alias.name).collect::<Vec<String>>())
+            .unwrap_or_else(|err| {
+                errors.push(format!("alias names: {}", err));
+                Vec::new()
+            });
+        aliases.sort();
+        aliases.dedup();
+
+        let fields = client.get_field_mappings(&[]).await
+            .map(|fields| fields.into_iter().map(|field| field.name).collect())
+            .unwrap_or_else(|err| {
+                errors.push(format!("field names: {}", err));
+                Vec::new()
+            });
+
+        (Self::new(indicies, aliases, fields), errors)
+    }
+
+    /**
+     * Suggestions for a request path such as "my-index/_sea" or "_cat/indices?fo", with the cursor at the end.
+     * A leading '/' is allowed.
+     */
+    pub fn complete_path(&self, method: es::ElasticSearchMethodType, input: &str) -> Vec<Suggestion> {
+        let path = input.strip_prefix('/').unwrap_or(input);
+
+        if let Some((path, query)) = path.split_once('?') {
+            return self.complete_query_params(method, path, query);
+        }
+
+        let segments = path.split('/').collect::<Vec<&str>>();
+        let Some((partial, done)) = segments.split_last() else {
+            return Vec::new();
+        };
+
+        let mut suggestions: Vec<Suggestion> = Vec::new();
+
+        for endpoint in spec::ENDPOINTS.iter().filter(|endpoint| endpoint_allows(endpoint, method)) {
+            let pattern = endpoint.pattern.split('/').collect::<Vec<&str>>();
+
+            let prefix_matches = pattern.len() > done.len() && pattern.iter()
+                .zip(done.iter())
+                .all(|(pattern_segment, segment)| segment_matches(pattern_segment, segment));
+
+            if !prefix_matches {
+                continue;
+            }
+
+            match pattern[done.len()] {
+                "{index}" => {
+                    // Completes the last name of a comma separated list
+                    let name_partial = partial.rsplit(',').next().unwrap_or(partial);
+                    if name_partial.starts_with('_') {
+                        continue;
+                    }
+                    suggestions.extend(self.complete_names(name_partial));
+                },
+                literal if !is_placeholder(literal) && literal != *partial && starts_with_ignore_case(literal, partial) => {
+                    suggestions.push(Suggestion {
+                        label: literal.to_owned(),
+                        kind: SuggestionKind::Endpoint,
+                        replace_len: partial.chars().count(),
+                        insert: literal.to_owned(),
+                    });
+                },
+                _ => (),
+            }
+        }
+
+        dedup(suggestions)
+    }
+
+    fn complete_query_params(&self, method: es::ElasticSearchMethodType, path: &str, query: &str) -> Vec<Suggestion> {
+        let params = query.split('&').collect::<Vec<&str>>();
+        let Some((partial, used)) = params.split_last() else {
+            return Vec::new();
+        };
+
+        // Values are not completed
+        if partial.contains('=') {
+            return Vec::new();
+        }
+
+        let used = used.iter()
+            .map(|param| param.split('=').next().unwrap_or(param))
+            .collect::<Vec<&str>>();
+
+        let segments = path.split('/').collect::<Vec<&str>>();
+
+        let suggestions = spec::ENDPOINTS.iter()
+            .filter(|endpoint| endpoint_allows(endpoint, method))
+            .filter(|endpoint| {
+                let pattern = endpoint.pattern.split('/').collect::<Vec<&str>>();
+                pattern.len() == segments.len() && pattern.iter()
+                    .zip(segments.iter())
+                    .all(|(pattern_segment, segment)| segment_matches(pattern_segment, segment))
+            })
+            .flat_map(|endpoint| endpoint.params.iter())
+            .filter(|param| !used.contains(param) && starts_with_ignore_case(param, partial))
+            .map(|param| Suggestion {
+                label: param.to_string(),
+                kind: SuggestionKind::Parameter,
+                replace_len: partial.chars().count(),
+                insert: format!("{}=", param),
+            })
+            .collect();
+
+        dedup(suggestions)
+    }
+
+    fn complete_names(&self, partial: &str) -> Vec<Suggestion> {
+        let indicies = self.indicies.iter().map(|name| (name, SuggestionKind::Index));
+        let aliases = self.aliases.iter().map(|name| (name, SuggestionKind::Alias));
+
+        indicies.chain(aliases)
+            // Hidden and system indicies are only offered once asked for
+            .filter(|(name, _)| !name.starts_with('.') || partial.starts_with('.'))
+            .filter(|(name, _)| name.as_str() != partial && starts_with_ignore_case(name, partial))
+            .map(|(name, kind)| Suggestion {
+                label: name.to_owned(),
+                kind,
+                replace_len: partial.chars().count(),
+                insert: name.to_owned(),
+            })
+            .collect()
+    }
+
+    /**
+     * Suggestions for a search request body, given the text from its start up to the cursor.
+     * Offers query DSL keys where a key is expected, and field names where the DSL expects a field.
+     */
+    pub fn complete_body(&self, text_before_cursor: &str) -> Vec<Suggestion> {
+        let Some(context) = scan_body(text_before_cursor) else {
+            return Vec::new();
+        };
+
+        // Only suggest once a key or value has been started, to stay out of the way while writing JSON punctuation
+        if !context.in_string && context.partial.is_empty() {
+            return Vec::new();
+        }
+
+        let (candidates, kind) = if context.expecting_key {
+            key_candidates(&context.parents, &self.fields)
+        } else {
+            value_candidates(context.value_key.as_deref(), &self.fields)
+        };
+
+        let suggestions = candidates.into_iter()
+            .filter(|candidate| starts_with_ignore_case(candidate, &context.partial))
+            .map(|candidate| {
+                let insert = match (context.in_string, context.expecting_key) {
+                    (true, true) => format!("{}\": ", candidate),
+                    (true, false) => format!("{}\"", candidate),
+                    (false, true) => format!("\"{}\": ", candidate),
+                    (false, false) => format!("\"{}\"", candidate),
+                };
+
+                Suggestion {
+                    label: candidate.to_owned(),
+                    kind,
+                    replace_len: context.partial.chars().count(),
+                    insert,
+                }
+            })
+            .collect();
+
+        dedup(suggestions)
+    }
+}
+
+fn lookup<'a>(table: &[(&str, &'a [&'a str])], key: &str) -> Option<&'a [&'a str]> {
+    table.iter().find(|(name, _)| *name == key).map(|(_, keys)| *keys)
+}
+
+fn is_aggregations_key(key: &str) -> bool {
+    key == "aggs" || key == "aggregations"
+}
+
+fn key_candidates<'a>(parents: &[String], fields: &'a [String]) -> (Vec<&'a str>, SuggestionKind) {
+    let from_spec = |keys: &[&'static str]| (keys.to_vec(), SuggestionKind::Key);
+    let field_names = || (fields.iter().map(String::as_str).collect(), SuggestionKind::Field);
+
+    let parent = parents.last().map(String::as_str);
+    let grandparent = parents.len().checked_sub(2).map(|idx| parents[idx].as_str());
+    let great_grandparent = parents.len().checked_sub(3).map(|idx| parents[idx].as_str());
+
+    let Some(parent) = parent else {
+        return from_spec(spec::SEARCH_BODY_KEYS);
+    };
+
+    // {"aggs": {"<name>": {"<type>": {...}}}}
+    if grandparent.is_some_and(is_aggregations_key) {
+        return from_spec(spec::AGGREGATION_TYPES);
+    }
+    if great_grandparent.is_some_and(is_aggregations_key) {
+        return from_spec(lookup(spec::AGGREGATION_KEYS, parent).unwrap_or_default());
+    }
+    if is_aggregations_key(parent) {
+        return (Vec::new(), SuggestionKind::Key);
+    }
+
+    // {"range": {"<field>": {...}}}
+    if let Some(grandparent) = grandparent && spec::FIELD_KEYED_CLAUSES.contains(&grandparent) {
+        return match grandparent {
+            "range" => from_spec(spec::RANGE_KEYS),
+            _ => from_spec(spec::FULL_TEXT_FIELD_KEYS),
+        };
+    }
+
+    if parent == "fields" && grandparent == Some("highlight") {
+        return field_names();
+    }
+    if parent == "sort" || spec::FIELD_KEYED_CLAUSES.contains(&parent) {
+        return field_names();
+    }
+    if spec::QUERY_CONTAINERS.contains(&parent) {
+        return from_spec(spec::QUERY_CLAUSES);
+    }
+
+    from_spec(lookup(spec::OBJECT_KEYS, parent).unwrap_or_default())
+}
+
+fn value_candidates<'a>(value_key: Option<&str>, fields: &'a [String]) -> (Vec<&'a str>, SuggestionKind) {
+    const FIELD_LIST_KEYS: [&str; 5] = ["fields", "_source", "includes", "excludes", "sort"];
+
+    match value_key {
+        Some(key) if spec::FIELD_VALUED_KEYS.contains(&key) || FIELD_LIST_KEYS.contains(&key) => {
+            (fields.iter().map(String::as_str).collect(), SuggestionKind::Field)
+        },
+        _ => (Vec::new(), SuggestionKind::Field),
+    }
+}
+
+fn dedup(suggestions: Vec<Suggestion>) -> Vec<Suggestion> {
+    let mut unique: Vec<Suggestion> = Vec::new();
+
+    for suggestion in suggestions {
+        if unique.len() == MAX_SUGGESTIONS {
+            break;
+        }
+        if !unique.iter().any(|existing| existing.insert == suggestion.insert) {
+            unique.push(suggestion);
+        }
+    }
+
+    unique
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn completer() -> Completer {
+        Completer::new(
+            vec!["logs-2024".to_owned(), "metrics".to_owned(), ".kibana".to_owned()],
+            vec!["logs".to_owned()],
+            vec!["message".to_owned(), "@timestamp".to_owned(), "status".to_owned()],
+        )
+    }
+
+    fn labels(suggestions: &[Suggestion]) -> Vec<&str> {
+        suggestions.iter().map(|suggestion| suggestion.label.as_str()).collect()
+    }
+
+    #[test]
+    fn completes_endpoints() {
+        let suggestions = completer().complete_path(es::ElasticSearchMethodType::GET, "/_cat/ind");
+
+        assert_eq!(labels(&suggestions), vec!["indices"]);
+        assert_eq!(suggestions[0].apply_to_end("/_cat/ind"), "/_cat/indices");
+    }
+
+    #[test]
+    fn completes_index_and_alias_names() {
+        let completer = completer();
+
+        let suggestions = completer.complete_path(es::ElasticSearchMethodType::GET, "metrics,lo");
+        assert_eq!(labels(&suggestions), vec!["logs-2024", "logs"]);
+        assert_eq!(suggestions[1].kind, SuggestionKind::Alias);
+
+        // Hidden indicies only once asked for
+        assert!(!labels(&completer.complete_path(es::ElasticSearchMethodType::GET, "")).contains(&".kibana"));
+        assert_eq!(labels(&completer.complete_path(es::ElasticSearchMethodType::GET, ".k")), vec![".kibana"]);
+    }
+
+    #[test]
+    fn completes_endpoints_allowed_for_the_method() {
+        let completer = completer();
+
+        assert!(labels(&completer.complete_path(es::ElasticSearchMethodType::POST, "logs/_")).contains(&"_update"));
+        assert!(!labels(&completer.complete_path(es::ElasticSearchMethodType::GET, "logs/_")).contains(&"_update"));
+    }
+
+    #[test]
+    fn completes_unused_query_params() {
+        let suggestions = completer().complete_path(es::ElasticSearchMethodType::GET, "logs/_search?size=1&s");
+
+        assert!(!labels(&suggestions).contains(&"size"));
+        assert!(labels(&suggestions).contains(&"sort"));
+        assert_eq!(suggestions[0].insert, format!("{}=", suggestions[0].label));
+    }
+
+    #[test]
+    fn completes_body_keys() {
+        let suggestions = completer().complete_body("{\n  \"qu");
+
+        assert_eq!(labels(&suggestions), vec!["query"]);
+        assert_eq!(suggestions[0].insert, "query\": ");
+    }
+
+    #[test]
+    fn completes_field_names_where_the_dsl_expects_them() {
+        let completer = completer();
+
+        assert_eq!(labels(&completer.complete_body("{\"query\": {\"match\": {\"mes")), vec!["message"]);
+        assert_eq!(labels(&completer.complete_body("{\"aggs\": {\"by_status\": {\"terms\": {\"field\": \"st")), vec!["status"]);
+    }
+
+    #[test]
+    fn stays_quiet_on_punctuation() {
+        assert!(completer().complete_body("{\"query\": ").is_empty());
+    }
+}
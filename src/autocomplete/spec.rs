@@ -0,0 +1,195 @@
+// Bundled description of the Elasticsearch REST API and query DSL used for autocompletion.
+// This is not exhaustive, it covers the endpoints and keys most used day to day.
+//
+// Path patterns use `{index}` for a comma separated list of index, alias or data stream names
+// and `{...}` for any other user provided value.
+
+pub struct Endpoint {
+    pub pattern: &'static str,
+    pub methods: &'static [&'static str],
+    pub params: &'static [&'static str],
+}
+
+const SEARCH_PARAMS: &[&str] = &[
+    "q", "size", "from", "sort", "_source", "_source_includes", "_source_excludes", "track_total_hits",
+    "timeout", "allow_no_indices", "expand_wildcards", "ignore_unavailable", "routing", "preference",
+    "scroll", "search_type", "request_cache", "pretty", "filter_path",
+];
+
+const CAT_PARAMS: &[&str] = &["v", "h", "s", "format", "help", "bytes", "pretty"];
+
+const WRITE_PARAMS: &[&str] = &["refresh", "routing", "timeout", "wait_for_active_shards", "pipeline", "pretty"];
+
+pub const ENDPOINTS: &[Endpoint] = &[
+    Endpoint { pattern: "_search", methods: &["GET", "POST"], params: SEARCH_PARAMS },
+    Endpoint { pattern: "{index}/_search", methods: &["GET", "POST"], params: SEARCH_PARAMS },
+    Endpoint { pattern: "_msearch", methods: &["GET", "POST"], params: &["max_concurrent_searches", "pretty"] },
+    Endpoint { pattern: "{index}/_msearch", methods: &["GET", "POST"], params: &["max_concurrent_searches", "pretty"] },
+    Endpoint { pattern: "_count", methods: &["GET", "POST"], params: &["q", "pretty"] },
+    Endpoint { pattern: "{index}/_count", methods: &["GET", "POST"], params: &["q", "pretty"] },
+    Endpoint { pattern: "_search/scroll", methods: &["GET", "POST", "DELETE"], params: &["scroll", "pretty"] },
+    Endpoint { pattern: "_pit", methods: &["DELETE"], params: &[] },
+    Endpoint { pattern: "{index}/_pit", methods: &["POST"], params: &["keep_alive", "pretty"] },
+    Endpoint { pattern: "{index}/_doc", methods: &["POST"], params: WRITE_PARAMS },
+    Endpoint { pattern: "{index}/_doc/{...}", methods: &["GET", "PUT", "POST", "DELETE"], params: WRITE_PARAMS },
+    Endpoint { pattern: "{index}/_create/{...}", methods: &["PUT", "POST"], params: WRITE_PARAMS },
+    Endpoint { pattern: "{index}/_update/{...}", methods: &["POST"], params: WRITE_PARAMS },
+    Endpoint { pattern: "{index}/_source/{...}", methods: &["GET"], params: &["_source_includes", "_source_excludes", "pretty"] },
+    Endpoint { pattern: "_bulk", methods: &["POST", "PUT"], params: WRITE_PARAMS },
+    Endpoint { pattern: "{index}/_bulk", methods: &["POST", "PUT"], params: WRITE_PARAMS },
+    Endpoint { pattern: "_mget", methods: &["GET", "POST"], params: &["_source", "pretty"] },
+    Endpoint { pattern: "{index}/_mget", methods: &["GET", "POST"], params: &["_source", "pretty"] },
+    Endpoint { pattern: "{index}/_delete_by_query", methods: &["POST"], params: &["conflicts", "refresh", "slices", "wait_for_completion", "pretty"] },
+    Endpoint { pattern: "{index}/_update_by_query", methods: &["POST"], params: &["conflicts", "refresh", "slices", "wait_for_completion", "pretty"] },
+    Endpoint { pattern: "_reindex", methods: &["POST"], params: &["refresh", "slices", "wait_for_completion", "pretty"] },
+    Endpoint { pattern: "{index}", methods: &["GET", "PUT", "DELETE"], params: &["expand_wildcards", "ignore_unavailable", "pretty"] },
+    Endpoint { pattern: "{index}/_mapping", methods: &["GET", "PUT"], params: &["pretty"] },
+    Endpoint { pattern: "_mapping", methods: &["GET"], params: &["pretty"] },
+    Endpoint { pattern: "{index}/_settings", methods: &["GET", "PUT"], params: &["include_defaults", "flat_settings", "pretty"] },
+    Endpoint { pattern: "_settings", methods: &["GET"], params: &["include_defaults", "flat_settings", "pretty"] },
+    Endpoint { pattern: "{index}/_alias", methods: &["GET"], params: &["pretty"] },
+    Endpoint { pattern: "{index}/_alias/{...}", methods: &["GET", "PUT", "DELETE"], params: &["pretty"] },
+    Endpoint { pattern: "_alias", methods: &["GET"], params: &["pretty"] },
+    Endpoint { pattern: "_alias/{...}", methods: &["GET"], params: &["pretty"] },
+    Endpoint { pattern: "_aliases", methods: &["POST"], params: &["pretty"] },
+    Endpoint { pattern: "{index}/_refresh", methods: &["POST", "GET"], params: &["pretty"] },
+    Endpoint { pattern: "{index}/_flush", methods: &["POST", "GET"], params: &["pretty"] },
+    Endpoint { pattern: "{index}/_forcemerge", methods: &["POST"], params: &["max_num_segments", "pretty"] },
+    Endpoint { pattern: "{index}/_open", methods: &["POST"], params: &["pretty"] },
+    Endpoint { pattern: "{index}/_close", methods: &["POST"], params: &["pretty"] },
+    Endpoint { pattern: "{index}/_rollover", methods: &["POST"], params: &["dry_run", "pretty"] },
+    Endpoint { pattern: "{index}/_stats", methods: &["GET"], params: &["level", "pretty"] },
+    Endpoint { pattern: "_stats", methods: &["GET"], params: &["level", "pretty"] },
+    Endpoint { pattern: "{index}/_analyze", methods: &["GET", "POST"], params: &["pretty"] },
+    Endpoint { pattern: "_analyze", methods: &["GET", "POST"], params: &["pretty"] },
+    Endpoint { pattern: "{index}/_validate/query", methods: &["GET", "POST"], params: &["explain", "rewrite", "pretty"] },
+    Endpoint { pattern: "{index}/_explain/{...}", methods: &["GET", "POST"], params: &["pretty"] },
+    Endpoint { pattern: "{index}/_field_caps", methods: &["GET", "POST"], params: &["fields", "pretty"] },
+    Endpoint { pattern: "_field_caps", methods: &["GET", "POST"], params: &["fields", "pretty"] },
+    Endpoint { pattern: "_index_template", methods: &["GET"], params: &["pretty"] },
+    Endpoint { pattern: "_index_template/{...}", methods: &["GET", "PUT", "DELETE"], params: &["pretty"] },
+    Endpoint { pattern: "_component_template", methods: &["GET"], params: &["pretty"] },
+    Endpoint { pattern: "_component_template/{...}", methods: &["GET", "PUT", "DELETE"], params: &["pretty"] },
+    Endpoint { pattern: "_data_stream", methods: &["GET"], params: &["expand_wildcards", "pretty"] },
+    Endpoint { pattern: "_data_stream/{index}", methods: &["GET", "PUT", "DELETE"], params: &["expand_wildcards", "pretty"] },
+    Endpoint { pattern: "_data_stream/{index}/_stats", methods: &["GET"], params: &["pretty"] },
+    Endpoint { pattern: "_ilm/policy", methods: &["GET"], params: &["pretty"] },
+    Endpoint { pattern: "_ilm/policy/{...}", methods: &["GET", "PUT", "DELETE"], params: &["pretty"] },
+    Endpoint { pattern: "{index}/_ilm/explain", methods: &["GET"], params: &["only_errors", "only_managed", "pretty"] },
+    Endpoint { pattern: "_ingest/pipeline", methods: &["GET"], params: &["pretty"] },
+    Endpoint { pattern: "_ingest/pipeline/{...}", methods: &["GET", "PUT", "DELETE"], params: &["pretty"] },
+    Endpoint { pattern: "_ingest/pipeline/_simulate", methods: &["GET", "POST"], params: &["verbose", "pretty"] },
+    Endpoint { pattern: "_cluster/health", methods: &["GET"], params: &["level", "wait_for_status", "timeout", "pretty"] },
+    Endpoint { pattern: "_cluster/health/{index}", methods: &["GET"], params: &["level", "wait_for_status", "timeout", "pretty"] },
+    Endpoint { pattern: "_cluster/state", methods: &["GET"], params: &["filter_path", "pretty"] },
+    Endpoint { pattern: "_cluster/stats", methods: &["GET"], params: &["pretty"] },
+    Endpoint { pattern: "_cluster/settings", methods: &["GET", "PUT"], params: &["include_defaults", "flat_settings", "pretty"] },
+    Endpoint { pattern: "_cluster/allocation/explain", methods: &["GET", "POST"], params: &["include_disk_info", "pretty"] },
+    Endpoint { pattern: "_cluster/pending_tasks", methods: &["GET"], params: &["pretty"] },
+    Endpoint { pattern: "_nodes", methods: &["GET"], params: &["pretty"] },
+    Endpoint { pattern: "_nodes/stats", methods: &["GET"], params: &["pretty"] },
+    Endpoint { pattern: "_nodes/hot_threads", methods: &["GET"], params: &["threads", "interval", "pretty"] },
+    Endpoint { pattern: "_tasks", methods: &["GET"], params: &["actions", "detailed", "pretty"] },
+    Endpoint { pattern: "_tasks/{...}", methods: &["GET"], params: &["pretty"] },
+    Endpoint { pattern: "_tasks/{...}/_cancel", methods: &["POST"], params: &["pretty"] },
+    Endpoint { pattern: "_snapshot", methods: &["GET"], params: &["pretty"] },
+    Endpoint { pattern: "_snapshot/{...}", methods: &["GET", "PUT", "DELETE"], params: &["pretty"] },
+    Endpoint { pattern: "_cat/indices", methods: &["GET"], params: CAT_PARAMS },
+    Endpoint { pattern: "_cat/indices/{index}", methods: &["GET"], params: CAT_PARAMS },
+    Endpoint { pattern: "_cat/aliases", methods: &["GET"], params: CAT_PARAMS },
+    Endpoint { pattern: "_cat/aliases/{...}", methods: &["GET"], params: CAT_PARAMS },
+    Endpoint { pattern: "_cat/count", methods: &["GET"], params: CAT_PARAMS },
+    Endpoint { pattern: "_cat/count/{index}", methods: &["GET"], params: CAT_PARAMS },
+    Endpoint { pattern: "_cat/health", methods: &["GET"], params: CAT_PARAMS },
+    Endpoint { pattern: "_cat/nodes", methods: &["GET"], params: CAT_PARAMS },
+    Endpoint { pattern: "_cat/shards", methods: &["GET"], params: CAT_PARAMS },
+    Endpoint { pattern: "_cat/shards/{index}", methods: &["GET"], params: CAT_PARAMS },
+    Endpoint { pattern: "_cat/allocation", methods: &["GET"], params: CAT_PARAMS },
+    Endpoint { pattern: "_cat/templates", methods: &["GET"], params: CAT_PARAMS },
+    Endpoint { pattern: "_cat/thread_pool", methods: &["GET"], params: CAT_PARAMS },
+    Endpoint { pattern: "_cat/segments", methods: &["GET"], params: CAT_PARAMS },
+    Endpoint { pattern: "_cat/recovery", methods: &["GET"], params: CAT_PARAMS },
+    Endpoint { pattern: "_cat/pending_tasks", methods: &["GET"], params: CAT_PARAMS },
+    Endpoint { pattern: "_cat/tasks", methods: &["GET"], params: CAT_PARAMS },
+];
+
+// Keys of a search request body
+pub const SEARCH_BODY_KEYS: &[&str] = &[
+    "query", "size", "from", "sort", "_source", "fields", "aggs", "aggregations", "highlight",
+    "track_total_hits", "search_after", "pit", "post_filter", "timeout", "min_score", "collapse",
+    "script_fields", "runtime_mappings", "explain", "version", "seq_no_primary_term", "stored_fields",
+    "docvalue_fields", "indices_boost", "rescore", "suggest", "knn",
+];
+
+// Keys that start a query clause
+pub const QUERY_CLAUSES: &[&str] = &[
+    "match_all", "match_none", "match", "match_phrase", "match_phrase_prefix", "multi_match",
+    "query_string", "simple_query_string", "bool", "term", "terms", "terms_set", "range", "exists",
+    "prefix", "wildcard", "regexp", "fuzzy", "ids", "nested", "has_child", "has_parent",
+    "function_score", "constant_score", "dis_max", "boosting", "geo_distance", "geo_bounding_box",
+    "script", "script_score", "more_like_this", "combined_fields", "intervals",
+];
+
+// Keys whose value is a query clause (or a list of them)
+pub const QUERY_CONTAINERS: &[&str] = &[
+    "query", "must", "should", "must_not", "filter", "post_filter", "positive", "negative", "queries",
+];
+
+// Query clauses keyed by field name, e.g. {"match": {"<field>": ...}}
+pub const FIELD_KEYED_CLAUSES: &[&str] = &[
+    "match", "match_phrase", "match_phrase_prefix", "term", "terms", "range", "prefix",
+    "wildcard", "regexp", "fuzzy", "geo_distance", "intervals",
+];
+
+// Keys whose value is a field name, e.g. {"exists": {"field": "<field>"}}
+pub const FIELD_VALUED_KEYS: &[&str] = &["field", "path", "default_field"];
+
+// Keys valid inside a particular object, keyed by the key of the object
+pub const OBJECT_KEYS: &[(&str, &[&str])] = &[
+    ("bool", &["must", "should", "must_not", "filter", "minimum_should_match", "boost"]),
+    ("exists", &["field"]),
+    ("nested", &["path", "query", "score_mode", "inner_hits", "ignore_unmapped"]),
+    ("multi_match", &["query", "fields", "type", "operator", "minimum_should_match", "fuzziness"]),
+    ("query_string", &["query", "default_field", "fields", "default_operator", "analyze_wildcard", "lenient"]),
+    ("simple_query_string", &["query", "fields", "default_operator", "flags"]),
+    ("function_score", &["query", "functions", "score_mode", "boost_mode", "max_boost", "min_score", "field_value_factor", "random_score", "script_score"]),
+    ("constant_score", &["filter", "boost"]),
+    ("dis_max", &["queries", "tie_breaker"]),
+    ("boosting", &["positive", "negative", "negative_boost"]),
+    ("ids", &["values"]),
+    ("highlight", &["fields", "pre_tags", "post_tags", "fragment_size", "number_of_fragments", "type", "require_field_match", "encoder", "order"]),
+    ("_source", &["includes", "excludes"]),
+    ("collapse", &["field", "inner_hits"]),
+    ("pit", &["id", "keep_alive"]),
+];
+
+// Keys of a range clause on a field
+pub const RANGE_KEYS: &[&str] = &["gt", "gte", "lt", "lte", "format", "time_zone", "boost", "relation"];
+
+// Keys of a full text clause on a field, e.g. {"match": {"<field>": {...}}}
+pub const FULL_TEXT_FIELD_KEYS: &[&str] = &["query", "operator", "fuzziness", "analyzer", "boost", "minimum_should_match", "zero_terms_query"];
+
+pub const AGGREGATION_TYPES: &[&str] = &[
+    "terms", "date_histogram", "histogram", "range", "date_range", "filter", "filters", "composite",
+    "avg", "sum", "min", "max", "cardinality", "value_count", "percentiles", "stats", "extended_stats",
+    "top_hits", "nested", "reverse_nested", "significant_terms", "missing", "aggs",
+];
+
+pub const AGGREGATION_KEYS: &[(&str, &[&str])] = &[
+    ("terms", &["field", "size", "order", "min_doc_count", "missing", "include", "exclude"]),
+    ("date_histogram", &["field", "calendar_interval", "fixed_interval", "format", "time_zone", "min_doc_count", "extended_bounds"]),
+    ("histogram", &["field", "interval", "min_doc_count", "extended_bounds"]),
+    ("range", &["field", "ranges", "keyed"]),
+    ("date_range", &["field", "ranges", "format", "keyed"]),
+    ("composite", &["sources", "size", "after"]),
+    ("avg", &["field", "missing"]),
+    ("sum", &["field", "missing"]),
+    ("min", &["field", "missing"]),
+    ("max", &["field", "missing"]),
+    ("cardinality", &["field", "precision_threshold"]),
+    ("value_count", &["field"]),
+    ("percentiles", &["field", "percents", "keyed"]),
+    ("stats", &["field"]),
+    ("extended_stats", &["field"]),
+    ("top_hits", &["size", "sort", "_source"]),
+];
@@ -0,0 +1,366 @@
+use std::io::Read;
+
+use elastic_ermine::{es, export, profile, tail};
+
+use crate::output::{Output, OutputArgs};
+
+#[derive(Debug, clap::Args)]
+pub struct SearchArgs {
+    /// Indices or aliases to search, all indices when none are given
+    #[arg(value_delimiter = ',')]
+    indices: Vec<String>,
+
+    /// Query string, e.g. 'status:error AND service:api'
+    #[arg(long, short = 'q', conflicts_with = "body")]
+    query: Option<String>,
+
+    /// Search body as JSON, @file to read it from a file or - to read it from stdin
+    #[arg(long, short = 'b')]
+    body: Option<String>,
+
+    /// Number of hits to return
+    #[arg(long, short = 'n', default_value_t = 10)]
+    size: u64,
+
+    /// Sort by a field, as field or field:asc or field:desc. Can be repeated
+    #[arg(long, short = 's', value_parser = parse_sort)]
+    sort: Vec<es::Sort>,
+}
+
+#[derive(Debug, clap::Args)]
+pub struct TailArgs {
+    /// Indices or aliases to follow, all indices when none are given
+    #[arg(value_delimiter = ',')]
+    indices: Vec<String>,
+
+    /// Query string, e.g. 'level:error'
+    #[arg(long, short = 'q', conflicts_with = "body")]
+    query: Option<String>,
+
+    /// Search body as JSON, @file to read it from a file or - to read it from stdin. Its sort and size are ignored
+    #[arg(long, short = 'b')]
+    body: Option<String>,
+
+    /// Date field the documents are ordered by
+    #[arg(long, short = 't', default_value = "@timestamp")]
+    timestamp_field: String,
+
+    /// Field ordering documents that share a timestamp, ideally unique per document such as an event id
+    #[arg(long)]
+    tiebreaker: Option<String>,
+
+    /// Number of the latest documents shown before following new ones
+    #[arg(long, short = 'n', default_value_t = 10, value_parser = clap::value_parser!(u64).range(1..))]
+    lines: u64,
+
+    /// Seconds between two polls
+    #[arg(long, short = 'i', default_value_t = 2, value_parser = clap::value_parser!(u64).range(1..))]
+    interval: u64,
+}
+
+#[derive(Debug, clap::Args)]
+pub struct GetArgs {
+    index: String,
+    id: String,
+}
+
+#[derive(Debug, clap::Args)]
+pub struct SwapAliasArgs {
+    alias: String,
+
+    /// Index the alias is moved to, from every index it points to now
+    index: String,
+
+    /// Make the index the write index of the alias
+    #[arg(long)]
+    write_index: bool,
+}
+
+#[derive(Debug, clap::Args)]
+pub struct RequestArgs {
+    /// GET, POST, PUT, PATCH, DELETE or HEAD
+    method: es::ElasticSearchMethodType,
+
+    /// Path of the request, e.g. _cluster/settings
+    path: String,
+
+    /// Request body as JSON or NDJSON, @file to read it from a file or - to read it from stdin
+    #[arg(long, short = 'b')]
+    body: Option<String>,
+}
+
+/**
+ * Value of options taking a body: the value itself, @file for the contents of a file or - for stdin
+ */
+pub fn read_body_arg(arg: &str) -> Result<String, Box<dyn std::error::Error>> {
+    if arg == "-" {
+        let mut body = String::new();
+        std::io::stdin().read_to_string(&mut body)?;
+        Ok(body)
+    } else if let Some(path) = arg.strip_prefix('@') {
+        std::fs::read_to_string(path)
+            .map_err(|err| format!("Unable to read {}: {}", path, err).into())
+    } else {
+        Ok(arg.to_owned())
+    }
+}
+
+// A single JSON value, or one value per line as used by _bulk and _msearch
+fn parse_bodies(body: &str) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error>> {
+    if let Ok(value) = serde_json::from_str::<serde_json::Value>(body) {
+        return Ok(vec![value]);
+    }
+
+    body.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(idx, line)| {
+            serde_json::from_str::<serde_json::Value>(line)
+                .map_err(|err| format!("Invalid JSON on line {} of the body: {}", idx + 1, err).into())
+        })
+        .collect()
+}
+
+fn parse_sort(sort: &str) -> Result<es::Sort, String> {
+    match sort.rsplit_once(':') {
+        None => Ok(es::Sort::asc(sort)),
+        Some((field, "asc")) => Ok(es::Sort::asc(field)),
+        Some((field, "desc")) => Ok(es::Sort::desc(field)),
+        Some((field, order)) => Err(format!("Invalid sort order {} of {}, expected asc or desc", order, field)),
+    }
+}
+
+// Index and id are percent-encoded, ids may contain characters such as / or ?
+fn document_path(index: &str, id: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let mut url = reqwest::Url::parse("http://localhost/")?;
+    url.path_segments_mut()
+        .map_err(|_| "Unable to build the document path")?
+        .pop_if_empty()
+        .extend([index, "_doc", id]);
+
+    Ok(url.path().trim_start_matches('/').to_owned())
+}
+
+fn operation_output(res: es::OperationResult) -> Output {
+    match res {
+        es::OperationResult::Json(value) => Output::Document(value),
+        es::OperationResult::Text(text) => Output::Text(text),
+    }
+}
+
+pub fn profiles(out: &OutputArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let profiles = profile::load_profiles()?;
+
+    if profiles.is_empty() {
+        let path = profile::profiles_file().map(|path| path.to_string_lossy().to_string()).unwrap_or_default();
+        eprintln!("No profiles defined in {}", path);
+    }
+
+    let records = profiles.into_iter()
+        .map(|(name, profile)| serde_json::json!({ "name": name, "url": profile.url }))
+        .collect();
+
+    out.print(Output::Records { records, columns: vec!["name".to_owned(), "url".to_owned()] })
+}
+
+pub async fn indices(client: &es::ElasticsearchClient, out: &OutputArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let mut indices = client.get_indicies().await?;
+    indices.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let records = indices.into_iter()
+        .map(|index| serde_json::json!({
+            "name": index.name,
+            "uuid": index.uuid,
+            "docs": index.docs_count,
+            "deleted_docs": index.docs_deleted_count,
+            "size": index.dataset_size,
+            "primary_shards": index.primary_shard_count,
+            "replica_shards": index.replica_shard_count,
+        }))
+        .collect();
+
+    let columns = ["name", "uuid", "docs", "deleted_docs", "size", "primary_shards", "replica_shards"]
+        .map(str::to_owned)
+        .to_vec();
+
+    out.print(Output::Records { records, columns })
+}
+
+pub async fn aliases(client: &es::ElasticsearchClient, out: &OutputArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let mut aliases = client.get_aliases().await?;
+    aliases.sort_by(|a, b| a.name.cmp(&b.name).then(a.index_ref.cmp(&b.index_ref)));
+
+    let records = aliases.into_iter()
+        .map(|alias| serde_json::json!({ "alias": alias.name, "index": alias.index_ref }))
+        .collect();
+
+    out.print(Output::Records { records, columns: vec!["alias".to_owned(), "index".to_owned()] })
+}
+
+pub async fn data_streams(client: &es::ElasticsearchClient, out: &OutputArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let mut data_streams = client.get_data_streams().await?;
+    data_streams.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let stats = client.get_data_stream_stats().await?;
+
+    let records = data_streams.into_iter()
+        .map(|data_stream| {
+            let size = stats.iter()
+                .find(|stats| stats.data_stream == data_stream.name)
+                .map(|stats| stats.store_size_bytes);
+
+            serde_json::json!({
+                "name": data_stream.name,
+                "generation": data_stream.generation,
+                "status": data_stream.status,
+                "template": data_stream.template,
+                "ilm_policy": data_stream.ilm_policy,
+                "backing_indices": data_stream.indices.len(),
+                "write_index": data_stream.write_index().map(|index| &index.index_name),
+                "size_bytes": size,
+            })
+        })
+        .collect();
+
+    let columns = ["name", "generation", "status", "template", "ilm_policy", "backing_indices", "write_index", "size_bytes"]
+        .map(str::to_owned)
+        .to_vec();
+
+    out.print(Output::Records { records, columns })
+}
+
+// Removes the alias from its indices and adds it to the new one in a single atomic request
+pub async fn swap_alias(client: &es::ElasticsearchClient, args: SwapAliasArgs, out: &OutputArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let current = client.get_index_aliases().await?;
+
+    let actions = es::AliasAction::swap(&args.alias, &current, args.index, args.write_index)?;
+    client.update_aliases(&actions).await?;
+
+    let records = actions.iter()
+        .map(|action| serde_json::json!({ "action": action.name(), "index": action.index(), "alias": action.alias() }))
+        .collect();
+
+    out.print(Output::Records { records, columns: vec!["action".to_owned(), "index".to_owned(), "alias".to_owned()] })
+}
+
+pub async fn search(client: &es::ElasticsearchClient, args: SearchArgs, out: &OutputArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let mut body = match (&args.query, &args.body) {
+        (Some(query), _) => es::SearchBody::new().query(es::Query::query_string(query)).to_json(),
+        (None, Some(body)) => serde_json::from_str::<serde_json::Value>(&read_body_arg(body)?)
+            .map_err(|err| format!("Invalid search body: {}", err))?,
+        (None, None) => serde_json::json!({}),
+    };
+
+    let Some(body_object) = body.as_object_mut() else {
+        return Err("Search body must be a JSON object".into());
+    };
+
+    body_object.entry("size").or_insert(args.size.into());
+    if !args.sort.is_empty() {
+        body_object.insert("sort".to_owned(), serde_json::to_value(&args.sort)?);
+    }
+
+    let res = client.search(&args.indices, Some(&body)).await?;
+
+    eprintln!("{} hits in {} ms", res.hits.hits.len(), res.time_took_ms);
+
+    // Metadata columns first, then every field of the returned documents
+    let mut columns = ["_index", "_id", "_score"].map(str::to_owned).to_vec();
+    let fields = res.hits.hits.iter()
+        .filter_map(|hit| hit.get("_source"))
+        .flat_map(export::leaf_paths)
+        .collect::<std::collections::BTreeSet<String>>();
+    columns.extend(fields);
+
+    out.print(Output::Records { records: res.hits.hits, columns })
+}
+
+/**
+ * Prints the latest documents then polls for new ones until interrupted
+ */
+pub async fn tail(client: &es::ElasticsearchClient, args: TailArgs, out: &OutputArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let body = match (&args.query, &args.body) {
+        (Some(query), _) => Some(es::SearchBody::new().query(es::Query::query_string(query)).to_json()),
+        (None, Some(body)) => Some(
+            serde_json::from_str::<serde_json::Value>(&read_body_arg(body)?)
+                .map_err(|err| format!("Invalid search body: {}", err))?
+        ),
+        (None, None) => None,
+    };
+
+    let mut tail = tail::Tail::new(args.indices, body, &args.timestamp_field)?;
+    if let Some(tiebreaker) = args.tiebreaker {
+        tail = tail.tiebreaker(tiebreaker);
+    }
+
+    // Many documents can arrive between two polls, they are printed a page at a time
+    const MAX_PER_POLL: usize = 1000;
+
+    let mut stream = out.stream();
+    let mut max = args.lines as usize;
+
+    loop {
+        let res = tail.poll(client, max).await?;
+
+        // Timestamp first, then every field of the documents
+        let mut columns = vec![args.timestamp_field.clone()];
+        let fields = res.hits.hits.iter()
+            .filter_map(|hit| hit.get("_source"))
+            .flat_map(export::leaf_paths)
+            .filter(|field| *field != args.timestamp_field)
+            .collect::<std::collections::BTreeSet<String>>();
+        columns.extend(fields);
+
+        let full = res.hits.hits.len() == max;
+        stream.print(res.hits.hits, columns)?;
+
+        max = MAX_PER_POLL;
+        if !full {
+            tokio::time::sleep(std::time::Duration::from_secs(args.interval)).await;
+        }
+    }
+}
+
+pub async fn get(client: &es::ElasticsearchClient, args: GetArgs, out: &OutputArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let path = document_path(&args.index, &args.id)?;
+    let res = client.operation(es::ElasticSearchMethodType::GET, &path, None).await?;
+    out.print(operation_output(res))
+}
+
+pub async fn request(client: &es::ElasticsearchClient, args: RequestArgs, out: &OutputArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let bodies = match &args.body {
+        Some(body) => parse_bodies(&read_body_arg(body)?)?,
+        None => Vec::new(),
+    };
+
+    let (status, res) = match bodies.as_slice() {
+        [] => client.operation_with_status(args.method, &args.path, None).await?,
+        [body] => client.operation_with_status(args.method, &args.path, Some(body)).await?,
+        bodies => client.operation_ndjson_with_status(args.method, &args.path, bodies).await?,
+    };
+
+    // The error response is printed like any other, the exit code tells it failed
+    out.print(operation_output(res.clone()))?;
+
+    if !(200..300).contains(&status) {
+        return Err(es::OperationStatusError { status, response: res }.into());
+    }
+
+    Ok(())
+}
+
+pub async fn health(client: &es::ElasticsearchClient, out: &OutputArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let res = client.operation(es::ElasticSearchMethodType::GET, "_cluster/health", None).await?;
+    out.print(operation_output(res))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_the_index_and_id_of_the_document_path() {
+        assert_eq!(document_path("logs", "1").unwrap(), "logs/_doc/1");
+        assert_eq!(document_path("logs", "a/b?c#d e%").unwrap(), "logs/_doc/a%2Fb%3Fc%23d%20e%25");
+    }
+}
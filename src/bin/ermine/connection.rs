@@ -0,0 +1,59 @@
+use elastic_ermine::{es, profile};
+
+// Options shared by every command. Each falls back to an environment variable,
+// and both take precedence over the settings of the selected profile.
+#[derive(Debug, clap::Args)]
+#[command(next_help_heading = "Connection options")]
+pub struct ConnectionArgs {
+    /// Connection profile from profiles.json
+    #[arg(long, short = 'p', env = "ES_PROFILE", global = true)]
+    profile: Option<String>,
+
+    /// Cluster url, e.g. http://localhost:9200
+    #[arg(long, env = "ES_URL", global = true)]
+    url: Option<String>,
+
+    /// Username for basic auth
+    #[arg(long, short = 'u', env = "ES_USER", global = true)]
+    user: Option<String>,
+
+    /// Password for basic auth
+    #[arg(long, env = "ES_PASSWORD", hide_env_values = true, global = true)]
+    password: Option<String>,
+
+    /// AWS region, signs requests with AWS SigV4
+    #[arg(long, env = "ES_AWS_REGION", global = true, conflicts_with = "user")]
+    aws_region: Option<String>,
+
+    /// AWS profile to take credentials from, the default credential chain is used otherwise
+    #[arg(long, env = "ES_AWS_PROFILE", global = true)]
+    aws_profile: Option<String>,
+
+    /// PEM certificate of the certificate authority of the cluster
+    #[arg(long, env = "ES_CACERT", global = true)]
+    cacert: Option<std::path::PathBuf>,
+}
+
+impl ConnectionArgs {
+    pub fn client(&self) -> Result<es::ElasticsearchClient, Box<dyn std::error::Error>> {
+        let base = match &self.profile {
+            Some(name) => profile::load_profile(name)?,
+            None => Default::default(),
+        };
+
+        let profile = base.merged_with(profile::Profile {
+            url: self.url.clone(),
+            username: self.user.clone(),
+            password: self.password.clone(),
+            aws_region: self.aws_region.clone(),
+            aws_profile: self.aws_profile.clone(),
+            cacert: self.cacert.clone(),
+        });
+
+        if profile.url.is_none() {
+            return Err(crate::UsageError("Missing cluster url, set --url, ES_URL or a --profile with a url".to_owned()).into());
+        }
+
+        profile.client()
+    }
+}
@@ -0,0 +1,150 @@
+use clap::{Parser, Subcommand};
+use elastic_ermine::es;
+
+mod commands;
+mod connection;
+mod output;
+mod repl;
+mod runbook;
+mod transfer;
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "ermine",
+    version,
+    about = "Query and manage Elasticsearch clusters from the command line",
+    after_help = "Exit codes:\n  0  success\n  1  other errors\n  2  invalid arguments\n  3  unable to connect to the cluster\n  4  authentication or authorization refused by the cluster\n  5  request rejected by the cluster, e.g. an invalid query or a missing index",
+)]
+struct Cli {
+    #[command(flatten)]
+    connection: connection::ConnectionArgs,
+
+    #[command(flatten)]
+    output: output::OutputArgs,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// List indices
+    Indices,
+    /// List aliases and the indices they point to
+    Aliases,
+    /// Move an alias onto another index atomically, e.g. after reindexing into it
+    SwapAlias(commands::SwapAliasArgs),
+    /// List data streams with their generation, template, lifecycle policy and size
+    DataStreams,
+    /// Search documents with a query string or a search body
+    Search(commands::SearchArgs),
+    /// Follow the documents matching a query as they are indexed, like tail -f
+    Tail(commands::TailArgs),
+    /// Get a document by id
+    Get(commands::GetArgs),
+    /// Send any request to the cluster, e.g. `ermine request GET _cat/shards`
+    Request(commands::RequestArgs),
+    /// Show the health of the cluster
+    Health,
+    /// Export every document matching a query as CSV, NDJSON or a JSON array
+    Export(transfer::ExportArgs),
+    /// Import documents from an NDJSON, JSON or CSV file
+    Import(transfer::ImportArgs),
+    /// Interactive console taking the Dev Tools request syntax, e.g. `GET _cat/indices`
+    Console,
+    /// Run a file of Dev Tools requests, checking the `# assert` lines of the file against the responses
+    Run(runbook::RunArgs),
+    /// List the connection profiles of profiles.json
+    Profiles,
+}
+
+fn main() {
+    let cli = Cli::parse();
+
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap_or_else(|e| {
+            eprintln!("Failed to create runtime: {}", e);
+            std::process::exit(1);
+        });
+
+    if let Err(err) = rt.block_on(run(cli)) {
+        // Output piped into a command that stopped reading, e.g. head
+        if is_broken_pipe(err.as_ref()) {
+            return;
+        }
+
+        eprintln!("error: {}", es::report(err.as_ref()));
+        std::process::exit(exit_code(err.as_ref()));
+    }
+}
+
+// Arguments found to be invalid once parsed, exiting as clap's own argument errors do
+#[derive(Debug)]
+pub struct UsageError(pub String);
+
+impl std::fmt::Display for UsageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for UsageError {}
+
+fn is_broken_pipe(err: &(dyn std::error::Error + 'static)) -> bool {
+    let io_err = err.downcast_ref::<std::io::Error>().or_else(|| match err.downcast_ref::<csv::Error>()?.kind() {
+        csv::ErrorKind::Io(err) => Some(err),
+        _ => None,
+    });
+
+    io_err.is_some_and(|err| err.kind() == std::io::ErrorKind::BrokenPipe)
+}
+
+// Lets scripts tell apart an unreachable cluster, refused credentials and a rejected request
+fn exit_code(err: &(dyn std::error::Error + 'static)) -> i32 {
+    if err.is::<UsageError>() {
+        return 2;
+    }
+
+    if let Some(err) = err.downcast_ref::<es::OperationStatusError>() {
+        return if matches!(err.status, 401 | 403) { 4 } else { 5 };
+    }
+
+    let Some(err) = err.downcast_ref::<reqwest::Error>() else {
+        return 1;
+    };
+
+    match err.status().map(|status| status.as_u16()) {
+        Some(401 | 403) => 4,
+        Some(_) => 5,
+        None if err.is_connect() || err.is_timeout() || err.is_request() => 3,
+        None => 1,
+    }
+}
+
+async fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
+    // The only command that does not talk to a cluster
+    if let Command::Profiles = cli.command {
+        return commands::profiles(&cli.output);
+    }
+
+    let client = cli.connection.client()?;
+
+    match cli.command {
+        Command::Indices => commands::indices(&client, &cli.output).await,
+        Command::Aliases => commands::aliases(&client, &cli.output).await,
+        Command::SwapAlias(args) => commands::swap_alias(&client, args, &cli.output).await,
+        Command::DataStreams => commands::data_streams(&client, &cli.output).await,
+        Command::Search(args) => commands::search(&client, args, &cli.output).await,
+        Command::Tail(args) => commands::tail(&client, args, &cli.output).await,
+        Command::Get(args) => commands::get(&client, args, &cli.output).await,
+        Command::Request(args) => commands::request(&client, args, &cli.output).await,
+        Command::Health => commands::health(&client, &cli.output).await,
+        Command::Export(args) => transfer::export(client, args).await,
+        Command::Import(args) => transfer::import(client, args).await,
+        Command::Console => repl::run(&client).await,
+        Command::Run(args) => runbook::run(&client, args).await,
+        Command::Profiles => unreachable!("handled before connecting"),
+    }
+}
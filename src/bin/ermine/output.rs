@@ -0,0 +1,283 @@
+use std::io::Write;
+
+use elastic_ermine::export;
+
+// Longer table cells are cut short, the other formats always give full values
+const MAX_TABLE_CELL_CHARS: usize = 80;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    // Aligned columns for humans
+    #[default]
+    Table,
+    Json,
+    Ndjson,
+    Csv,
+    Yaml,
+}
+
+#[derive(Debug, clap::Args)]
+#[command(next_help_heading = "Output options")]
+pub struct OutputArgs {
+    /// Output format
+    #[arg(long, short = 'o', value_enum, default_value_t, global = true)]
+    output: OutputFormat,
+
+    /// Fields to output as dotted paths, e.g. _id,user.name. Paths that are not found are looked up in _source
+    #[arg(long, value_delimiter = ',', global = true)]
+    fields: Vec<String>,
+}
+
+pub enum Output {
+    // Rows of a listing, along with the columns shown when no fields are selected
+    Records {
+        records: Vec<serde_json::Value>,
+        columns: Vec<String>,
+    },
+    // Single JSON response, shown as field and value lines in tables
+    Document(serde_json::Value),
+    // Responses that are not JSON are printed as they are
+    Text(String),
+}
+
+// Value at a dotted path of a record, falling back to the _source of documents
+fn record_value(record: &serde_json::Value, path: &str) -> Option<serde_json::Value> {
+    export::field_value(record, path)
+        .or_else(|| record.get("_source").and_then(|source| export::field_value(source, path)))
+}
+
+/**
+ * Dotted paths of the values of a record, with the fields of _source listed without their "_source." prefix
+ * after the metadata fields
+ */
+fn record_paths(record: &serde_json::Value) -> Vec<String> {
+    let mut metadata = record.clone();
+    let source = metadata.as_object_mut().and_then(|record| record.remove("_source"));
+
+    let mut paths = export::leaf_paths(&metadata).into_iter().collect::<Vec<String>>();
+    if let Some(source) = source {
+        paths.extend(export::leaf_paths(&source));
+    }
+    paths
+}
+
+fn cell_text(value: Option<&serde_json::Value>) -> String {
+    match value {
+        None | Some(serde_json::Value::Null) => String::new(),
+        Some(serde_json::Value::String(string)) => string.to_owned(),
+        Some(other) => other.to_string(),
+    }
+}
+
+fn table_cell(value: Option<&serde_json::Value>) -> String {
+    let text = cell_text(value).replace(['\n', '\t'], " ");
+
+    match text.chars().count() {
+        0 => "-".to_owned(),
+        count if count > MAX_TABLE_CELL_CHARS => text.chars().take(MAX_TABLE_CELL_CHARS - 1).collect::<String>() + "…",
+        _ => text,
+    }
+}
+
+fn column_widths(rows: &[Vec<String>]) -> Vec<usize> {
+    rows.iter().fold(Vec::<usize>::new(), |mut widths, row| {
+        widths.resize(widths.len().max(row.len()), 0);
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+        widths
+    })
+}
+
+fn print_table_row(out: &mut impl Write, row: &[String], widths: &[usize]) -> std::io::Result<()> {
+    let line = row.iter()
+        .zip(widths)
+        .map(|(cell, width)| format!("{:width$}", cell, width = *width))
+        .collect::<Vec<String>>()
+        .join("  ");
+    writeln!(out, "{}", line.trim_end())
+}
+
+fn print_table(out: &mut impl Write, rows: &[Vec<String>]) -> std::io::Result<()> {
+    let widths = column_widths(rows);
+    for row in rows {
+        print_table_row(out, row, &widths)?;
+    }
+    Ok(())
+}
+
+// Record reduced to the selected fields, keyed by their paths
+fn project(record: &serde_json::Value, fields: &[String]) -> serde_json::Value {
+    serde_json::Value::Object(
+        fields.iter()
+            .map(|field| (field.to_owned(), record_value(record, field).unwrap_or(serde_json::Value::Null)))
+            .collect()
+    )
+}
+
+fn print_csv(out: &mut impl Write, records: &[serde_json::Value], columns: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut writer = csv::Writer::from_writer(out);
+
+    writer.write_record(columns)?;
+    for record in records {
+        writer.write_record(columns.iter().map(|column| cell_text(record_value(record, column).as_ref())))?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+impl OutputArgs {
+    pub fn stream(&self) -> RecordStream<'_> {
+        RecordStream { args: self, columns: None, widths: Vec::new(), csv: None }
+    }
+
+    pub fn print(&self, output: Output) -> Result<(), Box<dyn std::error::Error>> {
+        let mut out = std::io::stdout().lock();
+
+        let (records, columns, single) = match output {
+            Output::Text(text) => {
+                write!(out, "{}", text)?;
+                return Ok(());
+            },
+            Output::Records { records, columns } => (records, columns, false),
+            Output::Document(document) => {
+                let columns = record_paths(&document);
+                (vec![document], columns, true)
+            },
+        };
+
+        let columns = if self.fields.is_empty() { columns } else { self.fields.clone() };
+
+        let records = if self.fields.is_empty() {
+            records
+        } else {
+            records.iter().map(|record| project(record, &self.fields)).collect()
+        };
+
+        match self.output {
+            OutputFormat::Table if single => {
+                let Some(record) = records.first() else {
+                    return Ok(());
+                };
+
+                let rows = columns.iter()
+                    .map(|column| vec![column.to_owned(), table_cell(record_value(record, column).as_ref())])
+                    .collect::<Vec<Vec<String>>>();
+                print_table(&mut out, &rows)?;
+            },
+            OutputFormat::Table => {
+                let rows = std::iter::once(columns.clone())
+                    .chain(records.iter().map(|record| {
+                        columns.iter()
+                            .map(|column| table_cell(record_value(record, column).as_ref()))
+                            .collect()
+                    }))
+                    .collect::<Vec<Vec<String>>>();
+                print_table(&mut out, &rows)?;
+            },
+            OutputFormat::Json => {
+                let value = match (single, records.first()) {
+                    (true, Some(record)) => record.clone(),
+                    _ => serde_json::Value::Array(records),
+                };
+                writeln!(out, "{}", serde_json::to_string_pretty(&value)?)?;
+            },
+            OutputFormat::Ndjson => {
+                for record in &records {
+                    writeln!(out, "{}", serde_json::to_string(record)?)?;
+                }
+            },
+            OutputFormat::Csv => print_csv(&mut out, &records, &columns)?,
+            OutputFormat::Yaml => {
+                let value = match (single, records.first()) {
+                    (true, Some(record)) => record.clone(),
+                    _ => serde_json::Value::Array(records),
+                };
+                write!(out, "{}", serde_yaml::to_string(&value)?)?;
+            },
+        }
+
+        Ok(())
+    }
+}
+
+/**
+ * Prints records as they arrive, such as the documents followed by `tail`.
+ * Columns and table widths are decided by the first records and headers printed once.
+ * JSON is printed one record per line, so that each can be read as soon as it is printed.
+ */
+pub struct RecordStream<'a> {
+    args: &'a OutputArgs,
+    columns: Option<Vec<String>>,
+    widths: Vec<usize>,
+    csv: Option<csv::Writer<std::io::Stdout>>,
+}
+
+impl RecordStream<'_> {
+    pub fn print(&mut self, records: Vec<serde_json::Value>, columns: Vec<String>) -> Result<(), Box<dyn std::error::Error>> {
+        if records.is_empty() {
+            return Ok(());
+        }
+
+        let first = self.columns.is_none();
+        let columns = self.columns
+            .get_or_insert_with(|| if self.args.fields.is_empty() { columns } else { self.args.fields.clone() })
+            .clone();
+
+        let records = if self.args.fields.is_empty() {
+            records
+        } else {
+            records.iter().map(|record| project(record, &self.args.fields)).collect()
+        };
+
+        let mut out = std::io::stdout().lock();
+
+        match self.args.output {
+            OutputFormat::Table => {
+                let rows = records.iter()
+                    .map(|record| {
+                        columns.iter()
+                            .map(|column| table_cell(record_value(record, column).as_ref()))
+                            .collect()
+                    })
+                    .collect::<Vec<Vec<String>>>();
+
+                if first {
+                    let header = std::iter::once(columns.clone()).chain(rows.iter().cloned()).collect::<Vec<Vec<String>>>();
+                    self.widths = column_widths(&header);
+                    print_table_row(&mut out, &columns, &self.widths)?;
+                }
+                for row in &rows {
+                    print_table_row(&mut out, row, &self.widths)?;
+                }
+            },
+            OutputFormat::Json | OutputFormat::Ndjson => {
+                for record in &records {
+                    writeln!(out, "{}", serde_json::to_string(record)?)?;
+                }
+            },
+            OutputFormat::Csv => {
+                let writer = match &mut self.csv {
+                    Some(writer) => writer,
+                    None => {
+                        let mut writer = csv::Writer::from_writer(std::io::stdout());
+                        writer.write_record(&columns)?;
+                        self.csv.insert(writer)
+                    },
+                };
+                for record in &records {
+                    writer.write_record(columns.iter().map(|column| cell_text(record_value(record, column).as_ref())))?;
+                }
+                writer.flush()?;
+            },
+            OutputFormat::Yaml => {
+                for record in &records {
+                    write!(out, "---\n{}", serde_yaml::to_string(record)?)?;
+                }
+            },
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,297 @@
+// Interactive console taking the Dev Tools request syntax, for use where the GUI is not available, e.g. over SSH.
+//
+// A request line without a body is sent on Enter. POST, PUT and PATCH requests continue on the next lines
+// until their body is closed, NDJSON requests (_bulk, _msearch) and requests without a body end with an empty line.
+// Alt+Enter starts a new line, e.g. to give a GET request a body.
+use std::borrow::Cow;
+use std::io::{IsTerminal, Write};
+
+use rustyline::completion::Pair;
+use rustyline::error::ReadlineError;
+use rustyline::validate::{ValidationContext, ValidationResult};
+
+use elastic_ermine::{autocomplete, console, es, util};
+
+const METHODS: [&str; 6] = ["GET", "POST", "PUT", "PATCH", "DELETE", "HEAD"];
+const MAX_HISTORY: usize = 1000;
+
+type Editor = rustyline::Editor<ConsoleHelper, rustyline::history::DefaultHistory>;
+
+const HELP: &str = "\
+Requests use the Dev Tools syntax:
+
+  GET _cat/indices?v
+
+  POST my-index/_search
+  {
+    \"query\": { \"match\": { \"message\": \"error\" } }
+  }
+
+Enter sends a request once its body is complete, an empty line ends bodies of NDJSON requests
+such as _bulk. Alt+Enter starts a new line. Tab completes endpoints, index names and query keys.
+Type exit or press Ctrl+D to quit.";
+
+fn history_file() -> Option<std::path::PathBuf> {
+    util::app_data_dir().map(|dir| dir.join("console_history.txt"))
+}
+
+fn is_ndjson_path(path: &str) -> bool {
+    let path = path.split('?').next().unwrap_or(path);
+    path.split('/').any(|segment| matches!(segment, "_bulk" | "_msearch" | "_msearch_template"))
+}
+
+struct ConsoleHelper {
+    completer: autocomplete::Completer,
+    colored: bool,
+}
+
+impl rustyline::completion::Completer for ConsoleHelper {
+    type Candidate = Pair;
+
+    fn complete(&self, line: &str, pos: usize, _ctx: &rustyline::Context<'_>) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let before_cursor = &line[..pos];
+
+        let suggestions = match before_cursor.split_once('\n') {
+            Some((_, body)) => self.completer.complete_body(body),
+            None => match before_cursor.trim_start().split_once(char::is_whitespace) {
+                Some((method, path)) => match method.parse::<es::ElasticSearchMethodType>() {
+                    Ok(method) => self.completer.complete_path(method, path.trim_start()),
+                    Err(_) => Vec::new(),
+                },
+                None => {
+                    let partial = before_cursor.trim_start();
+                    let methods = METHODS.iter()
+                        .filter(|method| method.starts_with(&partial.to_uppercase()))
+                        .map(|method| Pair { display: method.to_string(), replacement: format!("{} ", method) })
+                        .collect();
+                    return Ok((pos - partial.len(), methods));
+                },
+            },
+        };
+
+        // All suggestions of a completion replace the same text
+        let Some(replace_len) = suggestions.first().map(|suggestion| suggestion.replace_len) else {
+            return Ok((pos, Vec::new()));
+        };
+
+        let replaced_bytes = before_cursor.chars()
+            .rev()
+            .take(replace_len)
+            .map(char::len_utf8)
+            .sum::<usize>();
+
+        let candidates = suggestions.into_iter()
+            .filter(|suggestion| suggestion.replace_len == replace_len)
+            .map(|suggestion| Pair { display: suggestion.label, replacement: suggestion.insert })
+            .collect();
+
+        Ok((pos - replaced_bytes, candidates))
+    }
+}
+
+impl rustyline::hint::Hinter for ConsoleHelper {
+    type Hint = String;
+}
+
+impl rustyline::highlight::Highlighter for ConsoleHelper {
+    fn highlight_prompt<'b, 's: 'b, 'p: 'b>(&'s self, prompt: &'p str, default: bool) -> Cow<'b, str> {
+        if self.colored && default {
+            Cow::Owned(format!("\x1b[1;32m{}\x1b[0m", prompt))
+        } else {
+            Cow::Borrowed(prompt)
+        }
+    }
+}
+
+impl rustyline::validate::Validator for ConsoleHelper {
+    fn validate(&self, ctx: &mut ValidationContext) -> rustyline::Result<ValidationResult> {
+        let input = ctx.input();
+        let request_line = input.lines().next().unwrap_or_default().trim();
+
+        let Some((method, path)) = request_line.split_once(char::is_whitespace) else {
+            return Ok(ValidationResult::Valid(None));
+        };
+
+        // Parse errors are reported once the input is sent
+        let Ok(method) = method.parse::<es::ElasticSearchMethodType>() else {
+            return Ok(ValidationResult::Valid(None));
+        };
+
+        if console::ends_inside_body(input) {
+            return Ok(ValidationResult::Incomplete);
+        }
+
+        let complete = if input.ends_with('\n') {
+            true
+        } else if !input.contains('\n') {
+            matches!(method, es::ElasticSearchMethodType::GET | es::ElasticSearchMethodType::DELETE | es::ElasticSearchMethodType::HEAD)
+        } else {
+            !is_ndjson_path(path)
+        };
+
+        Ok(if complete { ValidationResult::Valid(None) } else { ValidationResult::Incomplete })
+    }
+}
+
+impl rustyline::Helper for ConsoleHelper {}
+
+// Pretty printed JSON with ANSI colors for keys, strings, numbers and literals
+fn write_colored_json(value: &serde_json::Value, indent: usize, out: &mut String) {
+    let pad = "  ".repeat(indent + 1);
+
+    match value {
+        serde_json::Value::Object(map) if !map.is_empty() => {
+            out.push_str("{\n");
+            for (idx, (key, value)) in map.iter().enumerate() {
+                out.push_str(&format!("{}\x1b[34m{}\x1b[0m: ", pad, serde_json::Value::String(key.to_owned())));
+                write_colored_json(value, indent + 1, out);
+                out.push_str(if idx + 1 < map.len() { ",\n" } else { "\n" });
+            }
+            out.push_str(&"  ".repeat(indent));
+            out.push('}');
+        },
+        serde_json::Value::Array(values) if !values.is_empty() => {
+            out.push_str("[\n");
+            for (idx, value) in values.iter().enumerate() {
+                out.push_str(&pad);
+                write_colored_json(value, indent + 1, out);
+                out.push_str(if idx + 1 < values.len() { ",\n" } else { "\n" });
+            }
+            out.push_str(&"  ".repeat(indent));
+            out.push(']');
+        },
+        serde_json::Value::String(_) => out.push_str(&format!("\x1b[32m{}\x1b[0m", value)),
+        serde_json::Value::Number(_) => out.push_str(&format!("\x1b[33m{}\x1b[0m", value)),
+        serde_json::Value::Bool(_) | serde_json::Value::Null => out.push_str(&format!("\x1b[35m{}\x1b[0m", value)),
+        // Empty objects and arrays
+        _ => out.push_str(&value.to_string()),
+    }
+}
+
+fn print_response(res: es::OperationResult, colored: bool) -> std::io::Result<()> {
+    let mut stdout = std::io::stdout().lock();
+
+    match res {
+        es::OperationResult::Json(value) if colored => {
+            let mut out = String::new();
+            write_colored_json(&value, 0, &mut out);
+            writeln!(stdout, "{}", out)
+        },
+        es::OperationResult::Json(value) => {
+            writeln!(stdout, "{}", serde_json::to_string_pretty(&value).unwrap_or_default())
+        },
+        es::OperationResult::Text(text) => {
+            write!(stdout, "{}", text)?;
+            if !text.ends_with('\n') {
+                writeln!(stdout)?;
+            }
+            Ok(())
+        },
+    }
+}
+
+// Fails only when the response can not be written out
+async fn send(client: &es::ElasticsearchClient, request: &console::ConsoleRequest, colored: bool) -> std::io::Result<()> {
+    let start = std::time::Instant::now();
+
+    let res = match request.bodies.as_slice() {
+        [] => client.operation_with_status(request.method, &request.path, None).await,
+        [body] => client.operation_with_status(request.method, &request.path, Some(body)).await,
+        bodies => client.operation_ndjson_with_status(request.method, &request.path, bodies).await,
+    };
+
+    match res {
+        Ok((status, res)) => {
+            eprintln!("{} {} - {} in {} ms", request.method, request.path, status, start.elapsed().as_millis());
+            print_response(res, colored)
+        },
+        Err(err) => {
+            eprintln!("error: {}", es::report(err.as_ref()));
+            Ok(())
+        },
+    }
+}
+
+// Index, alias and field names of the cluster, completion falls back to the bundled spec when unavailable
+pub async fn run(client: &es::ElasticsearchClient) -> Result<(), Box<dyn std::error::Error>> {
+    let colored = std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+
+    let (completer, errors) = autocomplete::Completer::load(client).await;
+    for err in errors {
+        eprintln!("Unable to load {} for completion", err);
+    }
+
+    let config = rustyline::Config::builder()
+        .max_history_size(MAX_HISTORY)?
+        .history_ignore_dups(true)?
+        .auto_add_history(false)
+        .completion_type(rustyline::CompletionType::List)
+        .build();
+
+    let mut editor = Editor::with_config(config)?;
+    editor.set_helper(Some(ConsoleHelper { completer, colored }));
+    editor.bind_sequence(
+        rustyline::KeyEvent(rustyline::KeyCode::Enter, rustyline::Modifiers::ALT),
+        rustyline::EventHandler::Simple(rustyline::Cmd::Newline),
+    );
+
+    let history = history_file();
+    if let Some(history) = &history && history.exists() && let Err(err) = editor.load_history(history) {
+        // Moved aside rather than overwritten on exit, so that it can still be recovered
+        let corrupt = history.with_extension("txt.corrupt");
+        eprintln!("Unable to load the history from {}, starting an empty history: {}", history.to_string_lossy(), err);
+        if let Err(err) = std::fs::rename(history, &corrupt) {
+            eprintln!("Unable to move {} aside: {}", history.to_string_lossy(), err);
+        }
+    }
+
+    eprintln!("Type help for the request syntax, exit or Ctrl+D to quit");
+
+    let res = read_eval(&mut editor, client, colored).await;
+
+    // Also saved when the console fails, e.g. once stdout is closed
+    if let Some(history) = &history && let Err(err) = save_history(&mut editor, history) {
+        eprintln!("Unable to save the history to {}: {}", history.to_string_lossy(), err);
+    }
+
+    res
+}
+
+fn save_history(editor: &mut Editor, history: &std::path::Path) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(dir) = history.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    editor.save_history(history)?;
+    Ok(())
+}
+
+// Reads and sends requests until exit or end of input
+async fn read_eval(editor: &mut Editor, client: &es::ElasticsearchClient, colored: bool) -> Result<(), Box<dyn std::error::Error>> {
+    loop {
+        let input = match editor.readline("es> ") {
+            Ok(input) => input,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => return Ok(()),
+            Err(err) => return Err(err.into()),
+        };
+
+        match input.trim() {
+            "" => continue,
+            "exit" | "quit" => return Ok(()),
+            "help" => {
+                writeln!(std::io::stdout().lock(), "{}", HELP)?;
+                continue;
+            },
+            _ => (),
+        }
+
+        editor.add_history_entry(input.trim_end())?;
+
+        for request in console::parse(&input) {
+            match request {
+                Ok(request) => send(client, &request, colored).await?,
+                Err(err) => eprintln!("error: {}", err),
+            }
+        }
+    }
+}
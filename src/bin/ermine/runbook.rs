@@ -0,0 +1,441 @@
+// Runs files of Dev Tools requests, such as runbooks or smoke tests.
+//
+// `{{name}}` is replaced by the value of a --var, or of the environment variable of that name.
+// Comment lines of the form `# assert <path> <op> <value>` check the response of the request above them, e.g.
+//
+//   GET _cluster/health
+//   # assert status == green
+//   # assert number_of_nodes >= 3
+//
+// Paths are dotted, numbers index into arrays (hits.hits.0._id). Values are JSON, or text when not valid JSON.
+use std::io::Write;
+
+use elastic_ermine::{console, es, export};
+
+#[derive(Debug, clap::Args)]
+pub struct RunArgs {
+    /// File of requests in the Dev Tools syntax
+    file: std::path::PathBuf,
+
+    /// Value of a {{name}} variable, as name=value. Can be repeated, the environment is used otherwise
+    #[arg(long = "var", short = 'v', value_parser = parse_var)]
+    vars: Vec<(String, String)>,
+
+    /// Run the remaining requests after a request or assertion fails
+    #[arg(long)]
+    continue_on_error: bool,
+
+    /// Print the response of each request
+    #[arg(long)]
+    show_responses: bool,
+}
+
+fn parse_var(var: &str) -> Result<(String, String), String> {
+    var.split_once('=')
+        .map(|(name, value)| (name.trim().to_owned(), value.to_owned()))
+        .filter(|(name, _)| !name.is_empty())
+        .ok_or_else(|| format!("Invalid variable {}, expected name=value", var))
+}
+
+fn var_value(name: &str, vars: &[(String, String)]) -> Result<String, String> {
+    vars.iter()
+        .rev()
+        .find(|(var, _)| var == name)
+        .map(|(_, value)| value.to_owned())
+        .or_else(|| std::env::var(name).ok())
+        .ok_or_else(|| format!("No value for variable {}, use --var {}=... or set it in the environment", name, name))
+}
+
+// Position within the JSON of a body, following the console syntax
+#[derive(Default)]
+struct BodyState {
+    in_string: bool,
+    in_triple_quote: bool,
+    escaped: bool,
+    depth: i64,
+}
+
+impl BodyState {
+    fn at_top_level(&self) -> bool {
+        !self.in_string && !self.in_triple_quote && self.depth <= 0
+    }
+
+    /**
+     * Replaces the variables of a line of the body, escaped when within a JSON string.
+     * Triple quoted strings are escaped by the console parser itself, and trailing comments are left as they are.
+     */
+    fn substitute_line(&mut self, line: &str, vars: &[(String, String)], out: &mut String) -> Result<(), String> {
+        let mut rest = line;
+
+        while let Some(c) = rest.chars().next() {
+            if let Some(var) = rest.strip_prefix("{{") && let Some(len) = var.find("}}") {
+                let value = var_value(var[..len].trim(), vars)?;
+                if self.in_string {
+                    let quoted = serde_json::Value::String(value).to_string();
+                    out.push_str(&quoted[1..quoted.len() - 1]);
+                } else {
+                    out.push_str(&value);
+                }
+                rest = &var[len + 2..];
+                continue;
+            }
+
+            if rest.starts_with("\"\"\"") && !self.in_string {
+                self.in_triple_quote = !self.in_triple_quote;
+                out.push_str("\"\"\"");
+                rest = &rest[3..];
+                continue;
+            }
+
+            if self.in_string {
+                if self.escaped {
+                    self.escaped = false;
+                } else if c == '\\' {
+                    self.escaped = true;
+                } else if c == '"' {
+                    self.in_string = false;
+                }
+            } else if !self.in_triple_quote {
+                match c {
+                    '#' => break,
+                    '/' if rest.starts_with("//") => break,
+                    '"' => self.in_string = true,
+                    '{' | '[' => self.depth += 1,
+                    '}' | ']' => self.depth -= 1,
+                    _ => (),
+                }
+            }
+
+            out.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+
+        out.push_str(rest);
+        Ok(())
+    }
+}
+
+fn is_request_line(line: &str) -> bool {
+    line.split_whitespace()
+        .next()
+        .is_some_and(|method| method.parse::<es::ElasticSearchMethodType>().is_ok())
+}
+
+/**
+ * Replaces every {{name}} of the request lines and bodies, failing on the first variable without a value.
+ * Comments, such as assertions, are left as they are.
+ */
+fn substitute_vars(text: &str, vars: &[(String, String)]) -> Result<String, String> {
+    let mut out = String::with_capacity(text.len());
+    let mut body = BodyState::default();
+
+    for line in text.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+
+        if body.at_top_level() && (trimmed.starts_with('#') || trimmed.starts_with("//")) {
+            out.push_str(line);
+        } else if body.at_top_level() && is_request_line(line) {
+            // Values are inserted into paths as they are
+            BodyState::default().substitute_line(line, vars, &mut out)?;
+            body = BodyState::default();
+        } else {
+            body.substitute_line(line, vars, &mut out)?;
+        }
+    }
+
+    Ok(out)
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Operator {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Operator {
+    // Longer operators first so that <= is not read as <
+    const ALL: [(&str, Operator); 6] = [
+        ("==", Operator::Eq),
+        ("!=", Operator::Ne),
+        ("<=", Operator::Le),
+        (">=", Operator::Ge),
+        ("<", Operator::Lt),
+        (">", Operator::Gt),
+    ];
+}
+
+#[derive(Debug)]
+struct Assertion {
+    // Zero based line of the file
+    line: usize,
+    text: String,
+    path: String,
+    operator: Operator,
+    expected: serde_json::Value,
+}
+
+fn assertion_text(line: &str) -> Option<&str> {
+    let trimmed = line.trim_start();
+    trimmed.strip_prefix('#')
+        .or_else(|| trimmed.strip_prefix("//"))
+        .and_then(|comment| comment.trim_start().strip_prefix("assert "))
+        .map(str::trim)
+}
+
+fn parse_assertion(line: usize, text: &str) -> Result<Assertion, String> {
+    let (idx, symbol, operator) = Operator::ALL.iter()
+        .filter_map(|(symbol, operator)| text.find(symbol).map(|idx| (idx, *symbol, *operator)))
+        .min_by_key(|(idx, _, _)| *idx)
+        .ok_or_else(|| format!("line {}: missing operator in assertion {}, expected one of == != < <= > >=", line + 1, text))?;
+
+    let path = text[..idx].trim();
+    let expected = text[idx + symbol.len()..].trim();
+
+    if path.is_empty() || expected.is_empty() {
+        return Err(format!("line {}: invalid assertion {}, expected <path> {} <value>", line + 1, text, symbol));
+    }
+
+    Ok(Assertion {
+        line,
+        text: text.to_owned(),
+        path: path.to_owned(),
+        operator,
+        expected: serde_json::from_str(expected).unwrap_or_else(|_| serde_json::Value::String(expected.to_owned())),
+    })
+}
+
+fn response_value(response: &serde_json::Value, path: &str) -> Option<serde_json::Value> {
+    let pointer = path.split('.')
+        .map(|segment| format!("/{}", segment.replace('~', "~0").replace('/', "~1")))
+        .collect::<String>();
+
+    response.pointer(&pointer)
+        .cloned()
+        .or_else(|| export::field_value(response, path))
+}
+
+// Error message when the assertion does not hold
+fn check(assertion: &Assertion, response: &es::OperationResult) -> Result<(), String> {
+    let es::OperationResult::Json(response) = response else {
+        return Err("response is not JSON".to_owned());
+    };
+
+    let Some(actual) = response_value(response, &assertion.path) else {
+        return Err(format!("{} not found in the response", assertion.path));
+    };
+
+    let holds = match assertion.operator {
+        Operator::Eq => actual == assertion.expected,
+        Operator::Ne => actual != assertion.expected,
+        operator => {
+            let (Some(actual), Some(expected)) = (actual.as_f64(), assertion.expected.as_f64()) else {
+                return Err(format!("{} is {}, which can not be compared to {}", assertion.path, actual, assertion.expected));
+            };
+
+            match operator {
+                Operator::Lt => actual < expected,
+                Operator::Le => actual <= expected,
+                Operator::Gt => actual > expected,
+                _ => actual >= expected,
+            }
+        },
+    };
+
+    if holds {
+        Ok(())
+    } else {
+        Err(format!("{} is {}", assertion.path, actual))
+    }
+}
+
+async fn send(
+    client: &es::ElasticsearchClient,
+    request: &console::ConsoleRequest
+) -> Result<(u16, es::OperationResult), Box<dyn std::error::Error>> {
+    match request.bodies.as_slice() {
+        [] => client.operation_with_status(request.method, &request.path, None).await,
+        [body] => client.operation_with_status(request.method, &request.path, Some(body)).await,
+        bodies => client.operation_ndjson_with_status(request.method, &request.path, bodies).await,
+    }
+}
+
+pub async fn run(client: &es::ElasticsearchClient, args: RunArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let contents = std::fs::read_to_string(&args.file)
+        .map_err(|err| format!("Unable to read {}: {}", args.file.to_string_lossy(), err))?;
+    let contents = substitute_vars(&contents, &args.vars)?;
+
+    // Nothing is sent unless the whole file is valid
+    let requests = console::parse(&contents).into_iter()
+        .collect::<Result<Vec<console::ConsoleRequest>, console::ParseError>>()
+        .map_err(|err| format!("{}: {}", args.file.to_string_lossy(), err))?;
+
+    let assertions = contents.lines()
+        .enumerate()
+        .filter_map(|(line, text)| assertion_text(text).map(|text| parse_assertion(line, text)))
+        .collect::<Result<Vec<Assertion>, String>>()?;
+
+    if let Some(orphan) = assertions.iter().find(|assertion| requests.first().is_none_or(|first| assertion.line < first.start_line)) {
+        return Err(format!("line {}: assertion {} is not preceded by a request", orphan.line + 1, orphan.text).into());
+    }
+
+    let mut out = std::io::stdout().lock();
+    let mut failed = 0;
+
+    for (idx, request) in requests.iter().enumerate() {
+        let next_start = requests.get(idx + 1).map(|next| next.start_line).unwrap_or(usize::MAX);
+        let request_assertions = assertions.iter()
+            .filter(|assertion| assertion.line > request.start_line && assertion.line < next_start);
+
+        let start = std::time::Instant::now();
+        let res = send(client, request).await;
+
+        let (status, response) = match res {
+            Ok(res) => res,
+            Err(err) => {
+                failed += 1;
+                // Reported once, by the caller, when stopping here
+                if !args.continue_on_error {
+                    writeln!(out, "FAILED {} {}", request.method, request.path)?;
+                    return Err(err);
+                }
+                writeln!(out, "FAILED {} {}: {}", request.method, request.path, es::report(err.as_ref()))?;
+                continue;
+            },
+        };
+
+        let request_assertions = request_assertions.collect::<Vec<&Assertion>>();
+        let failures = request_assertions.iter()
+            .filter_map(|assertion| check(assertion, &response).err().map(|err| (*assertion, err)))
+            .collect::<Vec<(&Assertion, String)>>();
+
+        // Requests expected to fail are checked by their assertions, e.g. # assert error.type == index_not_found_exception
+        let error_status = !(200..300).contains(&status) && request_assertions.is_empty();
+
+        let outcome = if failures.is_empty() && !error_status { "ok" } else { "FAILED" };
+        writeln!(out, "{} {} {} ({}, {} ms)", outcome, request.method, request.path, status, start.elapsed().as_millis())?;
+
+        for (assertion, err) in &failures {
+            writeln!(out, "  line {}: assert {}: {}", assertion.line + 1, assertion.text, err)?;
+        }
+
+        if args.show_responses {
+            match &response {
+                es::OperationResult::Json(value) => writeln!(out, "{}", serde_json::to_string_pretty(value)?)?,
+                es::OperationResult::Text(text) => writeln!(out, "{}", text.trim_end())?,
+            }
+        }
+
+        if error_status {
+            failed += 1;
+            if !args.continue_on_error {
+                return Err(format!("{} {} returned HTTP status {}", request.method, request.path, status).into());
+            }
+        } else if !failures.is_empty() {
+            failed += 1;
+            if !args.continue_on_error {
+                return Err(format!("Assertion failed on line {}", failures[0].0.line + 1).into());
+            }
+        }
+    }
+
+    if failed > 0 {
+        return Err(format!("{} of {} requests failed", failed, requests.len()).into());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+        vars.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+    }
+
+    #[test]
+    fn substitutes_request_lines_and_bodies() {
+        let text = "GET {{index}}/_search\n{\n  \"size\": {{size}},\n  \"query\": { \"term\": { \"user\": \"{{user}}\" } }\n}\n";
+        let out = substitute_vars(text, &vars(&[("index", "logs"), ("size", "10"), ("user", "kim")])).unwrap();
+
+        assert_eq!(out, "GET logs/_search\n{\n  \"size\": 10,\n  \"query\": { \"term\": { \"user\": \"kim\" } }\n}\n");
+    }
+
+    #[test]
+    fn escapes_values_within_strings() {
+        let text = "POST logs/_doc\n{ \"message\": \"{{message}}\" }\n";
+        let out = substitute_vars(text, &vars(&[("message", "say \"hi\"\\n")])).unwrap();
+
+        assert_eq!(out, "POST logs/_doc\n{ \"message\": \"say \\\"hi\\\"\\\\n\" }\n");
+        let requests = console::parse(&out);
+        assert_eq!(requests[0].as_ref().unwrap().bodies[0]["message"], "say \"hi\"\\n");
+    }
+
+    #[test]
+    fn leaves_triple_quoted_values_to_the_parser() {
+        let text = "POST logs/_doc\n{ \"message\": \"\"\"{{message}}\"\"\" }\n";
+        let out = substitute_vars(text, &vars(&[("message", "say \"hi\"")])).unwrap();
+
+        assert_eq!(out, "POST logs/_doc\n{ \"message\": \"\"\"say \"hi\"\"\"\" }\n");
+    }
+
+    #[test]
+    fn leaves_comments_alone() {
+        let text = "# uses {{undefined}}\nGET _cluster/health // {{undefined}}\n# assert status == {{undefined}}\n";
+
+        assert_eq!(substitute_vars(text, &[]).unwrap(), text);
+    }
+
+    #[test]
+    fn fails_on_missing_variable() {
+        let err = substitute_vars("GET {{ermine_test_undefined}}/_search\n", &[]).unwrap_err();
+
+        assert!(err.contains("ermine_test_undefined"), "{}", err);
+    }
+
+    #[test]
+    fn later_vars_take_precedence() {
+        let out = substitute_vars("GET {{index}}\n", &vars(&[("index", "a"), ("index", "b")])).unwrap();
+
+        assert_eq!(out, "GET b\n");
+    }
+
+    #[test]
+    fn parses_assertions() {
+        let assertion = parse_assertion(3, "hits.total.value >= 10").unwrap();
+        assert_eq!(assertion.line, 3);
+        assert_eq!(assertion.path, "hits.total.value");
+        assert!(matches!(assertion.operator, Operator::Ge));
+        assert_eq!(assertion.expected, serde_json::json!(10));
+
+        // Values that are not JSON are taken as text
+        let assertion = parse_assertion(0, "status == green").unwrap();
+        assert!(matches!(assertion.operator, Operator::Eq));
+        assert_eq!(assertion.expected, serde_json::json!("green"));
+
+        let assertion = parse_assertion(0, "timed_out != true").unwrap();
+        assert!(matches!(assertion.operator, Operator::Ne));
+        assert_eq!(assertion.expected, serde_json::json!(true));
+
+        let assertion = parse_assertion(0, "took < 100").unwrap();
+        assert!(matches!(assertion.operator, Operator::Lt));
+    }
+
+    #[test]
+    fn rejects_invalid_assertions() {
+        assert!(parse_assertion(0, "status green").is_err());
+        assert!(parse_assertion(0, "== green").is_err());
+        assert!(parse_assertion(0, "status ==").is_err());
+    }
+
+    #[test]
+    fn finds_assertion_comments() {
+        assert_eq!(assertion_text("  # assert status == green"), Some("status == green"));
+        assert_eq!(assertion_text("// assert took < 100 "), Some("took < 100"));
+        assert_eq!(assertion_text("# a comment"), None);
+    }
+}
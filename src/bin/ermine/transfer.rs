@@ -0,0 +1,159 @@
+use std::io::Write;
+
+use elastic_ermine::{es, export, import};
+
+use crate::commands;
+
+fn parse_export_format(format: &str) -> Result<export::Format, String> {
+    export::Format::ALL.into_iter()
+        .find(|known| known.extension().eq_ignore_ascii_case(format))
+        .ok_or_else(|| format!("Unknown format {}, expected csv, ndjson or json", format))
+}
+
+fn parse_import_format(format: &str) -> Result<import::Format, String> {
+    import::Format::ALL.into_iter()
+        .find(|known| known.extensions().iter().any(|extension| extension.eq_ignore_ascii_case(format)))
+        .ok_or_else(|| format!("Unknown format {}, expected ndjson, json or csv", format))
+}
+
+#[derive(Debug, clap::Args)]
+pub struct ExportArgs {
+    /// Indices or aliases to export from, all indices when none are given
+    #[arg(value_delimiter = ',')]
+    indices: Vec<String>,
+
+    /// Query string the documents must match
+    #[arg(long, short = 'q', conflicts_with = "body")]
+    query: Option<String>,
+
+    /// Search body as JSON, @file to read it from a file or - to read it from stdin
+    #[arg(long, short = 'b')]
+    body: Option<String>,
+
+    /// csv, ndjson or json
+    #[arg(long, default_value = "ndjson", value_parser = parse_export_format)]
+    format: export::Format,
+
+    /// Field paths of the CSV columns, _id and every mapped field (plus unmapped fields of the first page) when none are given
+    #[arg(long, value_delimiter = ',')]
+    columns: Vec<String>,
+
+    /// File to write to, stdout when missing
+    #[arg(long, short = 'f')]
+    file: Option<std::path::PathBuf>,
+}
+
+#[derive(Debug, clap::Args)]
+pub struct ImportArgs {
+    /// NDJSON, JSON array or CSV file
+    file: std::path::PathBuf,
+
+    /// Index to import into
+    #[arg(long, short = 'i')]
+    index: String,
+
+    /// ndjson, json or csv, guessed from the file extension when missing
+    #[arg(long, value_parser = parse_import_format)]
+    format: Option<import::Format>,
+
+    /// Field holding the id of each document
+    #[arg(long)]
+    id_field: Option<String>,
+
+    /// CSV file to write the rejected rows to, they are printed otherwise
+    #[arg(long)]
+    report: Option<std::path::PathBuf>,
+}
+
+pub async fn export(client: es::ElasticsearchClient, args: ExportArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let body = match (&args.query, &args.body) {
+        (Some(query), _) => Some(es::SearchBody::new().query(es::Query::query_string(query.to_owned())).to_json()),
+        (None, Some(body)) => Some(
+            serde_json::from_str::<serde_json::Value>(&commands::read_body_arg(body)?)
+                .map_err(|err| format!("Invalid search body: {}", err))?
+        ),
+        (None, None) => None,
+    };
+
+    let writer: Box<dyn Write> = match &args.file {
+        Some(path) => Box::new(std::io::BufWriter::new(
+            std::fs::File::create(path).map_err(|err| format!("Unable to create {}: {}", path.to_string_lossy(), err))?
+        )),
+        None => Box::new(std::io::BufWriter::new(std::io::stdout())),
+    };
+
+    let mut export = export::Export::new(client, args.indices, body, args.format, args.columns, writer)?;
+
+    loop {
+        let progress = match export.next_page().await {
+            Ok(progress) => progress,
+            Err(err) => {
+                export.close().await;
+                return Err(err);
+            },
+        };
+
+        match progress.total {
+            Some(total) => eprint!("\rExported {} of {} documents", progress.exported, total),
+            None => eprint!("\rExported {} documents", progress.exported),
+        }
+
+        if progress.finished {
+            eprintln!();
+            return Ok(());
+        }
+    }
+}
+
+pub async fn import(client: es::ElasticsearchClient, args: ImportArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let format = args.format
+        .or_else(|| import::Format::from_path(&args.file))
+        .ok_or_else(|| format!("Unable to tell the format of {}, use --format", args.file.to_string_lossy()))?;
+
+    let contents = std::fs::read_to_string(&args.file)
+        .map_err(|err| format!("Unable to read {}: {}", args.file.to_string_lossy(), err))?;
+
+    let rows = import::parse(format, &contents)
+        .map_err(|err| format!("Unable to parse {}: {}", args.file.to_string_lossy(), err))?;
+
+    let mut import = import::Import::new(client, args.index, rows, args.id_field);
+
+    let progress = loop {
+        let progress = import.next_batch().await?;
+
+        eprint!(
+            "\r{} of {} rows processed, {} indexed, {} rejected, {:.0} docs/s",
+            progress.processed, progress.total, progress.indexed, progress.rejected, progress.documents_per_second()
+        );
+
+        if progress.finished {
+            eprintln!();
+            break progress;
+        }
+    };
+
+    eprintln!(
+        "Indexed {} of {} documents in {:.1}s",
+        progress.indexed, progress.total, progress.elapsed.as_secs_f64()
+    );
+
+    if progress.rejected == 0 {
+        return Ok(());
+    }
+
+    match &args.report {
+        Some(report) => {
+            let file = std::fs::File::create(report)
+                .map_err(|err| format!("Unable to create {}: {}", report.to_string_lossy(), err))?;
+            import::write_report(import.rejected(), file)?;
+            eprintln!("Rejected rows written to {}", report.to_string_lossy());
+        },
+        None => {
+            for rejected in import.rejected() {
+                eprintln!("Line {} rejected: {}", rejected.line, rejected.reason);
+            }
+        },
+    }
+
+    Err(format!("{} rows were rejected", progress.rejected).into())
+}
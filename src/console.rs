@@ -0,0 +1,378 @@
+// Parser for the Kibana Console request syntax, e.g.
+//
+//   # comment
+//   GET /_cat/indices
+//
+//   POST /my-index/_search
+//   {
+//     "query": { "match_all": {} }
+//   }
+//
+// A request is a `METHOD path` line followed by zero or more JSON bodies (multiple bodies are sent as NDJSON,
+// as used by `_bulk` and `_msearch`). Bodies may contain `#` and `//` comments as well as `"""` triple quoted strings.
+use crate::es;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConsoleRequest {
+    pub method: es::ElasticSearchMethodType,
+    pub path: String,
+    pub bodies: Vec<serde_json::Value>,
+    // Zero based, inclusive range of buffer lines the request spans
+    pub start_line: usize,
+    pub end_line: usize,
+}
+
+impl ConsoleRequest {
+    // Body in the form expected by the Dev Tools editor and history
+    pub fn body_text(&self) -> String {
+        match self.bodies.as_slice() {
+            [] => String::new(),
+            [body] => serde_json::to_string_pretty(body).unwrap_or_default(),
+            bodies => bodies.iter()
+                .map(|body| body.to_string())
+                .collect::<Vec<String>>()
+                .join("\n"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    // Zero based, inclusive range of buffer lines the malformed request spans
+    pub start_line: usize,
+    pub end_line: usize,
+    // Zero based line the error was found on
+    pub line: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line + 1, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+// Request block before its body has been interpreted as JSON
+struct RawRequest {
+    method: Result<es::ElasticSearchMethodType, String>,
+    path: String,
+    body: String,
+    // Buffer line each line of the body starts on, triple quoted strings spanning lines take a single body line
+    body_lines: Vec<usize>,
+    start_line: usize,
+    end_line: usize,
+}
+
+#[derive(Default)]
+struct BodyScanState {
+    in_string: bool,
+    in_triple_quote: bool,
+    escaped: bool,
+    depth: i64,
+}
+
+impl BodyScanState {
+    // A new request can only begin outside of any JSON value
+    fn at_top_level(&self) -> bool {
+        !self.in_string && !self.in_triple_quote && self.depth <= 0
+    }
+
+    /**
+     * Converts a line of console body syntax into plain JSON, removing comments and
+     * turning triple quoted strings into regular JSON strings.
+     */
+    fn normalize_line(&mut self, line: &str, out: &mut String) {
+        let chars = line.chars().collect::<Vec<char>>();
+        let mut idx = 0;
+
+        while idx < chars.len() {
+            let c = chars[idx];
+            let is_triple_quote = chars.get(idx..idx + 3) == Some(&['"', '"', '"']);
+
+            if self.in_triple_quote {
+                if is_triple_quote {
+                    self.in_triple_quote = false;
+                    out.push('"');
+                    idx += 3;
+                    continue;
+                }
+                match c {
+                    '"' => out.push_str("\\\""),
+                    '\\' => out.push_str("\\\\"),
+                    '\t' => out.push_str("\\t"),
+                    _ => out.push(c),
+                }
+            } else if self.in_string {
+                out.push(c);
+                if self.escaped {
+                    self.escaped = false;
+                } else if c == '\\' {
+                    self.escaped = true;
+                } else if c == '"' {
+                    self.in_string = false;
+                }
+            } else if is_triple_quote {
+                self.in_triple_quote = true;
+                out.push('"');
+                idx += 3;
+                continue;
+            } else {
+                match c {
+                    '#' => break,
+                    '/' if chars.get(idx + 1) == Some(&'/') => break,
+                    '"' => self.in_string = true,
+                    '{' | '[' => self.depth += 1,
+                    '}' | ']' => self.depth -= 1,
+                    _ => (),
+                }
+                out.push(c);
+            }
+            idx += 1;
+        }
+
+        if self.in_triple_quote {
+            out.push_str("\\n");
+        } else {
+            out.push('\n');
+        }
+    }
+}
+
+fn is_comment(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    trimmed.starts_with('#') || trimmed.starts_with("//")
+}
+
+// Returns (method, path) if the line starts a request
+fn request_line(line: &str) -> Option<(Result<es::ElasticSearchMethodType, String>, String)> {
+    let trimmed = line.trim();
+    let (method, rest) = trimmed.split_once(char::is_whitespace).unwrap_or((trimmed, ""));
+
+    const METHODS: [&str; 6] = ["GET", "POST", "PUT", "PATCH", "DELETE", "HEAD"];
+
+    if !METHODS.iter().any(|known| known.eq_ignore_ascii_case(method)) {
+        return None;
+    }
+
+    let path = rest.trim();
+    // Trailing comments are allowed after the path
+    let path = path.split_once(" #").map(|(path, _)| path).unwrap_or(path);
+    let path = path.split_once(" //").map(|(path, _)| path).unwrap_or(path);
+
+    Some((method.parse::<es::ElasticSearchMethodType>(), path.trim().to_owned()))
+}
+
+fn split_requests(buffer: &str) -> Vec<RawRequest> {
+    let mut requests: Vec<RawRequest> = Vec::new();
+    let mut state = BodyScanState::default();
+
+    for (line_idx, line) in buffer.lines().enumerate() {
+        if state.at_top_level() {
+            if let Some((method, path)) = request_line(line) {
+                state = BodyScanState::default();
+                requests.push(RawRequest {
+                    method,
+                    path,
+                    body: String::new(),
+                    body_lines: vec![line_idx + 1],
+                    start_line: line_idx,
+                    end_line: line_idx,
+                });
+                continue;
+            }
+
+            if line.trim().is_empty() || is_comment(line) {
+                // Keeps body line numbers in sync with the buffer for error reporting
+                if let Some(request) = requests.last_mut() {
+                    request.body.push('\n');
+                    request.body_lines.push(line_idx + 1);
+                }
+                continue;
+            }
+        }
+
+        // Text before the first request is ignored
+        if let Some(request) = requests.last_mut() {
+            state.normalize_line(line, &mut request.body);
+            if !state.in_triple_quote {
+                request.body_lines.push(line_idx + 1);
+            }
+            request.end_line = line_idx;
+        }
+    }
+
+    requests
+}
+
+fn parse_bodies(body: &str) -> Result<Vec<serde_json::Value>, serde_json::Error> {
+    serde_json::Deserializer::from_str(body)
+        .into_iter::<serde_json::Value>()
+        .collect()
+}
+
+/**
+ * Splits a buffer into requests. Each request is parsed independently so a
+ * malformed request does not prevent the others from being used.
+ */
+pub fn parse(buffer: &str) -> Vec<Result<ConsoleRequest, ParseError>> {
+    split_requests(buffer).into_iter().map(|raw| {
+        let method = raw.method.map_err(|err| ParseError {
+            start_line: raw.start_line,
+            end_line: raw.end_line,
+            line: raw.start_line,
+            message: err,
+        })?;
+
+        if raw.path.is_empty() {
+            return Err(ParseError {
+                start_line: raw.start_line,
+                end_line: raw.end_line,
+                line: raw.start_line,
+                message: "Missing request path".to_owned(),
+            });
+        }
+
+        let bodies = parse_bodies(&raw.body).map_err(|err| ParseError {
+            start_line: raw.start_line,
+            end_line: raw.end_line,
+            // serde_json lines are one based and relative to the body
+            line: raw.body_lines.get(err.line().saturating_sub(1)).copied().unwrap_or(raw.end_line),
+            message: format!("Invalid request body: {}", err),
+        })?;
+
+        Ok(ConsoleRequest {
+            method,
+            path: raw.path,
+            bodies,
+            start_line: raw.start_line,
+            end_line: raw.end_line,
+        })
+    })
+    .collect()
+}
+
+/**
+ * Returns the request the given (zero based) line belongs to.
+ * Lines between two requests belong to the preceding request.
+ */
+pub fn request_at_line(
+    requests: &[Result<ConsoleRequest, ParseError>],
+    line: usize
+) -> Option<&Result<ConsoleRequest, ParseError>> {
+    requests.iter()
+        .rev()
+        .find(|request| {
+            let start_line = match request {
+                Ok(request) => request.start_line,
+                Err(err) => err.start_line,
+            };
+            start_line <= line
+        })
+}
+
+/**
+ * Whether the buffer ends inside a body, in an unclosed JSON value or string.
+ * Line based editors keep reading lines until this is false.
+ */
+pub fn ends_inside_body(buffer: &str) -> bool {
+    let mut state = BodyScanState::default();
+    let mut body = String::new();
+
+    for line in buffer.lines() {
+        if state.at_top_level() && request_line(line).is_some() {
+            state = BodyScanState::default();
+            continue;
+        }
+        state.normalize_line(line, &mut body);
+    }
+
+    !state.at_top_level()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn parse_ok(buffer: &str) -> Vec<ConsoleRequest> {
+        parse(buffer).into_iter().collect::<Result<Vec<ConsoleRequest>, ParseError>>().unwrap()
+    }
+
+    #[test]
+    fn splits_a_buffer_into_requests() {
+        let requests = parse_ok("GET /_cat/indices\n\nPOST /logs/_search\n{\n  \"size\": 1\n}\nHEAD /logs\n");
+
+        assert_eq!(requests.len(), 3);
+        assert_eq!((requests[0].method, requests[0].path.as_str()), (es::ElasticSearchMethodType::GET, "/_cat/indices"));
+        assert!(requests[0].bodies.is_empty());
+        assert_eq!((requests[1].method, requests[1].start_line, requests[1].end_line), (es::ElasticSearchMethodType::POST, 2, 5));
+        assert_eq!(requests[1].bodies, vec![json!({"size": 1})]);
+        assert_eq!(requests[2].method, es::ElasticSearchMethodType::HEAD);
+    }
+
+    #[test]
+    fn skips_comments() {
+        let requests = parse_ok("# before\nGET /logs/_search # trailing\n{\n  // inside\n  \"size\": 2 # after a value\n}\n");
+
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].path, "/logs/_search");
+        assert_eq!(requests[0].bodies, vec![json!({"size": 2})]);
+    }
+
+    #[test]
+    fn keeps_comment_characters_inside_strings() {
+        let requests = parse_ok("POST /logs/_doc\n{\"url\": \"http://host/#anchor\"}\n");
+
+        assert_eq!(requests[0].bodies, vec![json!({"url": "http://host/#anchor"})]);
+    }
+
+    #[test]
+    fn turns_triple_quoted_strings_into_json_strings() {
+        let requests = parse_ok("POST /_scripts/painless/_execute\n{\n  \"script\": {\"source\": \"\"\"\n    String s = \"a\\b\";\n    GET /not-a-request\n  \"\"\"}\n}\n");
+
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].bodies, vec![json!({"script": {"source": "\n    String s = \"a\\b\";\n    GET /not-a-request\n  "}})]);
+    }
+
+    #[test]
+    fn reads_ndjson_bodies() {
+        let requests = parse_ok("POST /_bulk\n{\"index\": {\"_index\": \"logs\"}}\n{\"message\": \"a\"}\n\nGET /logs/_count\n");
+
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].bodies, vec![json!({"index": {"_index": "logs"}}), json!({"message": "a"})]);
+        assert_eq!(requests[0].body_text(), "{\"index\":{\"_index\":\"logs\"}}\n{\"message\":\"a\"}");
+    }
+
+    #[test]
+    fn reports_malformed_requests_on_their_own() {
+        let requests = parse("GET\n\nPOST /logs/_doc\n{\n  \"a\": \n}\n\nGET /logs/_count\n");
+
+        assert_eq!(requests[0].as_ref().unwrap_err().message, "Missing request path");
+        assert_eq!(requests[1].as_ref().unwrap_err().line, 5);
+        assert_eq!(requests[2].as_ref().unwrap().path, "/logs/_count");
+    }
+
+    #[test]
+    fn reports_errors_after_multi_line_triple_quoted_strings_on_their_line() {
+        let requests = parse("GET /a\n\nPOST /_scripts/s\n{\"script\": {\"source\": \"\"\"\n  a\n  b\n\"\"\"},\n  \"bad\": \n}\n");
+
+        assert_eq!(requests[1].as_ref().unwrap_err().line, 8);
+    }
+
+    #[test]
+    fn finds_the_request_of_a_line() {
+        let requests = parse("GET /a\n\nGET /b\n{}\n\n");
+
+        assert_eq!(request_at_line(&requests, 1).unwrap().as_ref().unwrap().path, "/a");
+        assert_eq!(request_at_line(&requests, 4).unwrap().as_ref().unwrap().path, "/b");
+    }
+
+    #[test]
+    fn tells_when_a_body_is_unfinished() {
+        assert!(ends_inside_body("POST /logs/_search\n{\n  \"query\": {"));
+        assert!(ends_inside_body("POST /logs/_doc\n{\"a\": \"\"\"\nb"));
+        assert!(!ends_inside_body("POST /logs/_search\n{\n}\n"));
+    }
+}
@@ -0,0 +1,386 @@
+// Conversion between requests and curl command lines.
+// Only the subset of curl options relevant to talking to Elasticsearch is understood.
+use crate::es;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CurlRequest {
+    pub method: es::ElasticSearchMethodType,
+    pub url: String,
+    // Path and query of the url, in the form taken by `ElasticsearchClient::operation`
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    // Value of -u/--user
+    pub user: Option<String>,
+    pub body: Option<String>,
+}
+
+// Options that take a value which has no bearing on the request itself
+const IGNORED_OPTIONS_WITH_VALUE: [&str; 26] = [
+    "--cacert", "-E", "--cert", "--key", "-o", "--output", "-w", "--write-out",
+    "--connect-timeout", "-m", "--max-time", "--aws-sigv4", "--retry",
+    "-A", "--user-agent", "-e", "--referer", "-b", "--cookie", "-c", "--cookie-jar",
+    "-x", "--proxy", "--resolve", "-K", "--config",
+];
+
+// Options that take a value and would change the request in ways a request cannot express
+const UNSUPPORTED_OPTIONS_WITH_VALUE: [&str; 6] = [
+    "-F", "--form", "--form-string", "-T", "--upload-file", "--variable",
+];
+
+// Short options whose value may be attached, e.g. -XPOST
+const SHORT_OPTIONS_WITH_VALUE: [&str; 16] = [
+    "-X", "-H", "-d", "-u", "-o", "-w", "-m", "-A", "-e", "-b", "-c", "-x", "-E", "-K", "-F", "-T",
+];
+
+/**
+ * Quotes a value so that a POSIX shell passes it through unchanged
+ */
+pub fn shell_quote(value: &str) -> String {
+    if !value.is_empty() && value.chars().all(|c| c.is_ascii_alphanumeric() || "-_./:=@,".contains(c)) {
+        return value.to_owned();
+    }
+
+    format!("'{}'", value.replace('\'', r#"'\''"#))
+}
+
+/**
+ * Builds a curl command equivalent to calling `ElasticsearchClient::operation` with the same arguments.
+ * Unless reveal_secrets is set, passwords and AWS credentials are replaced by shell variables
+ * (ES_PASSWORD, AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY).
+ */
+pub async fn command(
+    client: &es::ElasticsearchClient,
+    method_type: es::ElasticSearchMethodType,
+    path: &str,
+    body: Option<&str>,
+    reveal_secrets: bool
+) -> Result<String, Box<dyn std::error::Error>> {
+    let url = reqwest::Url::parse(client.root_url())?.join(path)?;
+
+    // curl -X HEAD would wait for a body that never comes
+    let mut args = match method_type {
+        es::ElasticSearchMethodType::HEAD => vec!["curl".to_owned(), "-I".to_owned()],
+        method_type => vec!["curl".to_owned(), "-X".to_owned(), method_type.as_str().to_owned()],
+    };
+    args.push(shell_quote(url.as_str()));
+
+    match client.auth() {
+        Some(es::Auth::BASIC(basic_auth)) => {
+            let user = match (&basic_auth.password, reveal_secrets) {
+                (Some(password), true) => shell_quote(&format!("{}:{}", basic_auth.username, password)),
+                (Some(_), false) => format!("{}\"${{ES_PASSWORD}}\"", shell_quote(&format!("{}:", basic_auth.username))),
+                (None, _) => shell_quote(&basic_auth.username),
+            };
+            args.push("-u".to_owned());
+            args.push(user);
+        },
+        Some(es::Auth::AWS(aws_sigv4)) => {
+            args.push("--aws-sigv4".to_owned());
+            args.push(shell_quote(&format!("aws:amz:{}:es", aws_sigv4.region)));
+
+            args.push("--user".to_owned());
+            if reveal_secrets {
+                let credentials = es::ElasticsearchClient::aws_credentials(aws_sigv4).await?;
+                args.push(shell_quote(&format!("{}:{}", credentials.access_key_id(), credentials.secret_access_key())));
+
+                if let Some(session_token) = credentials.session_token() {
+                    args.push("-H".to_owned());
+                    args.push(shell_quote(&format!("x-amz-security-token: {}", session_token)));
+                }
+            } else {
+                args.push("\"${AWS_ACCESS_KEY_ID}:${AWS_SECRET_ACCESS_KEY}\"".to_owned());
+            }
+        },
+        None => (),
+    }
+
+    if let Some(body) = body.filter(|body| !body.trim().is_empty()) {
+        // JSON is compacted so the command stays on a single line
+        let body = serde_json::from_str::<serde_json::Value>(body)
+            .map(|json| json.to_string())
+            .unwrap_or(body.to_owned());
+
+        args.push("-H".to_owned());
+        args.push(shell_quote("Content-Type: application/json"));
+        args.push("-d".to_owned());
+        args.push(shell_quote(&body));
+    }
+
+    Ok(args.join(" "))
+}
+
+/**
+ * Splits a command line into words following POSIX shell quoting rules:
+ * single quotes, double quotes, backslash escapes and backslash-newline continuations.
+ */
+fn split_words(command: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut has_word = false;
+    let mut chars = command.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                has_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => current.push(c),
+                        None => return Err("Unterminated single quote".to_owned()),
+                    }
+                }
+            },
+            '"' => {
+                has_word = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(escaped @ ('"' | '\\' | '$' | '`')) => current.push(escaped),
+                            Some('\n') => (),
+                            Some(other) => {
+                                current.push('\\');
+                                current.push(other);
+                            },
+                            None => return Err("Unterminated double quote".to_owned()),
+                        },
+                        Some(c) => current.push(c),
+                        None => return Err("Unterminated double quote".to_owned()),
+                    }
+                }
+            },
+            '\\' => match chars.next() {
+                // Line continuation
+                Some('\n') => (),
+                Some('\r') if chars.peek() == Some(&'\n') => {
+                    chars.next();
+                },
+                Some(escaped) => {
+                    has_word = true;
+                    current.push(escaped);
+                },
+                None => (),
+            },
+            c if c.is_whitespace() => {
+                if has_word {
+                    words.push(std::mem::take(&mut current));
+                    has_word = false;
+                }
+            },
+            c => {
+                has_word = true;
+                current.push(c);
+            },
+        }
+    }
+
+    if has_word {
+        words.push(current);
+    }
+
+    Ok(words)
+}
+
+// Splits "-XPOST" and "--request=POST" style options into option and value
+fn split_attached_value(word: &str) -> (&str, Option<&str>) {
+    if let Some(long) = word.strip_prefix("--") {
+        return match long.split_once('=') {
+            Some((option, value)) => (&word[..option.len() + 2], Some(value)),
+            None => (word, None),
+        };
+    }
+
+    if word.len() > 2 && word.starts_with('-') && !word.starts_with("--") {
+        let option = &word[..2];
+        if SHORT_OPTIONS_WITH_VALUE.contains(&option) {
+            return (option, Some(&word[2..]));
+        }
+    }
+
+    (word, None)
+}
+
+// Same as curl's --data-urlencode: content, =content or name=content, of which only content is encoded
+fn url_encoded_data(data: &str) -> Result<String, String> {
+    let (name, content) = match data.split_once('=') {
+        Some((name, content)) => (name, content),
+        None if data.contains('@') => return Err(format!("Reading the body from a file ({}) is not supported", data)),
+        None => ("", data),
+    };
+
+    let encoded = content.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => char::from(byte).to_string(),
+            byte => format!("%{:02X}", byte),
+        })
+        .collect::<String>();
+
+    Ok(if name.is_empty() { encoded } else { format!("{}={}", name, encoded) })
+}
+
+pub fn parse(command: &str) -> Result<CurlRequest, String> {
+    let words = split_words(command.trim())?;
+    let mut words = words.iter().map(String::as_str);
+
+    match words.next() {
+        Some("curl") => (),
+        _ => return Err("Command does not start with curl".to_owned()),
+    }
+
+    let mut method: Option<es::ElasticSearchMethodType> = None;
+    let mut url: Option<String> = None;
+    let mut headers = Vec::new();
+    let mut user = None;
+    let mut data: Vec<String> = Vec::new();
+    // -G sends the data as the query string of a GET
+    let mut data_as_query = false;
+
+    while let Some(word) = words.next() {
+        let (option, attached_value) = split_attached_value(word);
+
+        let mut value = |option: &str| -> Result<String, String> {
+            attached_value
+                .or_else(|| words.next())
+                .map(str::to_owned)
+                .ok_or(format!("Missing value after {}", option))
+        };
+
+        match option {
+            "-X" | "--request" => method = Some(value(option)?.parse::<es::ElasticSearchMethodType>()?),
+            "-H" | "--header" => {
+                let header = value(option)?;
+                let (name, header_value) = header.split_once(':')
+                    .ok_or(format!("Invalid header {}", header))?;
+                headers.push((name.trim().to_owned(), header_value.trim().to_owned()));
+            },
+            "-u" | "--user" => user = Some(value(option)?),
+            "-d" | "--data" | "--data-raw" | "--data-binary" | "--data-ascii" => {
+                let body = value(option)?;
+                if body.starts_with('@') && option != "--data-raw" {
+                    return Err(format!("Reading the body from a file ({}) is not supported", body));
+                }
+                data.push(body);
+            },
+            "--data-urlencode" => data.push(url_encoded_data(&value(option)?)?),
+            "-G" | "--get" => data_as_query = true,
+            "-I" | "--head" => method = Some(es::ElasticSearchMethodType::HEAD),
+            "--json" => {
+                data.push(value(option)?);
+                headers.push(("Content-Type".to_owned(), "application/json".to_owned()));
+            },
+            "--url" => url = Some(value(option)?),
+            option if IGNORED_OPTIONS_WITH_VALUE.contains(&option) => {
+                value(option)?;
+            },
+            option if UNSUPPORTED_OPTIONS_WITH_VALUE.contains(&option) => {
+                return Err(format!("{} is not supported", option));
+            },
+            option if option.starts_with('-') => (), // Flags such as -s, -k, --compressed
+            positional => {
+                if url.is_some() {
+                    return Err(format!("Unexpected argument {}", positional));
+                }
+                url = Some(positional.to_owned());
+            },
+        }
+    }
+
+    let mut url = url.ok_or("Missing url".to_owned())?;
+    let mut parsed_url = reqwest::Url::parse(&url).map_err(|err| format!("Invalid url {}: {}", url, err))?;
+
+    if data_as_query && !data.is_empty() {
+        let query = parsed_url.query()
+            .into_iter()
+            .map(str::to_owned)
+            .chain(data.drain(..))
+            .collect::<Vec<String>>()
+            .join("&");
+        parsed_url.set_query(Some(&query));
+        url = parsed_url.to_string();
+        method = method.or(Some(es::ElasticSearchMethodType::GET));
+    }
+
+    let path = match parsed_url.query() {
+        Some(query) => format!("{}?{}", parsed_url.path(), query),
+        None => parsed_url.path().to_owned(),
+    };
+
+    // Same as curl, which joins multiple data options with '&'
+    let body = (!data.is_empty()).then(|| data.join("&"));
+
+    let method = method.unwrap_or(if body.is_some() {
+        es::ElasticSearchMethodType::POST
+    } else {
+        es::ElasticSearchMethodType::GET
+    });
+
+    Ok(CurlRequest {
+        method,
+        url,
+        path,
+        headers,
+        user,
+        body,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quotes_only_when_needed() {
+        assert_eq!(shell_quote("http://localhost:9200/_search"), "http://localhost:9200/_search");
+        assert_eq!(shell_quote("{\"a\": 1}"), "'{\"a\": 1}'");
+        assert_eq!(shell_quote("it's"), r#"'it'\''s'"#);
+        assert_eq!(split_words(&shell_quote("it's")).unwrap(), vec!["it's"]);
+    }
+
+    #[test]
+    fn parses_a_request() {
+        let request = parse("curl -XPOST 'http://localhost:9200/logs/_search?size=1' \\\n  -H 'Content-Type: application/json' -u elastic:secret -d '{\"query\": {\"match_all\": {}}}'").unwrap();
+
+        assert_eq!(request.method, es::ElasticSearchMethodType::POST);
+        assert_eq!(request.path, "/logs/_search?size=1");
+        assert_eq!(request.headers, vec![("Content-Type".to_owned(), "application/json".to_owned())]);
+        assert_eq!(request.user.as_deref(), Some("elastic:secret"));
+        assert_eq!(request.body.as_deref(), Some("{\"query\": {\"match_all\": {}}}"));
+    }
+
+    #[test]
+    fn defaults_to_post_with_data() {
+        assert_eq!(parse("curl http://localhost:9200/").unwrap().method, es::ElasticSearchMethodType::GET);
+        assert_eq!(parse("curl --data-raw '{}' http://localhost:9200/logs/_doc").unwrap().method, es::ElasticSearchMethodType::POST);
+    }
+
+    #[test]
+    fn skips_options_and_their_values() {
+        let request = parse("curl -s -A 'Mozilla 5.0' -e http://referer -b 'a=b' -o out.json --compressed http://localhost:9200/_cat/indices").unwrap();
+
+        assert_eq!(request.url, "http://localhost:9200/_cat/indices");
+        assert_eq!(request.method, es::ElasticSearchMethodType::GET);
+    }
+
+    #[test]
+    fn sends_data_as_query_with_get() {
+        let request = parse("curl -G --data-urlencode 'q=status:500 AND host:a' -d size=5 'http://localhost:9200/logs/_search?pretty'").unwrap();
+
+        assert_eq!(request.method, es::ElasticSearchMethodType::GET);
+        assert_eq!(request.path, "/logs/_search?pretty&q=status%3A500%20AND%20host%3Aa&size=5");
+        assert_eq!(request.body, None);
+    }
+
+    #[test]
+    fn sends_head_requests() {
+        assert_eq!(parse("curl -I http://localhost:9200/logs").unwrap().method, es::ElasticSearchMethodType::HEAD);
+    }
+
+    #[test]
+    fn rejects_what_a_request_cannot_express() {
+        assert_eq!(parse("curl -F 'file=@a.json' http://localhost:9200/").unwrap_err(), "-F is not supported");
+        assert!(parse("curl -d @body.json http://localhost:9200/").is_err());
+        assert!(parse("wget http://localhost:9200/").is_err());
+        assert!(parse("curl 'http://localhost:9200/").is_err());
+    }
+}
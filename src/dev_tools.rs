@@ -1,12 +1,27 @@
-use crate::{assets, es, widget};
+use std::sync::Arc;
+
+use crate::{assets, es, logs, widget};
+use elastic_ermine::{autocomplete, console, curl};
+
+mod history;
 
 pub enum Action {
     None,
+    Run(iced::Task<Message>),
     InvokeOperation{
         method: es::ElasticSearchMethodType,
         path: String,
         body: String,
     },
+    InvokeConsoleRequests(Vec<console::ConsoleRequest>),
+    CopyAsCurl{
+        method: es::ElasticSearchMethodType,
+        path: String,
+        body: String,
+        reveal_secrets: bool,
+    },
+    // Loads the completions of the cluster while running the task
+    LoadCompletions(iced::Task<Message>),
 }
 
 #[derive(Debug, Clone)]
@@ -14,18 +29,139 @@ pub enum Message {
     RequestTypeSelected(es::ElasticSearchMethodType),
     RequestBodyEditPerformed(iced::widget::text_editor::Action),
     PathUpdated(String),
-    HTTPOperationReturned(Result<es::OperationResult, String>), // Perhaps Value should be a reference for large results
+    HTTPOperationReturned(Result<es::OperationResult, String>, String, history::Entry), // Perhaps Value should be a reference for large results
     SendButtonPressed,
+    ClusterActivated(String),
+    HistoryLoaded(String, Result<Vec<history::Entry>, String>),
+    HistoryToggled,
+    HistorySearchUpdated(String),
+    HistoryRestorePressed(usize),
+    HistoryResendPressed(usize),
+    EditorModeSelected(EditorMode),
+    ConsoleEditPerformed(iced::widget::text_editor::Action),
+    ConsoleRunAtCursorPressed,
+    ConsoleRunAllPressed,
+    ConsoleResponseReturned(Result<es::OperationResult, String>, String, history::Entry),
+    ConsoleRunFinished(Result<(), String>),
+    CopyAsCurlPressed,
+    CurlRevealSecretsToggled(bool),
+    CurlCopied(Result<(), String>),
+    PasteCurlPressed,
+    CurlPasted(Option<String>),
+    ClearPressed,
+    FormatPressed,
+    ResponseTreeUpdated(widget::json_tree::Message),
+    ResponseTextScrolled(iced::widget::scrollable::Viewport),
+    ConsoleResponseTreeUpdated(usize, widget::json_tree::Message),
+    // Along with what failed to load, left out of the completions
+    CompletionsLoaded(String, autocomplete::Completer, Vec<String>),
+    PathSuggestionPicked(usize),
+    BodySuggestionPicked(usize),
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct View {
+    log: logs::Log,
     request_type: es::ElasticSearchMethodType,
     request_path: String,
-    request_body: iced::widget::text_editor::Content,
-    result: Option<Result<es::OperationResult, String>>,
+    request_body: widget::JsonEditor,
+    result: Option<Result<ResponseBody, String>>,
+    response_text_list: widget::virtual_list::State,
 
     send_button_state: SendButtonState,
+
+    history_open: bool,
+    history_search: String,
+    // Cluster the displayed history belongs to
+    history_cluster: Option<String>,
+    history: Vec<history::Entry>,
+    history_error: Option<String>,
+
+    editor_mode: EditorMode,
+    console_buffer: widget::JsonEditor,
+    console_responses: Vec<ConsoleResponse>,
+    console_run_state: SendButtonState,
+
+    curl_reveal_secrets: bool,
+    // Outcome of the last editor action (copy or paste as curl, format)
+    editor_notice: Option<String>,
+
+    completer: autocomplete::Completer,
+    // Cluster the index and field names of the completer belong to
+    completer_cluster: Option<String>,
+    path_suggestions: Vec<autocomplete::Suggestion>,
+    body_suggestions: Vec<autocomplete::Suggestion>,
+}
+
+impl Default for View {
+    fn default() -> Self {
+        Self {
+            log: Default::default(),
+            request_type: Default::default(),
+            request_path: Default::default(),
+            request_body: widget::JsonEditor::new(widget::json_editor::Syntax::Json),
+            result: None,
+            response_text_list: Default::default(),
+            send_button_state: Default::default(),
+            history_open: false,
+            history_search: Default::default(),
+            history_cluster: None,
+            history: Vec::new(),
+            history_error: None,
+            editor_mode: Default::default(),
+            console_buffer: widget::JsonEditor::new(widget::json_editor::Syntax::Console),
+            console_responses: Vec::new(),
+            console_run_state: Default::default(),
+            curl_reveal_secrets: false,
+            editor_notice: None,
+            completer: Default::default(),
+            completer_cluster: None,
+            path_suggestions: Vec::new(),
+            body_suggestions: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub enum EditorMode {
+    #[default]
+    Request,
+    // Kibana Console syntax with possibly many requests
+    Console,
+}
+
+#[derive(Debug)]
+struct ConsoleResponse {
+    // e.g. "GET /_cat/indices"
+    label: String,
+    status: Option<u16>,
+    duration_ms: Option<u64>,
+    result: Result<ResponseBody, String>,
+}
+
+fn log_operation(log: &logs::Log, entry: &history::Entry, result: &Result<es::OperationResult, String>) {
+    match result {
+        Ok(_) => log.info(format!(
+            "{} {} returned {} in {} ms",
+            entry.method, entry.path, entry.status.unwrap_or_default(), entry.duration_ms
+        )),
+        Err(err) => log.error(format!("{} {} failed: {}", entry.method, entry.path, err)),
+    }
+}
+
+#[derive(Debug)]
+enum ResponseBody {
+    Json(widget::JsonTree),
+    Text(String),
+}
+
+impl From<es::OperationResult> for ResponseBody {
+    fn from(result: es::OperationResult) -> Self {
+        match result {
+            es::OperationResult::Json(json) => ResponseBody::Json(widget::JsonTree::new(json)),
+            es::OperationResult::Text(text) => ResponseBody::Text(text),
+        }
+    }
 }
 
 #[derive(Debug, Default)]
@@ -35,22 +171,38 @@ enum SendButtonState {
     Waiting,
 }
 
-const SUPPORTED_METHODS: [es::ElasticSearchMethodType; 5] =[
+const SUPPORTED_METHODS: [es::ElasticSearchMethodType; 6] =[
     es::ElasticSearchMethodType::GET,
     es::ElasticSearchMethodType::POST,
     es::ElasticSearchMethodType::PUT,
     es::ElasticSearchMethodType::PATCH,
     es::ElasticSearchMethodType::DELETE,
+    es::ElasticSearchMethodType::HEAD,
 ];
 
 impl View {
+    pub fn new(log: logs::Log) -> Self {
+        Self { log, ..Default::default() }
+    }
+
     pub fn view(&self) -> iced::Element<'_, Message> {
         iced::widget::row![
-            self.editor()
+            self.history_open.then(|| {
+                self.history_panel()
+                    .width(iced::FillPortion(1))
+                    .height(iced::Fill)
+            }),
+            match self.editor_mode {
+                EditorMode::Request => self.editor(),
+                EditorMode::Console => self.console_editor(),
+            }
             .width(iced::FillPortion(1))
             .height(iced::Fill),
             iced::widget::container(
-                self.response()
+                match self.editor_mode {
+                    EditorMode::Request => self.response(),
+                    EditorMode::Console => self.console_responses(),
+                }
             )
             .width(iced::FillPortion(1))
             .height(iced::Fill),
@@ -63,35 +215,326 @@ impl View {
         match message {
             Message::RequestTypeSelected(method) => {
                 self.request_type = method;
+                self.path_suggestions.clear();
                 Action::None
             },
             Message::RequestBodyEditPerformed(action) => {
+                let is_edit = action.is_edit();
                 self.request_body.perform(action);
+
+                // Moving the cursor around dismisses the suggestions
+                self.body_suggestions = if is_edit {
+                    self.completer.complete_body(&self.request_body.text_before_cursor())
+                } else {
+                    Vec::new()
+                };
                 Action::None
             },
             Message::PathUpdated(new_path) => {
                 self.request_path = new_path;
+                self.path_suggestions = self.completer.complete_path(self.request_type, &self.request_path);
                 Action::None
             },
-            Message::HTTPOperationReturned(value) => {
-                self.send_button_state = SendButtonState::Ready;
-                self.result = Some(value);
+            Message::PathSuggestionPicked(idx) => {
+                if let Some(suggestion) = self.path_suggestions.get(idx) {
+                    self.request_path = suggestion.apply_to_end(&self.request_path);
+                }
+                self.path_suggestions = self.completer.complete_path(self.request_type, &self.request_path);
                 Action::None
             },
+            Message::BodySuggestionPicked(idx) => {
+                if let Some(suggestion) = self.body_suggestions.get(idx) {
+                    self.request_body.replace_before_cursor(suggestion.replace_len, &suggestion.insert);
+                }
+                self.body_suggestions.clear();
+                Action::None
+            },
+            Message::CompletionsLoaded(cluster, completer, errors) => {
+                // Endpoints and query DSL keys are still completed from the bundled spec
+                self.completer = completer;
+                if !errors.is_empty() {
+                    let errors = errors.join(", ");
+                    self.log.error(format!("Failed to load names for autocomplete: {}", errors));
+                    self.editor_notice = Some(format!("Failed to load {}", errors));
+                }
+                self.completer_cluster = Some(cluster);
+                Action::None
+            },
+            Message::HTTPOperationReturned(value, cluster, entry) => {
+                self.send_button_state = SendButtonState::Ready;
+                log_operation(&self.log, &entry, &value);
+                self.result = Some(value.map(ResponseBody::from));
+                Action::Run(iced::Task::perform(
+                    history::append(self.log.clone(), cluster.clone(), entry),
+                    move |res| Message::HistoryLoaded(cluster.clone(), res)
+                ))
+            },
             Message::SendButtonPressed => {
                 self.send_button_state = SendButtonState::Waiting;
+                self.path_suggestions.clear();
+                self.body_suggestions.clear();
                 Action::InvokeOperation { 
                     method: self.request_type, 
                     path: self.request_path.clone(), 
                     body: self.request_body.text()
                 }
             },
+            Message::ClusterActivated(cluster) => {
+                // History does not wait for the completions, which are listed from the cluster
+                let load_history = if self.history_cluster.as_ref() == Some(&cluster) {
+                    iced::Task::none()
+                } else {
+                    let history_cluster = cluster.clone();
+                    iced::Task::perform(
+                        history::load(self.log.clone(), cluster.clone()),
+                        move |res| Message::HistoryLoaded(history_cluster.clone(), res)
+                    )
+                };
+
+                if self.completer_cluster.as_ref() != Some(&cluster) {
+                    Action::LoadCompletions(load_history)
+                } else {
+                    Action::Run(load_history)
+                }
+            },
+            Message::HistoryLoaded(cluster, res) => {
+                match res {
+                    Ok(entries) => {
+                        self.history = entries;
+                        self.history_error = None;
+                    },
+                    Err(err) => {
+                        self.history = Vec::new();
+                        self.history_error = Some(format!("Failed to load history: {}", err));
+                    },
+                }
+                self.history_cluster = Some(cluster);
+                Action::None
+            },
+            Message::HistoryToggled => {
+                self.history_open = !self.history_open;
+                Action::None
+            },
+            Message::HistorySearchUpdated(search) => {
+                self.history_search = search;
+                Action::None
+            },
+            Message::HistoryRestorePressed(idx) => {
+                self.restore_history_entry(idx);
+                Action::None
+            },
+            Message::HistoryResendPressed(idx) => {
+                // Restoring while a request is sent would show its response under another request
+                if !matches!(self.send_button_state, SendButtonState::Ready) {
+                    return Action::None;
+                }
+                if self.restore_history_entry(idx) {
+                    self.update(Message::SendButtonPressed)
+                } else {
+                    Action::None
+                }
+            },
+            Message::EditorModeSelected(mode) => {
+                self.editor_mode = mode;
+                Action::None
+            },
+            Message::ConsoleEditPerformed(action) => {
+                self.console_buffer.perform(action);
+                Action::None
+            },
+            Message::ConsoleRunAtCursorPressed => {
+                let requests = console::parse(&self.console_buffer.text());
+                let cursor_line = self.console_buffer.cursor().position.line;
+
+                match console::request_at_line(&requests, cursor_line) {
+                    Some(request) => self.run_console_requests(vec![request.clone()]),
+                    None => {
+                        self.console_responses = vec![ConsoleResponse {
+                            label: format!("Line {}", cursor_line + 1),
+                            status: None,
+                            duration_ms: None,
+                            result: Err("No request under the cursor".to_owned()),
+                        }];
+                        Action::None
+                    },
+                }
+            },
+            Message::ConsoleRunAllPressed => {
+                let requests = console::parse(&self.console_buffer.text());
+                self.run_console_requests(requests)
+            },
+            Message::ConsoleResponseReturned(result, cluster, entry) => {
+                log_operation(&self.log, &entry, &result);
+                self.console_responses.push(ConsoleResponse {
+                    label: format!("{} {}", entry.method, entry.path),
+                    status: entry.status,
+                    duration_ms: Some(entry.duration_ms),
+                    result: result.map(ResponseBody::from),
+                });
+                Action::Run(iced::Task::perform(
+                    history::append(self.log.clone(), cluster.clone(), entry),
+                    move |res| Message::HistoryLoaded(cluster.clone(), res)
+                ))
+            },
+            Message::CopyAsCurlPressed => {
+                Action::CopyAsCurl {
+                    method: self.request_type,
+                    path: self.request_path.clone(),
+                    body: self.request_body.text(),
+                    reveal_secrets: self.curl_reveal_secrets,
+                }
+            },
+            Message::CurlRevealSecretsToggled(reveal) => {
+                self.curl_reveal_secrets = reveal;
+                Action::None
+            },
+            Message::CurlCopied(res) => {
+                self.editor_notice = Some(match res {
+                    Ok(()) => "Copied curl command to clipboard".to_owned(),
+                    Err(err) => format!("Failed to copy as curl: {}", err),
+                });
+                Action::None
+            },
+            Message::PasteCurlPressed => {
+                Action::Run(iced::clipboard::read().map(Message::CurlPasted))
+            },
+            Message::CurlPasted(contents) => {
+                let res = contents
+                    .ok_or("Clipboard is empty".to_owned())
+                    .and_then(|command| self.apply_pasted_curl(&command));
+
+                if let Err(err) = res {
+                    self.editor_notice = Some(format!("Failed to paste curl: {}", err));
+                }
+                Action::None
+            },
+            Message::ClearPressed => {
+                self.request_body.clear();
+                self.editor_notice = None;
+                Action::None
+            },
+            Message::FormatPressed => {
+                self.editor_notice = self.request_body.format().err();
+                Action::None
+            },
+            Message::ResponseTreeUpdated(message) => {
+                match &mut self.result {
+                    Some(Ok(ResponseBody::Json(tree))) => Action::Run(tree.update(message)),
+                    _ => Action::None,
+                }
+            },
+            Message::ResponseTextScrolled(viewport) => {
+                self.response_text_list.scrolled(viewport);
+                Action::None
+            },
+            Message::ConsoleResponseTreeUpdated(idx, message) => {
+                match self.console_responses.get_mut(idx).map(|response| &mut response.result) {
+                    Some(Ok(ResponseBody::Json(tree))) => Action::Run(tree.update(message)),
+                    _ => Action::None,
+                }
+            },
+            Message::ConsoleRunFinished(res) => {
+                self.console_run_state = SendButtonState::Ready;
+                if let Err(err) = res {
+                    self.console_responses.push(ConsoleResponse {
+                        label: "Run aborted".to_owned(),
+                        status: None,
+                        duration_ms: None,
+                        result: Err(err),
+                    });
+                }
+                Action::None
+            },
+        }
+    }
+
+    fn apply_pasted_curl(&mut self, command: &str) -> Result<(), String> {
+        let request = curl::parse(command)?;
+
+        self.editor_mode = EditorMode::Request;
+        self.request_type = request.method;
+        self.request_path = request.path;
+
+        let body = request.body.unwrap_or_default();
+        let body = serde_json::from_str::<serde_json::Value>(&body).ok()
+            .and_then(|json| serde_json::to_string_pretty(&json).ok())
+            .unwrap_or(body);
+        self.request_body.set_text(&body);
+
+        // Authentication and the cluster url come from the connection settings, other headers are not supported
+        let ignored = request.headers.iter()
+            .map(|(name, _)| name.as_str())
+            .filter(|name| !name.eq_ignore_ascii_case("content-type"))
+            .chain(request.user.as_ref().map(|_| "--user"))
+            .collect::<Vec<&str>>();
+
+        self.editor_notice = Some(if ignored.is_empty() {
+            "Pasted curl command".to_owned()
+        } else {
+            format!("Pasted curl command, ignored: {}", ignored.join(", "))
+        });
+
+        Ok(())
+    }
+
+    // Requests that failed to parse are reported right away and are not sent
+    fn run_console_requests(&mut self, requests: Vec<Result<console::ConsoleRequest, console::ParseError>>) -> Action {
+        if matches!(self.console_run_state, SendButtonState::Waiting) {
+            return Action::None;
+        }
+
+        self.console_responses.clear();
+
+        let mut valid_requests = Vec::new();
+
+        for request in requests {
+            match request {
+                Ok(request) => valid_requests.push(request),
+                Err(err) => self.console_responses.push(ConsoleResponse {
+                    label: format!("Line {}", err.start_line + 1),
+                    status: None,
+                    duration_ms: None,
+                    result: Err(err.to_string()),
+                }),
+            }
+        }
+
+        if valid_requests.is_empty() {
+            Action::None
+        } else {
+            self.console_run_state = SendButtonState::Waiting;
+            Action::InvokeConsoleRequests(valid_requests)
+        }
+    }
+
+    // Returns false if there is no entry at idx. Entries are restored into the request editor, which is shown
+    fn restore_history_entry(&mut self, idx: usize) -> bool {
+        match self.history.get(idx) {
+            Some(entry) => {
+                self.editor_mode = EditorMode::Request;
+                self.request_type = entry.method;
+                self.request_path = entry.path.clone();
+                self.request_body.set_text(&entry.body);
+                true
+            },
+            None => false,
         }
     }
 
+    fn editor_mode_section(&self) -> iced::widget::Row<'_, Message> {
+        iced::widget::row![
+            widget::RadioArea::new("Request", EditorMode::Request, Some(self.editor_mode), Message::EditorModeSelected)
+                .width(iced::FillPortion(1)),
+            widget::RadioArea::new("Console", EditorMode::Console, Some(self.editor_mode), Message::EditorModeSelected)
+                .width(iced::FillPortion(1)),
+        ]
+        .spacing(10)
+    }
+
     pub fn editor(&self) -> iced::widget::Container<'_, Message> {
         widget::section(
         iced::widget::column![
+            self.editor_mode_section(),
             iced::widget::row![
                 iced::widget::pick_list(
                     SUPPORTED_METHODS,
@@ -115,9 +558,40 @@ impl View {
                 }
                 .width(iced::Shrink),
             ],
+            (!self.path_suggestions.is_empty()).then(|| {
+                widget::suggestion_list(&self.path_suggestions, Message::PathSuggestionPicked)
+            }),
+            iced::widget::row![
+                iced::widget::button(
+                    iced::widget::text("Copy as curl")
+                        .size(15)
+                    )
+                    .on_press(Message::CopyAsCurlPressed)
+                    .height(iced::Shrink),
+                iced::widget::button(
+                    iced::widget::text("Paste curl")
+                        .size(15)
+                    )
+                    .on_press(Message::PasteCurlPressed)
+                    .height(iced::Shrink),
+                iced::widget::checkbox(self.curl_reveal_secrets)
+                    .label("Include secrets")
+                    .on_toggle(Message::CurlRevealSecretsToggled),
+                iced::widget::space::horizontal(),
+                self.editor_notice.as_ref()
+                    .map(|notice| iced::widget::text(notice).size(12)),
+            ]
+            .spacing(5)
+            .align_y(iced::Center),
             iced::widget::row![
                 iced::widget::text("REQUEST BODY (JSON)"),
                 iced::widget::space::horizontal(),
+                iced::widget::button(
+                    iced::widget::text("History")
+                        .size(15)
+                    )
+                    .on_press(Message::HistoryToggled)
+                    .height(iced::Shrink),
                 iced::widget::button(
                     iced::widget::row![
                         assets::x_icon()
@@ -129,6 +603,7 @@ impl View {
                         .align_y(iced::Center)
                         .spacing(5)
                     )
+                    .on_press(Message::ClearPressed)
                     .height(iced::Shrink),
                 iced::widget::button(
                     iced::widget::row![
@@ -140,59 +615,345 @@ impl View {
                         ]
                         .align_y(iced::Center)
                         .spacing(5)
-                    ).height(iced::Shrink),
+                    )
+                    .on_press(Message::FormatPressed)
+                    .height(iced::Shrink),
             ]
             .align_y(iced::Center),
-            iced::widget::text_editor(&self.request_body)
-                .on_action(Message::RequestBodyEditPerformed)
-                .height(iced::Length::Fill)
-                .placeholder(r#"{"size":10000,"query":{"match_all":{}}}"#),
+            iced::widget::stack![
+                self.request_body.view(
+                    Message::RequestBodyEditPerformed,
+                    r#"{"size":10000,"query":{"match_all":{}}}"#
+                ),
+                (!self.body_suggestions.is_empty()).then(|| {
+                    iced::widget::container(
+                        widget::suggestion_list(&self.body_suggestions, Message::BodySuggestionPicked)
+                            .max_width(400)
+                    )
+                    .align_right(iced::Fill)
+                    .align_bottom(iced::Fill)
+                    .padding(10)
+                }),
+            ],
         ])
     }
 
+    fn console_editor(&self) -> iced::widget::Container<'_, Message> {
+        let running = matches!(self.console_run_state, SendButtonState::Waiting);
+
+        widget::section(
+        iced::widget::column![
+            self.editor_mode_section(),
+            iced::widget::row![
+                iced::widget::text("CONSOLE"),
+                iced::widget::space::horizontal(),
+                running.then(|| assets::loading_icon().width(iced::Shrink)),
+                iced::widget::button(
+                    iced::widget::text("Run at cursor")
+                        .size(15)
+                    )
+                    .on_press_maybe((!running).then_some(Message::ConsoleRunAtCursorPressed))
+                    .height(iced::Shrink),
+                iced::widget::button(
+                    iced::widget::text("Run all")
+                        .size(15)
+                    )
+                    .on_press_maybe((!running).then_some(Message::ConsoleRunAllPressed))
+                    .height(iced::Shrink),
+                iced::widget::button(
+                    iced::widget::text("History")
+                        .size(15)
+                    )
+                    .on_press(Message::HistoryToggled)
+                    .height(iced::Shrink),
+            ]
+            .spacing(5)
+            .align_y(iced::Center),
+            self.console_buffer.view(
+                Message::ConsoleEditPerformed,
+                "GET /_cat/indices\n\nPOST /my-index/_search\n{\n  \"query\": { \"match_all\": {} }\n}"
+            ),
+        ]
+        .spacing(5))
+    }
+
+    fn history_panel(&self) -> iced::widget::Container<'_, Message> {
+        let ready = matches!(self.send_button_state, SendButtonState::Ready);
+
+        // Most recent first
+        let entries = self.history.iter()
+            .enumerate()
+            .rev()
+            .filter(|(_, entry)| entry.matches(&self.history_search))
+            .map(|(idx, entry)| Self::history_item(entry, idx, ready).into());
+
+        widget::section_with_header(
+            iced::widget::text("History"),
+            iced::widget::column![
+                iced::widget::text_input("Search history", &self.history_search)
+                    .on_input(Message::HistorySearchUpdated),
+                self.history_error.as_ref()
+                    .map(iced::widget::text),
+                iced::widget::scrollable(
+                    iced::widget::column(entries)
+                        .spacing(5)
+                )
+                .width(iced::Fill)
+                .height(iced::Fill),
+            ]
+            .spacing(10)
+        )
+    }
+
+    // Entries are only re-sent when no request is being sent
+    fn history_item(entry: &history::Entry, idx: usize, ready: bool) -> iced::widget::Container<'_, Message> {
+        let status = entry.status
+            .map(|status| status.to_string())
+            .unwrap_or("failed".to_owned());
+
+        iced::widget::container(
+            iced::widget::column![
+                iced::widget::text(format!("{} {}", entry.method, entry.path)),
+                iced::widget::text(format!(
+                    "{} | {} | {} ms", 
+                    entry.timestamp.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S"),
+                    status,
+                    entry.duration_ms
+                ))
+                .size(12),
+                iced::widget::row![
+                    iced::widget::button(iced::widget::text("Restore").size(12))
+                        .on_press(Message::HistoryRestorePressed(idx)),
+                    iced::widget::button(iced::widget::text("Re-send").size(12))
+                        .on_press_maybe(ready.then_some(Message::HistoryResendPressed(idx))),
+                ]
+                .spacing(5),
+            ]
+            .spacing(5)
+        )
+        .width(iced::Fill)
+        .padding(5)
+        .style(iced::widget::container::bordered_box)
+    }
+
     pub fn response(&self) -> iced::widget::Container<'_, Message> {
+        let body: iced::Element<'_, Message> = match &self.result {
+            Some(Ok(ResponseBody::Json(tree))) => iced::Element::from(tree.view_scrollable())
+                .map(Message::ResponseTreeUpdated),
+            // Laid out line by line so that only the lines in view are rendered
+            Some(Ok(ResponseBody::Text(text))) => widget::virtual_list::virtual_list(
+                &self.response_text_list,
+                widget::virtual_list::Heights::Fixed {
+                    count: text.lines().count(),
+                    height: widget::json_tree::ROW_HEIGHT,
+                },
+                |range| text.lines()
+                    .skip(range.start)
+                    .take(range.len())
+                    .map(|line| iced_selection::text(line).font(iced::Font::MONOSPACE).size(14).into())
+                    .collect(),
+                Message::ResponseTextScrolled
+            )
+            .into(),
+            Some(Err(msg)) => iced::widget::scrollable(iced_selection::text(msg))
+                .width(iced::Fill)
+                .height(iced::Fill)
+                .into(),
+            None => iced::widget::space::vertical().into(),
+        };
+
+        widget::section_with_header(
+            iced::widget::text("Results"), 
+            body
+        )
+    }
+
+    fn console_responses(&self) -> iced::widget::Container<'_, Message> {
+        let responses = self.console_responses.iter().enumerate().map(|(idx, response)| {
+            let stats = match (response.status, response.duration_ms) {
+                (Some(status), Some(duration_ms)) => format!("{} | {} ms", status, duration_ms),
+                (None, Some(duration_ms)) => format!("failed | {} ms", duration_ms),
+                (_, None) => "not sent".to_owned(),
+            };
+
+            widget::section_with_header(
+                iced::widget::row![
+                    iced::widget::text(&response.label),
+                    iced::widget::space::horizontal(),
+                    iced::widget::text(stats).size(12),
+                ]
+                .align_y(iced::Center),
+                Self::response_body(&response.result, move |message| Message::ConsoleResponseTreeUpdated(idx, message))
+            )
+            .width(iced::Fill)
+            .into()
+        });
+
         widget::section_with_header(
             iced::widget::text("Results"), 
             iced::widget::scrollable(
-                self.result.as_ref().map(|res| {
-                    match res {
-                        Ok(val) => match val {
-                            es::OperationResult::Json(json_val) => 
-                                iced_selection::text(
-                                    serde_json::to_string_pretty(json_val)
-                                        .unwrap_or_else(|err| format!("{} Failed to deserialize {:?}", err, val))),
-                            es::OperationResult::Text(text_val) => iced_selection::text(text_val),
-                        },
-                        Err(msg) => iced_selection::text(msg),
-                    }
-                })
+                iced::widget::column(responses)
+                    .spacing(10)
             )
             .width(iced::Fill)
             .height(iced::Fill)
         )
     }
 
+    fn response_body<'a>(
+        res: &'a Result<ResponseBody, String>,
+        on_tree_message: impl Fn(widget::json_tree::Message) -> Message + 'a
+    ) -> iced::Element<'a, Message> {
+        match res {
+            Ok(ResponseBody::Json(tree)) => iced::Element::from(tree.view()).map(on_tree_message),
+            Ok(ResponseBody::Text(text)) => iced_selection::text(text).into(),
+            Err(msg) => iced_selection::text(msg).into(),
+        }
+    }
+
     pub fn try_invoke_es_operation_with_client(
         client_res: Result<es::ElasticsearchClient, String>,
+        cluster: String,
         method: es::ElasticSearchMethodType,
         path: String,
         body: String
     ) -> iced::Task<Message> {
-        iced::Task::perform(
+        iced::Task::future(
             async move {
-                let client = client_res?;
+                let timestamp = chrono::Utc::now();
+                let start = std::time::Instant::now();
 
-                let body_json = (!body.is_empty()).then(|| {
-                    serde_json::from_str::<serde_json::Value>(&body)
-                })
-                .transpose()
-                .map_err(|err| err.to_string())?;
-                
-                client.operation(method, path.as_ref(), body_json.as_ref()).await.map_err(|err| {
-                    err.to_string()
-                })
-            }, 
-            Message::HTTPOperationReturned
+                let (res, status) = Self::es_operation(client_res, method, &path, &body).await;
+
+                let entry = history::Entry {
+                    method,
+                    path,
+                    body,
+                    timestamp,
+                    status,
+                    duration_ms: start.elapsed().as_millis() as u64,
+                };
+
+                Message::HTTPOperationReturned(res, cluster, entry)
+            }
+        )
+    }
+
+    pub fn try_load_completions_with_client(
+        client_res: Result<es::ElasticsearchClient, String>,
+        cluster: String,
+    ) -> iced::Task<Message> {
+        iced::Task::future(
+            async move {
+                let (completer, errors) = match client_res {
+                    Ok(client) => autocomplete::Completer::load(&client).await,
+                    Err(err) => (Default::default(), vec![format!("names from the cluster: {}", err)]),
+                };
+
+                Message::CompletionsLoaded(cluster, completer, errors)
+            }
         )
     }
+
+    // Also returns the response status code if one was received
+    async fn es_operation(
+        client_res: Result<es::ElasticsearchClient, String>,
+        method: es::ElasticSearchMethodType,
+        path: &str,
+        body: &str
+    ) -> (Result<es::OperationResult, String>, Option<u16>) {
+        let client = match client_res {
+            Ok(client) => client,
+            Err(err) => return (Err(err), None),
+        };
+
+        let body_json = match (!body.is_empty()).then(|| serde_json::from_str::<serde_json::Value>(body)).transpose() {
+            Ok(body_json) => body_json,
+            Err(err) => return (Err(err.to_string()), None),
+        };
+
+        Self::with_status(client.operation_with_status(method, path, body_json.as_ref()).await)
+    }
+
+    // Error statuses come with their response, errors are left for failures to send the request
+    fn with_status(
+        res: Result<(u16, es::OperationResult), Box<dyn std::error::Error>>
+    ) -> (Result<es::OperationResult, String>, Option<u16>) {
+        match res {
+            Ok((status, res)) => (Ok(res), Some(status)),
+            Err(err) => (Err(err.to_string()), None),
+        }
+    }
+
+    /**
+     * Requests are sent one after another, in order, as later requests commonly depend on
+     * earlier ones (e.g. create an index then index documents into it).
+     */
+    pub fn try_invoke_console_requests_with_client(
+        client_res: Result<es::ElasticsearchClient, String>,
+        cluster: String,
+        requests: Vec<console::ConsoleRequest>
+    ) -> iced::Task<Message> {
+        let client = match client_res {
+            Ok(client) => Arc::new(client),
+            Err(err) => return iced::Task::done(Message::ConsoleRunFinished(Err(err))),
+        };
+
+        requests.into_iter()
+            .map(|request| {
+                let client = client.clone();
+                let cluster = cluster.clone();
+                iced::Task::future(async move {
+                    let timestamp = chrono::Utc::now();
+                    let start = std::time::Instant::now();
+
+                    let (res, status) = Self::console_operation(&client, &request).await;
+
+                    let entry = history::Entry {
+                        method: request.method,
+                        body: request.body_text(),
+                        path: request.path,
+                        timestamp,
+                        status,
+                        duration_ms: start.elapsed().as_millis() as u64,
+                    };
+
+                    Message::ConsoleResponseReturned(res, cluster, entry)
+                })
+            })
+            .fold(iced::Task::none(), iced::Task::chain)
+            .chain(iced::Task::done(Message::ConsoleRunFinished(Ok(()))))
+    }
+
+    pub fn try_copy_as_curl_with_client(
+        client_res: Result<es::ElasticsearchClient, String>,
+        method: es::ElasticSearchMethodType,
+        path: String,
+        body: String,
+        reveal_secrets: bool
+    ) -> iced::Task<Message> {
+        iced::Task::future(async move {
+            let client = client_res?;
+            curl::command(&client, method, &path, Some(&body), reveal_secrets).await
+                .map_err(|err| err.to_string())
+        })
+        .then(|res| match res {
+            Ok(command) => iced::clipboard::write(command)
+                .chain(iced::Task::done(Message::CurlCopied(Ok(())))),
+            Err(err) => iced::Task::done(Message::CurlCopied(Err(err))),
+        })
+    }
+
+    async fn console_operation(
+        client: &es::ElasticsearchClient,
+        request: &console::ConsoleRequest
+    ) -> (Result<es::OperationResult, String>, Option<u16>) {
+        let res = match request.bodies.as_slice() {
+            [] => client.operation_with_status(request.method, &request.path, None).await,
+            [body] => client.operation_with_status(request.method, &request.path, Some(body)).await,
+            bodies => client.operation_ndjson_with_status(request.method, &request.path, bodies).await,
+        };
+
+        Self::with_status(res)
+    }
 }
@@ -0,0 +1,100 @@
+use std::sync::LazyLock;
+
+use iced::futures::lock::Mutex;
+
+use crate::{es, logs, util};
+
+// Oldest entries are dropped once a cluster's history grows past this
+const MAX_ENTRIES: usize = 500;
+
+// Appends of requests sent at the same time would otherwise load the same history and drop each other's entry
+static APPEND: LazyLock<Mutex<()>> = LazyLock::new(|| Mutex::new(()));
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Entry {
+    pub method: es::ElasticSearchMethodType,
+    pub path: String,
+    pub body: String,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    // None when the request never got a response (e.g. connection failure, invalid body)
+    pub status: Option<u16>,
+    pub duration_ms: u64,
+}
+
+impl Entry {
+    pub fn matches(&self, search: &str) -> bool {
+        if search.is_empty() {
+            return true;
+        }
+
+        let search = search.to_lowercase();
+
+        self.method.as_str().to_lowercase().contains(&search)
+            || self.path.to_lowercase().contains(&search)
+            || self.body.to_lowercase().contains(&search)
+    }
+}
+
+fn history_file(cluster: &str) -> Result<std::path::PathBuf, String> {
+    util::app_data_dir()
+        .map(|dir| dir.join("history").join(format!("{}.json", util::cluster_file_name(cluster))))
+        .ok_or("Unable to determine data directory".to_owned())
+}
+
+/**
+ * Entries are ordered oldest first.
+ * A cluster without any history yet yields an empty list, as does a corrupt history, which is
+ * moved aside (to <cluster>.json.corrupt) rather than blocking any further history.
+ */
+pub async fn load(log: logs::Log, cluster: String) -> Result<Vec<Entry>, String> {
+    let path = history_file(&cluster)?;
+
+    match tokio::fs::read_to_string(&path).await {
+        Ok(contents) => match serde_json::from_str::<Vec<Entry>>(&contents) {
+            Ok(entries) => Ok(entries),
+            Err(err) => {
+                let corrupt = path.with_extension("json.corrupt");
+                log.error(format!("Unable to parse {}, starting an empty history: {}", path.to_string_lossy(), err));
+                tokio::fs::rename(&path, &corrupt).await
+                    .map_err(|err| format!("Unable to move {} aside, error kind: {}", path.to_string_lossy(), err.kind()))?;
+                Ok(Vec::new())
+            },
+        },
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(err) => Err(format!("Unable to read {}, error kind: {}", path.to_string_lossy(), err.kind())),
+    }
+}
+
+pub async fn save(cluster: String, entries: Vec<Entry>) -> Result<(), String> {
+    let path = history_file(&cluster)?;
+
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await
+            .map_err(|err| format!("Unable to create {}, error kind: {}", parent.to_string_lossy(), err.kind()))?;
+    }
+
+    let contents = serde_json::to_string(&entries).map_err(|err| err.to_string())?;
+
+    tokio::fs::write(&path, contents).await
+        .map_err(|err| format!("Unable to write {}, error kind: {}", path.to_string_lossy(), err.kind()))
+}
+
+/**
+ * Appends to the history stored on disk rather than the one held in memory so that
+ * entries recorded for a cluster other than the displayed one are not lost.
+ * Returns the full updated history of the cluster.
+ */
+pub async fn append(log: logs::Log, cluster: String, entry: Entry) -> Result<Vec<Entry>, String> {
+    let _append = APPEND.lock().await;
+
+    let mut entries = load(log, cluster.clone()).await?;
+    entries.push(entry);
+
+    if entries.len() > MAX_ENTRIES {
+        entries.drain(..entries.len() - MAX_ENTRIES);
+    }
+
+    save(cluster, entries.clone()).await?;
+
+    Ok(entries)
+}
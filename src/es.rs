@@ -1,7 +1,17 @@
 use aws_credential_types::provider::ProvideCredentials;
 use std::fmt::Write;
 mod types;
+mod query;
+mod search_body;
+mod aggregations;
+mod data_streams;
+mod aliases;
 pub use crate::es::types::*;
+pub use crate::es::query::*;
+pub use crate::es::search_body::*;
+pub use crate::es::aggregations::*;
+pub use crate::es::data_streams::*;
+pub use crate::es::aliases::*;
 
 #[derive(Debug)]
 pub enum Auth {
@@ -42,7 +52,7 @@ impl ClientConfig {
             builder = builder.add_root_certificate(cert.clone());
         }
 
-        return builder.build();
+        builder.build()
     }
 }
 
@@ -53,21 +63,45 @@ struct ElasticSearchError {
 
 impl ElasticSearchError {
     fn new(err: String) -> Self {
-        return Self {
-            err: err,
+        Self {
+            err,
         }
     }
 }
 
 impl std::fmt::Display for ElasticSearchError {
     fn fmt (&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        return write!(f, "{}", self.err);
+        write!(f, "{}", self.err)
     }
 }
 
+// Error status of an operation, along with the response explaining it
+#[derive(Debug, Clone)]
+pub struct OperationStatusError {
+    pub status: u16,
+    pub response: OperationResult,
+}
+
+impl std::fmt::Display for OperationStatusError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        // {"error": {"type": "index_not_found_exception", "reason": "no such index [x]"}, "status": 404}
+        let reason = match &self.response {
+            OperationResult::Json(json) => match (json.pointer("/error/type"), json.pointer("/error/reason")) {
+                (Some(error_type), Some(reason)) => format!("{}: {}", error_type.as_str().unwrap_or_default(), reason.as_str().unwrap_or_default()),
+                _ => json.get("error").unwrap_or(json).to_string(),
+            },
+            OperationResult::Text(text) => text.trim().to_owned(),
+        };
+
+        write!(f, "HTTP status {}: {}", self.status, reason)
+    }
+}
+
+impl std::error::Error for OperationStatusError {}
+
 impl std::error::Error for ElasticSearchError {}
 
-#[derive(serde::Deserialize)]
+#[derive(Debug, Clone, serde::Deserialize)]
 pub struct ElasticSearchIndex {
     #[serde(rename = "index")]
     pub name: String,
@@ -82,6 +116,21 @@ pub struct ElasticSearchIndex {
     pub docs_deleted_count: Option<String>,
     #[serde(default, rename="dataset.size")]
     pub dataset_size: Option<String>,
+    // green, yellow or red, missing for closed indicies
+    #[serde(default)]
+    pub health: Option<String>,
+    // open or close
+    #[serde(default)]
+    pub status: Option<String>,
+    // Only set by get_indicies_with_hidden, _cat/indices does not tell
+    #[serde(skip)]
+    pub hidden: bool,
+}
+
+impl ElasticSearchIndex {
+    pub fn is_closed(&self) -> bool {
+        self.status.as_deref() == Some("close")
+    }
 }
 
 #[derive(serde::Deserialize)]
@@ -92,14 +141,16 @@ pub struct ElasticSearchAlias {
     pub index_ref: String,
 }
 
-#[derive(Default, Debug, Clone, Copy, PartialEq)]
+#[derive(Default, Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum ElasticSearchMethodType {
     #[default]
     GET,
     POST,
     PUT,
     PATCH,
-    DELETE
+    DELETE,
+    // Only the status tells the outcome, e.g. HEAD my-index checks whether it exists
+    HEAD,
 }
 
 impl std::fmt::Display for ElasticSearchMethodType {
@@ -108,6 +159,22 @@ impl std::fmt::Display for ElasticSearchMethodType {
     }
 }
 
+impl std::str::FromStr for ElasticSearchMethodType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().as_str() {
+            "GET" => Ok(ElasticSearchMethodType::GET),
+            "POST" => Ok(ElasticSearchMethodType::POST),
+            "PUT" => Ok(ElasticSearchMethodType::PUT),
+            "PATCH" => Ok(ElasticSearchMethodType::PATCH),
+            "DELETE" => Ok(ElasticSearchMethodType::DELETE),
+            "HEAD" => Ok(ElasticSearchMethodType::HEAD),
+            _ => Err(format!("Unsupported method {}", s)),
+        }
+    }
+}
+
 impl ElasticSearchMethodType {
     pub fn as_str(&self) -> &str {
         match self  {
@@ -116,6 +183,7 @@ impl ElasticSearchMethodType {
             ElasticSearchMethodType::PUT => "PUT",
             ElasticSearchMethodType::PATCH => "PATCH",
             ElasticSearchMethodType::DELETE => "DELETE",
+            ElasticSearchMethodType::HEAD => "HEAD",
         }
     }
 }
@@ -123,19 +191,27 @@ impl ElasticSearchMethodType {
 impl ElasticsearchClient {
     pub fn new(root_url: String) -> Result<Self, Box<dyn std::error::Error>> {
         let config = ClientConfig {
-            root_url: root_url,
+            root_url,
             auth: None,
             cert: None,
         };
 
         let client = config.build_reqwest_client()?;
 
-        return Ok(Self {
-            config: config,
-            client: client,
+        Ok(Self {
+            config,
+            client,
         })
     }
 
+    pub fn root_url(&self) -> &str {
+        &self.config.root_url
+    }
+
+    pub fn auth(&self) -> Option<&Auth> {
+        self.config.auth.as_ref()
+    }
+
     pub fn use_auth(&mut self, auth: Auth) {
         self.config.auth = Some(auth);
     }
@@ -154,26 +230,26 @@ impl ElasticsearchClient {
 
         self.client = self.config.build_reqwest_client()?;
 
-        return Ok(());
+        Ok(())
     }
 
     pub fn use_custom_pem_certificate_from_buf(&mut self, buffer: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
         let cert = reqwest::Certificate::from_pem(buffer)?;
 
-        return self.use_custom_certificate(cert);
+        self.use_custom_certificate(cert)
     }
 
     pub fn use_custom_der_certificate_from_buf(&mut self, buffer: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
         let cert = reqwest::Certificate::from_der(buffer)?;
 
-        return self.use_custom_certificate(cert);
+        self.use_custom_certificate(cert)
     }
 
     pub fn use_custom_certificate(&mut self, certificate: reqwest::tls::Certificate) -> Result<(), Box<dyn std::error::Error>> {
         self.config.cert = Some(certificate);
         self.client = self.config.build_reqwest_client()?;
 
-        return Ok(())
+        Ok(())
     }
 
     // True by default
@@ -182,7 +258,7 @@ impl ElasticsearchClient {
 
         self.client = self.config.build_reqwest_client()?;
 
-        return Ok(());
+        Ok(())
     }
 
     pub async fn test_connection(&self) -> Result<(), Box<dyn std::error::Error>> {
@@ -191,17 +267,14 @@ impl ElasticsearchClient {
 
         let request = self.request_add_auth(builder).await?;
 
-        self.client.execute(request).await.map_err(|err| {
-            let msg = ElasticsearchClient::report(&err);
-            println!("error report: {}", msg);
-            ElasticSearchError {
-                err: msg,
-            }
-        })?.error_for_status()?;
+        // The report is shown by the caller, printing it would mix it with the output of the CLI
+        self.execute_json(request).await.map_err(|err| ElasticSearchError {
+            err: report(err.as_ref()),
+        })?;
 
         // TODO check if response matches expected
 
-        return Ok(());
+        Ok(())
     }
 
     async fn request_add_auth(&self, request_builder: reqwest::RequestBuilder) -> Result<reqwest::Request, Box<dyn std::error::Error>> {
@@ -216,7 +289,7 @@ impl ElasticsearchClient {
                 },
             }
         }
-        return Ok(request_builder.build()?);
+        Ok(request_builder.build()?)
     }
 
     // Result<Vec<ElasticSearchIndex>, Box<dyn std::error::Error>>
@@ -229,12 +302,38 @@ impl ElasticsearchClient {
 
         let request = self.request_add_auth(builder).await?;
 
-        let res = self.client.execute(request).await?
-            .error_for_status()?
-            .text()
-            .await?;
+        let res = self.execute_json(request).await?;
+
+        Ok(serde_json::from_value::<Vec<ElasticSearchIndex>>(res)?)
+    }
+
+    /**
+     * Hidden indicies along with the others, marked as such.
+     * Hidden indicies include the backing indicies of data streams and most system indicies.
+     */
+    pub async fn get_indicies_with_hidden(&self) -> Result<Vec<ElasticSearchIndex>, Box<dyn std::error::Error>> {
+        let base_url = reqwest::Url::parse(&self.config.root_url)?;
+        let indicies_url = base_url.join("_cat/indices?expand_wildcards=all&format=json")?;
+        // Indicies without the setting are listed with empty settings
+        let hidden_url = base_url.join("_all/_settings/index.hidden?expand_wildcards=all&flat_settings=true")?;
+
+        let request = self.request_add_auth(self.client.get(indicies_url)).await?;
+        let res = self.execute_json(request).await?;
+        let mut indicies = serde_json::from_value::<Vec<ElasticSearchIndex>>(res)?;
 
-        Ok(serde_json::from_str::<Vec<ElasticSearchIndex>>(&res)?)
+        let request = self.request_add_auth(self.client.get(hidden_url)).await?;
+        let settings = serde_json::from_value::<std::collections::HashMap<String, serde_json::Value>>(
+            self.execute_json(request).await?
+        )?;
+
+        for index in indicies.iter_mut() {
+            index.hidden = settings.get(&index.name)
+                .and_then(|settings| settings.pointer("/settings/index.hidden"))
+                .and_then(serde_json::Value::as_str)
+                .is_some_and(|hidden| hidden == "true");
+        }
+
+        Ok(indicies)
     }
 
     pub async fn get_aliases(&self) -> Result<Vec<ElasticSearchAlias>, Box<dyn std::error::Error>> {
@@ -245,12 +344,174 @@ impl ElasticsearchClient {
 
         let request = self.request_add_auth(builder).await?;
 
-        let res = self.client.execute(request).await?
-            .error_for_status()?
-            .text()
-            .await?;
+        let res = self.execute_json(request).await?;
+
+        Ok(serde_json::from_value::<Vec<ElasticSearchAlias>>(res)?)
+    }
+
+    // Aliases of every index, hidden ones included, sorted by alias then index
+    pub async fn get_index_aliases(&self) -> Result<Vec<IndexAlias>, Box<dyn std::error::Error>> {
+        #[derive(serde::Deserialize)]
+        struct IndexAliases {
+            #[serde(default)]
+            aliases: std::collections::HashMap<String, AliasOptions>,
+        }
+
+        let base_url = reqwest::Url::parse(&self.config.root_url)?;
+        let url = base_url.join("_alias?expand_wildcards=all")?;
+
+        let request = self.request_add_auth(self.client.get(url)).await?;
+
+        let res = self.execute_json(request).await?;
+
+        let mut aliases = serde_json::from_value::<std::collections::HashMap<String, IndexAliases>>(res)?
+            .into_iter()
+            .flat_map(|(index, index_aliases)| {
+                index_aliases.aliases.into_iter()
+                    .map(move |(alias, options)| IndexAlias { alias, index: index.clone(), options })
+            })
+            .collect::<Vec<IndexAlias>>();
+        aliases.sort_by(|a, b| a.alias.cmp(&b.alias).then(a.index.cmp(&b.index)));
+
+        Ok(aliases)
+    }
+
+    /**
+     * Applies the actions atomically, either all of them or none.
+     * See https://www.elastic.co/docs/api/doc/elasticsearch/operation/operation-indices-update-aliases
+     */
+    pub async fn update_aliases(&self, actions: &[AliasAction]) -> Result<(), Box<dyn std::error::Error>> {
+        let base_url = reqwest::Url::parse(&self.config.root_url)?;
+        let url = base_url.join("_aliases")?;
+
+        let body = serde_json::json!({ "actions": actions });
+        let request = self.request_add_auth(self.client.post(url).json(&body)).await?;
+
+        self.execute_json(request).await?;
+
+        Ok(())
+    }
+
+    // Hidden and system data streams included
+    pub async fn get_data_streams(&self) -> Result<Vec<DataStream>, Box<dyn std::error::Error>> {
+        #[derive(serde::Deserialize)]
+        struct Response {
+            data_streams: Vec<DataStream>,
+        }
+
+        let base_url = reqwest::Url::parse(&self.config.root_url)?;
+        let url = base_url.join("_data_stream?expand_wildcards=all")?;
+
+        let request = self.request_add_auth(self.client.get(url)).await?;
+
+        let res = self.execute_json(request).await?;
+
+        Ok(serde_json::from_value::<Response>(res)?.data_streams)
+    }
+
+    pub async fn get_data_stream_stats(&self) -> Result<Vec<DataStreamStats>, Box<dyn std::error::Error>> {
+        #[derive(serde::Deserialize)]
+        struct Response {
+            data_streams: Vec<DataStreamStats>,
+        }
+
+        let base_url = reqwest::Url::parse(&self.config.root_url)?;
+        let url = base_url.join("_data_stream/_stats?expand_wildcards=all&human=true")?;
+
+        let request = self.request_add_auth(self.client.get(url)).await?;
+
+        let res = self.execute_json(request).await?;
+
+        Ok(serde_json::from_value::<Response>(res)?.data_streams)
+    }
+
+    /**
+     * Rolls a data stream or an alias with a write index over to a new write index, unconditionally.
+     * See https://www.elastic.co/docs/api/doc/elasticsearch/operation/operation-indices-rollover
+     */
+    pub async fn rollover(&self, target: &str) -> Result<RolloverResult, Box<dyn std::error::Error>> {
+        let base_url = reqwest::Url::parse(&self.config.root_url)?;
+        let url = base_url
+            .join((target.to_owned() + "/").as_str())?
+            .join("_rollover")?;
+
+        let request = self.request_add_auth(self.client.post(url)).await?;
+
+        let res = self.execute_json(request).await?;
+
+        Ok(serde_json::from_value::<RolloverResult>(res)?)
+    }
+
+    // Deletes the backing indicies along with the data stream
+    pub async fn delete_data_stream(&self, name: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let base_url = reqwest::Url::parse(&self.config.root_url)?;
+        let url = base_url.join("_data_stream/")?.join(name)?;
+
+        let request = self.request_add_auth(self.client.delete(url)).await?;
+
+        self.execute_json(request).await?;
+
+        Ok(())
+    }
+
+    /**
+     * Fields of the mappings of the given indicies, sorted by name and deduplicated across indicies.
+     * indicies as an empty list implies all indicies
+     */
+    pub async fn get_field_mappings(&self, indicies: &[String]) -> Result<Vec<FieldMapping>, Box<dyn std::error::Error>> {
+        let base_url = reqwest::Url::parse(&self.config.root_url)?;
+
+        let url = if indicies.is_empty() {
+            base_url.join("_mapping")?
+        } else {
+            base_url
+                .join((indicies.join(",") + "/").as_str())?
+                .join("_mapping")?
+        };
+
+        let builder = self.client.get(url);
+
+        let request = self.request_add_auth(builder).await?;
+
+        let res = self.execute_json(request).await?;
+
+        let mut fields = std::collections::BTreeMap::new();
+
+        if let Some(indicies) = res.as_object() {
+            for index in indicies.values() {
+                if let Some(properties) = index.pointer("/mappings/properties") {
+                    ElasticsearchClient::flatten_mapping_properties("", properties, &mut fields);
+                }
+            }
+        }
+
+        Ok(fields.into_iter()
+            .map(|(name, field_type)| FieldMapping { name, field_type })
+            .collect())
+    }
+
+    fn flatten_mapping_properties(prefix: &str, properties: &serde_json::Value, fields: &mut std::collections::BTreeMap<String, String>) {
+        let Some(properties) = properties.as_object() else {
+            return;
+        };
+
+        for (name, mapping) in properties {
+            let name = format!("{}{}", prefix, name);
+
+            if let Some(field_type) = mapping.get("type").and_then(serde_json::Value::as_str) {
+                fields.entry(name.clone()).or_insert(field_type.to_owned());
+            }
+
+            // Object and nested fields
+            if let Some(properties) = mapping.get("properties") {
+                ElasticsearchClient::flatten_mapping_properties(&format!("{}.", name), properties, fields);
+            }
 
-        Ok(serde_json::from_str::<Vec<ElasticSearchAlias>>(&res)?)
+            // Multi-fields, e.g. "title.keyword"
+            if let Some(multi_fields) = mapping.get("fields") {
+                ElasticsearchClient::flatten_mapping_properties(&format!("{}.", name), multi_fields, fields);
+            }
+        }
     }
 
     /**
@@ -261,7 +522,7 @@ impl ElasticsearchClient {
      * For body params, anything listed https://www.elastic.co/docs/api/doc/elasticsearch/operation/operation-search#operation-search-body-application-json
      * is valid.
      **/
-    pub async fn search(&self, indicies: &Vec<String>, body_params: Option<&serde_json::Value>) -> Result<OperationSearchResult, Box<dyn std::error::Error>> {
+    pub async fn search(&self, indicies: &[String], body_params: Option<&serde_json::Value>) -> Result<OperationSearchResult, Box<dyn std::error::Error>> {
         let base_url = reqwest::Url::parse(&self.config.root_url)?;
 
         let url = base_url
@@ -276,27 +537,72 @@ impl ElasticsearchClient {
 
         let request = self.request_add_auth(builder).await?;
 
-        let res = self.client.execute(request).await?
-            .error_for_status()?
-            .text()
-            .await?;
+        let res = self.execute_json(request).await?;
 
-        Ok(serde_json::from_str::<OperationSearchResult>(&res)?)
+        Ok(serde_json::from_value::<OperationSearchResult>(res)?)
     }
 
     /**
      * See https://www.elastic.co/docs/reference/query-languages/query-dsl/query-dsl-query-string-query
      */
-    pub async fn query_string(&self, indicies: &Vec<String>, query: String) -> Result<OperationSearchResult, Box<dyn std::error::Error>> {
-        let body = serde_json::json!({
-            "query": {
-                "query_string": {
-                    "query": query,
-                }
-            }
-        });
+    pub async fn query_string(
+        &self,
+        indicies: &[String],
+        query: String,
+        sort: &[Sort]
+    ) -> Result<OperationSearchResult, Box<dyn std::error::Error>> {
+        let body = SearchBody {
+            query: Some(Query::query_string(query)),
+            sort: sort.to_vec(),
+            ..Default::default()
+        };
+
+        self.search(indicies, Some(&body.to_json())).await
+    }
+
+    /**
+     * Opens a point in time over the given indicies, to be passed as "pit" in search bodies.
+     * indicies as an empty list implies all indicies
+     * See https://www.elastic.co/docs/api/doc/elasticsearch/operation/operation-open-point-in-time
+     */
+    pub async fn open_point_in_time(&self, indicies: &[String], keep_alive: &str) -> Result<String, Box<dyn std::error::Error>> {
+        let base_url = reqwest::Url::parse(&self.config.root_url)?;
+
+        let target = if indicies.is_empty() {
+            "*".to_owned()
+        } else {
+            indicies.join(",")
+        };
+
+        let mut url = base_url
+            .join((target + "/").as_str())?
+            .join("_pit")?;
+        url.query_pairs_mut().append_pair("keep_alive", keep_alive);
+
+        let builder = self.client.post(url);
+
+        let request = self.request_add_auth(builder).await?;
+
+        let res = self.execute_json(request).await?;
+
+        res.get("id")
+            .and_then(serde_json::Value::as_str)
+            .map(str::to_owned)
+            .ok_or_else(|| ElasticSearchError::new("Point in time response is missing its id".to_owned()).into())
+    }
 
-        self.search(indicies, Some(&body)).await
+    pub async fn close_point_in_time(&self, id: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let base_url = reqwest::Url::parse(&self.config.root_url)?;
+        let url = base_url.join("_pit")?;
+
+        let builder = self.client.delete(url)
+            .json(&serde_json::json!({ "id": id }));
+
+        let request = self.request_add_auth(builder).await?;
+
+        self.execute_json(request).await?;
+
+        Ok(())
     }
 
     pub async fn operation(
@@ -306,47 +612,128 @@ impl ElasticsearchClient {
         body: Option<&serde_json::Value>
     ) -> Result<OperationResult, Box<dyn std::error::Error>> 
     {
+        let (status, response) = self.operation_with_status(method_type, path, body).await?;
+
+        if !(200..300).contains(&status) {
+            return Err(OperationStatusError { status, response }.into());
+        }
+
+        Ok(response)
+    }
+
+    /**
+     * Same as `operation` but also returns the status code of the response.
+     * Error statuses are returned along with the response, which tells what went wrong (e.g. index_not_found_exception).
+     */
+    pub async fn operation_with_status(
+        &self, 
+        method_type: ElasticSearchMethodType, 
+        path: &str, 
+        body: Option<&serde_json::Value>
+    ) -> Result<(u16, OperationResult), Box<dyn std::error::Error>> 
+    {
+        let mut builder = self.operation_builder(method_type, path)?;
+
+        if let Some(request_body) = body {
+            builder = builder.json(request_body);
+        }
+
+        self.execute_operation(builder).await
+    }
+
+    /**
+     * Sends each body as a line of NDJSON, as expected by APIs such as `_bulk` and `_msearch`.
+     * See https://www.elastic.co/docs/api/doc/elasticsearch/operation/operation-bulk
+     */
+    pub async fn operation_ndjson_with_status(
+        &self, 
+        method_type: ElasticSearchMethodType, 
+        path: &str, 
+        bodies: &[serde_json::Value]
+    ) -> Result<(u16, OperationResult), Box<dyn std::error::Error>> 
+    {
+        let mut ndjson = String::new();
+        for body in bodies {
+            ndjson.push_str(&serde_json::to_string(body)?);
+            ndjson.push('\n');
+        }
+
+        let builder = self.operation_builder(method_type, path)?
+            .header(reqwest::header::CONTENT_TYPE, "application/x-ndjson")
+            .body(ndjson);
+
+        self.execute_operation(builder).await
+    }
+
+    fn operation_builder(&self, method_type: ElasticSearchMethodType, path: &str) -> Result<reqwest::RequestBuilder, Box<dyn std::error::Error>> {
         let base_url = reqwest::Url::parse(&self.config.root_url)?;
         let url = base_url.join(path)?;
 
-        let mut builder = match method_type {
+        Ok(match method_type {
             ElasticSearchMethodType::POST => self.client.post(url),
             ElasticSearchMethodType::GET => self.client.get(url),
             ElasticSearchMethodType::PUT => self.client.put(url),
             ElasticSearchMethodType::PATCH => self.client.patch(url),
             ElasticSearchMethodType::DELETE => self.client.delete(url),
-        };
+            ElasticSearchMethodType::HEAD => self.client.head(url),
+        })
+    }
 
-        if let Some(request_body) = body {
-            builder = builder.json(request_body);
+    async fn execute_operation(&self, builder: reqwest::RequestBuilder) -> Result<(u16, OperationResult), Box<dyn std::error::Error>> {
+        let request = self.request_add_auth(builder).await?;
+
+        self.execute_request(request).await
+    }
+
+    /**
+     * JSON of a successful response.
+     * Error statuses fail as an OperationStatusError, along with the response telling why (e.g. index_not_found_exception).
+     */
+    async fn execute_json(&self, request: reqwest::Request) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+        let (status, response) = self.execute_request(request).await?;
+
+        if !(200..300).contains(&status) {
+            return Err(OperationStatusError { status, response }.into());
         }
 
-        let request = self.request_add_auth(builder).await?;
+        match response {
+            OperationResult::Json(json) => Ok(json),
+            OperationResult::Text(text) => Err(ElasticSearchError::new(format!("Expected a JSON response, got: {}", text)).into()),
+        }
+    }
 
-        let res = self.client.execute(request).await?
-            .error_for_status()?
-            .text()
-            .await?;
+    async fn execute_request(&self, request: reqwest::Request) -> Result<(u16, OperationResult), Box<dyn std::error::Error>> {
+        // The body of error statuses is returned as well, it explains the error
+        let response = self.client.execute(request).await?;
 
-        Ok(serde_json::from_str::<serde_json::Value>(&res).map_or_else(
+        let status = response.status().as_u16();
+
+        let res = response.text().await?;
+
+        Ok((status, serde_json::from_str::<serde_json::Value>(&res).map_or_else(
             |_err| {
                 OperationResult::Text(res)
             },
             |json_val| {
                 OperationResult::Json(json_val)
             }
-        ))
+        )))
     }
 
-    async fn sign_request_sigv4(request: &mut reqwest::Request, config: &AwsSigv4) -> Result<(), Box<dyn std::error::Error>> {
+    pub(crate) async fn aws_credentials(config: &AwsSigv4) -> Result<aws_credential_types::Credentials, Box<dyn std::error::Error>> {
         let mut credentials_provider = aws_config::default_provider::credentials::DefaultCredentialsChain::builder();
         if let Some(profile) = &config.profile {
             credentials_provider = credentials_provider.profile_name(profile);
         }
         let credentials_provider = credentials_provider.build().await;
 
-        let identity = credentials_provider
+        Ok(credentials_provider
             .provide_credentials()
+            .await?)
+    }
+
+    async fn sign_request_sigv4(request: &mut reqwest::Request, config: &AwsSigv4) -> Result<(), Box<dyn std::error::Error>> {
+        let identity = ElasticsearchClient::aws_credentials(config)
             .await?
             .into();
 
@@ -370,9 +757,8 @@ impl ElasticsearchClient {
             .collect::<Result<Vec<_>, _>>()?;
         
         let body = request.body()
-            .map(|b| b.as_bytes())
-            .flatten()
-            .map(|b| aws_sigv4::http_request::SignableBody::Bytes(b))
+            .and_then(|b| b.as_bytes())
+            .map(aws_sigv4::http_request::SignableBody::Bytes)
             .unwrap_or(aws_sigv4::http_request::SignableBody::Bytes(&[]));
 
         let signable = aws_sigv4::http_request::SignableRequest::new(
@@ -399,16 +785,16 @@ impl ElasticsearchClient {
             Err(ElasticSearchError::new("sigv4 signed results not all in request header format".to_owned()))?;
         }
 
-        return Ok(())
+        Ok(())
     }
+}
 
-    // TODO add this for all reqwest error
-    fn report(mut err: &dyn std::error::Error) -> String {
-        let mut s = format!("{}", err);
-        while let Some(src) = err.source() {
-            let _ = write!(s, "\n\nCaused by: {}", src);
-            err = src;
-        }
-        s
+// Message of an error followed by its sources, which is where reqwest errors tell what went wrong
+pub fn report(mut err: &dyn std::error::Error) -> String {
+    let mut s = format!("{}", err);
+    while let Some(src) = err.source() {
+        let _ = write!(s, "\n\nCaused by: {}", src);
+        err = src;
     }
+    s
 }
\ No newline at end of file
@@ -0,0 +1,388 @@
+// Typed aggregation results.
+// Responses do not name the type of their aggregations, so results are told apart by their shape:
+// buckets, a single value, percentile values or stats. Anything else is kept as its JSON.
+use std::collections::BTreeMap;
+
+use crate::es::{Aggregation, AggregationKind, DateHistogramAggregation, Query, RangeParams};
+
+// Results by aggregation name, as found in the "aggregations" of a search response
+pub type Aggregations = BTreeMap<String, AggregationResult>;
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+#[serde(from = "serde_json::Value")]
+pub enum AggregationResult {
+    // terms, date_histogram, histogram, range, filters, composite, ...
+    Buckets(BucketsResult),
+    // filter, nested, global, ... whose documents fall in a single bucket
+    SingleBucket(Bucket),
+    // avg, sum, min, max, cardinality, value_count
+    Value(ValueResult),
+    Percentiles(Vec<Percentile>),
+    // stats and extended_stats
+    Stats(StatsResult),
+    Other(serde_json::Value),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BucketsResult {
+    pub buckets: Vec<Bucket>,
+    // Key to pass as "after" for the next page of a composite aggregation
+    pub after_key: Option<serde_json::Value>,
+    // Documents in terms not returned as buckets
+    pub sum_other_doc_count: Option<u64>,
+    // Interval an auto_date_histogram chose, e.g. "30m" or "1M"
+    pub interval: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bucket {
+    // Term, histogram start (epoch millis for dates), range or filter name, object of source values for composite
+    pub key: serde_json::Value,
+    pub key_as_string: Option<String>,
+    pub doc_count: u64,
+    // Bounds of range buckets
+    pub from: Option<f64>,
+    pub to: Option<f64>,
+    pub aggregations: Aggregations,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueResult {
+    // Missing when no document had a value
+    pub value: Option<f64>,
+    pub value_as_string: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Percentile {
+    // As named in the response, e.g. "99.0"
+    pub percent: String,
+    pub value: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatsResult {
+    pub count: u64,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub avg: Option<f64>,
+    pub sum: Option<f64>,
+}
+
+// Keys of bucket objects that are not sub aggregations
+const BUCKET_KEYS: [&str; 9] = [
+    "key", "key_as_string", "doc_count", "from", "from_as_string", "to", "to_as_string", "meta", "bg_count",
+];
+
+fn sub_aggregations(map: &serde_json::Map<String, serde_json::Value>) -> Aggregations {
+    map.iter()
+        .filter(|(key, value)| !BUCKET_KEYS.contains(&key.as_str()) && value.is_object())
+        .map(|(key, value)| (key.to_owned(), AggregationResult::from(value.clone())))
+        .collect()
+}
+
+fn bucket(key: serde_json::Value, map: &serde_json::Map<String, serde_json::Value>) -> Bucket {
+    Bucket {
+        key,
+        key_as_string: map.get("key_as_string").and_then(serde_json::Value::as_str).map(str::to_owned),
+        doc_count: map.get("doc_count").and_then(serde_json::Value::as_u64).unwrap_or_default(),
+        from: map.get("from").and_then(serde_json::Value::as_f64),
+        to: map.get("to").and_then(serde_json::Value::as_f64),
+        aggregations: sub_aggregations(map),
+    }
+}
+
+fn buckets(value: &serde_json::Value) -> Vec<Bucket> {
+    match value {
+        serde_json::Value::Array(buckets) => buckets.iter()
+            .filter_map(serde_json::Value::as_object)
+            .map(|map| bucket(map.get("key").cloned().unwrap_or_default(), map))
+            .collect(),
+        // Keyed buckets, such as those of filters or keyed ranges
+        serde_json::Value::Object(buckets) => buckets.iter()
+            .filter_map(|(key, value)| value.as_object().map(|map| bucket(key.as_str().into(), map)))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn percentiles(values: &serde_json::Value) -> Vec<Percentile> {
+    match values {
+        serde_json::Value::Object(values) => values.iter()
+            .filter(|(percent, _)| !percent.ends_with("_as_string"))
+            .map(|(percent, value)| Percentile { percent: percent.to_owned(), value: value.as_f64() })
+            .collect(),
+        // Not keyed, [{"key": 99.0, "value": ...}]
+        serde_json::Value::Array(values) => values.iter()
+            .map(|value| Percentile {
+                percent: value.get("key").map(|key| key.to_string()).unwrap_or_default(),
+                value: value.get("value").and_then(serde_json::Value::as_f64),
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+impl From<serde_json::Value> for AggregationResult {
+    fn from(value: serde_json::Value) -> Self {
+        let Some(map) = value.as_object() else {
+            return AggregationResult::Other(value);
+        };
+
+        let number = |key: &str| map.get(key).and_then(serde_json::Value::as_f64);
+
+        if let Some(value) = map.get("buckets") {
+            AggregationResult::Buckets(BucketsResult {
+                buckets: buckets(value),
+                after_key: map.get("after_key").cloned(),
+                sum_other_doc_count: map.get("sum_other_doc_count").and_then(serde_json::Value::as_u64),
+                interval: map.get("interval").and_then(serde_json::Value::as_str).map(str::to_owned),
+            })
+        } else if let Some(values) = map.get("values") && (values.is_object() || values.is_array()) {
+            AggregationResult::Percentiles(percentiles(values))
+        } else if let Some(count) = map.get("count").and_then(serde_json::Value::as_u64)
+            && ["min", "max", "avg", "sum"].iter().all(|key| map.contains_key(*key))
+        {
+            AggregationResult::Stats(StatsResult {
+                count,
+                min: number("min"),
+                max: number("max"),
+                avg: number("avg"),
+                sum: number("sum"),
+            })
+        } else if map.contains_key("value") {
+            AggregationResult::Value(ValueResult {
+                value: number("value"),
+                value_as_string: map.get("value_as_string").and_then(serde_json::Value::as_str).map(str::to_owned),
+            })
+        } else if map.contains_key("doc_count") {
+            AggregationResult::SingleBucket(bucket(serde_json::Value::Null, map))
+        } else {
+            AggregationResult::Other(value)
+        }
+    }
+}
+
+impl Bucket {
+    // Short text naming the bucket, as shown in tables
+    pub fn label(&self) -> String {
+        if let Some(key) = &self.key_as_string {
+            return key.to_owned();
+        }
+
+        match &self.key {
+            serde_json::Value::String(key) => key.to_owned(),
+            serde_json::Value::Null => "-".to_owned(),
+            // Composite keys, one value per source
+            serde_json::Value::Object(sources) => sources.iter()
+                .map(|(source, value)| match value {
+                    serde_json::Value::String(value) => format!("{}={}", source, value),
+                    value => format!("{}={}", source, value),
+                })
+                .collect::<Vec<String>>()
+                .join(", "),
+            key => key.to_string(),
+        }
+    }
+}
+
+// Date math unit of a calendar interval, quarters being 3 months
+fn calendar_unit(interval: &str) -> Option<&'static str> {
+    match interval {
+        "second" | "1s" => Some("1s"),
+        "minute" | "1m" => Some("1m"),
+        "hour" | "1h" => Some("1h"),
+        "day" | "1d" => Some("1d"),
+        "week" | "1w" => Some("1w"),
+        "month" | "1M" => Some("1M"),
+        "quarter" | "1q" => Some("3M"),
+        "year" | "1y" => Some("1y"),
+        _ => None,
+    }
+}
+
+// Fixed interval such as "30s" or "12h" in milliseconds
+fn fixed_interval_millis(interval: &str) -> Option<i64> {
+    let unit_start = interval.find(|c: char| !c.is_ascii_digit())?;
+    let count = interval[..unit_start].parse::<i64>().ok()?;
+
+    let unit_millis = match &interval[unit_start..] {
+        "ms" => 1,
+        "s" => 1_000,
+        "m" => 60_000,
+        "h" => 3_600_000,
+        "d" => 86_400_000,
+        _ => return None,
+    };
+    Some(count * unit_millis)
+}
+
+impl DateHistogramAggregation {
+    /**
+     * Range of the bucket starting at the epoch millis key. Calendar intervals end through date math,
+     * months and days not all having the same length, in the time zone of the aggregation.
+     */
+    fn bucket_range(&self, key: &serde_json::Value) -> Option<RangeParams> {
+        let start = key.as_i64()?;
+        let range = RangeParams::default().gte(start).format("epoch_millis");
+
+        let range = if let Some(unit) = self.calendar_interval.as_deref().and_then(calendar_unit) {
+            range.lt(format!("{}||+{}", start, unit))
+        } else {
+            range.lt(start + self.fixed_interval.as_deref().and_then(fixed_interval_millis)?)
+        };
+
+        Some(match &self.time_zone {
+            Some(time_zone) => range.time_zone(time_zone),
+            None => range,
+        })
+    }
+}
+
+impl Aggregation {
+    /**
+     * Query matching the documents of a bucket this aggregation returned, e.g. a term query for a terms bucket.
+     * None for aggregations whose buckets can not be expressed as a query.
+     */
+    pub fn bucket_filter(&self, bucket: &Bucket) -> Option<Query> {
+        match &self.kind {
+            // Keys of boolean terms are 1 and 0, "true" and "false" as strings
+            AggregationKind::Terms(terms) if !bucket.key.is_null() => {
+                let key = bucket.key_as_string.clone().map_or_else(|| bucket.key.clone(), Into::into);
+                Some(Query::term(&terms.field, key))
+            },
+            AggregationKind::DateHistogram(histogram) => {
+                Some(Query::range(&histogram.field, histogram.bucket_range(&bucket.key)?))
+            },
+            AggregationKind::Histogram(histogram) => {
+                let start = bucket.key.as_f64()?;
+                Some(Query::range(&histogram.field, RangeParams::default().gte(start).lt(start + histogram.interval)))
+            },
+            AggregationKind::Range(range) => {
+                let params = RangeParams {
+                    gte: bucket.from.map(Into::into),
+                    lt: bucket.to.map(Into::into),
+                    ..Default::default()
+                };
+                Some(Query::range(&range.field, params))
+            },
+            AggregationKind::Filters(filters) => filters.filters.get(bucket.key.as_str()?).cloned(),
+            AggregationKind::Filter(query) => Some(query.clone()),
+            AggregationKind::Other(aggregation) => {
+                let sources = aggregation.get("composite")?.get("sources")?.as_array()?;
+                let keys = bucket.key.as_object()?;
+
+                // Only terms sources are matched, other sources do not narrow the query
+                let filters = sources.iter()
+                    .filter_map(serde_json::Value::as_object)
+                    .flat_map(|source| source.iter())
+                    .filter_map(|(name, source)| {
+                        let field = source.get("terms")?.get("field")?.as_str()?;
+                        let key = keys.get(name).filter(|key| !key.is_null())?;
+                        Some(Query::term(field, key.clone()))
+                    })
+                    .collect::<Vec<Query>>();
+
+                (!filters.is_empty()).then(|| filters.into_iter()
+                    .fold(crate::es::BoolQuery::new(), crate::es::BoolQuery::filter)
+                    .into())
+            },
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn buckets_of(value: serde_json::Value) -> BucketsResult {
+        match AggregationResult::from(value) {
+            AggregationResult::Buckets(result) => result,
+            other => panic!("expected buckets, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn reads_buckets_and_sub_aggregations() {
+        let result = buckets_of(serde_json::json!({
+            "sum_other_doc_count": 4,
+            "buckets": [
+                { "key": "error", "doc_count": 10, "avg_took": { "value": 12.5 } },
+                { "key": "warn", "doc_count": 3, "avg_took": { "value": null } },
+            ],
+        }));
+
+        assert_eq!(result.sum_other_doc_count, Some(4));
+        assert_eq!(result.buckets.len(), 2);
+        assert_eq!(result.buckets[0].label(), "error");
+        assert_eq!(result.buckets[0].doc_count, 10);
+        assert_eq!(
+            result.buckets[0].aggregations.get("avg_took"),
+            Some(&AggregationResult::Value(ValueResult { value: Some(12.5), value_as_string: None })),
+        );
+        assert_eq!(
+            result.buckets[1].aggregations.get("avg_took"),
+            Some(&AggregationResult::Value(ValueResult { value: None, value_as_string: None })),
+        );
+    }
+
+    #[test]
+    fn reads_keyed_buckets() {
+        let result = buckets_of(serde_json::json!({
+            "buckets": { "errors": { "doc_count": 2 }, "warnings": { "doc_count": 5 } }
+        }));
+
+        assert_eq!(result.buckets.iter().map(Bucket::label).collect::<Vec<String>>(), ["errors", "warnings"]);
+        assert_eq!(result.buckets[1].doc_count, 5);
+    }
+
+    #[test]
+    fn reads_other_shapes() {
+        assert!(matches!(
+            AggregationResult::from(serde_json::json!({ "values": { "50.0": 12.0, "99.0": 80.5 } })),
+            AggregationResult::Percentiles(percentiles) if percentiles.len() == 2,
+        ));
+        assert_eq!(
+            AggregationResult::from(serde_json::json!({ "count": 3, "min": 1.0, "max": 5.0, "avg": 3.0, "sum": 9.0 })),
+            AggregationResult::Stats(StatsResult { count: 3, min: Some(1.0), max: Some(5.0), avg: Some(3.0), sum: Some(9.0) }),
+        );
+        assert!(matches!(
+            AggregationResult::from(serde_json::json!({ "doc_count": 7, "by_host": { "buckets": [] } })),
+            AggregationResult::SingleBucket(bucket) if bucket.doc_count == 7 && bucket.aggregations.contains_key("by_host"),
+        ));
+        assert!(matches!(
+            AggregationResult::from(serde_json::json!({ "hits": { "hits": [] } })),
+            AggregationResult::Other(_),
+        ));
+    }
+
+    #[test]
+    fn filters_boolean_terms_by_their_string_key() {
+        let bucket = &buckets_of(serde_json::json!({
+            "buckets": [{ "key": 1, "key_as_string": "true", "doc_count": 4 }]
+        })).buckets[0];
+
+        assert_eq!(Aggregation::terms("enabled").bucket_filter(bucket), Some(Query::term("enabled", "true")));
+    }
+
+    #[test]
+    fn ends_calendar_buckets_with_date_math() {
+        let bucket = &buckets_of(serde_json::json!({
+            "buckets": [{ "key": 1706745600000i64, "key_as_string": "2024-02-01", "doc_count": 4 }]
+        })).buckets[0];
+
+        let filter = Aggregation::date_histogram("@timestamp", "month").bucket_filter(bucket);
+        let range = RangeParams::default().gte(1706745600000i64).format("epoch_millis").lt("1706745600000||+1M");
+        assert_eq!(filter, Some(Query::range("@timestamp", range)));
+    }
+
+    #[test]
+    fn ends_fixed_buckets_after_their_interval() {
+        let bucket = &buckets_of(serde_json::json!({ "buckets": [{ "key": 60000, "doc_count": 1 }] })).buckets[0];
+
+        let filter = Aggregation::date_histogram("@timestamp", "30s").bucket_filter(bucket);
+        let range = RangeParams::default().gte(60000).format("epoch_millis").lt(90000);
+        assert_eq!(filter, Some(Query::range("@timestamp", range)));
+    }
+}
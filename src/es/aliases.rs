@@ -0,0 +1,290 @@
+// Typed actions of the aliases API, applied atomically, and aliases along with their options, see
+// https://www.elastic.co/docs/api/doc/elasticsearch/operation/operation-indices-update-aliases
+use serde::{Deserialize, Serialize};
+
+/**
+ * E.g.
+ * vec![
+ *     AliasAction::remove("logs-v1", "logs"),
+ *     AddAlias::new("logs-v2", "logs").is_write_index(true).into(),
+ * ]
+ */
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AliasAction {
+    Add(AddAlias),
+    Remove(RemoveAlias),
+    // Deletes the index, letting an alias replace it in the same request
+    RemoveIndex(RemoveIndex),
+}
+
+impl AliasAction {
+    pub fn add(index: impl Into<String>, alias: impl Into<String>) -> Self {
+        AliasAction::Add(AddAlias::new(index, alias))
+    }
+
+    pub fn remove(index: impl Into<String>, alias: impl Into<String>) -> Self {
+        AliasAction::Remove(RemoveAlias { index: index.into(), alias: alias.into(), must_exist: None })
+    }
+
+    pub fn remove_index(index: impl Into<String>) -> Self {
+        AliasAction::RemoveIndex(RemoveIndex { index: index.into() })
+    }
+
+    /**
+     * Moves the alias from the indicies it points to onto another, for blue/green reindexing, given the current
+     * aliases of the cluster. The filter and routing of the alias are kept, taken from its write index if it has one.
+     * Applied together, searches through the alias never see both or neither, and the request fails
+     * if the alias was removed from any of the indicies in the meantime.
+     */
+    pub fn swap<'a>(
+        alias: &str,
+        current: impl IntoIterator<Item = &'a IndexAlias>,
+        to: impl Into<String>,
+        is_write_index: bool
+    ) -> Result<Vec<Self>, Box<dyn std::error::Error>> {
+        let to = to.into();
+        let current = current.into_iter()
+            .filter(|index_alias| index_alias.alias == alias)
+            .collect::<Vec<&IndexAlias>>();
+
+        let Some(options) = current.iter()
+            .find(|index_alias| index_alias.options.is_write_index == Some(true))
+            .or(current.first())
+            .map(|index_alias| &index_alias.options)
+        else {
+            return Err(format!("Alias {} does not exist", alias).into());
+        };
+
+        let mut actions = current.iter()
+            .filter(|index_alias| index_alias.index != to)
+            .map(|index_alias| AliasAction::Remove(RemoveAlias {
+                index: index_alias.index.clone(),
+                alias: alias.to_owned(),
+                must_exist: Some(true),
+            }))
+            .collect::<Vec<Self>>();
+
+        let add = AddAlias {
+            filter: options.filter.clone(),
+            index_routing: options.index_routing.clone(),
+            search_routing: options.search_routing.clone(),
+            is_write_index: is_write_index.then_some(true),
+            ..AddAlias::new(to, alias)
+        };
+        actions.push(add.into());
+        Ok(actions)
+    }
+
+    // As named in requests
+    pub fn name(&self) -> &'static str {
+        match self {
+            AliasAction::Add(_) => "add",
+            AliasAction::Remove(_) => "remove",
+            AliasAction::RemoveIndex(_) => "remove_index",
+        }
+    }
+
+    pub fn index(&self) -> &str {
+        match self {
+            AliasAction::Add(add) => &add.index,
+            AliasAction::Remove(remove) => &remove.index,
+            AliasAction::RemoveIndex(remove_index) => &remove_index.index,
+        }
+    }
+
+    pub fn alias(&self) -> Option<&str> {
+        match self {
+            AliasAction::Add(add) => Some(&add.alias),
+            AliasAction::Remove(remove) => Some(&remove.alias),
+            AliasAction::RemoveIndex(_) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AddAlias {
+    pub index: String,
+    pub alias: String,
+    // Query limiting the documents searched through the alias, sent as given
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<serde_json::Value>,
+    // Sets both the index and search routing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub routing: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub index_routing: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search_routing: Option<String>,
+    // Documents indexed through an alias of several indicies go to its write index
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_write_index: Option<bool>,
+}
+
+impl AddAlias {
+    pub fn new(index: impl Into<String>, alias: impl Into<String>) -> Self {
+        Self {
+            index: index.into(),
+            alias: alias.into(),
+            filter: None,
+            routing: None,
+            index_routing: None,
+            search_routing: None,
+            is_write_index: None,
+        }
+    }
+
+    // E.g. `serde_json::to_value(Query::term("user.id", "kimchy"))?`
+    pub fn filter(mut self, filter: serde_json::Value) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    pub fn routing(mut self, routing: impl Into<String>) -> Self {
+        self.routing = Some(routing.into());
+        self
+    }
+
+    pub fn index_routing(mut self, index_routing: impl Into<String>) -> Self {
+        self.index_routing = Some(index_routing.into());
+        self
+    }
+
+    pub fn search_routing(mut self, search_routing: impl Into<String>) -> Self {
+        self.search_routing = Some(search_routing.into());
+        self
+    }
+
+    pub fn is_write_index(mut self, is_write_index: bool) -> Self {
+        self.is_write_index = Some(is_write_index);
+        self
+    }
+}
+
+impl From<AddAlias> for AliasAction {
+    fn from(add: AddAlias) -> Self {
+        AliasAction::Add(add)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RemoveAlias {
+    pub index: String,
+    pub alias: String,
+    // Fails the request when the alias does not exist, instead of ignoring the action
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub must_exist: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RemoveIndex {
+    pub index: String,
+}
+
+// Alias of an index along with its options, see
+// https://www.elastic.co/docs/api/doc/elasticsearch/operation/operation-indices-get-alias
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexAlias {
+    pub alias: String,
+    pub index: String,
+    pub options: AliasOptions,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct AliasOptions {
+    // Kept as stored in the cluster, so that a swapped alias matches the same documents
+    #[serde(default)]
+    pub filter: Option<serde_json::Value>,
+    #[serde(default)]
+    pub index_routing: Option<String>,
+    #[serde(default)]
+    pub search_routing: Option<String>,
+    // Unset unless the alias points to several indicies
+    #[serde(default)]
+    pub is_write_index: Option<bool>,
+    #[serde(default)]
+    pub is_hidden: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index_alias(index: &str, alias: &str, options: AliasOptions) -> IndexAlias {
+        IndexAlias { alias: alias.to_owned(), index: index.to_owned(), options }
+    }
+
+    #[test]
+    fn swap_keeps_the_alias_options() {
+        let options = AliasOptions {
+            filter: Some(serde_json::json!({ "term": { "tenant": { "value": "acme" } } })),
+            index_routing: Some("1".to_owned()),
+            search_routing: Some("1,2".to_owned()),
+            ..Default::default()
+        };
+        let current = [
+            index_alias("logs-v1", "logs", options),
+            index_alias("logs-v1", "other", AliasOptions::default()),
+        ];
+
+        let actions = AliasAction::swap("logs", &current, "logs-v2", true).unwrap();
+
+        assert_eq!(serde_json::to_value(&actions).unwrap(), serde_json::json!([
+            { "remove": { "index": "logs-v1", "alias": "logs", "must_exist": true } },
+            { "add": {
+                "index": "logs-v2",
+                "alias": "logs",
+                "filter": { "term": { "tenant": { "value": "acme" } } },
+                "index_routing": "1",
+                "search_routing": "1,2",
+                "is_write_index": true,
+            } },
+        ]));
+    }
+
+    #[test]
+    fn swap_keeps_untyped_filter_parameters() {
+        let filter = serde_json::json!({
+            "bool": {
+                "filter": [
+                    { "match_phrase": { "msg": { "query": "a b", "analyzer": "whitespace" } } },
+                    { "range": { "t": { "gte": "now-1d", "relation": "within" } } },
+                ],
+            }
+        });
+        let options = AliasOptions { filter: Some(filter.clone()), ..Default::default() };
+        let current = [index_alias("logs-v1", "logs", options)];
+
+        let actions = AliasAction::swap("logs", &current, "logs-v2", false).unwrap();
+
+        let Some(AliasAction::Add(add)) = actions.last() else {
+            panic!("expected an add action last, got {:?}", actions);
+        };
+        assert_eq!(add.filter, Some(filter));
+    }
+
+    #[test]
+    fn swap_takes_the_options_of_the_write_index() {
+        let write = AliasOptions { is_write_index: Some(true), index_routing: Some("w".to_owned()), ..Default::default() };
+        let current = [
+            index_alias("logs-v1", "logs", AliasOptions { is_write_index: Some(false), ..Default::default() }),
+            index_alias("logs-v2", "logs", write),
+        ];
+
+        let actions = AliasAction::swap("logs", &current, "logs-v3", false).unwrap();
+
+        assert_eq!(actions.len(), 3);
+        let Some(AliasAction::Add(add)) = actions.last() else {
+            panic!("expected an add action last, got {:?}", actions);
+        };
+        assert_eq!(add.index_routing.as_deref(), Some("w"));
+        assert_eq!(add.is_write_index, None);
+    }
+
+    #[test]
+    fn swap_fails_for_an_unknown_alias() {
+        let current = [index_alias("logs-v1", "logs", AliasOptions::default())];
+
+        assert!(AliasAction::swap("metrics", &current, "metrics-v2", false).is_err());
+    }
+}
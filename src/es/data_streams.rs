@@ -0,0 +1,74 @@
+// Data streams and their backing indicies, see
+// https://www.elastic.co/docs/api/doc/elasticsearch/operation/operation-indices-get-data-stream
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+pub struct DataStream {
+    pub name: String,
+    #[serde(default, deserialize_with = "deserialize_timestamp_field")]
+    pub timestamp_field: Option<String>,
+    // Oldest first, the last one is the write index
+    pub indices: Vec<BackingIndex>,
+    // Incremented on each rollover
+    pub generation: u64,
+    // Health of the backing indicies, GREEN, YELLOW or RED
+    pub status: String,
+    // Index template the data stream was created from
+    #[serde(default)]
+    pub template: Option<String>,
+    #[serde(default)]
+    pub ilm_policy: Option<String>,
+    #[serde(default)]
+    pub hidden: bool,
+    #[serde(default)]
+    pub system: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+pub struct BackingIndex {
+    pub index_name: String,
+    pub index_uuid: String,
+    #[serde(default)]
+    pub ilm_policy: Option<String>,
+    // Index Lifecycle Management, Data stream lifecycle or Unmanaged
+    #[serde(default)]
+    pub managed_by: Option<String>,
+}
+
+// See https://www.elastic.co/docs/api/doc/elasticsearch/operation/operation-indices-data-streams-stats-1
+#[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+pub struct DataStreamStats {
+    pub data_stream: String,
+    pub backing_indices: u64,
+    pub store_size_bytes: u64,
+    // Human readable size, e.g. 1.2gb
+    #[serde(default)]
+    pub store_size: Option<String>,
+    // Epoch millis of the newest document, 0 when empty
+    #[serde(default)]
+    pub maximum_timestamp: i64,
+}
+
+// See https://www.elastic.co/docs/api/doc/elasticsearch/operation/operation-indices-rollover
+#[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+pub struct RolloverResult {
+    pub old_index: String,
+    pub new_index: String,
+    pub rolled_over: bool,
+}
+
+// {"name": "@timestamp"}
+fn deserialize_timestamp_field<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    #[derive(serde::Deserialize)]
+    struct TimestampField {
+        name: String,
+    }
+
+    Ok(<Option<TimestampField> as serde::Deserialize>::deserialize(deserializer)?.map(|field| field.name))
+}
+
+impl DataStream {
+    // Index documents are written to
+    pub fn write_index(&self) -> Option<&BackingIndex> {
+        self.indices.last()
+    }
+}
@@ -0,0 +1,851 @@
+// Typed Query DSL, serializing to the JSON expected in the "query" of a search body.
+// See https://www.elastic.co/docs/reference/query-languages/query-dsl
+//
+// Deserializing also accepts the shorthand forms of the DSL (e.g. {"term": {"user.id": "kimchy"}}),
+// which are serialized back in their full form.
+use serde::de::Error as _;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Query {
+    MatchAll(MatchAllQuery),
+    Bool(BoolQuery),
+    Term(FieldQuery<TermParams>),
+    Terms(TermsQuery),
+    Match(FieldQuery<MatchParams>),
+    MatchPhrase(FieldQuery<MatchPhraseParams>),
+    Range(FieldQuery<RangeParams>),
+    Exists(ExistsQuery),
+    Prefix(FieldQuery<PrefixParams>),
+    Wildcard(FieldQuery<WildcardParams>),
+    Nested(NestedQuery),
+    FunctionScore(FunctionScoreQuery),
+    QueryString(QueryStringQuery),
+    // Queries without a typed variant, such as geo queries, kept as their JSON
+    #[serde(untagged)]
+    Other(serde_json::Map<String, serde_json::Value>),
+}
+
+impl Query {
+    pub fn match_all() -> Self {
+        Query::MatchAll(MatchAllQuery::default())
+    }
+
+    pub fn term(field: impl Into<String>, value: impl Into<serde_json::Value>) -> Self {
+        Query::Term(FieldQuery::new(field, TermParams { value: value.into(), ..Default::default() }))
+    }
+
+    pub fn terms<V: Into<serde_json::Value>>(field: impl Into<String>, values: impl IntoIterator<Item = V>) -> Self {
+        Query::Terms(TermsQuery {
+            field: field.into(),
+            values: values.into_iter().map(Into::into).collect(),
+            boost: None,
+            extra: Default::default(),
+        })
+    }
+
+    // `match` being a keyword
+    pub fn match_query(field: impl Into<String>, query: impl Into<serde_json::Value>) -> Self {
+        Query::Match(FieldQuery::new(field, MatchParams { query: query.into(), ..Default::default() }))
+    }
+
+    pub fn match_phrase(field: impl Into<String>, query: impl Into<String>) -> Self {
+        Query::MatchPhrase(FieldQuery::new(field, MatchPhraseParams { query: query.into(), ..Default::default() }))
+    }
+
+    /**
+     * E.g. `Query::range("age", RangeParams::default().gte(18).lt(65))`
+     */
+    pub fn range(field: impl Into<String>, params: RangeParams) -> Self {
+        Query::Range(FieldQuery::new(field, params))
+    }
+
+    pub fn exists(field: impl Into<String>) -> Self {
+        Query::Exists(ExistsQuery { field: field.into(), boost: None, extra: Default::default() })
+    }
+
+    pub fn prefix(field: impl Into<String>, value: impl Into<String>) -> Self {
+        Query::Prefix(FieldQuery::new(field, PrefixParams { value: value.into(), ..Default::default() }))
+    }
+
+    // `*` matches any characters, `?` a single one
+    pub fn wildcard(field: impl Into<String>, pattern: impl Into<String>) -> Self {
+        Query::Wildcard(FieldQuery::new(field, WildcardParams { value: pattern.into(), ..Default::default() }))
+    }
+
+    pub fn nested(path: impl Into<String>, query: Query) -> Self {
+        Query::Nested(NestedQuery {
+            path: path.into(),
+            query: Box::new(query),
+            score_mode: None,
+            ignore_unmapped: None,
+            boost: None,
+            extra: Default::default(),
+        })
+    }
+
+    pub fn query_string(query: impl Into<String>) -> Self {
+        Query::QueryString(QueryStringQuery { query: query.into(), ..Default::default() })
+    }
+
+    // Has no effect on queries without a typed variant
+    pub fn boost(mut self, boost: f64) -> Self {
+        let target = match &mut self {
+            Query::MatchAll(query) => &mut query.boost,
+            Query::Bool(query) => &mut query.boost,
+            Query::Term(query) => &mut query.params.boost,
+            Query::Terms(query) => &mut query.boost,
+            Query::Match(query) => &mut query.params.boost,
+            Query::MatchPhrase(query) => &mut query.params.boost,
+            Query::Range(query) => &mut query.params.boost,
+            Query::Exists(query) => &mut query.boost,
+            Query::Prefix(query) | Query::Wildcard(query) => &mut query.params.boost,
+            Query::Nested(query) => &mut query.boost,
+            Query::FunctionScore(query) => &mut query.boost,
+            Query::QueryString(query) => &mut query.boost,
+            Query::Other(_) => return self,
+        };
+        *target = Some(boost);
+        self
+    }
+}
+
+impl From<BoolQuery> for Query {
+    fn from(query: BoolQuery) -> Self {
+        Query::Bool(query)
+    }
+}
+
+impl From<NestedQuery> for Query {
+    fn from(query: NestedQuery) -> Self {
+        Query::Nested(query)
+    }
+}
+
+impl From<FunctionScoreQuery> for Query {
+    fn from(query: FunctionScoreQuery) -> Self {
+        Query::FunctionScore(query)
+    }
+}
+
+impl From<QueryStringQuery> for Query {
+    fn from(query: QueryStringQuery) -> Self {
+        Query::QueryString(query)
+    }
+}
+
+/**
+ * Parameters of a query given under the name of the field it applies to, e.g. {"match": {"message": {...}}}
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldQuery<T> {
+    pub field: String,
+    pub params: T,
+}
+
+impl<T> FieldQuery<T> {
+    pub fn new(field: impl Into<String>, params: T) -> Self {
+        Self { field: field.into(), params }
+    }
+}
+
+// Parameters that can also be given as a single value instead of an object
+pub trait Shorthand: Sized {
+    fn from_shorthand(value: serde_json::Value) -> Option<Self>;
+}
+
+impl<T: Serialize> Serialize for FieldQuery<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+
+        let mut map = serializer.serialize_map(Some(1))?;
+        map.serialize_entry(&self.field, &self.params)?;
+        map.end()
+    }
+}
+
+impl<'de, T: serde::de::DeserializeOwned + Shorthand> Deserialize<'de> for FieldQuery<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let map = serde_json::Map::<String, serde_json::Value>::deserialize(deserializer)?;
+
+        let mut entries = map.into_iter();
+        let (Some((field, value)), None) = (entries.next(), entries.next()) else {
+            return Err(D::Error::custom("expected the parameters of a single field"));
+        };
+
+        let params = if value.is_object() {
+            serde_json::from_value(value).map_err(D::Error::custom)?
+        } else {
+            T::from_shorthand(value).ok_or_else(|| D::Error::custom(format!("invalid value for field {}", field)))?
+        };
+
+        Ok(Self { field, params })
+    }
+}
+
+fn one_or_many<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Vec<Query>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        Many(Vec<Query>),
+        One(Box<Query>),
+    }
+
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::Many(queries) => queries,
+        OneOrMany::One(query) => vec![*query],
+    })
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MatchAllQuery {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub boost: Option<f64>,
+    // Parameters without a typed field, such as _name, kept as their JSON
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/**
+ * See https://www.elastic.co/docs/reference/query-languages/query-dsl/query-dsl-bool-query
+ */
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BoolQuery {
+    #[serde(default, deserialize_with = "one_or_many", skip_serializing_if = "Vec::is_empty")]
+    pub must: Vec<Query>,
+    // Like must, without scoring
+    #[serde(default, deserialize_with = "one_or_many", skip_serializing_if = "Vec::is_empty")]
+    pub filter: Vec<Query>,
+    #[serde(default, deserialize_with = "one_or_many", skip_serializing_if = "Vec::is_empty")]
+    pub should: Vec<Query>,
+    #[serde(default, deserialize_with = "one_or_many", skip_serializing_if = "Vec::is_empty")]
+    pub must_not: Vec<Query>,
+    // A count such as 2 or a percentage such as "75%"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub minimum_should_match: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub boost: Option<f64>,
+    // Parameters without a typed field, such as _name, kept as their JSON
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl BoolQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn must(mut self, query: Query) -> Self {
+        self.must.push(query);
+        self
+    }
+
+    pub fn filter(mut self, query: Query) -> Self {
+        self.filter.push(query);
+        self
+    }
+
+    pub fn should(mut self, query: Query) -> Self {
+        self.should.push(query);
+        self
+    }
+
+    pub fn must_not(mut self, query: Query) -> Self {
+        self.must_not.push(query);
+        self
+    }
+
+    pub fn minimum_should_match(mut self, minimum: impl Into<serde_json::Value>) -> Self {
+        self.minimum_should_match = Some(minimum.into());
+        self
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TermParams {
+    pub value: serde_json::Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub case_insensitive: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub boost: Option<f64>,
+    // Parameters without a typed field, such as _name, kept as their JSON
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Shorthand for TermParams {
+    fn from_shorthand(value: serde_json::Value) -> Option<Self> {
+        Some(Self { value, ..Default::default() })
+    }
+}
+
+/**
+ * Matches any of the values, given as {"terms": {"<field>": [...], "boost": 1.0}}
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct TermsQuery {
+    pub field: String,
+    pub values: Vec<serde_json::Value>,
+    pub boost: Option<f64>,
+    // Parameters other than the values and boost, such as _name, kept as their JSON
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Serialize for TermsQuery {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry(&self.field, &self.values)?;
+        if let Some(boost) = self.boost {
+            map.serialize_entry("boost", &boost)?;
+        }
+        for (key, value) in &self.extra {
+            map.serialize_entry(key, value)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for TermsQuery {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut map = serde_json::Map::<String, serde_json::Value>::deserialize(deserializer)?;
+
+        let boost = map.remove("boost")
+            .map(|boost| boost.as_f64().ok_or_else(|| D::Error::custom("boost must be a number")))
+            .transpose()?;
+
+        // The values being the only array
+        let mut fields = Vec::new();
+        let mut extra = serde_json::Map::new();
+        for (key, value) in map {
+            match value {
+                serde_json::Value::Array(values) => fields.push((key, values)),
+                value => {
+                    extra.insert(key, value);
+                },
+            }
+        }
+
+        let mut fields = fields.into_iter();
+        let (Some((field, values)), None) = (fields.next(), fields.next()) else {
+            return Err(D::Error::custom("expected an array of values for a single field"));
+        };
+
+        Ok(Self { field, values, boost, extra })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchOperator {
+    #[serde(alias = "OR")]
+    Or,
+    #[serde(alias = "AND")]
+    And,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MatchParams {
+    pub query: serde_json::Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operator: Option<MatchOperator>,
+    // "AUTO" or a maximum edit distance
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fuzziness: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub minimum_should_match: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub boost: Option<f64>,
+    // Parameters without a typed field, such as analyzer or _name, kept as their JSON
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Shorthand for MatchParams {
+    fn from_shorthand(query: serde_json::Value) -> Option<Self> {
+        Some(Self { query, ..Default::default() })
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MatchPhraseParams {
+    pub query: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slop: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub boost: Option<f64>,
+    // Parameters without a typed field, such as analyzer or _name, kept as their JSON
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Shorthand for MatchPhraseParams {
+    fn from_shorthand(query: serde_json::Value) -> Option<Self> {
+        Some(Self { query: query.as_str()?.to_owned(), ..Default::default() })
+    }
+}
+
+/**
+ * Bounds are numbers, or dates in the field format or date math such as "now-1d/d"
+ */
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RangeParams {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gt: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gte: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lt: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lte: Option<serde_json::Value>,
+    // Date format of the bounds, the format of the field otherwise
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_zone: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub boost: Option<f64>,
+    // Parameters without a typed field, such as relation or _name, kept as their JSON
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl RangeParams {
+    pub fn gt(mut self, bound: impl Into<serde_json::Value>) -> Self {
+        self.gt = Some(bound.into());
+        self
+    }
+
+    pub fn gte(mut self, bound: impl Into<serde_json::Value>) -> Self {
+        self.gte = Some(bound.into());
+        self
+    }
+
+    pub fn lt(mut self, bound: impl Into<serde_json::Value>) -> Self {
+        self.lt = Some(bound.into());
+        self
+    }
+
+    pub fn lte(mut self, bound: impl Into<serde_json::Value>) -> Self {
+        self.lte = Some(bound.into());
+        self
+    }
+
+    pub fn format(mut self, format: impl Into<String>) -> Self {
+        self.format = Some(format.into());
+        self
+    }
+
+    pub fn time_zone(mut self, time_zone: impl Into<String>) -> Self {
+        self.time_zone = Some(time_zone.into());
+        self
+    }
+}
+
+impl Shorthand for RangeParams {
+    fn from_shorthand(_value: serde_json::Value) -> Option<Self> {
+        None
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExistsQuery {
+    pub field: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub boost: Option<f64>,
+    // Parameters without a typed field, such as _name, kept as their JSON
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PrefixParams {
+    pub value: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub case_insensitive: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub boost: Option<f64>,
+    // Parameters without a typed field, such as rewrite or _name, kept as their JSON
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+// The value being the pattern
+pub type WildcardParams = PrefixParams;
+
+impl Shorthand for PrefixParams {
+    fn from_shorthand(value: serde_json::Value) -> Option<Self> {
+        Some(Self { value: value.as_str()?.to_owned(), ..Default::default() })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NestedScoreMode {
+    Avg,
+    Max,
+    Min,
+    None,
+    Sum,
+}
+
+/**
+ * See https://www.elastic.co/docs/reference/query-languages/query-dsl/query-dsl-nested-query
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NestedQuery {
+    pub path: String,
+    pub query: Box<Query>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score_mode: Option<NestedScoreMode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ignore_unmapped: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub boost: Option<f64>,
+    // Parameters without a typed field, such as inner_hits or _name, kept as their JSON
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FunctionScoreMode {
+    Multiply,
+    Sum,
+    Avg,
+    First,
+    Max,
+    Min,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BoostMode {
+    Multiply,
+    Replace,
+    Sum,
+    Avg,
+    Max,
+    Min,
+}
+
+/**
+ * See https://www.elastic.co/docs/reference/query-languages/query-dsl/query-dsl-function-score-query
+ */
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FunctionScoreQuery {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<Box<Query>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub functions: Vec<ScoreFunction>,
+    // How the scores of the functions are combined
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score_mode: Option<FunctionScoreMode>,
+    // How the combined score is combined with the score of the query
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub boost_mode: Option<BoostMode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_boost: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_score: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub boost: Option<f64>,
+    // Parameters without a typed field, such as _name, kept as their JSON
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl FunctionScoreQuery {
+    pub fn new(query: Query) -> Self {
+        Self { query: Some(Box::new(query)), ..Default::default() }
+    }
+
+    pub fn function(mut self, function: ScoreFunction) -> Self {
+        self.functions.push(function);
+        self
+    }
+
+    pub fn score_mode(mut self, score_mode: FunctionScoreMode) -> Self {
+        self.score_mode = Some(score_mode);
+        self
+    }
+
+    pub fn boost_mode(mut self, boost_mode: BoostMode) -> Self {
+        self.boost_mode = Some(boost_mode);
+        self
+    }
+}
+
+/**
+ * A function of a function_score query, applied to the documents matching its filter, or all documents without one.
+ * Decay functions (gauss, linear, exp) and scripts are given as their JSON.
+ */
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScoreFunction {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter: Option<Query>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub field_value_factor: Option<FieldValueFactor>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub random_score: Option<RandomScore>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub script_score: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gauss: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub linear: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exp: Option<serde_json::Value>,
+    // Parameters without a typed field, such as _name, kept as their JSON
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl ScoreFunction {
+    pub fn weight(weight: f64) -> Self {
+        Self { weight: Some(weight), ..Default::default() }
+    }
+
+    pub fn field_value_factor(factor: FieldValueFactor) -> Self {
+        Self { field_value_factor: Some(factor), ..Default::default() }
+    }
+
+    pub fn random_score(random_score: RandomScore) -> Self {
+        Self { random_score: Some(random_score), ..Default::default() }
+    }
+
+    pub fn filter(mut self, filter: Query) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FieldValueFactor {
+    pub field: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub factor: Option<f64>,
+    // none, log, log1p, log2p, ln, ln1p, ln2p, square, sqrt or reciprocal
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modifier: Option<String>,
+    // Value used for documents without the field
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub missing: Option<f64>,
+    // Parameters without a typed field kept as their JSON
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RandomScore {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+    // Parameters without a typed field kept as their JSON
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/**
+ * See https://www.elastic.co/docs/reference/query-languages/query-dsl/query-dsl-query-string-query
+ */
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct QueryStringQuery {
+    pub query: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_field: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_operator: Option<MatchOperator>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub boost: Option<f64>,
+    // Parameters without a typed field, such as analyze_wildcard or _name, kept as their JSON
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(json: serde_json::Value) -> serde_json::Value {
+        serde_json::to_value(serde_json::from_value::<Query>(json).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn keeps_untyped_match_parameters() {
+        let json = serde_json::json!({
+            "match": { "message": { "query": "disk full", "analyzer": "standard", "_name": "message_match" } }
+        });
+
+        let query = serde_json::from_value::<Query>(json.clone()).unwrap();
+        assert!(matches!(query, Query::Match(_)));
+        assert_eq!(serde_json::to_value(&query).unwrap(), json);
+    }
+
+    #[test]
+    fn keeps_untyped_bool_parameters() {
+        let json = serde_json::json!({
+            "bool": {
+                "filter": [{ "term": { "status": { "value": "error" } } }],
+                "_name": "errors",
+            }
+        });
+
+        let query = serde_json::from_value::<Query>(json.clone()).unwrap();
+        assert!(matches!(query, Query::Bool(_)));
+        assert_eq!(serde_json::to_value(&query).unwrap(), json);
+    }
+
+    fn assert_round_trips(json: serde_json::Value, is_variant: fn(&Query) -> bool) {
+        let query = serde_json::from_value::<Query>(json.clone()).unwrap();
+        assert!(is_variant(&query), "unexpected variant {:?}", query);
+        assert_eq!(serde_json::to_value(&query).unwrap(), json);
+    }
+
+    #[test]
+    fn keeps_untyped_match_all_parameters() {
+        assert_round_trips(
+            serde_json::json!({ "match_all": { "boost": 1.5, "_name": "everything" } }),
+            |query| matches!(query, Query::MatchAll(_))
+        );
+    }
+
+    #[test]
+    fn keeps_untyped_term_parameters() {
+        assert_round_trips(
+            serde_json::json!({ "term": { "status": { "value": "error", "_name": "errors" } } }),
+            |query| matches!(query, Query::Term(_))
+        );
+    }
+
+    #[test]
+    fn keeps_untyped_terms_parameters() {
+        assert_round_trips(
+            serde_json::json!({ "terms": { "status": ["error", "warn"], "boost": 2.0, "_name": "problems" } }),
+            |query| matches!(query, Query::Terms(_))
+        );
+    }
+
+    #[test]
+    fn keeps_untyped_match_phrase_parameters() {
+        assert_round_trips(
+            serde_json::json!({ "match_phrase": { "msg": { "query": "a b", "slop": 1, "analyzer": "whitespace" } } }),
+            |query| matches!(query, Query::MatchPhrase(_))
+        );
+    }
+
+    #[test]
+    fn keeps_untyped_range_parameters() {
+        assert_round_trips(
+            serde_json::json!({ "range": { "t": { "gte": "now-1d", "relation": "within" } } }),
+            |query| matches!(query, Query::Range(_))
+        );
+    }
+
+    #[test]
+    fn keeps_untyped_exists_parameters() {
+        assert_round_trips(
+            serde_json::json!({ "exists": { "field": "user", "_name": "has_user" } }),
+            |query| matches!(query, Query::Exists(_))
+        );
+    }
+
+    #[test]
+    fn keeps_untyped_prefix_parameters() {
+        assert_round_trips(
+            serde_json::json!({ "prefix": { "user.id": { "value": "ki", "rewrite": "constant_score" } } }),
+            |query| matches!(query, Query::Prefix(_))
+        );
+    }
+
+    #[test]
+    fn keeps_untyped_wildcard_parameters() {
+        assert_round_trips(
+            serde_json::json!({ "wildcard": { "user.id": { "value": "ki*y", "case_insensitive": true, "_name": "users" } } }),
+            |query| matches!(query, Query::Wildcard(_))
+        );
+    }
+
+    #[test]
+    fn keeps_untyped_nested_parameters() {
+        assert_round_trips(
+            serde_json::json!({
+                "nested": {
+                    "path": "comments",
+                    "query": { "match": { "comments.text": { "query": "great" } } },
+                    "score_mode": "max",
+                    "inner_hits": { "size": 3 },
+                }
+            }),
+            |query| matches!(query, Query::Nested(_))
+        );
+    }
+
+    #[test]
+    fn keeps_untyped_function_score_parameters() {
+        assert_round_trips(
+            serde_json::json!({
+                "function_score": {
+                    "query": { "match_all": {} },
+                    "functions": [{ "weight": 2.0 }],
+                    "boost_mode": "sum",
+                    "_name": "scored",
+                }
+            }),
+            |query| matches!(query, Query::FunctionScore(_))
+        );
+    }
+
+    #[test]
+    fn keeps_untyped_score_function_parameters() {
+        assert_round_trips(
+            serde_json::json!({
+                "function_score": {
+                    "functions": [
+                        {
+                            "filter": { "term": { "tier": { "value": "gold" } } },
+                            "field_value_factor": { "field": "likes", "modifier": "log1p", "_unknown": 1 },
+                            "weight": 2.0,
+                            "_name": "gold_likes",
+                        },
+                        { "random_score": { "seed": 10, "field": "_seq_no", "_unknown": 2 } },
+                    ],
+                }
+            }),
+            |query| matches!(query, Query::FunctionScore(_))
+        );
+    }
+
+    #[test]
+    fn keeps_untyped_query_string_parameters() {
+        assert_round_trips(
+            serde_json::json!({ "query_string": { "query": "status:err*", "default_operator": "and", "analyze_wildcard": true } }),
+            |query| matches!(query, Query::QueryString(_))
+        );
+    }
+
+    #[test]
+    fn writes_shorthands_in_full() {
+        let json = serde_json::json!({ "bool": { "must": { "match": { "message": "disk full" } } } });
+
+        assert_eq!(round_trip(json), serde_json::json!({
+            "bool": { "must": [{ "match": { "message": { "query": "disk full" } } }] }
+        }));
+    }
+
+    #[test]
+    fn keeps_untyped_queries() {
+        let json = serde_json::json!({
+            "geo_distance": { "distance": "200km", "pin.location": { "lat": 40, "lon": -70 } }
+        });
+
+        assert_eq!(round_trip(json.clone()), json);
+    }
+}
@@ -0,0 +1,786 @@
+// Typed search request body, see
+// https://www.elastic.co/docs/api/doc/elasticsearch/operation/operation-search#operation-search-body-application-json
+use std::collections::BTreeMap;
+
+use serde::de::Error as _;
+use serde::{Deserialize, Serialize};
+
+use crate::es::Query;
+
+/**
+ * E.g.
+ * SearchBody::new()
+ *     .query(BoolQuery::new().filter(Query::term("status", "error")).into())
+ *     .sort(Sort::desc("@timestamp"))
+ *     .size(20)
+ */
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SearchBody {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<Query>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    #[serde(default, deserialize_with = "deserialize_sorts", skip_serializing_if = "Vec::is_empty")]
+    pub sort: Vec<Sort>,
+    #[serde(default, rename = "_source", skip_serializing_if = "Option::is_none")]
+    pub source: Option<SourceFilter>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub highlight: Option<Highlight>,
+    #[serde(default, alias = "aggregations", skip_serializing_if = "BTreeMap::is_empty")]
+    pub aggs: BTreeMap<String, Aggregation>,
+    // true, false or the number of hits to count accurately up to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub track_total_hits: Option<serde_json::Value>,
+    // Parameters without a typed field, such as search_after or post_filter, kept as their JSON
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl SearchBody {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn query(mut self, query: Query) -> Self {
+        self.query = Some(query);
+        self
+    }
+
+    pub fn from(mut self, from: u64) -> Self {
+        self.from = Some(from);
+        self
+    }
+
+    pub fn size(mut self, size: u64) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    pub fn sort(mut self, sort: Sort) -> Self {
+        self.sort.push(sort);
+        self
+    }
+
+    pub fn source(mut self, source: SourceFilter) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    pub fn highlight(mut self, highlight: Highlight) -> Self {
+        self.highlight = Some(highlight);
+        self
+    }
+
+    pub fn aggregation(mut self, name: impl Into<String>, aggregation: Aggregation) -> Self {
+        self.aggs.insert(name.into(), aggregation);
+        self
+    }
+
+    // Body as passed to `ElasticsearchClient::search`
+    pub fn to_json(&self) -> serde_json::Value {
+        // Only fails for maps with non string keys, which none of the types have
+        serde_json::to_value(self).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+/**
+ * Sort on a field, or on "_score" or "_doc".
+ * Serialized as {"<field>": {"order": ...}}, or "<field>" without any option. Also deserialized from {"<field>": "asc"}
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sort {
+    pub field: String,
+    pub order: Option<SortOrder>,
+    // "_last", "_first" or a value used for documents without the field
+    pub missing: Option<serde_json::Value>,
+    // Type to sort indices without a mapping of the field as
+    pub unmapped_type: Option<String>,
+    // Parameters without a typed field, such as those of _geo_distance or mode, kept as their JSON
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Sort {
+    pub fn asc(field: impl Into<String>) -> Self {
+        Self { field: field.into(), order: Some(SortOrder::Asc), missing: None, unmapped_type: None, extra: serde_json::Map::new() }
+    }
+
+    pub fn desc(field: impl Into<String>) -> Self {
+        Self { field: field.into(), order: Some(SortOrder::Desc), missing: None, unmapped_type: None, extra: serde_json::Map::new() }
+    }
+
+    pub fn score() -> Self {
+        Self::desc("_score")
+    }
+
+    pub fn missing(mut self, missing: impl Into<serde_json::Value>) -> Self {
+        self.missing = Some(missing.into());
+        self
+    }
+
+    pub fn unmapped_type(mut self, unmapped_type: impl Into<String>) -> Self {
+        self.unmapped_type = Some(unmapped_type.into());
+        self
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct SortParams {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    order: Option<SortOrder>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    missing: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    unmapped_type: Option<String>,
+    #[serde(flatten)]
+    extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Serialize for Sort {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+
+        if self.order.is_none() && self.missing.is_none() && self.unmapped_type.is_none() && self.extra.is_empty() {
+            return serializer.serialize_str(&self.field);
+        }
+
+        let params = SortParams {
+            order: self.order,
+            missing: self.missing.clone(),
+            unmapped_type: self.unmapped_type.clone(),
+            extra: self.extra.clone(),
+        };
+
+        let mut map = serializer.serialize_map(Some(1))?;
+        map.serialize_entry(&self.field, &params)?;
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Sort {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (field, params) = match serde_json::Value::deserialize(deserializer)? {
+            serde_json::Value::String(field) => (field, SortParams::default()),
+            serde_json::Value::Object(map) => {
+                let mut entries = map.into_iter();
+                let (Some((field, params)), None) = (entries.next(), entries.next()) else {
+                    return Err(D::Error::custom("expected a single field to sort on"));
+                };
+
+                let params = match params {
+                    serde_json::Value::String(_) => SortParams {
+                        order: Some(serde_json::from_value(params).map_err(D::Error::custom)?),
+                        ..Default::default()
+                    },
+                    params => serde_json::from_value(params).map_err(D::Error::custom)?,
+                };
+                (field, params)
+            },
+            _ => return Err(D::Error::custom("expected a field name or an object")),
+        };
+
+        Ok(Self {
+            field,
+            order: params.order,
+            missing: params.missing,
+            unmapped_type: params.unmapped_type,
+            extra: params.extra,
+        })
+    }
+}
+
+// "sort" may be a single sort instead of an array
+fn deserialize_sorts<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Vec<Sort>, D::Error> {
+    match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::Array(sorts) => serde_json::from_value(serde_json::Value::Array(sorts)).map_err(D::Error::custom),
+        sort => Ok(vec![serde_json::from_value(sort).map_err(D::Error::custom)?]),
+    }
+}
+
+/**
+ * Which fields of _source hits return. A single field given as a string is deserialized as a list of one field.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum SourceFilter {
+    Enabled(bool),
+    Fields(Vec<String>),
+    Filter {
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        includes: Vec<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        excludes: Vec<String>,
+    },
+}
+
+impl SourceFilter {
+    pub fn fields<S: Into<String>>(fields: impl IntoIterator<Item = S>) -> Self {
+        SourceFilter::Fields(fields.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<'de> Deserialize<'de> for SourceFilter {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct Filter {
+            #[serde(default)]
+            includes: Vec<String>,
+            #[serde(default)]
+            excludes: Vec<String>,
+        }
+
+        match serde_json::Value::deserialize(deserializer)? {
+            serde_json::Value::Bool(enabled) => Ok(SourceFilter::Enabled(enabled)),
+            serde_json::Value::String(field) => Ok(SourceFilter::Fields(vec![field])),
+            fields @ serde_json::Value::Array(_) => serde_json::from_value(fields)
+                .map(SourceFilter::Fields)
+                .map_err(D::Error::custom),
+            filter => serde_json::from_value::<Filter>(filter)
+                .map(|filter| SourceFilter::Filter { includes: filter.includes, excludes: filter.excludes })
+                .map_err(D::Error::custom),
+        }
+    }
+}
+
+/**
+ * See https://www.elastic.co/docs/reference/elasticsearch/rest-apis/highlighting
+ */
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Highlight {
+    pub fields: BTreeMap<String, HighlightField>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pre_tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post_tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fragment_size: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub number_of_fragments: Option<u32>,
+    // Whether only fields the query searched are highlighted, true by default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub require_field_match: Option<bool>,
+    // Parameters without a typed field, such as type or boundary_scanner, kept as their JSON
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Highlight {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Field names may contain wildcards, e.g. "*"
+    pub fn field(mut self, field: impl Into<String>) -> Self {
+        self.fields.insert(field.into(), HighlightField::default());
+        self
+    }
+
+    pub fn tags(mut self, pre_tag: impl Into<String>, post_tag: impl Into<String>) -> Self {
+        self.pre_tags = vec![pre_tag.into()];
+        self.post_tags = vec![post_tag.into()];
+        self
+    }
+
+    // Characters per fragment, 100 by default
+    pub fn fragment_size(mut self, fragment_size: u32) -> Self {
+        self.fragment_size = Some(fragment_size);
+        self
+    }
+
+    pub fn number_of_fragments(mut self, number_of_fragments: u32) -> Self {
+        self.number_of_fragments = Some(number_of_fragments);
+        self
+    }
+
+    /**
+     * Fragments of the "highlight" section of a hit searched with this highlight, in the order returned.
+     * Only the first pre and post tags are looked for, <em> and </em> when none were given as Elasticsearch does.
+     */
+    pub fn hit_fragments<'a>(&self, hit: &'a serde_json::Value) -> Vec<HighlightFragment<'a>> {
+        let Some(fields) = hit.get("highlight").and_then(serde_json::Value::as_object) else {
+            return Vec::new();
+        };
+
+        fields.iter()
+            .flat_map(|(field, fragments)| fragments.as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(serde_json::Value::as_str)
+                .map(move |fragment| (field.as_str(), fragment)))
+            .map(|(field, fragment)| HighlightFragment { field, segments: self.split(fragment) })
+            .collect()
+    }
+
+    fn split<'a>(&self, mut fragment: &'a str) -> Vec<HighlightSegment<'a>> {
+        let pre_tag = self.pre_tags.first().map_or("<em>", String::as_str);
+        let post_tag = self.post_tags.first().map_or("</em>", String::as_str);
+
+        let mut segments = Vec::new();
+        let mut push = |text: &'a str, matched: bool| if !text.is_empty() {
+            segments.push(HighlightSegment { text, matched });
+        };
+
+        while let Some(start) = fragment.find(pre_tag) {
+            push(&fragment[..start], false);
+            let rest = &fragment[start + pre_tag.len()..];
+            let end = rest.find(post_tag).unwrap_or(rest.len());
+            push(&rest[..end], true);
+            fragment = rest.get(end + post_tag.len()..).unwrap_or_default();
+        }
+        push(fragment, false);
+
+        segments
+    }
+}
+
+// Highlighted excerpt of a field of a hit
+#[derive(Debug, Clone, PartialEq)]
+pub struct HighlightFragment<'a> {
+    pub field: &'a str,
+    pub segments: Vec<HighlightSegment<'a>>,
+}
+
+// Text of a fragment between tags, matched when the query matched it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HighlightSegment<'a> {
+    pub text: &'a str,
+    pub matched: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HighlightField {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fragment_size: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub number_of_fragments: Option<u32>,
+    // unified, plain or fvh
+    #[serde(default, rename = "type", skip_serializing_if = "Option::is_none")]
+    pub highlighter_type: Option<String>,
+    // Parameters without a typed field, such as matched_fields or highlight_query, kept as their JSON
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/**
+ * An aggregation and its sub aggregations, serialized as {"<kind>": {...}, "aggs": {...}}
+ * See https://www.elastic.co/docs/reference/aggregations
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Aggregation {
+    pub kind: AggregationKind,
+    pub aggs: BTreeMap<String, Aggregation>,
+    // Keys besides the kind, such as meta, kept as their JSON
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Aggregation {
+    pub fn new(kind: AggregationKind) -> Self {
+        Self { kind, aggs: BTreeMap::new(), extra: serde_json::Map::new() }
+    }
+
+    pub fn terms(field: impl Into<String>) -> Self {
+        Self::new(AggregationKind::Terms(TermsAggregation { field: field.into(), ..Default::default() }))
+    }
+
+    /**
+     * Interval such as "1d" or "month". Calendar units ("day", "1M", ...) use calendar_interval, others fixed_interval.
+     */
+    pub fn date_histogram(field: impl Into<String>, interval: impl Into<String>) -> Self {
+        const CALENDAR_UNITS: [&str; 16] = [
+            "minute", "1m", "hour", "1h", "day", "1d", "week", "1w", "month", "1M", "quarter", "1q", "year", "1y", "second", "1s",
+        ];
+
+        let interval = interval.into();
+        let (calendar_interval, fixed_interval) = if CALENDAR_UNITS.contains(&interval.as_str()) {
+            (Some(interval), None)
+        } else {
+            (None, Some(interval))
+        };
+
+        Self::new(AggregationKind::DateHistogram(DateHistogramAggregation {
+            field: field.into(),
+            calendar_interval,
+            fixed_interval,
+            ..Default::default()
+        }))
+    }
+
+    pub fn histogram(field: impl Into<String>, interval: f64) -> Self {
+        Self::new(AggregationKind::Histogram(HistogramAggregation { field: field.into(), interval, ..Default::default() }))
+    }
+
+    pub fn avg(field: impl Into<String>) -> Self {
+        Self::new(AggregationKind::Avg(MetricAggregation::new(field)))
+    }
+
+    pub fn sum(field: impl Into<String>) -> Self {
+        Self::new(AggregationKind::Sum(MetricAggregation::new(field)))
+    }
+
+    pub fn min(field: impl Into<String>) -> Self {
+        Self::new(AggregationKind::Min(MetricAggregation::new(field)))
+    }
+
+    pub fn max(field: impl Into<String>) -> Self {
+        Self::new(AggregationKind::Max(MetricAggregation::new(field)))
+    }
+
+    pub fn cardinality(field: impl Into<String>) -> Self {
+        Self::new(AggregationKind::Cardinality(MetricAggregation::new(field)))
+    }
+
+    pub fn filter(query: Query) -> Self {
+        Self::new(AggregationKind::Filter(query))
+    }
+
+    pub fn sub_aggregation(mut self, name: impl Into<String>, aggregation: Aggregation) -> Self {
+        self.aggs.insert(name.into(), aggregation);
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AggregationKind {
+    Terms(TermsAggregation),
+    DateHistogram(DateHistogramAggregation),
+    Histogram(HistogramAggregation),
+    Range(RangeAggregation),
+    Avg(MetricAggregation),
+    Sum(MetricAggregation),
+    Min(MetricAggregation),
+    Max(MetricAggregation),
+    Cardinality(MetricAggregation),
+    ValueCount(MetricAggregation),
+    Stats(MetricAggregation),
+    Filter(Query),
+    Filters(FiltersAggregation),
+    TopHits(TopHitsAggregation),
+    // Aggregations without a typed variant, kept as their JSON
+    #[serde(untagged)]
+    Other(serde_json::Map<String, serde_json::Value>),
+}
+
+impl Serialize for Aggregation {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::{Error as _, SerializeMap};
+
+        let serde_json::Value::Object(kind) = serde_json::to_value(&self.kind).map_err(S::Error::custom)? else {
+            return Err(S::Error::custom("aggregation is not an object"));
+        };
+
+        let mut map = serializer.serialize_map(None)?;
+        for (key, value) in kind.iter().chain(&self.extra) {
+            map.serialize_entry(key, value)?;
+        }
+        if !self.aggs.is_empty() {
+            map.serialize_entry("aggs", &self.aggs)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Aggregation {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut map = serde_json::Map::<String, serde_json::Value>::deserialize(deserializer)?;
+
+        let aggs = match map.remove("aggs").or_else(|| map.remove("aggregations")) {
+            Some(aggs) => serde_json::from_value(aggs).map_err(D::Error::custom)?,
+            None => BTreeMap::new(),
+        };
+
+        // The first key of a typed kind, the other keys being kept aside. Without any, the whole object is kept
+        let typed_kind = map.iter().find_map(|(key, value)| {
+            let kind = serde_json::json!({ key: value });
+            match serde_json::from_value::<AggregationKind>(kind) {
+                Ok(AggregationKind::Other(_)) | Err(_) => None,
+                Ok(kind) => Some((key.to_owned(), kind)),
+            }
+        });
+
+        let (kind, extra) = match typed_kind {
+            Some((key, kind)) => {
+                map.remove(&key);
+                (kind, map)
+            },
+            None => (AggregationKind::Other(map), serde_json::Map::new()),
+        };
+
+        Ok(Self { kind, aggs, extra })
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TermsAggregation {
+    pub field: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    // E.g. {"_count": "desc"} or {"_key": "asc"}
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_doc_count: Option<u64>,
+    // Bucket documents without the field are put in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub missing: Option<serde_json::Value>,
+    // Parameters without a typed field, such as include or shard_size, kept as their JSON
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DateHistogramAggregation {
+    pub field: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub calendar_interval: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fixed_interval: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_zone: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_doc_count: Option<u64>,
+    // {"min": ..., "max": ...} to return empty buckets up to these bounds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extended_bounds: Option<serde_json::Value>,
+    // Parameters without a typed field, such as offset, keyed or order, kept as their JSON
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HistogramAggregation {
+    pub field: String,
+    pub interval: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_doc_count: Option<u64>,
+    // Parameters without a typed field, such as offset or extended_bounds, kept as their JSON
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RangeAggregation {
+    pub field: String,
+    pub ranges: Vec<AggregationRange>,
+    // Parameters without a typed field, such as keyed or format, kept as their JSON
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+// From is inclusive and to exclusive
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AggregationRange {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to: Option<serde_json::Value>,
+}
+
+// Single or multi value metric on a field, such as avg or stats
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MetricAggregation {
+    pub field: String,
+    // Value used for documents without the field
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub missing: Option<serde_json::Value>,
+    // Parameters without a typed field, such as script or format, kept as their JSON
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl MetricAggregation {
+    pub fn new(field: impl Into<String>) -> Self {
+        Self { field: field.into(), ..Default::default() }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FiltersAggregation {
+    // One bucket per named filter
+    pub filters: BTreeMap<String, Query>,
+    // Parameters without a typed field, such as other_bucket_key, kept as their JSON
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TopHitsAggregation {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    #[serde(default, deserialize_with = "deserialize_sorts", skip_serializing_if = "Vec::is_empty")]
+    pub sort: Vec<Sort>,
+    #[serde(default, rename = "_source", skip_serializing_if = "Option::is_none")]
+    pub source: Option<SourceFilter>,
+    // Parameters without a typed field, such as from or highlight, kept as their JSON
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip<T: Serialize + serde::de::DeserializeOwned>(json: serde_json::Value) -> serde_json::Value {
+        serde_json::to_value(serde_json::from_value::<T>(json).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn keeps_untyped_search_parameters() {
+        let json = serde_json::json!({
+            "query": { "match_all": {} },
+            "size": 10,
+            "search_after": [1700000000000u64, "abc"],
+            "post_filter": { "term": { "status": { "value": "error" } } },
+        });
+
+        assert_eq!(round_trip::<SearchBody>(json.clone()), json);
+    }
+
+    #[test]
+    fn keeps_untyped_sort_parameters() {
+        let json = serde_json::json!([
+            { "_geo_distance": { "pin.location": [-70, 40], "order": "asc", "unit": "km" } },
+            { "price": { "order": "desc", "mode": "avg" } },
+            "_score",
+        ]);
+
+        assert_eq!(round_trip::<Vec<Sort>>(json.clone()), json);
+    }
+
+    #[test]
+    fn keeps_untyped_aggregation_parameters() {
+        let json = serde_json::json!({
+            "terms": { "field": "tags", "include": "error.*", "shard_size": 100 },
+            "meta": { "color": "red" },
+            "aggs": { "avg_price": { "avg": { "field": "price" } } },
+        });
+
+        let aggregation = serde_json::from_value::<Aggregation>(json.clone()).unwrap();
+        assert!(matches!(aggregation.kind, AggregationKind::Terms(_)));
+        assert_eq!(aggregation.extra.get("meta"), json.get("meta"));
+        assert_eq!(serde_json::to_value(&aggregation).unwrap(), json);
+    }
+
+    #[test]
+    fn keeps_untyped_aggregations() {
+        let json = serde_json::json!({ "geohash_grid": { "field": "location", "precision": 3 } });
+
+        let aggregation = serde_json::from_value::<Aggregation>(json.clone()).unwrap();
+        assert!(matches!(aggregation.kind, AggregationKind::Other(_)));
+        assert_eq!(serde_json::to_value(&aggregation).unwrap(), json);
+    }
+
+    fn assert_aggregation_round_trips(json: serde_json::Value, is_kind: fn(&AggregationKind) -> bool) {
+        let aggregation = serde_json::from_value::<Aggregation>(json.clone()).unwrap();
+        assert!(is_kind(&aggregation.kind), "unexpected kind {:?}", aggregation.kind);
+        assert!(aggregation.extra.is_empty(), "parameters kept outside of the kind {:?}", aggregation.extra);
+        assert_eq!(serde_json::to_value(&aggregation).unwrap(), json);
+    }
+
+    #[test]
+    fn keeps_untyped_date_histogram_parameters() {
+        assert_aggregation_round_trips(
+            serde_json::json!({ "date_histogram": {
+                "field": "t",
+                "calendar_interval": "1d",
+                "offset": "+6h",
+                "keyed": true,
+                "order": { "_key": "desc" },
+            } }),
+            |kind| matches!(kind, AggregationKind::DateHistogram(_))
+        );
+    }
+
+    #[test]
+    fn keeps_untyped_histogram_parameters() {
+        assert_aggregation_round_trips(
+            serde_json::json!({ "histogram": {
+                "field": "price",
+                "interval": 50.0,
+                "offset": 10,
+                "extended_bounds": { "min": 0, "max": 500 },
+            } }),
+            |kind| matches!(kind, AggregationKind::Histogram(_))
+        );
+    }
+
+    #[test]
+    fn keeps_untyped_range_parameters() {
+        assert_aggregation_round_trips(
+            serde_json::json!({ "range": {
+                "field": "price",
+                "ranges": [{ "to": 100 }, { "from": 100 }],
+                "keyed": true,
+                "format": "0.0",
+            } }),
+            |kind| matches!(kind, AggregationKind::Range(_))
+        );
+    }
+
+    #[test]
+    fn keeps_untyped_metric_parameters() {
+        assert_aggregation_round_trips(
+            serde_json::json!({ "avg": { "field": "price", "format": "0.00", "script": { "source": "_value * 2" } } }),
+            |kind| matches!(kind, AggregationKind::Avg(_))
+        );
+    }
+
+    #[test]
+    fn keeps_untyped_filters_parameters() {
+        assert_aggregation_round_trips(
+            serde_json::json!({ "filters": {
+                "filters": { "errors": { "term": { "status": { "value": "error" } } } },
+                "other_bucket_key": "rest",
+            } }),
+            |kind| matches!(kind, AggregationKind::Filters(_))
+        );
+    }
+
+    #[test]
+    fn keeps_untyped_top_hits_parameters() {
+        assert_aggregation_round_trips(
+            serde_json::json!({ "top_hits": {
+                "size": 1,
+                "from": 2,
+                "highlight": { "fields": { "message": {} } },
+            } }),
+            |kind| matches!(kind, AggregationKind::TopHits(_))
+        );
+    }
+
+    #[test]
+    fn keeps_untyped_highlight_parameters() {
+        let json = serde_json::json!({
+            "fields": {
+                "message": {
+                    "type": "fvh",
+                    "matched_fields": ["message", "message.plain"],
+                    "highlight_query": { "match": { "message": { "query": "disk" } } },
+                },
+            },
+            "type": "unified",
+            "boundary_scanner": "sentence",
+        });
+
+        assert_eq!(round_trip::<Highlight>(json.clone()), json);
+    }
+
+    #[test]
+    fn reads_sort_shorthands() {
+        let sorts = serde_json::from_value::<SearchBody>(serde_json::json!({ "sort": { "@timestamp": "desc" } })).unwrap().sort;
+
+        assert_eq!(sorts, vec![Sort::desc("@timestamp")]);
+    }
+}
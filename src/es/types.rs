@@ -9,7 +9,10 @@ pub struct OperationSearchResult {
     pub shards_used: OperationSearchShardsUsed,
     pub hits: OperationSearchHits,
     #[serde(default)]
-    pub aggregations: Option<serde_json::Value>,
+    pub aggregations: Option<crate::es::Aggregations>,
+    // Present when searching a point in time, may differ from the id searched with
+    #[serde(default)]
+    pub pit_id: Option<String>,
 }
 
 #[derive(Debug, serde::Deserialize, Clone)]
@@ -22,6 +25,16 @@ pub struct OperationSearchShardsUsed {
 #[derive(Debug, serde::Deserialize, Clone)]
 pub struct OperationSearchHits {
     pub hits: Vec<serde_json::Value>,
+    // Missing when track_total_hits is false
+    #[serde(default)]
+    pub total: Option<OperationSearchTotal>,
+}
+
+#[derive(Debug, serde::Deserialize, Clone)]
+pub struct OperationSearchTotal {
+    pub value: u64,
+    // "eq" or "gte" when the count stopped at the track_total_hits limit
+    pub relation: String,
 }
 
 #[derive(Debug, Clone)]
@@ -29,3 +42,11 @@ pub enum OperationResult {
     Json(serde_json::Value),
     Text(String),
 }
+
+// Field of an index mapping, with object fields flattened into dotted names (e.g. "user.name")
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldMapping {
+    pub name: String,
+    // Mapping type such as "keyword", "date" or "long"
+    pub field_type: String,
+}
@@ -0,0 +1,421 @@
+// Writes every document matching a search to a file.
+// Documents are paged through a point in time with search_after, so the export is consistent and not limited by max_result_window.
+use std::collections::BTreeSet;
+use std::io::Write;
+
+use crate::es;
+
+// Documents requested per search
+const PAGE_SIZE: usize = 1000;
+// How long the point in time is kept between two pages
+const KEEP_ALIVE: &str = "2m";
+
+// Search body keys the export sets itself or that make no sense when paging
+const IGNORED_BODY_KEYS: [&str; 8] = [
+    "from", "size", "pit", "search_after", "track_total_hits", "aggs", "aggregations", "scroll",
+];
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    #[default]
+    Csv,
+    Ndjson,
+    Json,
+}
+
+impl Format {
+    pub const ALL: [Format; 3] = [Format::Csv, Format::Ndjson, Format::Json];
+
+    pub fn extension(&self) -> &str {
+        match self {
+            Format::Csv => "csv",
+            Format::Ndjson => "ndjson",
+            Format::Json => "json",
+        }
+    }
+}
+
+impl std::fmt::Display for Format {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Format::Csv => write!(f, "CSV"),
+            Format::Ndjson => write!(f, "NDJSON"),
+            Format::Json => write!(f, "JSON array"),
+        }
+    }
+}
+
+/**
+ * Value at a dotted path of a document. Both nested objects ({"user": {"name": ..}})
+ * and dotted keys ({"user.name": ..}) are followed, arrays of objects yield the array of matches.
+ */
+pub fn field_value(source: &serde_json::Value, path: &str) -> Option<serde_json::Value> {
+    if let Some(value) = source.get(path) {
+        return Some(value.clone());
+    }
+
+    match source {
+        serde_json::Value::Object(map) => map.iter()
+            .filter_map(|(key, value)| {
+                path.strip_prefix(key.as_str())
+                    .and_then(|rest| rest.strip_prefix('.'))
+                    .map(|rest| (rest, value))
+            })
+            .find_map(|(rest, value)| field_value(value, rest)),
+        serde_json::Value::Array(values) => {
+            let matches = values.iter()
+                .filter_map(|value| field_value(value, path))
+                .collect::<Vec<serde_json::Value>>();
+            (!matches.is_empty()).then_some(serde_json::Value::Array(matches))
+        },
+        _ => None,
+    }
+}
+
+fn collect_leaf_paths(value: &serde_json::Value, prefix: &str, paths: &mut BTreeSet<String>) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, child) in map {
+                let path = if prefix.is_empty() {
+                    key.to_owned()
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                collect_leaf_paths(child, &path, paths);
+            }
+        },
+        serde_json::Value::Array(values) if values.iter().any(serde_json::Value::is_object) => {
+            for child in values {
+                collect_leaf_paths(child, prefix, paths);
+            }
+        },
+        _ => {
+            if !prefix.is_empty() {
+                paths.insert(prefix.to_owned());
+            }
+        },
+    }
+}
+
+// Dotted paths of the values of a document that are not objects, e.g. "user.name"
+pub fn leaf_paths(value: &serde_json::Value) -> BTreeSet<String> {
+    let mut paths = BTreeSet::new();
+    collect_leaf_paths(value, "", &mut paths);
+    paths
+}
+
+/**
+ * Mapped fields found in the _source of documents. Objects are found as their fields, while aliases
+ * and multi-fields such as "title.keyword" (the fields of a field that is not an object) never are.
+ */
+pub fn source_fields(mappings: &[es::FieldMapping]) -> Vec<String> {
+    let is_object = |field_type: &str| ["object", "nested"].contains(&field_type);
+    let values = mappings.iter()
+        .filter(|field| !is_object(&field.field_type))
+        .map(|field| field.name.as_str())
+        .collect::<BTreeSet<&str>>();
+    let is_multi_field = |field: &str| {
+        field.match_indices('.').any(|(idx, _)| values.contains(&field[..idx]))
+    };
+
+    mappings.iter()
+        .filter(|field| !is_object(&field.field_type) && field.field_type != "alias" && !is_multi_field(&field.name))
+        .map(|field| field.name.clone())
+        .collect()
+}
+
+// Metadata fields such as _id are read from the hit, other columns from its _source
+fn column_value(hit: &serde_json::Value, column: &str) -> Option<serde_json::Value> {
+    if column.starts_with('_') && let Some(value) = hit.get(column) {
+        return Some(value.clone());
+    }
+
+    hit.get("_source").and_then(|source| field_value(source, column))
+}
+
+fn csv_cell(value: Option<&serde_json::Value>) -> String {
+    match value {
+        None | Some(serde_json::Value::Null) => String::new(),
+        Some(serde_json::Value::String(string)) => string.to_owned(),
+        Some(other) => other.to_string(),
+    }
+}
+
+enum Sink<W: Write> {
+    Csv {
+        writer: Box<csv::Writer<W>>,
+        // Decided on the first page when no columns were given
+        columns: Option<Vec<String>>,
+        // Fields mapped by the exported indicies, obtained before the first page when no columns were given
+        mapped_fields: BTreeSet<String>,
+    },
+    Ndjson(W),
+    Json {
+        writer: W,
+        written: bool,
+    },
+}
+
+impl<W: Write> Sink<W> {
+    fn write_page(&mut self, hits: &[serde_json::Value]) -> Result<(), Box<dyn std::error::Error>> {
+        match self {
+            Sink::Csv { writer, columns, mapped_fields } => {
+                let columns = match columns {
+                    Some(columns) => columns,
+                    None => {
+                        // Fields of the first page are added in case of unmapped fields (e.g. "dynamic": false)
+                        let mut paths = std::mem::take(mapped_fields);
+                        for hit in hits {
+                            if let Some(source) = hit.get("_source") {
+                                paths.extend(leaf_paths(source));
+                            }
+                        }
+
+                        let page_columns = std::iter::once("_id".to_owned()).chain(paths).collect::<Vec<String>>();
+                        writer.write_record(&page_columns)?;
+                        columns.insert(page_columns)
+                    },
+                };
+
+                for hit in hits {
+                    writer.write_record(columns.iter().map(|column| csv_cell(column_value(hit, column).as_ref())))?;
+                }
+            },
+            Sink::Ndjson(writer) => {
+                for hit in hits {
+                    serde_json::to_writer(&mut *writer, hit)?;
+                    writer.write_all(b"\n")?;
+                }
+            },
+            Sink::Json { writer, written } => {
+                for hit in hits {
+                    writer.write_all(if *written { b",\n" } else { b"[\n" })?;
+                    serde_json::to_writer(&mut *writer, hit)?;
+                    *written = true;
+                }
+            },
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        match self {
+            Sink::Csv { writer, .. } => writer.flush()?,
+            Sink::Ndjson(writer) => writer.flush()?,
+            Sink::Json { writer, written } => {
+                writer.write_all(if *written { b"\n]\n" } else { b"[]\n" })?;
+                writer.flush()?;
+            },
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Progress {
+    pub exported: u64,
+    // Number of matching documents, known after the first page
+    pub total: Option<u64>,
+    pub finished: bool,
+}
+
+/**
+ * Export of the documents matching a search body, driven one page at a time by `next_page`
+ * so that callers can report progress and stop between pages.
+ */
+pub struct Export<W: Write> {
+    client: es::ElasticsearchClient,
+    indicies: Vec<String>,
+    body: serde_json::Map<String, serde_json::Value>,
+    sink: Sink<W>,
+    pit_id: Option<String>,
+    search_after: Option<serde_json::Value>,
+    progress: Progress,
+}
+
+impl<W: Write> Export<W> {
+    /**
+     * indicies as an empty list implies all indicies.
+     * CSV files get one column per given field path. When none are given they get _id, every field mapped by
+     * the indicies and any unmapped field of the first page, unmapped fields first seen in later pages are left out.
+     */
+    pub fn new(
+        client: es::ElasticsearchClient,
+        indicies: Vec<String>,
+        body: Option<serde_json::Value>,
+        format: Format,
+        columns: Vec<String>,
+        writer: W,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut body = match body {
+            Some(serde_json::Value::Object(body)) => body,
+            Some(_) => return Err("Search body must be a JSON object".into()),
+            None => Default::default(),
+        };
+
+        for key in IGNORED_BODY_KEYS {
+            body.remove(key);
+        }
+
+        // search_after needs a sort that is unique per document, _shard_doc is the cheapest tiebreaker
+        let mut sort = match body.remove("sort") {
+            Some(serde_json::Value::Array(sort)) => sort,
+            Some(sort) => vec![sort],
+            None => Vec::new(),
+        };
+        sort.push(serde_json::json!({ "_shard_doc": "asc" }));
+        body.insert("sort".to_owned(), serde_json::Value::Array(sort));
+
+        let sink = match format {
+            Format::Csv => {
+                let mut writer = Box::new(csv::Writer::from_writer(writer));
+                let columns = if columns.is_empty() {
+                    None
+                } else {
+                    writer.write_record(&columns)?;
+                    Some(columns)
+                };
+                Sink::Csv { writer, columns, mapped_fields: BTreeSet::new() }
+            },
+            Format::Ndjson => Sink::Ndjson(writer),
+            Format::Json => Sink::Json { writer, written: false },
+        };
+
+        Ok(Self {
+            client,
+            indicies,
+            body,
+            sink,
+            pit_id: None,
+            search_after: None,
+            progress: Default::default(),
+        })
+    }
+
+    // Fetches and writes the next page, the returned progress is marked finished once every document was written
+    pub async fn next_page(&mut self) -> Result<Progress, Box<dyn std::error::Error>> {
+        if self.progress.finished {
+            return Ok(self.progress);
+        }
+
+        let pit_id = match &self.pit_id {
+            Some(pit_id) => pit_id.clone(),
+            None => {
+                let pit_id = self.client.open_point_in_time(&self.indicies, KEEP_ALIVE).await?;
+                self.pit_id = Some(pit_id.clone());
+                pit_id
+            },
+        };
+
+        if self.search_after.is_none() && let Sink::Csv { columns: None, mapped_fields, .. } = &mut self.sink {
+            *mapped_fields = source_fields(&self.client.get_field_mappings(&self.indicies).await?)
+                .into_iter()
+                .collect();
+        }
+
+        let mut body = self.body.clone();
+        body.insert("size".to_owned(), PAGE_SIZE.into());
+        body.insert("pit".to_owned(), serde_json::json!({ "id": pit_id, "keep_alive": KEEP_ALIVE }));
+        match &self.search_after {
+            Some(search_after) => body.insert("search_after".to_owned(), search_after.clone()),
+            None => body.insert("track_total_hits".to_owned(), true.into()),
+        };
+
+        // Searching a point in time must not name indicies
+        let res = self.client.search(&[], Some(&serde_json::Value::Object(body))).await?;
+
+        if res.pit_id.is_some() {
+            self.pit_id = res.pit_id;
+        }
+        if let Some(total) = res.hits.total {
+            self.progress.total = Some(total.value);
+        }
+
+        self.sink.write_page(&res.hits.hits)?;
+        self.progress.exported += res.hits.hits.len() as u64;
+        self.search_after = res.hits.hits.last().and_then(|hit| hit.get("sort")).cloned();
+
+        if res.hits.hits.len() < PAGE_SIZE || self.search_after.is_none() {
+            self.sink.finish()?;
+            self.close().await;
+            self.progress.finished = true;
+        }
+
+        Ok(self.progress)
+    }
+
+    // Releases the point in time, which otherwise lives until KEEP_ALIVE passes
+    pub async fn close(&mut self) {
+        if let Some(pit_id) = self.pit_id.take() {
+            // Nothing left to do when this fails, the point in time expires on its own
+            let _ = self.client.close_point_in_time(&pit_id).await;
+        }
+    }
+}
+
+impl Export<Vec<u8>> {
+    // Output written since the last call, for callers writing it out themselves (e.g. without blocking an executor)
+    pub fn take_output(&mut self) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let output = match &mut self.sink {
+            // Records are written by hand, so a new writer does not write the header again
+            Sink::Csv { writer, .. } => {
+                let written = std::mem::replace(writer, Box::new(csv::Writer::from_writer(Vec::new())));
+                return Ok(written.into_inner().map_err(|err| err.into_error())?);
+            },
+            Sink::Ndjson(writer) => writer,
+            Sink::Json { writer, .. } => writer,
+        };
+        Ok(std::mem::take(output))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn follows_nested_objects_dotted_keys_and_arrays() {
+        let source = json!({"user": {"name": "a"}, "geo.country": "fr", "tags": [{"name": "x"}, {"name": "y"}]});
+
+        assert_eq!(field_value(&source, "user.name"), Some(json!("a")));
+        assert_eq!(field_value(&source, "geo.country"), Some(json!("fr")));
+        assert_eq!(field_value(&source, "tags.name"), Some(json!(["x", "y"])));
+        assert_eq!(leaf_paths(&source).into_iter().collect::<Vec<String>>(), vec!["geo.country", "tags.name", "user.name"]);
+    }
+
+    #[test]
+    fn leaves_objects_aliases_and_multi_fields_out_of_source_fields() {
+        let mappings = [
+            ("message", "text"),
+            ("message.keyword", "keyword"),
+            ("tags", "nested"),
+            ("tags.name", "keyword"),
+            ("user", "object"),
+            ("user.name", "text"),
+            ("user.name.raw", "keyword"),
+            ("user_name", "alias"),
+        ]
+        .map(|(name, field_type)| es::FieldMapping { name: name.to_owned(), field_type: field_type.to_owned() });
+
+        assert_eq!(source_fields(&mappings), vec!["message", "tags.name", "user.name"]);
+    }
+
+    #[test]
+    fn adds_unmapped_fields_of_the_first_page_to_mapped_columns() {
+        let mut sink = Sink::Csv {
+            writer: Box::new(csv::Writer::from_writer(Vec::new())),
+            columns: None,
+            mapped_fields: BTreeSet::from(["status".to_owned()]),
+        };
+
+        sink.write_page(&[json!({"_id": "1", "_source": {"message": "a"}})]).unwrap();
+        sink.write_page(&[json!({"_id": "2", "_source": {"status": 500, "late": true}})]).unwrap();
+        sink.finish().unwrap();
+
+        let Sink::Csv { writer, .. } = sink else {
+            unreachable!();
+        };
+        assert_eq!(String::from_utf8(writer.into_inner().unwrap()).unwrap(), "_id,message,status\n1,a,\n2,,500\n");
+    }
+}
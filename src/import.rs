@@ -0,0 +1,491 @@
+// Reads documents from NDJSON, JSON array or CSV files and indexes them through the bulk API.
+// Rows that cannot be read or that Elasticsearch rejects are collected so they can be reported instead of failing the whole import.
+use std::collections::BTreeMap;
+use std::io::Write;
+
+use crate::{es, export};
+
+// Documents sent per bulk request
+const BATCH_SIZE: usize = 500;
+// Documents looked at to infer the fields of a file
+const INFERENCE_SAMPLE: usize = 100;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    #[default]
+    Ndjson,
+    Json,
+    Csv,
+}
+
+impl Format {
+    pub const ALL: [Format; 3] = [Format::Ndjson, Format::Json, Format::Csv];
+
+    pub fn extensions(&self) -> &[&str] {
+        match self {
+            Format::Ndjson => &["ndjson", "jsonl"],
+            Format::Json => &["json"],
+            Format::Csv => &["csv"],
+        }
+    }
+
+    pub fn from_path(path: &std::path::Path) -> Option<Self> {
+        Format::ALL.into_iter().find(|format| {
+            format.extensions().iter().any(|extension| crate::util::path_has_extension(path, extension))
+        })
+    }
+}
+
+impl std::fmt::Display for Format {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Format::Ndjson => write!(f, "NDJSON"),
+            Format::Json => write!(f, "JSON array"),
+            Format::Csv => write!(f, "CSV"),
+        }
+    }
+}
+
+// Document of a file, or why it could not be read
+#[derive(Debug, Clone)]
+pub struct Row {
+    // One based line of NDJSON and CSV files, one based position of the element for JSON arrays
+    pub line: usize,
+    pub raw: String,
+    pub document: Result<serde_json::Value, String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Rejected {
+    pub line: usize,
+    pub reason: String,
+    pub raw: String,
+}
+
+/**
+ * Value of a CSV cell: booleans and numbers are recognised, empty cells are left out of the document.
+ * Numbers with leading zeros (zip codes, ids) stay strings.
+ */
+pub fn infer_value(cell: &str) -> Option<serde_json::Value> {
+    let cell = cell.trim();
+
+    if cell.is_empty() {
+        return None;
+    }
+
+    let digits = cell.strip_prefix('-').unwrap_or(cell);
+    let leading_zero = digits.len() > 1 && digits.starts_with('0') && !digits.starts_with("0.");
+
+    let value = match cell {
+        "true" => serde_json::Value::Bool(true),
+        "false" => serde_json::Value::Bool(false),
+        _ if leading_zero => serde_json::Value::from(cell),
+        _ => {
+            if let Ok(integer) = cell.parse::<i64>() {
+                serde_json::Value::from(integer)
+            } else if let Some(number) = cell.parse::<f64>().ok().and_then(serde_json::Number::from_f64) {
+                serde_json::Value::Number(number)
+            } else {
+                serde_json::Value::from(cell)
+            }
+        },
+    };
+
+    Some(value)
+}
+
+// Dotted CSV headers such as "user.name" become nested objects
+fn insert_path(document: &mut serde_json::Map<String, serde_json::Value>, path: &str, value: serde_json::Value) {
+    match path.split_once('.') {
+        Some((key, rest)) if !key.is_empty() && !rest.is_empty() => {
+            let child = document.entry(key.to_owned()).or_insert_with(|| serde_json::json!({}));
+            match child.as_object_mut() {
+                Some(child) => insert_path(child, rest, value),
+                None => {
+                    document.insert(path.to_owned(), value);
+                },
+            }
+        },
+        _ => {
+            document.insert(path.to_owned(), value);
+        },
+    }
+}
+
+fn object_row(line: usize, raw: String, value: serde_json::Value) -> Row {
+    let document = if value.is_object() {
+        Ok(value)
+    } else {
+        Err("Not a JSON object".to_owned())
+    };
+
+    Row { line, raw, document }
+}
+
+/**
+ * Splits the contents of a file into rows. Rows that cannot be read are kept, with the reason, to be reported.
+ * Fails only when the file as a whole cannot be read, e.g. a JSON file that is not an array.
+ */
+pub fn parse(format: Format, contents: &str) -> Result<Vec<Row>, Box<dyn std::error::Error>> {
+    match format {
+        Format::Ndjson => Ok(contents.lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(idx, line)| match serde_json::from_str::<serde_json::Value>(line) {
+                Ok(value) => object_row(idx + 1, line.to_owned(), value),
+                Err(err) => Row { line: idx + 1, raw: line.to_owned(), document: Err(err.to_string()) },
+            })
+            .collect()),
+        Format::Json => {
+            let values = match serde_json::from_str::<serde_json::Value>(contents)? {
+                serde_json::Value::Array(values) => values,
+                object @ serde_json::Value::Object(_) => vec![object],
+                _ => return Err("Expected an array of JSON objects".into()),
+            };
+
+            Ok(values.into_iter()
+                .enumerate()
+                .map(|(idx, value)| object_row(idx + 1, value.to_string(), value))
+                .collect())
+        },
+        Format::Csv => {
+            let mut reader = csv::ReaderBuilder::new()
+                .flexible(true)
+                .from_reader(contents.as_bytes());
+
+            let headers = reader.headers()?
+                .iter()
+                .map(|header| header.trim().to_owned())
+                .collect::<Vec<String>>();
+
+            Ok(reader.records()
+                .enumerate()
+                .map(|(idx, record)| {
+                    // Header is the first line
+                    let fallback_line = idx + 2;
+
+                    let record = match record {
+                        Ok(record) => record,
+                        Err(err) => return Row { line: fallback_line, raw: String::new(), document: Err(err.to_string()) },
+                    };
+
+                    let line = record.position().map_or(fallback_line, |position| position.line() as usize);
+                    let raw = record.iter().collect::<Vec<&str>>().join(",");
+
+                    if record.len() != headers.len() {
+                        let reason = format!("Expected {} columns, found {}", headers.len(), record.len());
+                        return Row { line, raw, document: Err(reason) };
+                    }
+
+                    let mut document = serde_json::Map::new();
+                    for (header, cell) in headers.iter().zip(record.iter()) {
+                        if let Some(value) = infer_value(cell) {
+                            insert_path(&mut document, header, value);
+                        }
+                    }
+
+                    Row { line, raw, document: Ok(serde_json::Value::Object(document)) }
+                })
+                .collect())
+        },
+    }
+}
+
+/**
+ * Source and id of a document shaped as a search hit ({"_index": .., "_id": .., "_source": {..}}),
+ * as written by exports, so hits are imported as the documents they hold.
+ */
+fn hit_parts(document: &serde_json::Value) -> Option<(&serde_json::Value, Option<&serde_json::Value>)> {
+    let source = document.get("_source").filter(|source| source.is_object())?;
+    let id = document.get("_id");
+
+    (id.is_some() || document.get("_index").is_some()).then_some((source, id))
+}
+
+fn type_name(value: &serde_json::Value) -> &'static str {
+    match value {
+        serde_json::Value::Null => "null",
+        serde_json::Value::Bool(_) => "boolean",
+        serde_json::Value::Number(number) if number.is_f64() => "double",
+        serde_json::Value::Number(_) => "long",
+        serde_json::Value::String(_) => "text",
+        serde_json::Value::Array(_) => "array",
+        serde_json::Value::Object(_) => "object",
+    }
+}
+
+/**
+ * Fields found in the first documents along with the type of their values.
+ * Fields seen with both whole and decimal numbers are doubles, other fields seen with several types are reported as "mixed".
+ */
+pub fn inferred_fields(rows: &[Row]) -> BTreeMap<String, &'static str> {
+    let mut fields = BTreeMap::new();
+
+    for document in rows.iter().filter_map(|row| row.document.as_ref().ok()).take(INFERENCE_SAMPLE) {
+        let document = hit_parts(document).map_or(document, |(source, _)| source);
+        for path in export::leaf_paths(document) {
+            let Some(value) = export::field_value(document, &path) else {
+                continue;
+            };
+
+            let value_type = type_name(&value);
+            fields.entry(path)
+                .and_modify(|known_type| {
+                    *known_type = match (*known_type, value_type) {
+                        (known_type, value_type) if known_type == value_type => known_type,
+                        ("long", "double") | ("double", "long") => "double",
+                        _ => "mixed",
+                    }
+                })
+                .or_insert(value_type);
+        }
+    }
+
+    fields
+}
+
+// CSV report of the rejected rows, with the reason each was rejected
+pub fn write_report<W: Write>(rejected: &[Rejected], writer: W) -> Result<(), Box<dyn std::error::Error>> {
+    let mut writer = csv::Writer::from_writer(writer);
+
+    writer.write_record(["line", "reason", "document"])?;
+    for row in rejected {
+        writer.write_record([row.line.to_string().as_str(), row.reason.as_str(), row.raw.as_str()])?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Progress {
+    pub processed: usize,
+    pub indexed: usize,
+    pub rejected: usize,
+    pub total: usize,
+    pub elapsed: std::time::Duration,
+    pub finished: bool,
+}
+
+impl Progress {
+    pub fn documents_per_second(&self) -> f64 {
+        let seconds = self.elapsed.as_secs_f64();
+        if seconds > 0.0 {
+            self.indexed as f64 / seconds
+        } else {
+            0.0
+        }
+    }
+}
+
+/**
+ * Import of rows into an index, driven one bulk request at a time by `next_batch`
+ * so that callers can report progress and stop between batches.
+ */
+pub struct Import {
+    client: es::ElasticsearchClient,
+    index: String,
+    // Path of the field holding the document id, "_id" is removed from the document.
+    // Rows shaped as search hits keep their _id unless another field is given.
+    id_field: Option<String>,
+    rows: Vec<Row>,
+    next: usize,
+    rejected: Vec<Rejected>,
+    progress: Progress,
+    started: Option<std::time::Instant>,
+}
+
+impl Import {
+    pub fn new(client: es::ElasticsearchClient, index: String, rows: Vec<Row>, id_field: Option<String>) -> Self {
+        let progress = Progress {
+            total: rows.len(),
+            ..Default::default()
+        };
+
+        Self {
+            client,
+            index,
+            id_field,
+            rows,
+            next: 0,
+            rejected: Vec::new(),
+            progress,
+            started: None,
+        }
+    }
+
+    pub fn rejected(&self) -> &[Rejected] {
+        &self.rejected
+    }
+
+    pub fn into_rejected(self) -> Vec<Rejected> {
+        self.rejected
+    }
+
+    fn reject(&mut self, row_idx: usize, reason: String) {
+        let row = &self.rows[row_idx];
+        self.rejected.push(Rejected { line: row.line, reason, raw: row.raw.clone() });
+        self.progress.rejected += 1;
+    }
+
+    // Bulk action line and source of a row
+    fn bulk_bodies(&self, document: &serde_json::Value) -> Result<[serde_json::Value; 2], String> {
+        let (mut document, hit_id) = match hit_parts(document) {
+            Some((source, id)) => (source.clone(), id.cloned()),
+            None => (document.clone(), None),
+        };
+        let mut action = serde_json::json!({});
+
+        let id = match self.id_field.as_deref() {
+            Some("_id") if hit_id.is_some() => hit_id,
+            Some("_id") => document.as_object_mut().and_then(|document| document.remove("_id")),
+            Some(id_field) => export::field_value(&document, id_field),
+            None => hit_id,
+        };
+
+        match (id, self.id_field.as_deref()) {
+            (Some(serde_json::Value::String(id)), _) => action["_id"] = id.into(),
+            (Some(serde_json::Value::Number(id)), _) => action["_id"] = id.to_string().into(),
+            (Some(_), id_field) => return Err(format!("Field {} is not a string or number", id_field.unwrap_or("_id"))),
+            (None, Some(id_field)) => return Err(format!("Missing id field {}", id_field)),
+            (None, None) => (),
+        }
+
+        Ok([serde_json::json!({ "index": action }), document])
+    }
+
+    // Sends the next batch of rows, the returned progress is marked finished once every row was processed
+    pub async fn next_batch(&mut self) -> Result<Progress, Box<dyn std::error::Error>> {
+        if self.progress.finished {
+            return Ok(self.progress);
+        }
+
+        let started = *self.started.get_or_insert_with(std::time::Instant::now);
+
+        let batch = self.next..(self.next + BATCH_SIZE).min(self.rows.len());
+        self.next = batch.end;
+
+        let mut bodies = Vec::new();
+        // Rows sent, in the order of the bulk response items
+        let mut sent = Vec::new();
+
+        for row_idx in batch.clone() {
+            let row_bodies = match &self.rows[row_idx].document {
+                Ok(document) => self.bulk_bodies(document),
+                Err(reason) => Err(reason.clone()),
+            };
+
+            match row_bodies {
+                Ok(row_bodies) => {
+                    bodies.extend(row_bodies);
+                    sent.push(row_idx);
+                },
+                Err(reason) => self.reject(row_idx, reason),
+            }
+        }
+
+        if !bodies.is_empty() {
+            let path = format!("{}/_bulk", self.index);
+            let (status, res) = self.client.operation_ndjson_with_status(es::ElasticSearchMethodType::POST, &path, &bodies).await?;
+            if !(200..300).contains(&status) {
+                return Err(es::OperationStatusError { status, response: res }.into());
+            }
+
+            let items = match res {
+                es::OperationResult::Json(res) => res.get("items")
+                    .and_then(serde_json::Value::as_array)
+                    .cloned()
+                    .unwrap_or_default(),
+                es::OperationResult::Text(text) => return Err(format!("Unexpected bulk response: {}", text).into()),
+            };
+
+            for (row_idx, item) in sent.into_iter().zip(items.iter()) {
+                match item.pointer("/index/error") {
+                    Some(error) => {
+                        let reason = error.get("reason")
+                            .and_then(serde_json::Value::as_str)
+                            .map(str::to_owned)
+                            .unwrap_or_else(|| error.to_string());
+                        self.reject(row_idx, reason);
+                    },
+                    None => self.progress.indexed += 1,
+                }
+            }
+        }
+
+        self.progress.processed = batch.end;
+        self.progress.elapsed = started.elapsed();
+
+        if self.next >= self.rows.len() {
+            // Makes the documents searchable right away, imports are usually followed by searches
+            if self.progress.indexed > 0 {
+                self.client.operation(es::ElasticSearchMethodType::POST, &format!("{}/_refresh", self.index), None).await?;
+            }
+            self.progress.finished = true;
+        }
+
+        Ok(self.progress)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn import(id_field: Option<&str>) -> Import {
+        let client = es::ElasticsearchClient::new("http://localhost:9200".to_owned()).unwrap();
+        Import::new(client, "logs".to_owned(), Vec::new(), id_field.map(str::to_owned))
+    }
+
+    #[test]
+    fn infers_cell_values() {
+        assert_eq!(infer_value(" 42 "), Some(json!(42)));
+        assert_eq!(infer_value("-1.5"), Some(json!(-1.5)));
+        assert_eq!(infer_value("true"), Some(json!(true)));
+        assert_eq!(infer_value("007"), Some(json!("007")));
+        assert_eq!(infer_value("0.5"), Some(json!(0.5)));
+        assert_eq!(infer_value("NaN"), Some(json!("NaN")));
+        assert_eq!(infer_value("hello"), Some(json!("hello")));
+        assert_eq!(infer_value("  "), None);
+    }
+
+    #[test]
+    fn nests_dotted_csv_headers() {
+        let rows = parse(Format::Csv, "user.name,user.age,tag\na,30,\n").unwrap();
+
+        assert_eq!(rows[0].document, Ok(json!({"user": {"name": "a", "age": 30}})));
+        assert_eq!(rows[0].line, 2);
+    }
+
+    #[test]
+    fn keeps_unreadable_rows() {
+        let rows = parse(Format::Ndjson, "{\"a\": 1}\n\n[1]\n{\n").unwrap();
+
+        assert_eq!(rows.iter().map(|row| row.line).collect::<Vec<usize>>(), vec![1, 3, 4]);
+        assert!(rows[0].document.is_ok());
+        assert_eq!(rows[1].document, Err("Not a JSON object".to_owned()));
+        assert!(rows[2].document.is_err());
+    }
+
+    #[test]
+    fn imports_hits_as_their_source() {
+        let hit = json!({"_index": "logs-old", "_id": "a1", "_score": 1.0, "_source": {"message": "hi", "code": 7}});
+
+        assert_eq!(import(None).bulk_bodies(&hit).unwrap(), [json!({"index": {"_id": "a1"}}), json!({"message": "hi", "code": 7})]);
+        assert_eq!(import(Some("_id")).bulk_bodies(&hit).unwrap()[0], json!({"index": {"_id": "a1"}}));
+        assert_eq!(import(Some("code")).bulk_bodies(&hit).unwrap()[0], json!({"index": {"_id": "7"}}));
+
+        let rows = vec![Row { line: 1, raw: hit.to_string(), document: Ok(hit) }];
+        assert_eq!(inferred_fields(&rows).into_iter().collect::<Vec<(String, &str)>>(), vec![("code".to_owned(), "long"), ("message".to_owned(), "text")]);
+    }
+
+    #[test]
+    fn takes_ids_from_documents() {
+        let document = json!({"_id": "x", "message": "hi"});
+
+        assert_eq!(import(Some("_id")).bulk_bodies(&document).unwrap(), [json!({"index": {"_id": "x"}}), json!({"message": "hi"})]);
+        assert_eq!(import(None).bulk_bodies(&document).unwrap()[0], json!({"index": {}}));
+        assert_eq!(import(Some("missing")).bulk_bodies(&document).unwrap_err(), "Missing id field missing");
+    }
+}
@@ -0,0 +1,405 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::{es, logs, widget};
+use elastic_ermine::import;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    ChooseFilePressed,
+    FileChosen(Option<std::path::PathBuf>),
+    FileRead(Result<Vec<import::Row>, String>),
+    FormatSelected(import::Format),
+    IndexUpdated(String),
+    IdFieldUpdated(String),
+    ImportPressed,
+    ImportUpdated(Result<ImportEvent, String>),
+    CancelPressed,
+    SaveReportPressed,
+    ReportFileChosen(Option<std::path::PathBuf>),
+    ReportSaved(Result<std::path::PathBuf, String>),
+}
+
+pub enum Action {
+    None,
+    Run(iced::Task<Message>),
+    Import(ImportRequest),
+}
+
+#[derive(Debug)]
+pub struct ImportRequest {
+    index: String,
+    rows: Vec<import::Row>,
+    id_field: Option<String>,
+    cancel: Arc<AtomicBool>,
+}
+
+#[derive(Debug, Clone)]
+pub enum ImportEvent {
+    Progressed(import::Progress),
+    Finished(import::Progress, Vec<import::Rejected>),
+}
+
+#[derive(Debug, Default)]
+enum ImportState {
+    #[default]
+    Idle,
+    Running {
+        progress: import::Progress,
+        // Checked by the import between batches
+        cancel: Arc<AtomicBool>,
+    },
+    Finished {
+        progress: import::Progress,
+        rejected: Vec<import::Rejected>,
+    },
+    Failed(String),
+}
+
+#[derive(Debug, Default)]
+pub struct View {
+    log: logs::Log,
+    path: Option<std::path::PathBuf>,
+    format: import::Format,
+    rows: Option<Result<Vec<import::Row>, String>>,
+    // Field path and inferred type of the fields of the file
+    fields: Vec<(String, &'static str)>,
+    index: String,
+    id_field: String,
+    state: ImportState,
+    report_status: Option<Result<String, String>>,
+}
+
+impl View {
+    pub fn new(log: logs::Log) -> Self {
+        Self { log, ..Default::default() }
+    }
+
+    #[must_use]
+    pub fn update(&mut self, message: Message) -> Action {
+        match message {
+            Message::ChooseFilePressed => Action::Run(
+                iced::Task::future(
+                    rfd::AsyncFileDialog::new()
+                        .add_filter("Documents", &["ndjson", "jsonl", "json", "csv"])
+                        .set_title("Select file to import")
+                        .pick_file()
+                )
+                .map(|handle| Message::FileChosen(handle.map(|handle| handle.path().to_path_buf())))
+            ),
+            Message::FileChosen(None) => Action::None,
+            Message::FileChosen(Some(path)) => {
+                if let Some(format) = import::Format::from_path(&path) {
+                    self.format = format;
+                }
+                if self.index.is_empty() && let Some(stem) = path.file_stem() {
+                    self.index = stem.to_string_lossy().to_lowercase();
+                }
+                self.path = Some(path);
+                self.read_file()
+            },
+            Message::FileRead(res) => {
+                self.fields = match &res {
+                    Ok(rows) => import::inferred_fields(rows).into_iter().collect(),
+                    Err(_) => Vec::new(),
+                };
+                self.rows = Some(res);
+                Action::None
+            },
+            Message::FormatSelected(format) => {
+                self.format = format;
+                self.read_file()
+            },
+            Message::IndexUpdated(index) => {
+                self.index = index;
+                Action::None
+            },
+            Message::IdFieldUpdated(id_field) => {
+                self.id_field = id_field;
+                Action::None
+            },
+            Message::ImportPressed => {
+                let Some(Ok(rows)) = &self.rows else {
+                    return Action::None;
+                };
+
+                if self.index.trim().is_empty() || matches!(self.state, ImportState::Running { .. }) {
+                    return Action::None;
+                }
+
+                let cancel = Arc::new(AtomicBool::new(false));
+                self.log.info(format!("Importing {} rows into {}", rows.len(), self.index.trim()));
+
+                self.report_status = None;
+                self.state = ImportState::Running {
+                    progress: import::Progress { total: rows.len(), ..Default::default() },
+                    cancel: cancel.clone(),
+                };
+
+                Action::Import(ImportRequest {
+                    index: self.index.trim().to_owned(),
+                    rows: rows.clone(),
+                    id_field: (!self.id_field.trim().is_empty()).then(|| self.id_field.trim().to_owned()),
+                    cancel,
+                })
+            },
+            Message::ImportUpdated(res) => {
+                let ImportState::Running { progress, .. } = &mut self.state else {
+                    return Action::None;
+                };
+
+                match res {
+                    Ok(ImportEvent::Progressed(new_progress)) => *progress = new_progress,
+                    Ok(ImportEvent::Finished(progress, rejected)) => {
+                        self.log.info(format!(
+                            "Imported {} of {} documents into {}, {} rejected",
+                            progress.indexed, progress.total, self.index, progress.rejected
+                        ));
+                        self.state = ImportState::Finished { progress, rejected };
+                    },
+                    Err(err) => {
+                        self.log.error(format!("Import failed: {}", err));
+                        self.state = ImportState::Failed(err);
+                    },
+                }
+                Action::None
+            },
+            Message::CancelPressed => {
+                if let ImportState::Running { cancel, .. } = &self.state {
+                    cancel.store(true, Ordering::Relaxed);
+                }
+                Action::None
+            },
+            Message::SaveReportPressed => Action::Run(
+                iced::Task::future(
+                    rfd::AsyncFileDialog::new()
+                        .add_filter("CSV", &["csv"])
+                        .set_file_name("rejected.csv")
+                        .set_title("Save rejected rows")
+                        .save_file()
+                )
+                .map(|handle| Message::ReportFileChosen(handle.map(|handle| handle.path().to_path_buf())))
+            ),
+            Message::ReportFileChosen(None) => Action::None,
+            Message::ReportFileChosen(Some(path)) => {
+                let ImportState::Finished { rejected, .. } = &self.state else {
+                    return Action::None;
+                };
+
+                let rejected = rejected.clone();
+                Action::Run(iced::Task::perform(Self::save_report(path, rejected), Message::ReportSaved))
+            },
+            Message::ReportSaved(res) => {
+                self.report_status = Some(res.map(|path| format!("Report saved to {}", path.to_string_lossy())));
+                Action::None
+            },
+        }
+    }
+
+    fn read_file(&mut self) -> Action {
+        let Some(path) = self.path.clone() else {
+            return Action::None;
+        };
+
+        self.rows = None;
+        self.fields.clear();
+        Action::Run(iced::Task::perform(Self::read_rows(path, self.format), Message::FileRead))
+    }
+
+    async fn read_rows(path: std::path::PathBuf, format: import::Format) -> Result<Vec<import::Row>, String> {
+        let contents = tokio::fs::read_to_string(&path).await
+            .map_err(|err| format!("Unable to read {}, error kind: {}", path.to_string_lossy(), err.kind()))?;
+
+        import::parse(format, &contents)
+            .map_err(|err| format!("Unable to parse {}: {}", path.to_string_lossy(), err))
+    }
+
+    async fn save_report(path: std::path::PathBuf, rejected: Vec<import::Rejected>) -> Result<std::path::PathBuf, String> {
+        let mut contents = Vec::new();
+        import::write_report(&rejected, &mut contents).map_err(|err| err.to_string())?;
+
+        tokio::fs::write(&path, contents).await
+            .map_err(|err| format!("Unable to write {}, error kind: {}", path.to_string_lossy(), err.kind()))?;
+
+        Ok(path)
+    }
+
+    pub fn try_import_with_client(
+        client_res: Result<es::ElasticsearchClient, String>,
+        request: ImportRequest
+    ) -> iced::Task<Message> {
+        iced::Task::run(Self::import(client_res, request), Message::ImportUpdated)
+    }
+
+    fn import(
+        client_res: Result<es::ElasticsearchClient, String>,
+        request: ImportRequest
+    ) -> impl iced::futures::Stream<Item = Result<ImportEvent, String>> {
+        iced::stream::try_channel(1, async move |mut sender| {
+            use iced::futures::SinkExt;
+
+            let client = client_res?;
+            let mut import = import::Import::new(client, request.index, request.rows, request.id_field);
+
+            loop {
+                // Batches already sent stay indexed
+                if request.cancel.load(Ordering::Relaxed) {
+                    return Err("Import cancelled".to_owned());
+                }
+
+                let progress = import.next_batch().await.map_err(|err| err.to_string())?;
+
+                if progress.finished {
+                    let _ = sender.send(ImportEvent::Finished(progress, import.into_rejected())).await;
+                    return Ok(());
+                }
+
+                let _ = sender.send(ImportEvent::Progressed(progress)).await;
+            }
+        })
+    }
+
+    pub fn view(&self) -> iced::Element<'_, Message> {
+        iced::widget::column![
+            widget::section_with_header(
+                iced::widget::text("Import"),
+                iced::widget::column![
+                    iced::widget::row![
+                        iced::widget::button("Choose file").on_press(Message::ChooseFilePressed),
+                        iced::widget::text(
+                            self.path.as_ref()
+                                .map(|path| path.to_string_lossy().to_string())
+                                .unwrap_or("No file selected".to_owned())
+                        ),
+                        iced::widget::space::horizontal(),
+                        iced::widget::pick_list(import::Format::ALL, Some(self.format), Message::FormatSelected),
+                    ]
+                    .spacing(10)
+                    .align_y(iced::Center),
+                    iced::widget::row![
+                        iced::widget::text_input("Index", &self.index)
+                            .on_input(Message::IndexUpdated),
+                        iced::widget::text_input("ID field (optional)", &self.id_field)
+                            .on_input(Message::IdFieldUpdated),
+                        iced::widget::button("Import").on_press_maybe(self.can_import().then_some(Message::ImportPressed)),
+                    ]
+                    .spacing(10)
+                    .align_y(iced::Center),
+                    self.status(),
+                ]
+                .spacing(10)
+            ),
+            self.preview()
+                .width(iced::Fill)
+                .height(iced::Fill),
+        ]
+        .spacing(10)
+        .into()
+    }
+
+    fn can_import(&self) -> bool {
+        matches!(&self.rows, Some(Ok(rows)) if !rows.is_empty())
+            && !self.index.trim().is_empty()
+            && !matches!(self.state, ImportState::Running { .. })
+    }
+
+    fn progress_text(progress: &import::Progress) -> String {
+        format!(
+            "{} of {} rows processed | {} indexed | {} rejected | {:.0} docs/s",
+            progress.processed, progress.total, progress.indexed, progress.rejected, progress.documents_per_second()
+        )
+    }
+
+    fn status(&self) -> iced::Element<'_, Message> {
+        match &self.state {
+            ImportState::Idle => iced::widget::space::vertical().height(0).into(),
+            ImportState::Running { progress, cancel } => {
+                let cancelling = cancel.load(Ordering::Relaxed);
+
+                iced::widget::row![
+                    iced::widget::progress_bar(0.0..=progress.total.max(1) as f32, progress.processed as f32)
+                        .length(200)
+                        .girth(10),
+                    iced::widget::text(Self::progress_text(progress)).size(14),
+                    iced::widget::space::horizontal(),
+                    iced::widget::button(iced::widget::text(if cancelling { "Cancelling" } else { "Cancel" }).size(14))
+                        .on_press_maybe((!cancelling).then_some(Message::CancelPressed)),
+                ]
+                .spacing(10)
+                .align_y(iced::Center)
+                .into()
+            },
+            ImportState::Finished { progress, rejected } => iced::widget::column![
+                iced::widget::row![
+                    iced::widget::text(Self::progress_text(progress)).size(14),
+                    iced::widget::space::horizontal(),
+                    (!rejected.is_empty()).then(|| {
+                        iced::widget::button(iced::widget::text("Save rejected rows").size(14))
+                            .on_press(Message::SaveReportPressed)
+                    }),
+                ]
+                .spacing(10)
+                .align_y(iced::Center),
+                self.report_status.as_ref().map(|status| match status {
+                    Ok(status) => iced::widget::text(status).size(14),
+                    Err(err) => iced::widget::text(err).size(14).style(iced::widget::text::danger),
+                }),
+            ]
+            .spacing(5)
+            .into(),
+            ImportState::Failed(err) => iced::widget::text(format!("Import failed: {}", err))
+                .size(14)
+                .style(iced::widget::text::danger)
+                .into(),
+        }
+    }
+
+    // Fields found in the file, as they will be indexed
+    fn preview(&self) -> iced::widget::Container<'_, Message> {
+        match &self.rows {
+            None => widget::section(
+                iced::widget::text(
+                    match self.path {
+                        Some(_) => "Reading file",
+                        None => "Choose an NDJSON, JSON or CSV file to import. CSV headers become field names, dotted headers create objects.",
+                    }
+                )
+                .align_x(iced::Center)
+                .align_y(iced::Center)
+            ),
+            Some(Err(err)) => widget::section_with_header(
+                iced::widget::text("ERROR"),
+                iced_selection::text(err)
+            ),
+            Some(Ok(rows)) => {
+                let unreadable = rows.iter().filter(|row| row.document.is_err()).count();
+
+                widget::section_with_header(
+                    iced::widget::row![
+                        iced::widget::text("Fields"),
+                        iced::widget::space::horizontal(),
+                        iced::widget::text(format!("{} rows | {} unreadable", rows.len(), unreadable)).size(14),
+                    ]
+                    .align_y(iced::Center),
+                    iced::widget::scrollable(
+                        iced::widget::column(self.fields.iter().map(|(field, field_type)| {
+                            iced::widget::row![
+                                iced_selection::text(field)
+                                    .size(14)
+                                    .width(iced::FillPortion(3)),
+                                iced::widget::text(*field_type)
+                                    .size(14)
+                                    .font(iced::Font::MONOSPACE)
+                                    .width(iced::FillPortion(1)),
+                            ]
+                            .into()
+                        }))
+                        .spacing(5)
+                        .width(iced::Fill)
+                    )
+                    .height(iced::Fill)
+                )
+            },
+        }
+    }
+}
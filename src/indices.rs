@@ -0,0 +1,645 @@
+// Indicies, data streams and aliases of the cluster, with rollover and deletion of data streams and alias management
+use std::collections::{HashMap, HashSet};
+
+use crate::{es, logs, widget};
+
+mod aliases;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    // Lists the indicies, data streams and aliases again, sent when the page is opened
+    Refresh,
+    TabSelected(Tab),
+    SearchUpdated(String),
+    ShowHiddenToggled(bool),
+    Listed(Listing),
+    DataStreamToggled(String),
+    RolloverPressed(String),
+    RolledOver(String, Result<es::RolloverResult, String>),
+    // Asks for confirmation before deleting
+    DeletePressed(String),
+    DeleteCancelled,
+    DeleteConfirmed,
+    Deleted(String, Result<(), String>),
+    AliasRemovePressed(usize),
+    AliasActionKindSelected(aliases::ActionKind),
+    AliasFormUpdated(aliases::FormField, String),
+    AliasFormWriteIndexToggled(bool),
+    AliasActionQueued,
+    AliasSwapAliasSelected(String),
+    AliasSwapIndexSelected(String),
+    AliasSwapWriteIndexToggled(bool),
+    AliasSwapQueued,
+    AliasPendingRemoved(usize),
+    AliasPendingCleared,
+    // Asks for confirmation first when any of the actions deletes an index
+    AliasesApplyPressed,
+    AliasesApplyCancelled,
+    AliasesApplyConfirmed,
+    // Number of actions applied, the first ones pending
+    AliasesApplied(Result<usize, String>),
+}
+
+// Data streams along with their stats by name, which may fail on their own
+type DataStreamsResult = Result<(Vec<es::DataStream>, Result<HashMap<String, es::DataStreamStats>, String>), String>;
+
+// Each list is obtained on its own, one failing leaves the others listed
+#[derive(Debug, Clone)]
+pub struct Listing {
+    indicies: Result<Vec<es::ElasticSearchIndex>, String>,
+    data_streams: DataStreamsResult,
+    aliases: Result<Vec<es::IndexAlias>, String>,
+}
+
+pub enum Action {
+    None,
+    TryClientInvoke(Context),
+}
+
+pub enum Context {
+    All,
+    Rollover(String),
+    DeleteDataStream(String),
+    // Applied atomically
+    UpdateAliases(Vec<es::AliasAction>),
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Tab {
+    #[default]
+    DataStreams,
+    Indicies,
+    Aliases,
+}
+
+#[derive(Debug, Default)]
+pub struct View {
+    log: logs::Log,
+    tab: Tab,
+    search: String,
+    show_hidden: bool,
+    // None until listed
+    indicies: Option<Result<Vec<es::ElasticSearchIndex>, String>>,
+    data_streams: Option<Result<Vec<es::DataStream>, String>>,
+    // Stats by data stream name, empty when they could not be obtained
+    data_stream_stats: HashMap<String, es::DataStreamStats>,
+    // Data streams listed with their backing indicies
+    expanded: HashSet<String>,
+    // Data stream awaiting confirmation of its deletion
+    confirm_delete: Option<String>,
+    // Data streams being rolled over or deleted
+    pending: HashSet<String>,
+    aliases: aliases::State,
+    // Outcome of the last rollover, deletion or alias actions applied
+    status: Option<Result<String, String>>,
+}
+
+impl View {
+    pub fn new(log: logs::Log) -> Self {
+        Self { log, ..Default::default() }
+    }
+
+    #[must_use]
+    pub fn update(&mut self, message: Message) -> Action {
+        match message {
+            Message::Refresh => Action::TryClientInvoke(Context::All),
+            Message::TabSelected(tab) => {
+                self.tab = tab;
+                Action::None
+            },
+            Message::SearchUpdated(search) => {
+                self.search = search;
+                Action::None
+            },
+            Message::ShowHiddenToggled(show) => {
+                self.show_hidden = show;
+                Action::None
+            },
+            Message::Listed(listing) => {
+                if let Err(err) = &listing.indicies {
+                    self.log.error(format!("Failed to list indicies: {}", err));
+                }
+                self.indicies = Some(listing.indicies.map(|mut indicies| {
+                    indicies.sort_by(|a, b| a.name.cmp(&b.name));
+                    indicies
+                }));
+
+                if let Err(err) = &listing.aliases {
+                    self.log.error(format!("Failed to list aliases: {}", err));
+                }
+                self.aliases.aliases = Some(listing.aliases);
+
+                match listing.data_streams {
+                    Ok((mut data_streams, stats)) => {
+                        data_streams.sort_by(|a, b| a.name.cmp(&b.name));
+                        self.expanded.retain(|name| data_streams.iter().any(|data_stream| data_stream.name == *name));
+                        self.data_streams = Some(Ok(data_streams));
+                        // Data streams are listed without stats when those cannot be obtained
+                        self.data_stream_stats = stats.unwrap_or_else(|err| {
+                            self.log.error(format!("Failed to get data stream stats: {}", err));
+                            HashMap::new()
+                        });
+                    },
+                    Err(err) => {
+                        self.log.error(format!("Failed to list data streams: {}", err));
+                        self.data_streams = Some(Err(err));
+                    },
+                }
+                Action::None
+            },
+            Message::DataStreamToggled(name) => {
+                if !self.expanded.remove(&name) {
+                    self.expanded.insert(name);
+                }
+                Action::None
+            },
+            Message::RolloverPressed(name) => {
+                if !self.pending.insert(name.clone()) {
+                    return Action::None;
+                }
+                self.status = None;
+                Action::TryClientInvoke(Context::Rollover(name))
+            },
+            Message::RolledOver(name, res) => {
+                self.pending.remove(&name);
+                self.status = Some(match res {
+                    Ok(rollover) => {
+                        self.log.info(format!("Rolled {} over from {} to {}", name, rollover.old_index, rollover.new_index));
+                        Ok(format!("Rolled {} over to {}", name, rollover.new_index))
+                    },
+                    Err(err) => {
+                        self.log.error(format!("Failed to roll {} over: {}", name, err));
+                        Err(format!("Failed to roll {} over: {}", name, err))
+                    },
+                });
+                Action::TryClientInvoke(Context::All)
+            },
+            Message::DeletePressed(name) => {
+                self.confirm_delete = Some(name);
+                Action::None
+            },
+            Message::DeleteCancelled => {
+                self.confirm_delete = None;
+                Action::None
+            },
+            Message::DeleteConfirmed => {
+                let Some(name) = self.confirm_delete.take() else {
+                    return Action::None;
+                };
+                if !self.pending.insert(name.clone()) {
+                    return Action::None;
+                }
+                self.status = None;
+                Action::TryClientInvoke(Context::DeleteDataStream(name))
+            },
+            Message::Deleted(name, res) => {
+                self.pending.remove(&name);
+                self.status = Some(match res {
+                    Ok(()) => {
+                        self.log.info(format!("Deleted data stream {}", name));
+                        Ok(format!("Deleted data stream {}", name))
+                    },
+                    Err(err) => {
+                        self.log.error(format!("Failed to delete data stream {}: {}", name, err));
+                        Err(format!("Failed to delete data stream {}: {}", name, err))
+                    },
+                });
+                Action::TryClientInvoke(Context::All)
+            },
+            Message::AliasRemovePressed(idx) => {
+                if let Some(Ok(aliases)) = &self.aliases.aliases && let Some(index_alias) = aliases.get(idx) {
+                    let action = es::AliasAction::remove(&index_alias.index, &index_alias.alias);
+                    if !self.aliases.pending.contains(&action) {
+                        self.aliases.pending.push(action);
+                    }
+                }
+                Action::None
+            },
+            Message::AliasActionKindSelected(kind) => {
+                self.aliases.form.kind = kind;
+                self.aliases.form_error = None;
+                Action::None
+            },
+            Message::AliasFormUpdated(field, value) => {
+                self.aliases.form.set(field, value);
+                Action::None
+            },
+            Message::AliasFormWriteIndexToggled(write_index) => {
+                self.aliases.form.write_index = write_index;
+                Action::None
+            },
+            Message::AliasActionQueued => {
+                match self.aliases.form.action() {
+                    Ok(action) => {
+                        self.aliases.pending.push(action);
+                        self.aliases.form_error = None;
+                    },
+                    Err(err) => self.aliases.form_error = Some(err),
+                }
+                Action::None
+            },
+            Message::AliasSwapAliasSelected(alias) => {
+                self.aliases.swap.alias = Some(alias);
+                Action::None
+            },
+            Message::AliasSwapIndexSelected(index) => {
+                self.aliases.swap.index = Some(index);
+                Action::None
+            },
+            Message::AliasSwapWriteIndexToggled(write_index) => {
+                self.aliases.swap.write_index = write_index;
+                Action::None
+            },
+            Message::AliasSwapQueued => {
+                if let Some(actions) = self.aliases.swap_actions() {
+                    self.aliases.pending.extend(actions);
+                    self.aliases.swap = aliases::Swap::default();
+                }
+                Action::None
+            },
+            Message::AliasPendingRemoved(idx) => {
+                if idx < self.aliases.pending.len() {
+                    self.aliases.pending.remove(idx);
+                    self.aliases.confirm_apply = false;
+                }
+                Action::None
+            },
+            Message::AliasPendingCleared => {
+                self.aliases.pending.clear();
+                self.aliases.confirm_apply = false;
+                Action::None
+            },
+            Message::AliasesApplyPressed => {
+                if self.aliases.deleted_indicies().is_empty() {
+                    self.update(Message::AliasesApplyConfirmed)
+                } else {
+                    self.aliases.confirm_apply = true;
+                    Action::None
+                }
+            },
+            Message::AliasesApplyCancelled => {
+                self.aliases.confirm_apply = false;
+                Action::None
+            },
+            Message::AliasesApplyConfirmed => {
+                self.aliases.confirm_apply = false;
+                if self.aliases.pending.is_empty() || self.aliases.applying {
+                    return Action::None;
+                }
+                self.aliases.applying = true;
+                self.status = None;
+                Action::TryClientInvoke(Context::UpdateAliases(self.aliases.pending.clone()))
+            },
+            Message::AliasesApplied(res) => {
+                self.aliases.applying = false;
+                self.status = Some(match res {
+                    Ok(count) => {
+                        // Actions queued while applying were not sent and stay pending
+                        let count = count.min(self.aliases.pending.len());
+                        let actions = self.aliases.pending.drain(..count)
+                            .map(|action| aliases::describe(&action))
+                            .collect::<Vec<String>>();
+                        self.log.info(format!("Applied alias actions: {}", actions.join("; ")));
+                        Ok(format!("Applied {} alias actions", count))
+                    },
+                    Err(err) => {
+                        self.log.error(format!("Failed to apply alias actions: {}", err));
+                        Err(format!("Failed to apply alias actions, none were applied: {}", err))
+                    },
+                });
+                Action::TryClientInvoke(Context::All)
+            },
+        }
+    }
+
+    pub fn try_invoke_with_client(
+        client_res: Result<es::ElasticsearchClient, String>,
+        context: Context
+    ) -> iced::Task<Message> {
+        match context {
+            Context::All => iced::Task::perform(Self::list(client_res), Message::Listed),
+            Context::Rollover(name) => iced::Task::perform(
+                Self::rollover(client_res, name.clone()),
+                move |res| Message::RolledOver(name.clone(), res)
+            ),
+            Context::DeleteDataStream(name) => iced::Task::perform(
+                Self::delete_data_stream(client_res, name.clone()),
+                move |res| Message::Deleted(name.clone(), res)
+            ),
+            Context::UpdateAliases(actions) => iced::Task::perform(
+                Self::update_aliases(client_res, actions),
+                Message::AliasesApplied
+            ),
+        }
+    }
+
+    async fn list(client_res: Result<es::ElasticsearchClient, String>) -> Listing {
+        let client = match client_res {
+            Ok(client) => client,
+            Err(err) => return Listing { indicies: Err(err.clone()), data_streams: Err(err.clone()), aliases: Err(err) },
+        };
+
+        let (indicies, data_streams, aliases) = iced::futures::join!(
+            Self::get_indicies(&client),
+            Self::get_data_streams(&client),
+            Self::get_aliases(&client)
+        );
+
+        Listing { indicies, data_streams, aliases }
+    }
+
+    async fn get_aliases(client: &es::ElasticsearchClient) -> Result<Vec<es::IndexAlias>, String> {
+        client.get_index_aliases().await
+            .map_err(|err| err.to_string())
+    }
+
+    async fn get_indicies(client: &es::ElasticsearchClient) -> Result<Vec<es::ElasticSearchIndex>, String> {
+        client.get_indicies_with_hidden().await
+            .map_err(|err| err.to_string())
+    }
+
+    async fn get_data_streams(client: &es::ElasticsearchClient) -> DataStreamsResult {
+        let data_streams = client.get_data_streams().await
+            .map_err(|err| err.to_string())?;
+
+        let stats = client.get_data_stream_stats().await
+            .map(|stats| stats.into_iter()
+                .map(|stats| (stats.data_stream.clone(), stats))
+                .collect())
+            .map_err(|err| err.to_string());
+
+        Ok((data_streams, stats))
+    }
+
+    async fn rollover(client_res: Result<es::ElasticsearchClient, String>, name: String) -> Result<es::RolloverResult, String> {
+        let client = client_res?;
+        client.rollover(&name).await
+            .map_err(|err| err.to_string())
+    }
+
+    async fn update_aliases(client_res: Result<es::ElasticsearchClient, String>, actions: Vec<es::AliasAction>) -> Result<usize, String> {
+        let client = client_res?;
+        client.update_aliases(&actions).await
+            .map_err(|err| err.to_string())?;
+        Ok(actions.len())
+    }
+
+    async fn delete_data_stream(client_res: Result<es::ElasticsearchClient, String>, name: String) -> Result<(), String> {
+        let client = client_res?;
+        client.delete_data_stream(&name).await
+            .map_err(|err| err.to_string())
+    }
+
+    fn matches(&self, name: &str, hidden: bool) -> bool {
+        let search = self.search.trim().to_lowercase();
+        (self.show_hidden || !hidden) && (search.is_empty() || name.to_lowercase().contains(&search))
+    }
+
+    pub fn view(&self) -> iced::Element<'_, Message> {
+        let tab_button = |label, tab| {
+            iced::widget::button(label)
+                .on_press(Message::TabSelected(tab))
+                .style(if self.tab == tab { iced::widget::button::primary } else { iced::widget::button::secondary })
+        };
+
+        iced::widget::column![
+            iced::widget::row![
+                tab_button("Data streams", Tab::DataStreams),
+                tab_button("Indices", Tab::Indicies),
+                tab_button("Aliases", Tab::Aliases),
+                iced::widget::text_input("Filter by name", &self.search)
+                    .on_input(Message::SearchUpdated),
+                iced::widget::checkbox(self.show_hidden)
+                    .label("Hidden")
+                    .on_toggle(Message::ShowHiddenToggled),
+                iced::widget::button("Refresh").on_press(Message::Refresh),
+            ]
+            .spacing(10)
+            .align_y(iced::Center),
+            self.status.as_ref().map(|status| match status {
+                Ok(status) => iced::widget::text(status).size(14),
+                Err(err) => iced::widget::text(err).size(14).style(iced::widget::text::danger),
+            }),
+            self.confirm_delete.as_ref().map(Self::delete_confirmation),
+            self.aliases.confirm_apply.then(|| self.aliases.apply_confirmation()),
+            self.tab_view(),
+        ]
+        .spacing(10)
+        .into()
+    }
+
+    fn tab_view(&self) -> iced::Element<'_, Message> {
+        match self.tab {
+            Tab::DataStreams => self.data_streams_view()
+                .width(iced::Fill)
+                .height(iced::Fill)
+                .into(),
+            Tab::Indicies => self.indicies_view()
+                .width(iced::Fill)
+                .height(iced::Fill)
+                .into(),
+            Tab::Aliases => self.aliases.view(self.index_names(), |name, hidden| self.matches(name, hidden))
+                .height(iced::Fill)
+                .into(),
+        }
+    }
+
+    // Indicies and data streams an alias can point to
+    fn index_names(&self) -> Vec<String> {
+        let indicies = self.indicies.iter()
+            .flatten()
+            .flatten()
+            .filter(|index| self.show_hidden || !index.hidden)
+            .map(|index| index.name.clone());
+
+        let data_streams = self.data_streams.iter()
+            .flatten()
+            .flatten()
+            .filter(|data_stream| self.show_hidden || !data_stream.hidden)
+            .map(|data_stream| data_stream.name.clone());
+
+        let mut names = indicies.chain(data_streams).collect::<Vec<String>>();
+        names.sort();
+        names
+    }
+
+    fn delete_confirmation(name: &String) -> iced::widget::Container<'_, Message> {
+        widget::section(
+            iced::widget::row![
+                iced::widget::text(format!("Delete data stream {} along with all of its backing indices?", name)),
+                iced::widget::space::horizontal(),
+                iced::widget::button("Delete")
+                    .on_press(Message::DeleteConfirmed)
+                    .style(iced::widget::button::danger),
+                iced::widget::button("Cancel")
+                    .on_press(Message::DeleteCancelled)
+                    .style(iced::widget::button::secondary),
+            ]
+            .spacing(10)
+            .align_y(iced::Center)
+        )
+    }
+
+    fn cell<'a>(content: impl iced::widget::text::IntoFragment<'a>, portion: u16) -> iced::widget::Text<'a> {
+        iced::widget::text(content)
+            .size(14)
+            .width(iced::FillPortion(portion))
+    }
+
+    fn data_streams_view(&self) -> iced::widget::Container<'_, Message> {
+        let data_streams = match &self.data_streams {
+            None => return widget::section(iced::widget::text("Listing data streams")),
+            Some(Err(err)) => return widget::section_with_header(
+                iced::widget::text("ERROR"),
+                iced_selection::text(err)
+            ),
+            Some(Ok(data_streams)) => data_streams,
+        };
+
+        let rows = data_streams.iter()
+            .filter(|data_stream| self.matches(&data_stream.name, data_stream.hidden))
+            .map(|data_stream| self.data_stream_item(data_stream).into());
+
+        widget::section_with_header(
+            iced::widget::row![
+                Self::cell("Name", 4),
+                Self::cell("Generation", 1),
+                Self::cell("Health", 1),
+                Self::cell("Template", 2),
+                Self::cell("ILM policy", 2),
+                Self::cell("Indices", 1),
+                Self::cell("Size", 1),
+                iced::widget::space().width(170),
+            ]
+            .spacing(5),
+            iced::widget::scrollable(
+                iced::widget::column(rows)
+                    .spacing(5)
+            )
+            .height(iced::Fill)
+        )
+    }
+
+    fn data_stream_item<'a>(&'a self, data_stream: &'a es::DataStream) -> iced::widget::Column<'a, Message> {
+        let name = &data_stream.name;
+        let expanded = self.expanded.contains(name);
+        let pending = self.pending.contains(name);
+        let size = self.data_stream_stats.get(name)
+            .and_then(|stats| stats.store_size.clone())
+            .unwrap_or_default();
+
+        let header = iced::widget::row![
+            iced::widget::row![
+                iced::widget::button(iced::widget::text(if expanded { "▾" } else { "▸" }).size(12))
+                    .on_press(Message::DataStreamToggled(name.clone()))
+                    .padding([0, 4])
+                    .style(iced::widget::button::text),
+                iced_selection::text(name).size(14),
+                data_stream.hidden.then(|| {
+                    iced::widget::text("hidden")
+                        .size(12)
+                        .style(iced::widget::text::secondary)
+                }),
+            ]
+            .spacing(5)
+            .align_y(iced::Center)
+            .width(iced::FillPortion(4)),
+            Self::cell(data_stream.generation.to_string(), 1),
+            Self::cell(data_stream.status.to_lowercase(), 1),
+            Self::cell(data_stream.template.as_deref().unwrap_or("-"), 2),
+            Self::cell(data_stream.ilm_policy.as_deref().unwrap_or("-"), 2),
+            Self::cell(data_stream.indices.len().to_string(), 1),
+            Self::cell(size, 1),
+            iced::widget::row![
+                iced::widget::button(iced::widget::text("Rollover").size(12))
+                    .on_press_maybe((!pending).then(|| Message::RolloverPressed(name.clone()))),
+                iced::widget::button(iced::widget::text("Delete").size(12))
+                    .on_press_maybe((!pending).then(|| Message::DeletePressed(name.clone())))
+                    .style(iced::widget::button::danger),
+            ]
+            .spacing(5)
+            .width(170),
+        ]
+        .spacing(5)
+        .align_y(iced::Center);
+
+        // Newest first, the write index on top
+        let backing_indicies = expanded.then(|| {
+            iced::widget::column(data_stream.indices.iter().rev().enumerate().map(|(idx, index)| {
+                iced::widget::row![
+                    iced::widget::space().width(20),
+                    iced_selection::text(&index.index_name)
+                        .size(12)
+                        .width(iced::FillPortion(4)),
+                    Self::cell(if idx == 0 { "write index" } else { "" }, 2).size(12),
+                    Self::cell(index.managed_by.as_deref().unwrap_or_default(), 2).size(12),
+                    Self::cell(index.ilm_policy.as_deref().unwrap_or_default(), 2).size(12),
+                ]
+                .spacing(5)
+                .into()
+            }))
+            .spacing(2)
+        });
+
+        iced::widget::column![
+            header,
+            backing_indicies,
+            iced::widget::rule::horizontal(1),
+        ]
+        .spacing(5)
+    }
+
+    fn indicies_view(&self) -> iced::widget::Container<'_, Message> {
+        let indicies = match &self.indicies {
+            None => return widget::section(iced::widget::text("Listing indices")),
+            Some(Err(err)) => return widget::section_with_header(
+                iced::widget::text("ERROR"),
+                iced_selection::text(err)
+            ),
+            Some(Ok(indicies)) => indicies,
+        };
+
+        let rows = indicies.iter()
+            .filter(|index| self.matches(&index.name, index.hidden))
+            .map(|index| {
+                iced::widget::row![
+                    iced::widget::row![
+                        iced_selection::text(&index.name).size(14),
+                        index.hidden.then(|| {
+                            iced::widget::text("hidden")
+                                .size(12)
+                                .style(iced::widget::text::secondary)
+                        }),
+                    ]
+                    .spacing(5)
+                    .align_y(iced::Center)
+                    .width(iced::FillPortion(4)),
+                    Self::cell(index.health.as_deref().unwrap_or("-"), 1),
+                    Self::cell(index.status.as_deref().unwrap_or("-"), 1),
+                    Self::cell(index.docs_count.as_deref().unwrap_or("-"), 1),
+                    Self::cell(index.dataset_size.as_deref().unwrap_or("-"), 1),
+                    Self::cell(format!("{} / {}", index.primary_shard_count, index.replica_shard_count), 1),
+                ]
+                .spacing(5)
+                .align_y(iced::Center)
+                .into()
+            });
+
+        widget::section_with_header(
+            iced::widget::row![
+                Self::cell("Name", 4),
+                Self::cell("Health", 1),
+                Self::cell("Status", 1),
+                Self::cell("Documents", 1),
+                Self::cell("Size", 1),
+                Self::cell("Shards / replicas", 1),
+            ]
+            .spacing(5),
+            iced::widget::scrollable(
+                iced::widget::column(rows)
+                    .spacing(5)
+            )
+            .height(iced::Fill)
+        )
+    }
+}
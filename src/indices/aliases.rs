@@ -0,0 +1,448 @@
+// Aliases tab of the indices page, alias actions are queued then applied together in a single atomic request
+use crate::{es, widget};
+
+use super::Message;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ActionKind {
+    #[default]
+    Add,
+    Remove,
+    RemoveIndex,
+}
+
+impl ActionKind {
+    pub const ALL: [ActionKind; 3] = [ActionKind::Add, ActionKind::Remove, ActionKind::RemoveIndex];
+}
+
+impl std::fmt::Display for ActionKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ActionKind::Add => "Add alias",
+            ActionKind::Remove => "Remove alias",
+            ActionKind::RemoveIndex => "Remove index",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum FormField {
+    Index,
+    Alias,
+    Filter,
+    Routing,
+    IndexRouting,
+    SearchRouting,
+}
+
+// Action entered by hand
+#[derive(Debug, Default)]
+pub struct Form {
+    pub kind: ActionKind,
+    pub index: String,
+    pub alias: String,
+    // Query as JSON, e.g. {"term": {"user.id": "kimchy"}}
+    pub filter: String,
+    pub routing: String,
+    pub index_routing: String,
+    pub search_routing: String,
+    pub write_index: bool,
+}
+
+impl Form {
+    pub fn set(&mut self, field: FormField, value: String) {
+        match field {
+            FormField::Index => self.index = value,
+            FormField::Alias => self.alias = value,
+            FormField::Filter => self.filter = value,
+            FormField::Routing => self.routing = value,
+            FormField::IndexRouting => self.index_routing = value,
+            FormField::SearchRouting => self.search_routing = value,
+        }
+    }
+
+    // The action entered, or why it cannot be queued
+    pub fn action(&self) -> Result<es::AliasAction, String> {
+        let index = self.index.trim();
+        let alias = self.alias.trim();
+
+        if index.is_empty() {
+            return Err("An index is required".to_owned());
+        }
+        if self.kind != ActionKind::RemoveIndex && alias.is_empty() {
+            return Err("An alias is required".to_owned());
+        }
+
+        Ok(match self.kind {
+            ActionKind::Add => {
+                let mut add = es::AddAlias::new(index, alias);
+                if !self.filter.trim().is_empty() {
+                    let filter = serde_json::from_str::<serde_json::Value>(&self.filter)
+                        .map_err(|err| format!("Invalid filter: {}", err))?;
+                    if !filter.is_object() {
+                        return Err("Invalid filter: expected a query object".to_owned());
+                    }
+                    add = add.filter(filter);
+                }
+                if !self.routing.trim().is_empty() {
+                    add = add.routing(self.routing.trim());
+                }
+                if !self.index_routing.trim().is_empty() {
+                    add = add.index_routing(self.index_routing.trim());
+                }
+                if !self.search_routing.trim().is_empty() {
+                    add = add.search_routing(self.search_routing.trim());
+                }
+                if self.write_index {
+                    add = add.is_write_index(true);
+                }
+                add.into()
+            },
+            ActionKind::Remove => es::AliasAction::remove(index, alias),
+            ActionKind::RemoveIndex => es::AliasAction::remove_index(index),
+        })
+    }
+}
+
+// Moves an alias onto another index, e.g. once the index was reindexed into
+#[derive(Debug, Default)]
+pub struct Swap {
+    pub alias: Option<String>,
+    pub index: Option<String>,
+    pub write_index: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct State {
+    // None until listed
+    pub aliases: Option<Result<Vec<es::IndexAlias>, String>>,
+    pub form: Form,
+    pub form_error: Option<String>,
+    pub swap: Swap,
+    // Applied together, in order
+    pub pending: Vec<es::AliasAction>,
+    // Pending actions delete indicies and await confirmation
+    pub confirm_apply: bool,
+    pub applying: bool,
+}
+
+// Line of the pending actions list
+pub fn describe(action: &es::AliasAction) -> String {
+    match action {
+        es::AliasAction::Add(add) => {
+            let options = [
+                add.is_write_index.is_some_and(|is_write_index| is_write_index).then(|| "write index".to_owned()),
+                add.filter.is_some().then(|| "filtered".to_owned()),
+                add.routing.as_ref().map(|routing| format!("routing {}", routing)),
+                add.index_routing.as_ref().map(|routing| format!("index routing {}", routing)),
+                add.search_routing.as_ref().map(|routing| format!("search routing {}", routing)),
+            ]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<String>>();
+
+            if options.is_empty() {
+                format!("Add alias {} to {}", add.alias, add.index)
+            } else {
+                format!("Add alias {} to {} ({})", add.alias, add.index, options.join(", "))
+            }
+        },
+        es::AliasAction::Remove(remove) => format!("Remove alias {} from {}", remove.alias, remove.index),
+        es::AliasAction::RemoveIndex(remove_index) => format!("Delete index {}", remove_index.index),
+    }
+}
+
+impl State {
+    fn alias_names(&self) -> Vec<String> {
+        let mut names = self.aliases.iter()
+            .flatten()
+            .flatten()
+            .map(|alias| alias.alias.clone())
+            .collect::<Vec<String>>();
+        names.dedup();
+        names
+    }
+
+    // Indicies the alias points to now
+    fn alias_indicies(&self, alias: &str) -> Vec<String> {
+        self.aliases.iter()
+            .flatten()
+            .flatten()
+            .filter(|index_alias| index_alias.alias == alias)
+            .map(|index_alias| index_alias.index.clone())
+            .collect()
+    }
+
+    // Indicies deleted by the pending actions
+    pub fn deleted_indicies(&self) -> Vec<&str> {
+        self.pending.iter()
+            .filter_map(|action| match action {
+                es::AliasAction::RemoveIndex(remove_index) => Some(remove_index.index.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn apply_confirmation(&self) -> iced::widget::Container<'_, Message> {
+        widget::section(
+            iced::widget::row![
+                iced::widget::text(format!(
+                    "Apply the alias actions, deleting {} along with all of their documents?",
+                    self.deleted_indicies().join(", ")
+                )),
+                iced::widget::space::horizontal(),
+                iced::widget::button("Apply")
+                    .on_press(Message::AliasesApplyConfirmed)
+                    .style(iced::widget::button::danger),
+                iced::widget::button("Cancel")
+                    .on_press(Message::AliasesApplyCancelled)
+                    .style(iced::widget::button::secondary),
+            ]
+            .spacing(10)
+            .align_y(iced::Center)
+        )
+    }
+
+    pub fn swap_actions(&self) -> Option<Vec<es::AliasAction>> {
+        let alias = self.swap.alias.as_ref()?;
+        let index = self.swap.index.as_ref()?;
+        let current = self.aliases.iter().flatten().flatten();
+        es::AliasAction::swap(alias, current, index.clone(), self.swap.write_index).ok()
+    }
+
+    pub fn view<'a>(
+        &'a self,
+        indicies: Vec<String>,
+        matches: impl Fn(&str, bool) -> bool + 'a
+    ) -> iced::widget::Row<'a, Message> {
+        iced::widget::row![
+            self.mappings_view(matches)
+                .width(iced::FillPortion(3))
+                .height(iced::Fill),
+            iced::widget::column![
+                self.swap_view(indicies),
+                self.form_view(),
+                self.pending_view(),
+            ]
+            .spacing(10)
+            .width(iced::FillPortion(2)),
+        ]
+        .spacing(10)
+    }
+
+    fn mappings_view<'a>(&'a self, matches: impl Fn(&str, bool) -> bool + 'a) -> iced::widget::Container<'a, Message> {
+        let aliases = match &self.aliases {
+            None => return widget::section(iced::widget::text("Listing aliases")),
+            Some(Err(err)) => return widget::section_with_header(
+                iced::widget::text("ERROR"),
+                iced_selection::text(err)
+            ),
+            Some(Ok(aliases)) => aliases,
+        };
+
+        let cell = |content, portion| {
+            iced::widget::text(content)
+                .size(14)
+                .width(iced::FillPortion(portion))
+        };
+
+        let rows = aliases.iter()
+            .enumerate()
+            .filter(|(_, index_alias)| {
+                let hidden = index_alias.options.is_hidden;
+                matches(&index_alias.alias, hidden) || matches(&index_alias.index, hidden)
+            })
+            .map(|(idx, index_alias)| {
+                let options = &index_alias.options;
+                let routing = match (&options.index_routing, &options.search_routing) {
+                    (None, None) => "-".to_owned(),
+                    (index_routing, search_routing) => format!(
+                        "{} / {}",
+                        index_routing.as_deref().unwrap_or("-"),
+                        search_routing.as_deref().unwrap_or("-")
+                    ),
+                };
+                let filter = options.filter.as_ref()
+                    .and_then(|filter| serde_json::to_string(filter).ok())
+                    .unwrap_or("-".to_owned());
+
+                iced::widget::row![
+                    iced_selection::text(&index_alias.alias)
+                        .size(14)
+                        .width(iced::FillPortion(2)),
+                    iced_selection::text(&index_alias.index)
+                        .size(14)
+                        .width(iced::FillPortion(2)),
+                    cell(if options.is_write_index == Some(true) { "yes".to_owned() } else { "-".to_owned() }, 1),
+                    cell(routing, 1),
+                    iced_selection::text(filter)
+                        .size(12)
+                        .font(iced::Font::MONOSPACE)
+                        .width(iced::FillPortion(3)),
+                    iced::widget::button(iced::widget::text("Remove").size(12))
+                        .on_press(Message::AliasRemovePressed(idx))
+                        .style(iced::widget::button::danger),
+                ]
+                .spacing(5)
+                .align_y(iced::Center)
+                .into()
+            });
+
+        widget::section_with_header(
+            iced::widget::row![
+                cell("Alias".to_owned(), 2),
+                cell("Index".to_owned(), 2),
+                cell("Write index".to_owned(), 1),
+                cell("Routing".to_owned(), 1),
+                cell("Filter".to_owned(), 3),
+                iced::widget::space().width(60),
+            ]
+            .spacing(5),
+            iced::widget::scrollable(
+                iced::widget::column(rows)
+                    .spacing(5)
+            )
+            .height(iced::Fill)
+        )
+    }
+
+    fn swap_view(&self, indicies: Vec<String>) -> iced::widget::Container<'_, Message> {
+        let from = self.swap.alias.as_ref()
+            .map(|alias| self.alias_indicies(alias))
+            .unwrap_or_default();
+
+        widget::section_with_header(
+            iced::widget::text("Swap alias"),
+            iced::widget::column![
+                iced::widget::row![
+                    iced::widget::pick_list(self.alias_names(), self.swap.alias.clone(), Message::AliasSwapAliasSelected)
+                        .placeholder("Alias"),
+                    iced::widget::text("to"),
+                    iced::widget::pick_list(indicies, self.swap.index.clone(), Message::AliasSwapIndexSelected)
+                        .placeholder("Index"),
+                ]
+                .spacing(5)
+                .align_y(iced::Center),
+                (!from.is_empty()).then(|| {
+                    iced::widget::text(format!("Points to {}", from.join(", ")))
+                        .size(12)
+                        .style(iced::widget::text::secondary)
+                }),
+                iced::widget::row![
+                    iced::widget::checkbox(self.swap.write_index)
+                        .label("Write index")
+                        .on_toggle(Message::AliasSwapWriteIndexToggled),
+                    iced::widget::space::horizontal(),
+                    iced::widget::button(iced::widget::text("Queue swap").size(14))
+                        .on_press_maybe(self.swap_actions().is_some().then_some(Message::AliasSwapQueued)),
+                ]
+                .spacing(5)
+                .align_y(iced::Center),
+            ]
+            .spacing(5)
+        )
+    }
+
+    fn form_view(&self) -> iced::widget::Container<'_, Message> {
+        let form = &self.form;
+        let input = |placeholder, value, field| {
+            iced::widget::text_input(placeholder, value)
+                .on_input(move |value| Message::AliasFormUpdated(field, value))
+                .size(14)
+        };
+
+        widget::section_with_header(
+            iced::widget::row![
+                iced::widget::text("Alias action"),
+                iced::widget::space::horizontal(),
+                iced::widget::pick_list(ActionKind::ALL, Some(form.kind), Message::AliasActionKindSelected),
+            ]
+            .align_y(iced::Center),
+            iced::widget::column![
+                iced::widget::row![
+                    input("Index, e.g. logs-v2 or logs-*", &form.index, FormField::Index),
+                    (form.kind != ActionKind::RemoveIndex).then(|| input("Alias", &form.alias, FormField::Alias)),
+                ]
+                .spacing(5),
+                (form.kind == ActionKind::Add).then(|| {
+                    iced::widget::column![
+                        input(r#"Filter, e.g. {"term": {"user.id": "kimchy"}}"#, &form.filter, FormField::Filter),
+                        iced::widget::row![
+                            input("Routing", &form.routing, FormField::Routing),
+                            input("Index routing", &form.index_routing, FormField::IndexRouting),
+                            input("Search routing", &form.search_routing, FormField::SearchRouting),
+                        ]
+                        .spacing(5),
+                        iced::widget::checkbox(form.write_index)
+                            .label("Write index")
+                            .on_toggle(Message::AliasFormWriteIndexToggled),
+                    ]
+                    .spacing(5)
+                }),
+                iced::widget::row![
+                    self.form_error.as_ref().map(|err| {
+                        iced::widget::text(err)
+                            .size(12)
+                            .style(iced::widget::text::danger)
+                    }),
+                    iced::widget::space::horizontal(),
+                    iced::widget::button(iced::widget::text("Queue").size(14))
+                        .on_press(Message::AliasActionQueued),
+                ]
+                .spacing(5)
+                .align_y(iced::Center),
+            ]
+            .spacing(5)
+        )
+    }
+
+    fn pending_view(&self) -> iced::widget::Container<'_, Message> {
+        let actions = self.pending.iter()
+            .enumerate()
+            .map(|(idx, action)| {
+                iced::widget::row![
+                    iced::widget::text(describe(action))
+                        .size(14)
+                        .style(match action {
+                            es::AliasAction::RemoveIndex(_) => iced::widget::text::danger,
+                            _ => iced::widget::text::default,
+                        })
+                        .width(iced::Fill),
+                    iced::widget::button(iced::widget::text("✕").size(12))
+                        .on_press_maybe((!self.applying).then_some(Message::AliasPendingRemoved(idx)))
+                        .padding([0, 4])
+                        .style(iced::widget::button::text),
+                ]
+                .spacing(5)
+                .align_y(iced::Center)
+                .into()
+            });
+
+        widget::section_with_header(
+            iced::widget::row![
+                iced::widget::text("Pending actions"),
+                iced::widget::space::horizontal(),
+                iced::widget::button(iced::widget::text("Clear").size(12))
+                    .on_press_maybe((!self.pending.is_empty() && !self.applying).then_some(Message::AliasPendingCleared))
+                    .style(iced::widget::button::secondary),
+                iced::widget::button(iced::widget::text(if self.applying { "Applying" } else { "Apply atomically" }).size(12))
+                    .on_press_maybe((!self.pending.is_empty() && !self.applying).then_some(Message::AliasesApplyPressed)),
+            ]
+            .spacing(5)
+            .align_y(iced::Center),
+            if self.pending.is_empty() {
+                iced::Element::from(
+                    iced::widget::text("Queued actions are applied together, either all of them or none")
+                        .size(12)
+                        .style(iced::widget::text::secondary)
+                )
+            } else {
+                iced::widget::scrollable(
+                    iced::widget::column(actions)
+                        .spacing(5)
+                )
+                .into()
+            }
+        )
+    }
+}
@@ -1,91 +1,11 @@
 pub mod es;
-
-pub mod cli {
-    use crate::es;
-
-    pub fn create_client() -> es::ElasticsearchClient {
-        let args: Vec<String> = std::env::args().collect();
-
-        let mut url_arg: Option<String> = None;
-        let mut auth_arg: Option<String> = None;
-        let mut cacert_arg: Option<String> = None;
-
-        let mut aws_profile: Option<String> = None;
-        let mut aws_region: Option<String> = None;
-
-        let mut idx = 1;
-
-        while idx < args.len() {
-            if args[idx] == "--url" {
-                url_arg = Some(args[idx+1].clone());
-                idx +=2;
-
-            } else if args[idx] == "--auth" {
-                // In form of "<username>:<password>"
-                // assumes username and password does not contain the character ':'
-                auth_arg = Some(args[idx+1].clone());
-                idx += 2;
-                
-            } else if args[idx] == "--cacert" {
-                cacert_arg = Some(args[idx+1].clone());
-                idx += 2;
-            } else if args[idx] == "--aws-profile" {
-                aws_profile = Some(args[idx+1].to_owned());
-                idx += 2;
-            } else if args[idx] == "--aws-region" {
-                aws_region = Some(args[idx+1].to_owned());
-                idx += 2;
-            } else {
-                idx += 1;
-            };
-        }
-
-        let url = url_arg.unwrap_or_else(|| {
-            println!("Missing --url");
-            std::process::exit(1);
-        });
-
-        let mut client = es::ElasticsearchClient::new(url).unwrap_or_else(|e| {
-            println!("Failed to create client: {}", e);
-            std::process::exit(1);
-        });
-
-        if let Some(auth) = &auth_arg {
-            let auth_parsed: Vec<&str> = auth.split(":").collect();
-            let username = auth_parsed[0];
-            let password = auth_parsed.get(1);
-
-            let basic_auth = es::Auth::BASIC(es::BasicAuth{
-                username: username.to_string(),
-                password: password.map(|x| {x.to_string()})
-            });
-
-            client.use_auth(basic_auth);
-        }
-
-        if let Some(region) = &aws_region {
-            if auth_arg.is_some() {
-                println!("Cannot use standard auth and aws sigv4");
-                std::process::exit(1);
-            }
-
-            let aws_auth = es::Auth::AWS(es::AwsSigv4{
-                region: region.to_owned(),
-                profile: aws_profile,
-            });
-            client.use_auth(aws_auth);
-        }
-
-        if let Some(cacert_file) = cacert_arg {
-            client.use_custom_pem_certificate(cacert_file).unwrap_or_else(|e| {
-                println!("Failed to create client: {}", e);
-                std::process::exit(1);
-            });
-        }
-
-        return client;
-    }
-}
+pub mod console;
+pub mod curl;
+pub mod autocomplete;
+pub mod export;
+pub mod import;
+pub mod profile;
+pub mod tail;
 
 pub mod util {
     pub fn path_has_extension(path: &std::path::Path, extension_to_match: &str) -> bool {
@@ -97,4 +17,40 @@ pub mod util {
     pub fn valid_url(url_str: &str) -> bool {
         reqwest::Url::parse(url_str).is_ok()
     }
+
+    // Directory where the application persists its own data (history, caches, ...)
+    pub fn app_data_dir() -> Option<std::path::PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("elastic_ermine"))
+    }
+
+    /**
+     * Turns the key of a cluster (currently its url) into something usable as a file name.
+     * Replacing characters alone would have e.g. http://a:9200/ and http://a:9200? share a file, so a
+     * hash of the key as given is appended.
+     */
+    pub fn cluster_file_name(cluster: &str) -> String {
+        let name = cluster.chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+            .collect::<String>();
+        let name = if name.is_empty() { "default" } else { &name };
+
+        format!("{}-{:016x}", name, fnv1a(cluster.as_bytes()))
+    }
+
+    // FNV-1a, unlike std's DefaultHasher it is stable across Rust releases, which file names need
+    fn fnv1a(bytes: &[u8]) -> u64 {
+        bytes.iter().fold(0xcbf29ce484222325, |hash, byte| (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn cluster_file_name_keeps_clusters_apart() {
+            assert_ne!(cluster_file_name("http://a:9200/"), cluster_file_name("http://a:9200?"));
+            assert!(cluster_file_name("http://a:9200").starts_with("http___a_9200-"));
+            assert!(cluster_file_name("").starts_with("default-"));
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,190 @@
+// In memory log of what the application did (requests sent, failures), shown on the Logs page
+use std::sync::{Arc, Mutex};
+
+use crate::widget;
+
+// Older entries are dropped past this
+const MAX_ENTRIES: usize = 10_000;
+const ROW_HEIGHT: f32 = 22.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Info,
+    Error,
+}
+
+impl Level {
+    fn as_str(&self) -> &str {
+        match self {
+            Level::Info => "INFO",
+            Level::Error => "ERROR",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Entry {
+    timestamp: chrono::DateTime<chrono::Local>,
+    level: Level,
+    message: String,
+}
+
+#[derive(Debug, Default)]
+struct Buffer {
+    entries: std::collections::VecDeque<Entry>,
+    // Incremented on every change, lets the view skip copying an unchanged buffer
+    generation: u64,
+}
+
+/**
+ * Log of the application, held by the app and cloned into the views recording to it.
+ * Clones share their entries, so tasks can record to a clone once the view is out of reach.
+ */
+#[derive(Debug, Clone, Default)]
+pub struct Log(Arc<Mutex<Buffer>>);
+
+impl Log {
+    fn record(&self, level: Level, message: String) {
+        let Ok(mut buffer) = self.0.lock() else {
+            return;
+        };
+
+        if buffer.entries.len() == MAX_ENTRIES {
+            buffer.entries.pop_front();
+        }
+        buffer.entries.push_back(Entry {
+            timestamp: chrono::Local::now(),
+            level,
+            message,
+        });
+        buffer.generation += 1;
+    }
+
+    pub fn info(&self, message: impl Into<String>) {
+        self.record(Level::Info, message.into());
+    }
+
+    pub fn error(&self, message: impl Into<String>) {
+        self.record(Level::Error, message.into());
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    // Picks up entries recorded since the last refresh
+    Refresh,
+    Scrolled(iced::widget::scrollable::Viewport),
+    SearchUpdated(String),
+    ErrorsOnlyToggled(bool),
+    ClearPressed,
+}
+
+#[derive(Debug, Default)]
+pub struct View {
+    log: Log,
+    entries: Vec<Entry>,
+    generation: u64,
+    search: String,
+    errors_only: bool,
+    list: widget::virtual_list::State,
+}
+
+impl View {
+    pub fn new(log: Log) -> Self {
+        Self { log, ..Default::default() }
+    }
+
+    pub fn update(&mut self, message: Message) {
+        match message {
+            Message::Refresh => {
+                if let Ok(buffer) = self.log.0.lock() && buffer.generation != self.generation {
+                    self.entries = buffer.entries.iter().cloned().collect();
+                    self.generation = buffer.generation;
+                }
+            },
+            Message::Scrolled(viewport) => self.list.scrolled(viewport),
+            Message::SearchUpdated(search) => self.search = search,
+            Message::ErrorsOnlyToggled(errors_only) => self.errors_only = errors_only,
+            Message::ClearPressed => {
+                if let Ok(mut buffer) = self.log.0.lock() {
+                    buffer.entries.clear();
+                    buffer.generation += 1;
+                }
+                self.update(Message::Refresh);
+            },
+        }
+    }
+
+    // Keeps the page up to date while it is shown
+    pub fn subscription(&self) -> iced::Subscription<Message> {
+        iced::time::every(std::time::Duration::from_secs(1)).map(|_| Message::Refresh)
+    }
+
+    pub fn view(&self) -> iced::Element<'_, Message> {
+        let search = self.search.to_lowercase();
+
+        // Most recent first
+        let entries = self.entries.iter()
+            .rev()
+            .filter(|entry| !self.errors_only || entry.level == Level::Error)
+            .filter(|entry| search.is_empty() || entry.message.to_lowercase().contains(&search))
+            .collect::<Vec<&Entry>>();
+
+        widget::section_with_header(
+            iced::widget::row![
+                iced::widget::text("Logs"),
+                iced::widget::space::horizontal(),
+                iced::widget::text(format!("{} entries", entries.len())).size(12),
+            ]
+            .align_y(iced::Center),
+            iced::widget::column![
+                iced::widget::row![
+                    iced::widget::text_input("Search logs", &self.search)
+                        .on_input(Message::SearchUpdated),
+                    iced::widget::checkbox(self.errors_only)
+                        .label("Errors only")
+                        .on_toggle(Message::ErrorsOnlyToggled),
+                    iced::widget::button(iced::widget::text("Clear").size(15))
+                        .on_press(Message::ClearPressed),
+                ]
+                .spacing(10)
+                .align_y(iced::Center),
+                widget::virtual_list::virtual_list(
+                    &self.list,
+                    widget::virtual_list::Heights::Fixed { count: entries.len(), height: ROW_HEIGHT },
+                    |range| entries[range].iter().map(|entry| Self::entry_row(entry)).collect(),
+                    Message::Scrolled
+                ),
+            ]
+            .spacing(10)
+        )
+        .width(iced::Fill)
+        .height(iced::Fill)
+        .into()
+    }
+
+    fn entry_row(entry: &Entry) -> iced::Element<'_, Message> {
+        let level = iced::widget::text(entry.level.as_str())
+            .size(14)
+            .font(iced::Font::MONOSPACE)
+            .width(50);
+
+        let level = match entry.level {
+            Level::Info => level,
+            Level::Error => level.style(iced::widget::text::danger),
+        };
+
+        iced::widget::row![
+            iced::widget::text(entry.timestamp.format("%Y-%m-%d %H:%M:%S").to_string())
+                .size(14)
+                .font(iced::Font::MONOSPACE),
+            level,
+            iced_selection::text(&entry.message)
+                .size(14)
+                .wrapping(iced::widget::text::Wrapping::None),
+        ]
+        .spacing(10)
+        .align_y(iced::Center)
+        .into()
+    }
+}
@@ -4,8 +4,11 @@ mod assets;
 mod widget;
 mod dev_tools;
 mod search;
+mod indices;
 mod settings;
 mod sidebar;
+mod logs;
+mod import_page;
 
 
 fn main() -> iced::Result {
@@ -21,6 +24,7 @@ fn main() -> iced::Result {
     iced::application(MyApp::new, MyApp::update, MyApp::view)
         .title("Elastic Ermine")
         .window(window)
+        .subscription(MyApp::subscription)
         .run()
 }
 
@@ -29,22 +33,40 @@ enum Message {
     DevToolsView(dev_tools::Message),
     SettingsView(settings::Message),
     SearchView(search::Message),
+    IndicesView(indices::Message),
     Sidebar(sidebar::Message),
+    LogsView(logs::Message),
+    ImportView(import_page::Message),
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 struct MyApp{
     dev_tools_view: dev_tools::View,
     settings_view: settings::View,
     search_view: search::View,
+    indices_view: indices::View,
     sidebar_view: sidebar::View,
+    logs_view: logs::View,
+    import_view: import_page::View,
 }
 
 impl MyApp {
     fn new() -> (Self, iced::Task<Message>) {
+        let log = logs::Log::default();
+        let app = MyApp {
+            dev_tools_view: dev_tools::View::new(log.clone()),
+            settings_view: settings::View::new(log.clone()),
+            search_view: search::View::new(log.clone()),
+            indices_view: indices::View::new(log.clone()),
+            sidebar_view: Default::default(),
+            import_view: import_page::View::new(log.clone()),
+            logs_view: logs::View::new(log),
+        };
+        // Shows the lists of indicies and aliases cached for the cluster of the last connection
+        let cluster_activated = search::Message::ClusterActivated(app.settings_view.cluster_key());
         (
-            MyApp::default(),
-            iced::Task::none()
+            app,
+            iced::Task::done(Message::SearchView(cluster_activated))
         )
     }
 
@@ -53,19 +75,49 @@ impl MyApp {
             Message::SearchView(message) => {
                 match self.search_view.update(message) {
                     search::Action::None => iced::Task::none(),
+                    search::Action::Run(task) => task.map(Message::SearchView),
                     search::Action::TryClientInvoke(context) => {
                         let client_res = self.settings_view.get_client();
                         search::View::try_invoke_with_client(client_res, context).map(Message::SearchView)
                     } 
                 }
             }
+            Message::IndicesView(message) => {
+                match self.indices_view.update(message) {
+                    indices::Action::None => iced::Task::none(),
+                    indices::Action::TryClientInvoke(context) => {
+                        let client_res = self.settings_view.get_client();
+                        indices::View::try_invoke_with_client(client_res, context).map(Message::IndicesView)
+                    },
+                }
+            },
             Message::DevToolsView(message) => {
                 match self.dev_tools_view.update(message) {
                     dev_tools::Action::None => iced::Task::none(),
+                    dev_tools::Action::Run(task) => task.map(Message::DevToolsView),
                     dev_tools::Action::InvokeOperation { method, path, body } => {
                         let client_res = self.settings_view.get_client();
                         dev_tools::View::try_invoke_es_operation_with_client(
-                            client_res, method, path, body
+                            client_res, self.settings_view.cluster_key(), method, path, body
+                        ).map(Message::DevToolsView)
+                    },
+                    dev_tools::Action::CopyAsCurl { method, path, body, reveal_secrets } => {
+                        let client_res = self.settings_view.get_client();
+                        dev_tools::View::try_copy_as_curl_with_client(
+                            client_res, method, path, body, reveal_secrets
+                        ).map(Message::DevToolsView)
+                    },
+                    dev_tools::Action::LoadCompletions(task) => {
+                        let client_res = self.settings_view.get_client();
+                        iced::Task::batch([
+                            task,
+                            dev_tools::View::try_load_completions_with_client(client_res, self.settings_view.cluster_key()),
+                        ]).map(Message::DevToolsView)
+                    },
+                    dev_tools::Action::InvokeConsoleRequests(requests) => {
+                        let client_res = self.settings_view.get_client();
+                        dev_tools::View::try_invoke_console_requests_with_client(
+                            client_res, self.settings_view.cluster_key(), requests
                         ).map(Message::DevToolsView)
                     },
                 }                
@@ -73,17 +125,63 @@ impl MyApp {
             Message::SettingsView(message) => {
                 match self.settings_view.update(message) {
                     settings::Action::Run(task) => task.map(Message::SettingsView),
+                    settings::Action::Connected => {
+                        let connected = search::Message::ConnectionEstablished(self.settings_view.cluster_key());
+                        iced::Task::batch([
+                            self.settings_view.save_connection().map(Message::SettingsView),
+                            self.update(Message::SearchView(connected)),
+                        ])
+                    },
                     settings::Action::None => iced::Task::none(),
                 }
             },
+            Message::ImportView(message) => {
+                match self.import_view.update(message) {
+                    import_page::Action::None => iced::Task::none(),
+                    import_page::Action::Run(task) => task.map(Message::ImportView),
+                    import_page::Action::Import(request) => {
+                        let client_res = self.settings_view.get_client();
+                        import_page::View::try_import_with_client(client_res, request).map(Message::ImportView)
+                    },
+                }
+            },
+            Message::LogsView(message) => {
+                self.logs_view.update(message);
+                iced::Task::none()
+            },
             Message::Sidebar(message) => {
                 match self.sidebar_view.update(message) {
                     sidebar::Action::None => iced::Task::none(),
+                    sidebar::Action::PageOpened(page) => self.page_opened(page),
                 }
             },
         }
     }
 
+    fn page_opened(&mut self, page: sidebar::Page) -> iced::Task<Message> {
+        match page {
+            sidebar::Page::DevConsole => {
+                let cluster_activated = dev_tools::Message::ClusterActivated(self.settings_view.cluster_key());
+                self.update(Message::DevToolsView(cluster_activated))
+            },
+            sidebar::Page::Logs => self.update(Message::LogsView(logs::Message::Refresh)),
+            sidebar::Page::Indices => self.update(Message::IndicesView(indices::Message::Refresh)),
+            sidebar::Page::Search => {
+                let cluster_activated = search::Message::ClusterActivated(self.settings_view.cluster_key());
+                self.update(Message::SearchView(cluster_activated))
+            },
+            sidebar::Page::Connection | sidebar::Page::Import => iced::Task::none(),
+        }
+    }
+
+    fn subscription(&self) -> iced::Subscription<Message> {
+        match self.sidebar_view.current_page() {
+            sidebar::Page::Logs => self.logs_view.subscription().map(Message::LogsView),
+            sidebar::Page::Search => self.search_view.subscription().map(Message::SearchView),
+            _ => iced::Subscription::none(),
+        }
+    }
+
     fn view(&self) -> iced::Element<'_, Message> {
         iced::widget::row![
             self.sidebar_view.view().map(Message::Sidebar),
@@ -101,15 +199,13 @@ impl MyApp {
         iced::widget::container(
             match self.sidebar_view.current_page() {
                 sidebar::Page::Search => self.search_view.view().map(Message::SearchView),
+                sidebar::Page::Indices => self.indices_view.view().map(Message::IndicesView),
                 sidebar::Page::Connection => self.settings_view.view().map(Message::SettingsView),
-                sidebar::Page::Logs => self.logs_section(),
+                sidebar::Page::Logs => self.logs_view.view().map(Message::LogsView),
+                sidebar::Page::Import => self.import_view.view().map(Message::ImportView),
                 sidebar::Page::DevConsole => self.dev_tools_view.view().map(Message::DevToolsView),
             }
         )
     }
 
-    fn logs_section(&self) -> iced::Element<'_, Message> {
-        iced::widget::text("Logs WIP").into()
-    }
-
 }
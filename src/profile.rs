@@ -0,0 +1,94 @@
+// Named connection settings, stored as profiles.json in the application data directory, e.g.
+//
+//   {
+//     "local": { "url": "http://localhost:9200" },
+//     "staging": { "url": "https://staging:9200", "username": "elastic", "cacert": "/etc/ssl/staging.pem" }
+//   }
+use std::collections::BTreeMap;
+
+use crate::{es, util};
+
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    pub url: Option<String>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub aws_region: Option<String>,
+    pub aws_profile: Option<String>,
+    // PEM certificate of the certificate authority of the cluster
+    pub cacert: Option<std::path::PathBuf>,
+}
+
+pub fn profiles_file() -> Option<std::path::PathBuf> {
+    util::app_data_dir().map(|dir| dir.join("profiles.json"))
+}
+
+// A missing profiles file has no profiles
+pub fn load_profiles() -> Result<BTreeMap<String, Profile>, Box<dyn std::error::Error>> {
+    let path = profiles_file().ok_or("Unable to determine data directory")?;
+
+    match std::fs::read_to_string(&path) {
+        Ok(contents) => serde_json::from_str(&contents)
+            .map_err(|err| format!("Unable to parse {}: {}", path.to_string_lossy(), err).into()),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+        Err(err) => Err(format!("Unable to read {}: {}", path.to_string_lossy(), err).into()),
+    }
+}
+
+pub fn load_profile(name: &str) -> Result<Profile, Box<dyn std::error::Error>> {
+    let mut profiles = load_profiles()?;
+
+    profiles.remove(name).ok_or_else(|| {
+        let known = profiles.keys().cloned().collect::<Vec<String>>();
+        if known.is_empty() {
+            format!("Unknown profile {}, no profiles are defined", name).into()
+        } else {
+            format!("Unknown profile {}, known profiles: {}", name, known.join(", ")).into()
+        }
+    })
+}
+
+impl Profile {
+    // Settings of other take precedence over the ones of self
+    pub fn merged_with(self, other: Profile) -> Profile {
+        Profile {
+            url: other.url.or(self.url),
+            username: other.username.or(self.username),
+            password: other.password.or(self.password),
+            aws_region: other.aws_region.or(self.aws_region),
+            aws_profile: other.aws_profile.or(self.aws_profile),
+            cacert: other.cacert.or(self.cacert),
+        }
+    }
+
+    pub fn client(&self) -> Result<es::ElasticsearchClient, Box<dyn std::error::Error>> {
+        let url = self.url.as_ref().ok_or("Missing cluster url")?;
+
+        if !util::valid_url(url) {
+            return Err(format!("Invalid cluster url {}", url).into());
+        }
+
+        let mut client = es::ElasticsearchClient::new(url.to_owned())?;
+
+        match (&self.username, &self.aws_region) {
+            (Some(_), Some(_)) => return Err("Cannot use both basic auth and AWS SigV4".into()),
+            (Some(username), None) => client.use_auth(es::Auth::BASIC(es::BasicAuth {
+                username: username.to_owned(),
+                password: self.password.clone(),
+            })),
+            (None, Some(region)) => client.use_auth(es::Auth::AWS(es::AwsSigv4 {
+                region: region.to_owned(),
+                profile: self.aws_profile.clone(),
+            })),
+            (None, None) => {},
+        }
+
+        if let Some(cacert) = &self.cacert {
+            client.use_custom_pem_certificate(cacert)
+                .map_err(|err| format!("Unable to use certificate {}: {}", cacert.to_string_lossy(), err))?;
+        }
+
+        Ok(client)
+    }
+}
@@ -1,58 +1,292 @@
 use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 
-use crate::{assets, es, widget};
+use crate::{assets, es, logs, widget};
+use elastic_ermine::{autocomplete, export, tail};
 use iced::widget::{column, row};
 
+mod aggregations;
+mod columns;
+mod filters;
+mod saved_queries;
+mod table;
+mod time_range;
+
 #[derive(Debug, Clone)]
 pub enum Message {
     SearchTypeChanged(SearchType),
     FilterRefreshPressed,
-    FilterRefreshResultsReturned(
-        Result<
-            (Vec<String>, Vec<String>), 
-            (String, Option<Vec<String>>, Option<Vec<String>>)
-        > // TODO: think if this should have an explicit type
-    ),
+    // Cluster the lists were refreshed for, along with them
+    FilterRefreshResultsReturned(String, FilterRefreshResult),
+    // Shows the cached lists of the cluster unless it is already shown
+    ClusterActivated(String),
+    // A connection to the cluster was just tested, the lists are refreshed
+    ConnectionEstablished(String),
+    FilterCacheLoaded(String, Result<Option<filters::Cache>, String>),
     SelectedFiltersUpdated(FiltersUpdate),
+    FilterSearchUpdated(String),
+    FilterShowHiddenToggled(bool),
+    FilterShowClosedToggled(bool),
+    FilterFamilyToggled(String),
+    FilterPatternUpdated(String),
+    FilterPatternSubmitted,
     SearchPressed,
-    SearchResultsReturned(Result<es::OperationSearchResult, String>),
+    SearchResultsReturned(u64, Result<es::OperationSearchResult, String>),
     GenericSearchBodyEditorActionPerformed(iced::widget::text_editor::Action),
     QueryStringUpdated(String),
-    ResultsAccordianClicked(usize)
+    HighlightToggled(bool),
+    HighlightFieldsUpdated(String),
+    HighlightFragmentSizeSelected(u32),
+    HighlightFragmentCountSelected(u32),
+    ResultsAccordianClicked(usize),
+    // Along with the request the mappings answer
+    FieldMappingsReturned(u64, Result<Vec<es::FieldMapping>, String>),
+    BodySuggestionPicked(usize),
+    HitTreeUpdated(usize, widget::json_tree::Message),
+    HitsScrolled(iced::widget::scrollable::Viewport),
+    FiltersScrolled(iced::widget::scrollable::Viewport),
+    DisplayModeSelected(DisplayMode),
+    RawTreeUpdated(widget::json_tree::Message),
+    ColumnAdded(String),
+    ColumnRemoved(usize),
+    // (from, to) positions
+    ColumnMoved(usize, usize),
+    ColumnResizeStarted(usize),
+    // Cursor x position within the table header
+    ColumnResizeMoved(f32),
+    ColumnResizeEnded,
+    ColumnSortPressed(String),
+    ColumnsLoaded(String, Result<Vec<columns::Column>, String>),
+    ColumnsSaved(Result<(), String>),
+    ExportFormatSelected(export::Format),
+    ExportPressed,
+    ExportFileChosen(Option<std::path::PathBuf>),
+    ExportProgressed(Result<export::Progress, String>),
+    ExportCancelPressed,
+    AggregationBucketPressed(aggregations::BucketPath),
+    AggregationPresentationSelected(String, aggregations::Presentation),
+    TimeFieldSelected(String),
+    TimePresetSelected(time_range::Preset),
+    TimeFromUpdated(String),
+    TimeToUpdated(String),
+    // First and last histogram bucket brushed over
+    HistogramBrushed(usize, usize),
+    TailPressed,
+    TailPausePressed,
+    TailStopPressed,
+    TailIntervalSelected(TailInterval),
+    TailTick,
+    TailTiebreakerUpdated(String),
+    // The tail along with the result of its poll, as the poll moves it past the documents returned
+    TailPolled(u64, Result<(tail::Tail, es::OperationSearchResult), String>),
+    SavedQueriesToggled,
+    // All saved queries after they were loaded or changed
+    SavedQueriesUpdated(Result<Vec<saved_queries::SavedQuery>, String>),
+    SavedQuerySearchUpdated(String),
+    SavedQueryTagSelected(Option<String>),
+    SavedQueryNameUpdated(String),
+    SavedQueryDescriptionUpdated(String),
+    SavedQueryTagsUpdated(String),
+    SavedQuerySavePressed,
+    SavedQueryLoadPressed(usize),
+    SavedQueryDeletePressed(usize),
+    SavedQueriesImportPressed,
+    SavedQueriesImportFileChosen(Option<std::path::PathBuf>),
+    SavedQueriesImported(Result<(usize, Vec<saved_queries::SavedQuery>), String>),
+    SavedQueriesExportPressed,
+    SavedQueriesExportFileChosen(Option<std::path::PathBuf>),
+    SavedQueriesExported(Result<usize, String>),
+}
+
+type KnownIndicies = Vec<(String, filters::IndexState)>;
+
+// Lists obtained by a refresh, those that failed to be obtained are None and explained by the error
+#[derive(Debug, Clone)]
+pub struct FilterRefreshResult {
+    data_streams: Option<KnownIndicies>,
+    indicies: Option<KnownIndicies>,
+    aliases: Option<Vec<String>>,
+    error: Option<String>,
 }
 
 pub enum Action {
     None,
+    Run(iced::Task<Message>),
     TryClientInvoke(Context),
 }
 
 pub enum Context {
-    AllIndiciesAndAliases,
+    // Saved as the cached lists of the cluster once listed
+    AllDataStreamsIndiciesAndAliases(String, logs::Log),
+    // Field names of the given indicies and aliases, all of them if empty, along with the request they answer
+    FieldMappings(u64, Vec<String>),
+    // Searches carry the request they answer
     GenericSearch{
+        request: u64,
         body: String,
         indices: Vec<String>,
         aliases: Vec<String>,
+        // Replaces any sort of the body
+        sort: Option<es::Sort>,
+        time: TimeSearch,
     },
     QueryStringSearch {
+        request: u64,
         query_string: String,
         indices: Vec<String>,
         aliases: Vec<String>,
+        sort: Option<es::Sort>,
+        time: TimeSearch,
+        highlight: Option<Box<es::Highlight>>,
+    },
+    Export(ExportRequest),
+    Tail(u64, tail::Tail),
+}
+
+// Filter of the time range and histogram over the time field, added to searches when a date field is chosen
+#[derive(Debug, Default)]
+pub struct TimeSearch {
+    filter: Option<Box<es::Query>>,
+    histogram: Option<Box<es::Aggregation>>,
+}
+
+// Polling period of tail mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TailInterval(u64);
+
+impl TailInterval {
+    pub const ALL: [TailInterval; 5] = [TailInterval(1), TailInterval(2), TailInterval(5), TailInterval(10), TailInterval(30)];
+}
+
+impl Default for TailInterval {
+    fn default() -> Self {
+        TailInterval(5)
     }
 }
 
+impl std::fmt::Display for TailInterval {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Every {} s", self.0)
+    }
+}
+
+#[derive(Debug)]
+struct TailState {
+    // Tells the polls of this tail from those of a tail stopped while they were in flight
+    generation: u64,
+    tail: tail::Tail,
+    paused: bool,
+    // A poll is in flight, ticks are skipped until it returns
+    polling: bool,
+}
+
+#[derive(Debug)]
+pub struct ExportRequest {
+    path: std::path::PathBuf,
+    indices: Vec<String>,
+    body: Option<serde_json::Value>,
+    format: export::Format,
+    // Field paths of the CSV columns
+    columns: Vec<String>,
+    cancel: Arc<AtomicBool>,
+}
+
+#[derive(Debug, Default)]
+enum ExportState {
+    #[default]
+    Idle,
+    Running {
+        path: std::path::PathBuf,
+        progress: export::Progress,
+        // Checked by the export between pages
+        cancel: Arc<AtomicBool>,
+    },
+    // Summary of the export or why it failed
+    Finished(Result<String, String>),
+}
+
 #[derive(Debug, Default)]
 pub struct View {
+    log: logs::Log,
     search_type: SearchType,
     refresh_filter_button_state: RefreshFilterButtonState,
     refresh_filter_errors: Option<String>,
+    known_data_streams_selected: std::collections::HashMap<String, bool>,
     known_indicies_selected: std::collections::HashMap<String, bool>,
     known_aliases_selected: std::collections::HashMap<String, bool>,
+    // States of the known data streams and indicies
+    index_states: HashMap<String, filters::IndexState>,
+    // Selected data streams, indicies and aliases the last refresh no longer listed
+    missing_filters: std::collections::HashSet<String>,
+    filter_options: filters::Options,
+    // Cluster the lists belong to, and whether they were refreshed since it was activated rather than cached
+    filters_cluster: Option<String>,
+    filters_refreshed: bool,
 
     query_string: String,
+    // Query string searches ask for highlights unless turned off
+    highlight_disabled: bool,
+    highlight_options: HighlightOptions,
 
     generic_search_search_button_state: GenericSearchSearchButtonState,
     generic_search_display_content: GenericSearchDisplaySectionValue,
-    generic_search_body_content: iced::widget::text_editor::Content,
+    generic_search_body_content: widget::JsonEditor,
+
+    completer: autocomplete::Completer,
+    body_suggestions: Vec<autocomplete::Suggestion>,
+
+    hits_list: widget::virtual_list::State,
+    filters_list: widget::virtual_list::State,
+
+    field_mappings: Vec<es::FieldMapping>,
+    // Incremented for every mappings request, the answers to earlier ones are dropped
+    field_mappings_request: u64,
+    // Incremented for every search, a time range or bucket may start one while another is pending
+    search_request: u64,
+    display_mode: DisplayMode,
+    raw_hits_tree: Option<widget::JsonTree>,
+    columns: Vec<columns::Column>,
+    // Index pattern the columns were loaded for
+    columns_pattern: Option<String>,
+    // Index pattern of the last search
+    results_pattern: String,
+    resizing_column: Option<usize>,
+    sort: Option<(String, es::SortOrder)>,
+    // Aggregations of the body last searched, telling which results can be charted
+    searched_aggregations: aggregations::Requested,
+    aggregation_presentations: aggregations::Presentations,
+    // Highlight of the last search, telling how the fragments of its hits are tagged
+    searched_highlight: Option<es::Highlight>,
+
+    // Fields of the selected indicies mapped as dates
+    date_fields: Vec<String>,
+    time_field: Option<String>,
+    time_range: time_range::TimeRange,
+    // Document counts over the time field of the last search
+    histogram: Option<es::BucketsResult>,
+
+    tail: Option<TailState>,
+    // Incremented for every tail started
+    tail_generation: u64,
+    tail_interval: TailInterval,
+    // Field ordering documents sharing a timestamp, the shard order when empty
+    tail_tiebreaker: String,
+
+    saved_queries: saved_queries::Library,
+
+    export_format: export::Format,
+    export_state: ExportState,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DisplayMode {
+    #[default]
+    Table,
+    Accordion,
+    Raw,
+    // Only offered when the results have aggregations
+    Aggregations,
 }
 
 #[derive(Debug, Default)]
@@ -62,21 +296,116 @@ enum RefreshFilterButtonState {
     Waiting,
 }
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum SearchType {
     #[default]
+    #[serde(rename = "query_string")]
     StringSearch,
+    #[serde(rename = "payload")]
     GenericSearch
 }
 
 #[derive(Debug, Clone)]
 pub enum FiltersUpdate {
+    AddDataStream(String),
+    RemoveDataStream(String),
     AddIndex(String),
     RemoveIndex(String),
     AddAlias(String),
     RemoveAlias(String),
+    // Applies to the data streams, indicies and aliases listed when selecting, to all of them when unselecting
+    SelectAll(bool),
+    SelectFamily(String, bool),
+}
+
+// Highlight of query string searches, as chosen next to the query string
+#[derive(Debug)]
+struct HighlightOptions {
+    // Comma separated, names may contain wildcards
+    fields: String,
+    fragment_size: u32,
+    fragment_count: u32,
+}
+
+impl Default for HighlightOptions {
+    fn default() -> Self {
+        Self { fields: "*".to_owned(), fragment_size: 100, fragment_count: DEFAULT_HIGHLIGHT_LINES as u32 }
+    }
+}
+
+/**
+ * Highlight of the chosen fields the query string matched, every field when none is given, see
+ * https://www.elastic.co/docs/reference/elasticsearch/rest-apis/highlighting
+ */
+fn query_string_highlight(options: &HighlightOptions) -> es::Highlight {
+    let mut fields = options.fields.split(',')
+        .map(str::trim)
+        .filter(|field| !field.is_empty())
+        .peekable();
+
+    let highlight = if fields.peek().is_none() {
+        es::Highlight::new().field("*")
+    } else {
+        fields.fold(es::Highlight::new(), es::Highlight::field)
+    };
+
+    highlight
+        .tags(HIGHLIGHT_PRE_TAG, HIGHLIGHT_POST_TAG)
+        .fragment_size(options.fragment_size)
+        .number_of_fragments(options.fragment_count)
+}
+
+// Fragments shown under the header of a hit searched with the highlight
+fn highlight_line_limit(highlight: &es::Highlight) -> usize {
+    // 0 asks for whole fields rather than fragments
+    highlight.number_of_fragments
+        .filter(|count| *count > 0)
+        .map_or(DEFAULT_HIGHLIGHT_LINES, |count| count as usize)
 }
 
+/**
+ * Adds filter clauses to the query of a search body.
+ * A bool query gets them added to its filter clauses, any other query is wrapped in a bool query.
+ */
+fn add_query_filters(body: &mut serde_json::Map<String, serde_json::Value>, filters: Vec<serde_json::Value>) {
+    let query = match body.remove("query") {
+        Some(serde_json::Value::Object(mut query)) if query.len() == 1 && query.get("bool").is_some_and(serde_json::Value::is_object) => {
+            if let Some(serde_json::Value::Object(bool_query)) = query.get_mut("bool") {
+                let existing = match bool_query.remove("filter") {
+                    Some(serde_json::Value::Array(existing)) => existing,
+                    Some(filter) => vec![filter],
+                    None => Vec::new(),
+                };
+                bool_query.insert("filter".to_owned(), existing.into_iter().chain(filters).collect());
+            }
+            serde_json::Value::Object(query)
+        },
+        Some(query) => serde_json::json!({ "bool": { "must": [query], "filter": filters } }),
+        None => serde_json::json!({ "bool": { "filter": filters } }),
+    };
+
+    body.insert("query".to_owned(), query);
+}
+
+const FILTER_ROW_HEIGHT: f32 = 28.0;
+// Hits kept in tail mode, the oldest are dropped past it
+const TAIL_BUFFER_SIZE: usize = 1000;
+// Square button expanding a hit, which sets the height of its header
+const EXPAND_BUTTON_SIZE: f32 = 30.0;
+// Between the id of a hit and its highlight fragments
+const HIT_HEADER_SPACING: f32 = 5.0;
+// Around the tree of a hit expanded in the table
+const ROW_TREE_PADDING: f32 = 10.0;
+// Highlight fragments shown under the header of a hit, each on a line of its own,
+// unless the highlight searched asks for another number of fragments
+const DEFAULT_HIGHLIGHT_LINES: usize = 3;
+const HIGHLIGHT_FRAGMENT_SIZES: [u32; 6] = [50, 100, 150, 200, 300, 500];
+const HIGHLIGHT_FRAGMENT_COUNTS: [u32; 5] = [1, 2, 3, 4, 5];
+const HIGHLIGHT_LINE_HEIGHT: f32 = 20.0;
+// Wrapped around the matches of query string searches, unlikely to appear in documents
+const HIGHLIGHT_PRE_TAG: &str = "@ermine-highlight@";
+const HIGHLIGHT_POST_TAG: &str = "@/ermine-highlight@";
+
 #[derive(Debug, Default)]
 enum GenericSearchSearchButtonState {
     #[default]
@@ -91,11 +420,16 @@ enum GenericSearchDisplaySectionValue {
     Error(String),
     Result{
         res: es::OperationSearchResult,
-        expanded_hits: Vec<bool>
+        // Tree of each expanded hit
+        expanded_hits: Vec<Option<widget::JsonTree>>
     }
 }
 
 impl View {
+    pub fn new(log: logs::Log) -> Self {
+        Self { log, ..Default::default() }
+    }
+
     #[must_use]
     pub fn update(&mut self, message: Message) -> Action {
         match message {
@@ -105,39 +439,72 @@ impl View {
             },
             Message::FilterRefreshPressed => {
                 self.refresh_filter_button_state = RefreshFilterButtonState::Waiting;
-                Action::TryClientInvoke(Context::AllIndiciesAndAliases)
+                Action::TryClientInvoke(Context::AllDataStreamsIndiciesAndAliases(self.filters_cluster.clone().unwrap_or_default(), self.log.clone()))
             },
-            Message::FilterRefreshResultsReturned(res) => {
+            Message::FilterRefreshResultsReturned(cluster, res) => {
+                // Refreshed for a cluster that is no longer active
+                if self.filters_cluster.as_ref().is_some_and(|active| *active != cluster) {
+                    return Action::None;
+                }
                 self.refresh_filter_button_state = RefreshFilterButtonState::Ready;
+                self.filters_cluster = Some(cluster);
+                self.filters_refreshed = true;
 
-                // TODO: existing selected filters that still exist on refresh should not be unselected
-                match res {
-                    Ok((indicies, aliases)) => {
-                        self.known_aliases_selected = aliases.into_iter().map(|alias| (alias, false)).collect();
-                        self.known_indicies_selected = indicies.into_iter().map(|index| (index, false)).collect();
-                        self.refresh_filter_errors = None;
-                    },
-                    Err((err, obtained_indicies, obtained_aliases)) => {
-                        // TODO: rethink if these partially returned results should be option or just plain list.
-                        // None could just be an empty list.
-        
-                        self.known_indicies_selected = match obtained_indicies {
-                            Some(indicies) => 
-                                indicies.into_iter().map(|index| (index, false)).collect(),
-                            None => HashMap::new(),
-                        };
+                // Lists that failed to be obtained are kept as they were
+                if let Some(err) = &res.error {
+                    self.log.error(format!("Failed to refresh filters: {}", err));
+                }
+                self.refresh_filter_errors = res.error.map(|err| format!("Failed to refresh filters: {}", err));
+                self.set_known_filters(res.data_streams, res.indicies, res.aliases);
+                self.request_field_mappings()
+            },
+            Message::ClusterActivated(cluster) => {
+                if self.filters_cluster.as_ref() == Some(&cluster) {
+                    return Action::None;
+                }
+                self.activate_cluster(cluster.clone());
+                Action::Run(iced::Task::perform(
+                    filters::load_cache(cluster.clone()),
+                    move |res| Message::FilterCacheLoaded(cluster.clone(), res)
+                ))
+            },
+            Message::ConnectionEstablished(cluster) => {
+                if self.filters_cluster.as_ref() != Some(&cluster) {
+                    self.activate_cluster(cluster.clone());
+                }
+                self.refresh_filter_button_state = RefreshFilterButtonState::Waiting;
+                Action::TryClientInvoke(Context::AllDataStreamsIndiciesAndAliases(cluster, self.log.clone()))
+            },
+            Message::FilterCacheLoaded(cluster, res) => {
+                // Lists refreshed while the cache loaded are more recent
+                if self.filters_cluster.as_ref() != Some(&cluster) || self.filters_refreshed {
+                    return Action::None;
+                }
 
-                        self.known_aliases_selected = match obtained_aliases {
-                            Some(aliases) => aliases.into_iter().map(|alias| (alias, false)).collect(),
-                            None => HashMap::new(),
-                        };
-                        self.refresh_filter_errors = Some(format!("Failed to refresh filters: {}", err)); 
+                match res {
+                    Ok(Some(cache)) => {
+                        self.set_known_filters(Some(cache.data_streams), Some(cache.indicies), Some(cache.aliases));
+                        self.request_field_mappings()
                     },
-                };
-                Action::None
+                    Ok(None) => Action::None,
+                    Err(err) => {
+                        self.log.error(format!("Failed to load cached filters: {}", err));
+                        Action::None
+                    },
+                }
             },
             Message::SelectedFiltersUpdated(filters_update) => {
                 match filters_update {
+                    FiltersUpdate::AddDataStream(data_stream) => {
+                        if self.known_data_streams_selected.contains_key(&data_stream) {
+                            self.known_data_streams_selected.insert(data_stream, true);
+                        }
+                    },
+                    FiltersUpdate::RemoveDataStream(data_stream) => {
+                        if self.known_data_streams_selected.contains_key(&data_stream) {
+                            self.known_data_streams_selected.insert(data_stream, false);
+                        }
+                    },
                     FiltersUpdate::AddIndex(idx) => {
                         if self.known_indicies_selected.contains_key(&idx) {
                             self.known_indicies_selected.insert(idx, true);
@@ -158,73 +525,903 @@ impl View {
                             self.known_aliases_selected.insert(idx, false);
                         }
                     },
+                    // Selects those listed, unselects all
+                    FiltersUpdate::SelectAll(true) => {
+                        let options = &self.filter_options;
+                        let states = &self.index_states;
+                        self.known_data_streams_selected.iter_mut()
+                            .filter(|(data_stream, _)| options.shows_index(data_stream, states.get(*data_stream)))
+                            .for_each(|(_, selected)| *selected = true);
+                        self.known_indicies_selected.iter_mut()
+                            .filter(|(index, _)| options.shows_index(index, states.get(*index)))
+                            .for_each(|(_, selected)| *selected = true);
+                        self.known_aliases_selected.iter_mut()
+                            .filter(|(alias, _)| options.matches(alias))
+                            .for_each(|(_, selected)| *selected = true);
+                    },
+                    FiltersUpdate::SelectAll(false) => {
+                        self.known_data_streams_selected.values_mut().for_each(|selected| *selected = false);
+                        self.known_indicies_selected.values_mut().for_each(|selected| *selected = false);
+                        self.known_aliases_selected.values_mut().for_each(|selected| *selected = false);
+                    },
+                    FiltersUpdate::SelectFamily(family, toggled) => {
+                        let options = &self.filter_options;
+                        let states = &self.index_states;
+                        self.known_indicies_selected.iter_mut()
+                            .filter(|(index, _)| filters::family(index).unwrap_or(index) == family)
+                            .filter(|(index, _)| options.shows_index(index, states.get(*index)))
+                            .for_each(|(_, selected)| *selected = toggled);
+                    },
+                }
+                self.request_field_mappings()
+            },
+            Message::FilterSearchUpdated(search) => {
+                self.filter_options.search = search;
+                Action::None
+            },
+            Message::FilterShowHiddenToggled(show) => {
+                self.filter_options.show_hidden = show;
+                self.unselect_not_shown()
+            },
+            Message::FilterShowClosedToggled(show) => {
+                self.filter_options.show_closed = show;
+                self.unselect_not_shown()
+            },
+            Message::FilterFamilyToggled(family) => {
+                if !self.filter_options.expanded.remove(&family) {
+                    self.filter_options.expanded.insert(family);
                 }
                 Action::None
             },
+            Message::FilterPatternUpdated(pattern) => {
+                self.filter_options.pattern = pattern;
+                Action::None
+            },
+            Message::FilterPatternSubmitted => self.request_field_mappings(),
             Message::SearchPressed => {
+                self.tail = None;
+                self.search_request += 1;
                 self.generic_search_search_button_state = GenericSearchSearchButtonState::Waiting;
+                self.results_pattern = columns::index_pattern(&self.selected_filters());
+                self.searched_aggregations = match self.search_type {
+                    SearchType::GenericSearch => aggregations::requested(&self.generic_search_body_content.text()).unwrap_or_default(),
+                    SearchType::StringSearch => Default::default(),
+                };
+                self.searched_highlight = match self.search_type {
+                    SearchType::GenericSearch => serde_json::from_str::<serde_json::Value>(&self.generic_search_body_content.text())
+                        .ok()
+                        .and_then(|mut body| body.get_mut("highlight").map(serde_json::Value::take))
+                        .and_then(|highlight| serde_json::from_value(highlight).ok()),
+                    SearchType::StringSearch => (!self.highlight_disabled).then(|| query_string_highlight(&self.highlight_options)),
+                };
+                Action::TryClientInvoke(self.search_context())
+            },
+            Message::SearchResultsReturned(request, _) if request != self.search_request => Action::None,
+            Message::SearchResultsReturned(_, operation_search_result) => {
+                self.generic_search_search_button_state = GenericSearchSearchButtonState::Ready;
+                match operation_search_result {
+                    Ok(mut res) => {
+                        self.log.info(format!("Search returned {} hits in {} ms", res.hits.hits.len(), res.time_took_ms));
 
-                let indices = self.known_indicies_selected.iter()
-                        .filter_map(|(index, selected)| selected.then_some(index.to_owned()))
-                        .collect::<Vec<String>>();
+                        self.histogram = match res.aggregations.as_mut().and_then(|aggregations| aggregations.remove(time_range::HISTOGRAM_NAME)) {
+                            Some(es::AggregationResult::Buckets(histogram)) => Some(histogram),
+                            _ => None,
+                        };
 
-                let aliases = self.known_aliases_selected.iter()
-                        .filter_map(|(alias, selected)| selected.then_some(alias.to_owned()))
-                        .collect::<Vec<String>>();
+                        // Searches with "size": 0 only ask for aggregations
+                        let has_aggregations = res.aggregations.as_ref().is_some_and(|aggregations| !aggregations.is_empty());
+                        if has_aggregations && res.hits.hits.is_empty() {
+                            self.display_mode = DisplayMode::Aggregations;
+                        } else if !has_aggregations && self.display_mode == DisplayMode::Aggregations {
+                            self.display_mode = DisplayMode::Table;
+                        }
 
-                Action::TryClientInvoke(
-                    match self.search_type {
-                        SearchType::StringSearch => Context::QueryStringSearch { query_string: self.query_string.clone(), indices, aliases },
-                        SearchType::GenericSearch => Context::GenericSearch { body: self.generic_search_body_content.text(), indices, aliases},
-                    }
+                        let expanded_hits = res.hits.hits.iter().map(|_| None).collect();
+                        self.generic_search_display_content = GenericSearchDisplaySectionValue::Result{res, expanded_hits};
+                        self.raw_hits_tree = None;
+                        self.build_raw_hits_tree();
+                    },
+                    Err(err) => {
+                        self.log.error(format!("Search failed: {}", err));
+                        self.histogram = None;
+                        self.generic_search_display_content = GenericSearchDisplaySectionValue::Error(format!("Failed to search: {}", err));
+                    },
+                }
+
+                self.load_columns()
+            },
+            Message::AggregationBucketPressed(path) => {
+                let GenericSearchDisplaySectionValue::Result { res, .. } = &self.generic_search_display_content else {
+                    return Action::None;
+                };
+
+                let aggregations = res.aggregations.clone().unwrap_or_default();
+                match aggregations::drill_down(&self.generic_search_body_content.text(), &aggregations, &path) {
+                    Ok(body) => {
+                        self.generic_search_body_content.set_text(&body);
+                        self.search_type = SearchType::GenericSearch;
+                        self.update(Message::SearchPressed)
+                    },
+                    Err(err) => {
+                        self.log.error(format!("Failed to filter on the bucket: {}", err));
+                        Action::None
+                    },
+                }
+            },
+            Message::AggregationPresentationSelected(name, presentation) => {
+                self.aggregation_presentations.insert(name, presentation);
+                Action::None
+            },
+            Message::TimeFieldSelected(field) => {
+                self.time_field = Some(field);
+                Action::None
+            },
+            Message::TimePresetSelected(preset) => {
+                self.time_range.preset = preset;
+                // Absolute ranges search once their bounds are entered
+                if preset == time_range::Preset::Absolute {
+                    Action::None
+                } else {
+                    self.update(Message::SearchPressed)
+                }
+            },
+            Message::TimeFromUpdated(from) => {
+                self.time_range.from = from;
+                Action::None
+            },
+            Message::TimeToUpdated(to) => {
+                self.time_range.to = to;
+                Action::None
+            },
+            Message::HistogramBrushed(first, last) => {
+                match self.histogram.as_ref().and_then(|histogram| time_range::buckets_range(histogram, first, last)) {
+                    Some((from, to)) => {
+                        self.time_range = time_range::TimeRange::between(from, to);
+                        self.update(Message::SearchPressed)
+                    },
+                    None => Action::None,
+                }
+            },
+            Message::TailPressed => {
+                let Some(field) = self.time_field.clone() else {
+                    self.log.error("Tail needs a date field to follow, none of the selected indicies has one");
+                    return Action::None;
+                };
+
+                let tail = self.search_body()
+                    .and_then(|body| tail::Tail::new(self.selected_filters(), body, field).map_err(|err| err.to_string()))
+                    .map(|tail| match self.tail_tiebreaker.trim() {
+                        "" => tail,
+                        tiebreaker => tail.tiebreaker(tiebreaker),
+                    });
+
+                match tail {
+                    Ok(tail) => {
+                        self.log.info("Tail started");
+                        self.results_pattern = columns::index_pattern(&self.selected_filters());
+                        self.histogram = None;
+                        self.generic_search_display_content = GenericSearchDisplaySectionValue::Default;
+                        self.tail_generation += 1;
+                        self.tail = Some(TailState { generation: self.tail_generation, tail: tail.clone(), paused: false, polling: true });
+                        Action::TryClientInvoke(Context::Tail(self.tail_generation, tail))
+                    },
+                    Err(err) => {
+                        self.log.error(format!("Failed to start tail: {}", err));
+                        Action::None
+                    },
+                }
+            },
+            Message::TailPausePressed => {
+                if let Some(state) = &mut self.tail {
+                    state.paused = !state.paused;
+                }
+                Action::None
+            },
+            Message::TailStopPressed => {
+                self.tail = None;
+                self.log.info("Tail stopped");
+                Action::None
+            },
+            Message::TailIntervalSelected(interval) => {
+                self.tail_interval = interval;
+                Action::None
+            },
+            Message::TailTiebreakerUpdated(tiebreaker) => {
+                self.tail_tiebreaker = tiebreaker;
+                Action::None
+            },
+            Message::TailTick => {
+                match &mut self.tail {
+                    Some(state) if !state.paused && !state.polling => {
+                        state.polling = true;
+                        Action::TryClientInvoke(Context::Tail(state.generation, state.tail.clone()))
+                    },
+                    _ => Action::None,
+                }
+            },
+            Message::TailPolled(generation, res) => {
+                // Stopped, or stopped and started again, while the poll was in flight
+                let Some(state) = self.tail.as_mut().filter(|state| state.generation == generation) else {
+                    return Action::None;
+                };
+                state.polling = false;
+
+                match res {
+                    Ok((tail, res)) => {
+                        state.tail = tail;
+                        self.append_tail_hits(res);
+                        self.load_columns()
+                    },
+                    Err(err) => {
+                        self.log.error(format!("Tail failed, pausing it: {}", err));
+                        state.paused = true;
+                        Action::None
+                    },
+                }
+            },
+            Message::SavedQueriesToggled => {
+                self.saved_queries.open = !self.saved_queries.open;
+                if self.saved_queries.open && self.saved_queries.queries.is_none() {
+                    Action::Run(iced::Task::perform(saved_queries::load(), Message::SavedQueriesUpdated))
+                } else {
+                    Action::None
+                }
+            },
+            Message::SavedQueriesUpdated(res) => {
+                match res {
+                    Ok(queries) => {
+                        self.saved_queries.queries = Some(queries);
+                        self.saved_queries.error = None;
+                    },
+                    Err(err) => {
+                        self.log.error(format!("Failed to update saved queries: {}", err));
+                        self.saved_queries.error = Some(format!("Failed to update saved queries: {}", err));
+                    },
+                }
+                Action::None
+            },
+            Message::SavedQuerySearchUpdated(search) => {
+                self.saved_queries.search = search;
+                Action::None
+            },
+            Message::SavedQueryTagSelected(tag) => {
+                self.saved_queries.tag = tag;
+                Action::None
+            },
+            Message::SavedQueryNameUpdated(name) => {
+                self.saved_queries.name = name;
+                Action::None
+            },
+            Message::SavedQueryDescriptionUpdated(description) => {
+                self.saved_queries.description = description;
+                Action::None
+            },
+            Message::SavedQueryTagsUpdated(tags) => {
+                self.saved_queries.tags = tags;
+                Action::None
+            },
+            Message::SavedQuerySavePressed => {
+                if self.saved_queries.name.trim().is_empty() {
+                    return Action::None;
+                }
+                Action::Run(iced::Task::perform(
+                    saved_queries::save(self.current_saved_query()),
+                    Message::SavedQueriesUpdated
+                ))
+            },
+            Message::SavedQueryLoadPressed(idx) => {
+                match self.saved_queries.queries.as_ref().and_then(|queries| queries.get(idx)) {
+                    Some(query) => {
+                        let query = query.clone();
+                        self.log.info(format!("Loaded saved query {}", query.name));
+                        self.load_saved_query(query)
+                    },
+                    None => Action::None,
+                }
+            },
+            Message::SavedQueryDeletePressed(idx) => {
+                match self.saved_queries.queries.as_ref().and_then(|queries| queries.get(idx)) {
+                    Some(query) => Action::Run(iced::Task::perform(
+                        saved_queries::delete(query.name.clone()),
+                        Message::SavedQueriesUpdated
+                    )),
+                    None => Action::None,
+                }
+            },
+            Message::SavedQueriesImportPressed => Action::Run(
+                iced::Task::future(
+                    rfd::AsyncFileDialog::new()
+                        .add_filter("JSON", &["json"])
+                        .set_title("Import saved queries")
+                        .pick_file()
                 )
+                .map(|handle| Message::SavedQueriesImportFileChosen(handle.map(|handle| handle.path().to_path_buf())))
+            ),
+            Message::SavedQueriesImportFileChosen(None) => Action::None,
+            Message::SavedQueriesImportFileChosen(Some(path)) => Action::Run(iced::Task::perform(
+                saved_queries::import(path),
+                Message::SavedQueriesImported
+            )),
+            Message::SavedQueriesImported(res) => {
+                let res = res.map(|(count, queries)| {
+                    self.log.info(format!("Imported {} saved queries", count));
+                    queries
+                });
+                self.update(Message::SavedQueriesUpdated(res))
             },
-            Message::SearchResultsReturned(operation_search_result) => {
-                self.generic_search_search_button_state = GenericSearchSearchButtonState::Ready;
-                match operation_search_result {
-                    Ok(res) => {
-                        let num_hits = res.hits.hits.len();
-                        self.generic_search_display_content = GenericSearchDisplaySectionValue::Result{res, expanded_hits: vec![false; num_hits]};
+            Message::SavedQueriesExportPressed => Action::Run(
+                iced::Task::future(
+                    rfd::AsyncFileDialog::new()
+                        .add_filter("JSON", &["json"])
+                        .set_file_name("saved_queries.json")
+                        .set_title("Export saved queries")
+                        .save_file()
+                )
+                .map(|handle| Message::SavedQueriesExportFileChosen(handle.map(|handle| handle.path().to_path_buf())))
+            ),
+            Message::SavedQueriesExportFileChosen(None) => Action::None,
+            Message::SavedQueriesExportFileChosen(Some(path)) => {
+                let queries = self.saved_queries.queries.clone().unwrap_or_default();
+                Action::Run(iced::Task::perform(saved_queries::export(path, queries), Message::SavedQueriesExported))
+            },
+            Message::SavedQueriesExported(res) => {
+                match res {
+                    Ok(count) => self.log.info(format!("Exported {} saved queries", count)),
+                    Err(err) => {
+                        self.log.error(format!("Failed to export saved queries: {}", err));
+                        self.saved_queries.error = Some(format!("Failed to export saved queries: {}", err));
+                    },
+                }
+                Action::None
+            },
+            Message::DisplayModeSelected(mode) => {
+                self.display_mode = mode;
+                self.build_raw_hits_tree();
+                Action::None
+            },
+            Message::RawTreeUpdated(message) => {
+                match &mut self.raw_hits_tree {
+                    Some(tree) => Action::Run(tree.update(message)),
+                    None => Action::None,
+                }
+            },
+            Message::ColumnAdded(field) => {
+                self.columns.push(columns::Column::new(field));
+                self.save_columns()
+            },
+            Message::ColumnRemoved(idx) => {
+                if idx < self.columns.len() {
+                    self.columns.remove(idx);
+                }
+                self.save_columns()
+            },
+            Message::ColumnMoved(from, to) => {
+                if from < self.columns.len() && to < self.columns.len() {
+                    let column = self.columns.remove(from);
+                    self.columns.insert(to, column);
+                }
+                self.save_columns()
+            },
+            Message::ColumnResizeStarted(idx) => {
+                self.resizing_column = Some(idx);
+                Action::None
+            },
+            Message::ColumnResizeMoved(cursor_x) => {
+                if let Some(idx) = self.resizing_column && idx < self.columns.len() {
+                    self.columns[idx].width = table::resized_width(&self.columns, idx, cursor_x);
+                }
+                Action::None
+            },
+            Message::ColumnResizeEnded => {
+                match self.resizing_column.take() {
+                    Some(_) => self.save_columns(),
+                    None => Action::None,
+                }
+            },
+            Message::ColumnSortPressed(field) => {
+                // Cycles through ascending, descending and unsorted
+                self.sort = match self.sort.take() {
+                    Some((sorted_field, es::SortOrder::Asc)) if sorted_field == field => Some((field, es::SortOrder::Desc)),
+                    Some((sorted_field, es::SortOrder::Desc)) if sorted_field == field => None,
+                    _ => Some((field, es::SortOrder::Asc)),
+                };
+
+                match self.generic_search_search_button_state {
+                    GenericSearchSearchButtonState::Ready => self.update(Message::SearchPressed),
+                    GenericSearchSearchButtonState::Waiting => Action::None,
+                }
+            },
+            Message::ColumnsLoaded(pattern, res) => {
+                // Ignores columns of a pattern that is no longer displayed
+                if pattern == self.results_pattern {
+                    self.columns = res.unwrap_or_else(|err| {
+                        self.log.error(format!("Failed to load columns: {}", err));
+                        Vec::new()
+                    });
+                    self.columns_pattern = Some(pattern);
+                }
+                Action::None
+            },
+            Message::ColumnsSaved(res) => {
+                if let Err(err) = res {
+                    self.log.error(format!("Failed to save columns: {}", err));
+                }
+                Action::None
+            },
+            Message::ExportFormatSelected(format) => {
+                self.export_format = format;
+                Action::None
+            },
+            Message::ExportPressed => {
+                let extension = self.export_format.extension().to_owned();
+                Action::Run(
+                    iced::Task::future(
+                        rfd::AsyncFileDialog::new()
+                            .add_filter(self.export_format.to_string(), &[extension.as_str()])
+                            .set_file_name(format!("export.{}", extension))
+                            .set_title("Export search results")
+                            .save_file()
+                    )
+                    .map(|handle| Message::ExportFileChosen(handle.map(|handle| handle.path().to_path_buf())))
+                )
+            },
+            Message::ExportFileChosen(None) => Action::None,
+            Message::ExportFileChosen(Some(path)) => {
+                if let ExportState::Running { .. } = self.export_state {
+                    return Action::None;
+                }
+
+                let body = match self.search_body() {
+                    Ok(body) => body,
+                    Err(err) => {
+                        self.export_state = ExportState::Finished(Err(err));
+                        return Action::None;
+                    },
+                };
+
+                let cancel = Arc::new(AtomicBool::new(false));
+                self.log.info(format!("Exporting {} to {}", self.export_format, path.to_string_lossy()));
+
+                self.export_state = ExportState::Running {
+                    path: path.clone(),
+                    progress: Default::default(),
+                    cancel: cancel.clone(),
+                };
+
+                Action::TryClientInvoke(Context::Export(ExportRequest {
+                    path,
+                    indices: self.selected_filters(),
+                    body,
+                    format: self.export_format,
+                    columns: self.columns.iter().map(|column| column.field.clone()).collect(),
+                    cancel,
+                }))
+            },
+            Message::ExportProgressed(res) => {
+                let ExportState::Running { path, progress, .. } = &mut self.export_state else {
+                    return Action::None;
+                };
+
+                match res {
+                    Ok(new_progress) if new_progress.finished => {
+                        let summary = format!("Exported {} documents to {}", new_progress.exported, path.to_string_lossy());
+                        self.log.info(&summary);
+                        self.export_state = ExportState::Finished(Ok(summary));
                     },
+                    Ok(new_progress) => *progress = new_progress,
                     Err(err) => {
-                        self.generic_search_display_content = GenericSearchDisplaySectionValue::Error(format!("Failed to search: {}", err));
+                        self.log.error(format!("Export failed: {}", err));
+                        self.export_state = ExportState::Finished(Err(err));
                     },
                 }
                 Action::None
             },
+            Message::ExportCancelPressed => {
+                if let ExportState::Running { cancel, .. } = &self.export_state {
+                    cancel.store(true, Ordering::Relaxed);
+                }
+                Action::None
+            },
             Message::GenericSearchBodyEditorActionPerformed(action) => {
+                let is_edit = action.is_edit();
                 self.generic_search_body_content.perform(action);
+
+                // Moving the cursor around dismisses the suggestions
+                self.body_suggestions = if is_edit {
+                    self.completer.complete_body(&self.generic_search_body_content.text_before_cursor())
+                } else {
+                    Vec::new()
+                };
+                Action::None
+            },
+            Message::BodySuggestionPicked(idx) => {
+                if let Some(suggestion) = self.body_suggestions.get(idx) {
+                    self.generic_search_body_content.replace_before_cursor(suggestion.replace_len, &suggestion.insert);
+                }
+                self.body_suggestions.clear();
+                Action::None
+            },
+            Message::FieldMappingsReturned(request, _) if request != self.field_mappings_request => Action::None,
+            Message::FieldMappingsReturned(_, res) => {
+                // Mappings only feed autocompletion and column choices, which carry on without them
+                self.field_mappings = res.unwrap_or_default();
+                self.completer.fields = self.field_mappings.iter()
+                    .map(|field| field.name.clone())
+                    .collect();
+
+                // The chosen time field is kept while the selected indicies still map it as a date
+                self.date_fields = time_range::date_fields(&self.field_mappings);
+                if !self.time_field.as_ref().is_some_and(|field| self.date_fields.contains(field)) {
+                    self.time_field = time_range::default_field(&self.date_fields);
+                }
                 Action::None
             },
             Message::QueryStringUpdated(query_string) => {
                 self.query_string = query_string;
                 Action::None
             },
+            Message::HighlightToggled(enabled) => {
+                self.highlight_disabled = !enabled;
+                Action::None
+            },
+            Message::HighlightFieldsUpdated(fields) => {
+                self.highlight_options.fields = fields;
+                Action::None
+            },
+            Message::HighlightFragmentSizeSelected(fragment_size) => {
+                self.highlight_options.fragment_size = fragment_size;
+                Action::None
+            },
+            Message::HighlightFragmentCountSelected(fragment_count) => {
+                self.highlight_options.fragment_count = fragment_count;
+                Action::None
+            },
             Message::ResultsAccordianClicked(idx) => {
-                if let GenericSearchDisplaySectionValue::Result { res: _res, expanded_hits} = &mut self.generic_search_display_content {
-                    if expanded_hits.len() > idx {
-                        let is_expanded = expanded_hits[idx];
-                        expanded_hits[idx] = !is_expanded;
+                if let GenericSearchDisplaySectionValue::Result { res, expanded_hits} = &mut self.generic_search_display_content
+                    && let (Some(hit), Some(expanded)) = (res.hits.hits.get(idx), expanded_hits.get_mut(idx)) {
+                        *expanded = match expanded {
+                            Some(_) => None,
+                            None => Some(widget::JsonTree::new(hit.clone())),
+                        };
                     }
-                }
 
                 Action::None
             },
+            Message::HitsScrolled(viewport) => {
+                self.hits_list.scrolled(viewport);
+                Action::None
+            },
+            Message::FiltersScrolled(viewport) => {
+                self.filters_list.scrolled(viewport);
+                Action::None
+            },
+            Message::HitTreeUpdated(idx, message) => {
+                match &mut self.generic_search_display_content {
+                    GenericSearchDisplaySectionValue::Result { expanded_hits, .. } => {
+                        match expanded_hits.get_mut(idx) {
+                            Some(Some(tree)) => Action::Run(tree.update(message)),
+                            _ => Action::None,
+                        }
+                    },
+                    _ => Action::None,
+                }
+            },
+        }
+    }
+
+    // Lists of another cluster do not apply, its selection does not carry over
+    fn activate_cluster(&mut self, cluster: String) {
+        self.filters_cluster = Some(cluster);
+        self.filters_refreshed = false;
+        self.known_data_streams_selected.clear();
+        self.known_indicies_selected.clear();
+        self.known_aliases_selected.clear();
+        self.index_states.clear();
+        self.missing_filters.clear();
+        self.refresh_filter_errors = None;
+        self.completer.indicies.clear();
+        self.completer.aliases.clear();
+    }
+
+    // Merges the lists obtained with those known, None leaves a list as it was
+    fn set_known_filters(
+        &mut self,
+        data_streams: Option<KnownIndicies>,
+        indicies: Option<KnownIndicies>,
+        aliases: Option<Vec<String>>
+    ) {
+        if let Some(data_streams) = data_streams {
+            filters::merge(
+                &mut self.known_data_streams_selected,
+                data_streams.iter().map(|(data_stream, _)| data_stream.to_owned()),
+                &mut self.missing_filters
+            );
+            let indicies = &self.known_indicies_selected;
+            self.index_states.retain(|name, _| indicies.contains_key(name));
+            self.index_states.extend(data_streams);
+        }
+
+        if let Some(indicies) = indicies {
+            self.completer.indicies = indicies.iter().map(|(index, _)| index.to_owned()).collect();
+            filters::merge(
+                &mut self.known_indicies_selected,
+                indicies.iter().map(|(index, _)| index.to_owned()),
+                &mut self.missing_filters
+            );
+            let data_streams = &self.known_data_streams_selected;
+            self.index_states.retain(|name, _| data_streams.contains_key(name));
+            self.index_states.extend(indicies);
+        }
+
+        if let Some(aliases) = aliases {
+            self.completer.aliases = aliases.clone();
+            filters::merge(&mut self.known_aliases_selected, aliases, &mut self.missing_filters);
+        }
+    }
+
+    fn search_context(&self) -> Context {
+        // Data streams are searched as indicies are
+        let indices = filters::searched(&self.known_data_streams_selected, &self.missing_filters)
+                .chain(filters::searched(&self.known_indicies_selected, &self.missing_filters))
+                .chain(self.filter_options.patterns())
+                .collect::<Vec<String>>();
+
+        let aliases = filters::searched(&self.known_aliases_selected, &self.missing_filters)
+                .collect::<Vec<String>>();
+
+        let sort = self.sort_clause();
+
+        let time = match &self.time_field {
+            Some(field) => TimeSearch {
+                filter: self.time_range.filter(field).map(Box::new),
+                histogram: Some(Box::new(time_range::histogram(field))),
+            },
+            None => TimeSearch::default(),
+        };
+
+        match self.search_type {
+            SearchType::StringSearch => Context::QueryStringSearch {
+                request: self.search_request,
+                query_string: self.query_string.clone(),
+                indices,
+                aliases,
+                sort,
+                time,
+                highlight: self.searched_highlight.clone().map(Box::new),
+            },
+            SearchType::GenericSearch => Context::GenericSearch {
+                request: self.search_request,
+                body: self.generic_search_body_content.text(),
+                indices,
+                aliases,
+                sort,
+                time,
+            },
+        }
+    }
+
+    // Body of the current query as sent by a search, including the column sort
+    fn search_body(&self) -> Result<Option<serde_json::Value>, String> {
+        let mut body = match self.search_type {
+            SearchType::StringSearch => Some(
+                es::SearchBody::new()
+                    .query(es::Query::query_string(self.query_string.clone()))
+                    .to_json()
+            ),
+            SearchType::GenericSearch => {
+                let text = self.generic_search_body_content.text();
+                (!text.trim().is_empty())
+                    .then(|| serde_json::from_str::<serde_json::Value>(&text))
+                    .transpose()
+                    .map_err(|err| format!("Invalid search body: {}", err))?
+            },
+        };
+
+        if let Some(sort) = self.sort_clause() {
+            match body.get_or_insert_with(|| serde_json::json!({})).as_object_mut() {
+                Some(body) => {
+                    body.insert("sort".to_owned(), serde_json::json!([sort]));
+                },
+                None => return Err("Search body must be a JSON object".to_owned()),
+            }
+        }
+
+        if let Some(filter) = self.time_field.as_ref().and_then(|field| self.time_range.filter(field)) {
+            match body.get_or_insert_with(|| serde_json::json!({})).as_object_mut() {
+                Some(body) => add_query_filters(body, vec![serde_json::json!(filter)]),
+                None => return Err("Search body must be a JSON object".to_owned()),
+            }
+        }
+
+        Ok(body)
+    }
+
+    // Text fields cannot be sorted on, their keyword multi-field is used instead when there is one
+    fn sort_clause(&self) -> Option<es::Sort> {
+        let (field, order) = self.sort.as_ref()?;
+
+        let is_text = self.field_mappings.iter()
+            .any(|mapping| mapping.name == *field && mapping.field_type == "text");
+        let keyword = format!("{}.keyword", field);
+        let has_keyword = self.field_mappings.iter()
+            .any(|mapping| mapping.name == keyword);
+
+        let sort_field = if is_text && has_keyword { &keyword } else { field };
+
+        Some(es::Sort {
+            field: sort_field.to_owned(),
+            order: Some(*order),
+            missing: None,
+            unmapped_type: None,
+            extra: serde_json::Map::new(),
+        })
+    }
+
+    // The current search as a saved query, named as entered in the saved queries panel
+    fn current_saved_query(&self) -> saved_queries::SavedQuery {
+        let selected = |known: &HashMap<String, bool>| {
+            let mut selected = known.iter()
+                .filter_map(|(name, selected)| selected.then_some(name.to_owned()))
+                .collect::<Vec<String>>();
+            selected.sort();
+            selected
+        };
+
+        saved_queries::SavedQuery {
+            name: self.saved_queries.name.trim().to_owned(),
+            description: self.saved_queries.description.trim().to_owned(),
+            search_type: self.search_type.clone(),
+            query: match self.search_type {
+                SearchType::StringSearch => self.query_string.clone(),
+                SearchType::GenericSearch => self.generic_search_body_content.text(),
+            },
+            data_streams: selected(&self.known_data_streams_selected),
+            indices: selected(&self.known_indicies_selected),
+            aliases: selected(&self.known_aliases_selected),
+            pattern: self.filter_options.pattern.trim().to_owned(),
+            columns: self.columns.clone(),
+            tags: saved_queries::parse_tags(&self.saved_queries.tags),
+        }
+    }
+
+    /**
+     * Restores the search, filters and columns of a saved query.
+     * Data streams, indicies and aliases not known yet are added to the filters, as they may only be missing until the next refresh.
+     */
+    fn load_saved_query(&mut self, query: saved_queries::SavedQuery) -> Action {
+        self.search_type = query.search_type.clone();
+        match query.search_type {
+            SearchType::StringSearch => self.query_string = query.query,
+            SearchType::GenericSearch => self.generic_search_body_content.set_text(&query.query),
+        }
+
+        self.known_data_streams_selected.values_mut().for_each(|selected| *selected = false);
+        self.known_indicies_selected.values_mut().for_each(|selected| *selected = false);
+        self.known_aliases_selected.values_mut().for_each(|selected| *selected = false);
+        self.known_data_streams_selected.extend(query.data_streams.into_iter().map(|data_stream| (data_stream, true)));
+        self.known_indicies_selected.extend(query.indices.into_iter().map(|index| (index, true)));
+        self.known_aliases_selected.extend(query.aliases.into_iter().map(|alias| (alias, true)));
+        self.filter_options.pattern = query.pattern;
+
+        // Kept over the columns stored for the index pattern until they are changed
+        if !query.columns.is_empty() {
+            self.results_pattern = columns::index_pattern(&self.selected_filters());
+            self.columns_pattern = Some(self.results_pattern.clone());
+            self.columns = query.columns;
+        }
+
+        self.saved_queries.name = query.name;
+        self.saved_queries.description = query.description;
+        self.saved_queries.tags = query.tags.join(", ");
+
+        self.request_field_mappings()
+    }
+
+    // Selected data streams and indicies are unselected along with hiding them, rather than searched unseen
+    fn unselect_not_shown(&mut self) -> Action {
+        let data_streams = filters::unselect_not_shown(&mut self.known_data_streams_selected, &self.index_states, &self.filter_options);
+        let indicies = filters::unselect_not_shown(&mut self.known_indicies_selected, &self.index_states, &self.filter_options);
+
+        if data_streams || indicies {
+            self.request_field_mappings()
+        } else {
+            Action::None
+        }
+    }
+
+    // Mappings of the selected filters, superseding any request still running
+    fn request_field_mappings(&mut self) -> Action {
+        self.field_mappings_request += 1;
+        Action::TryClientInvoke(Context::FieldMappings(self.field_mappings_request, self.selected_filters()))
+    }
+
+    fn save_columns(&self) -> Action {
+        Action::Run(iced::Task::perform(
+            columns::save(self.results_pattern.clone(), self.columns.clone()),
+            Message::ColumnsSaved
+        ))
+    }
+
+    // The raw view shows all hits in a single tree, only built once that view is shown
+    fn build_raw_hits_tree(&mut self) {
+        if self.display_mode != DisplayMode::Raw || self.raw_hits_tree.is_some() {
+            return;
+        }
+
+        if let GenericSearchDisplaySectionValue::Result { res, .. } = &self.generic_search_display_content {
+            self.raw_hits_tree = Some(widget::JsonTree::new(serde_json::Value::Array(res.hits.hits.clone())));
+        }
+    }
+
+    // Columns saved for the index pattern of the results, unless they are already loaded
+    fn load_columns(&self) -> Action {
+        if self.columns_pattern.as_ref() == Some(&self.results_pattern) {
+            Action::None
+        } else {
+            let pattern = self.results_pattern.clone();
+            Action::Run(iced::Task::perform(
+                columns::load(pattern.clone()),
+                move |res| Message::ColumnsLoaded(pattern.clone(), res)
+            ))
+        }
+    }
+
+    // New hits follow those shown, the oldest are dropped past TAIL_BUFFER_SIZE
+    fn append_tail_hits(&mut self, new_res: es::OperationSearchResult) {
+        match &mut self.generic_search_display_content {
+            GenericSearchDisplaySectionValue::Result { res, expanded_hits } => {
+                expanded_hits.extend(new_res.hits.hits.iter().map(|_| None));
+                res.hits.hits.extend(new_res.hits.hits);
+                res.time_took_ms = new_res.time_took_ms;
+                res.timed_out = new_res.timed_out;
+
+                let excess = res.hits.hits.len().saturating_sub(TAIL_BUFFER_SIZE);
+                res.hits.hits.drain(..excess);
+                expanded_hits.drain(..excess);
+            },
+            content => {
+                let expanded_hits = new_res.hits.hits.iter().map(|_| None).collect();
+                *content = GenericSearchDisplaySectionValue::Result { res: new_res, expanded_hits };
+            },
+        }
+
+        self.raw_hits_tree = None;
+        self.build_raw_hits_tree();
+    }
+
+    // Polls the tail while it runs, the page has to be shown for it to run
+    pub fn subscription(&self) -> iced::Subscription<Message> {
+        match &self.tail {
+            Some(state) if !state.paused => {
+                iced::time::every(std::time::Duration::from_secs(self.tail_interval.0)).map(|_| Message::TailTick)
+            },
+            _ => iced::Subscription::none(),
         }
     }
 
+    // Selected data streams, indicies and aliases, those missing from the cluster left out
+    fn selected_filters(&self) -> Vec<String> {
+        let data_streams = filters::searched(&self.known_data_streams_selected, &self.missing_filters);
+        let indices = filters::searched(&self.known_indicies_selected, &self.missing_filters);
+        let aliases = filters::searched(&self.known_aliases_selected, &self.missing_filters);
+
+        data_streams.chain(indices).chain(aliases).chain(self.filter_options.patterns()).collect()
+    }
+
     #[must_use]
     pub fn view(&self) -> iced::Element<'_, Message> {
         row![
             self.search_filters()
                 .align_x(iced::alignment::Horizontal::Left)
                 .width(iced::FillPortion(1))
-                .height(iced::Shrink),
+                .height(iced::Fill),
             match self.search_type {
                 SearchType::StringSearch => iced::widget::column![
                     widget::section(
                         iced::widget::column![
                             self.choose_search_type_section(),
+                            self.time_range_picker(),
+                            self.export_status(),
                             self.query_string_search_view(),
                         ]
                         .spacing(10)
@@ -237,6 +1434,8 @@ impl View {
                     widget::section(
                         iced::widget::column![
                             self.choose_search_type_section(),
+                            self.time_range_picker(),
+                            self.export_status(),
                             self.generic_search_view(),
                         ]
                         .spacing(10)
@@ -250,6 +1449,11 @@ impl View {
             .align_x(iced::alignment::Horizontal::Center)
             .width(iced::FillPortion(4))
             .height(iced::Fill),
+            self.saved_queries.open.then(|| {
+                self.saved_queries.view()
+                    .width(iced::FillPortion(2))
+                    .height(iced::Fill)
+            }),
         ]
         .spacing(10)
         .into()
@@ -261,9 +1465,89 @@ impl View {
                 .on_press(Message::SearchTypeChanged(SearchType::StringSearch)),
             iced::widget::button("Search Payload")
                 .on_press(Message::SearchTypeChanged(SearchType::GenericSearch)),
+            iced::widget::button("Saved Queries")
+                .on_press(Message::SavedQueriesToggled),
             iced::widget::space::horizontal(),
+            iced::widget::pick_list(export::Format::ALL, Some(self.export_format), Message::ExportFormatSelected),
+            iced::widget::button("Export")
+                .on_press_maybe(
+                    (!matches!(self.export_state, ExportState::Running { .. })).then_some(Message::ExportPressed)
+                ),
+            self.tail_controls(),
             self.generic_search_search_button()
         ]
+        .spacing(10)
+    }
+
+    // Start button, or pause and stop while tailing
+    fn tail_controls(&self) -> iced::widget::Row<'_, Message> {
+        let controls = match &self.tail {
+            None => row![
+                iced::widget::text_input("Tiebreaker, e.g. event.id", &self.tail_tiebreaker)
+                    .on_input(Message::TailTiebreakerUpdated)
+                    .width(180),
+                iced::widget::button("Tail")
+                    .on_press_maybe(self.time_field.is_some().then_some(Message::TailPressed)),
+            ],
+            Some(state) => row![
+                iced::widget::button(if state.paused { "Resume" } else { "Pause" })
+                    .on_press(Message::TailPausePressed),
+                iced::widget::button("Stop")
+                    .on_press(Message::TailStopPressed),
+            ],
+        };
+
+        controls
+            .push(iced::widget::pick_list(TailInterval::ALL, Some(self.tail_interval), Message::TailIntervalSelected))
+            .spacing(5)
+    }
+
+    // Only shown when the selected indicies have date fields
+    fn time_range_picker(&self) -> Option<iced::Element<'_, Message>> {
+        (!self.date_fields.is_empty())
+            .then(|| time_range::picker(&self.date_fields, self.time_field.as_ref(), &self.time_range))
+    }
+
+    fn export_status(&self) -> Option<iced::Element<'_, Message>> {
+        match &self.export_state {
+            ExportState::Idle => None,
+            ExportState::Running { progress, cancel, .. } => {
+                let cancelling = cancel.load(Ordering::Relaxed);
+                let status = match progress.total {
+                    Some(total) => format!("Exported {} of {} documents", progress.exported, total),
+                    None => "Starting export".to_owned(),
+                };
+
+                Some(
+                    row![
+                        iced::widget::progress_bar(
+                            0.0..=progress.total.unwrap_or(0).max(1) as f32,
+                            progress.exported as f32
+                        )
+                        .length(200)
+                        .girth(10),
+                        iced::widget::text(status).size(14),
+                        iced::widget::space::horizontal(),
+                        iced::widget::button(iced::widget::text(if cancelling { "Cancelling" } else { "Cancel" }).size(14))
+                            .on_press_maybe((!cancelling).then_some(Message::ExportCancelPressed)),
+                    ]
+                    .spacing(10)
+                    .align_y(iced::Center)
+                    .into()
+                )
+            },
+            ExportState::Finished(Ok(summary)) => Some(
+                iced_selection::text(summary)
+                    .size(14)
+                    .into()
+            ),
+            ExportState::Finished(Err(err)) => Some(
+                iced::widget::text(format!("Export failed: {}", err))
+                    .size(14)
+                    .style(iced::widget::text::danger)
+                    .into()
+            ),
+        }
     }
 
     fn search_filters(&self) -> iced::widget::Container<'_, Message> {
@@ -300,59 +1584,83 @@ impl View {
                 ),
             ].align_y(iced::Center),
             self.refresh_filter_errors.as_ref()
-                .map(|err| iced::widget::text(err)),
-            iced::widget::text("Indicies"),
-        ];
-
-        let filters = filters.extend(
-            self.known_indicies_selected
-                .iter()
-                .map(|(index, selected)|
-                    iced::widget::checkbox(*selected)
-                        .label(index)
-                        .on_toggle(|toggled| {
-                            if toggled {
-                                Message::SelectedFiltersUpdated(FiltersUpdate::AddIndex(index.to_owned()))
-                            } else {
-                                Message::SelectedFiltersUpdated(FiltersUpdate::RemoveIndex(index.to_owned()))
-                            }
-                        })
-                        .into()));
-
-        let filters = filters.push(iced::widget::text("Aliases"));
-
-        let filters = filters.extend(
-            self.known_aliases_selected
-                .iter()
-                .map(|(alias, selected)|
-                    iced::widget::checkbox(*selected)
-                        .label(alias)
-                        .on_toggle(|toggled| {
-                            if toggled {
-                                Message::SelectedFiltersUpdated(FiltersUpdate::AddAlias(alias.to_owned()))
-                            } else {
-                                Message::SelectedFiltersUpdated(FiltersUpdate::RemoveAlias(alias.to_owned()))
-                            }
-                        })
-                        .into()));
+                .map(iced::widget::text),
+            filters::controls(&self.filter_options),
+            self.filter_list(),
+        ]
+        .spacing(5);
 
         iced::widget::container(filters)
             .style(iced::widget::container::bordered_box)
             .padding(10)
     }
 
+    // Data streams followed by indicies grouped by family and aliases, sorted by name
+    fn filter_list(&self) -> iced::widget::Scrollable<'_, Message> {
+        let rows = filters::rows(
+            &self.known_data_streams_selected,
+            &self.known_indicies_selected,
+            &self.index_states,
+            &self.known_aliases_selected,
+            &self.missing_filters,
+            &self.filter_options
+        );
+
+        widget::virtual_list::virtual_list(
+            &self.filters_list,
+            widget::virtual_list::Heights::Fixed { count: rows.len(), height: FILTER_ROW_HEIGHT },
+            |range| rows[range].iter().map(filters::row_view).collect(),
+            Message::FiltersScrolled
+        )
+    }
+
     fn query_string_search_view(&self) -> iced::widget::Container<'_, Message> {
         iced::widget::container(
-            iced::widget::text_input("title:(quick OR brown)", &self.query_string)
-                .on_input(Message::QueryStringUpdated)
+            iced::widget::row![
+                iced::widget::text_input("title:(quick OR brown)", &self.query_string)
+                    .on_input(Message::QueryStringUpdated),
+                iced::widget::checkbox(!self.highlight_disabled)
+                    .label("Highlight matches")
+                    .on_toggle(Message::HighlightToggled),
+                (!self.highlight_disabled).then(|| self.highlight_options_view()),
+            ]
+            .spacing(10)
+            .align_y(iced::Center)
         )
     }
 
+    // Fields, fragment size and number of fragments of query string highlights
+    fn highlight_options_view(&self) -> iced::widget::Row<'_, Message> {
+        row![
+            iced::widget::text_input("Fields, e.g. message,title.*", &self.highlight_options.fields)
+                .on_input(Message::HighlightFieldsUpdated)
+                .width(180),
+            iced::widget::pick_list(HIGHLIGHT_FRAGMENT_SIZES, Some(self.highlight_options.fragment_size), Message::HighlightFragmentSizeSelected),
+            iced::widget::text("chars"),
+            iced::widget::pick_list(HIGHLIGHT_FRAGMENT_COUNTS, Some(self.highlight_options.fragment_count), Message::HighlightFragmentCountSelected),
+            iced::widget::text("fragments"),
+        ]
+        .spacing(5)
+        .align_y(iced::Center)
+    }
+
     fn generic_search_view(&self) -> iced::widget::Container<'_, Message> {
         iced::widget::container(
-            iced::widget::text_editor(&self.generic_search_body_content)
-                .on_action(Message::GenericSearchBodyEditorActionPerformed)
-                .height(iced::Fill)
+            iced::widget::stack![
+                self.generic_search_body_content.view(
+                    Message::GenericSearchBodyEditorActionPerformed,
+                    r#"{"query":{"match_all":{}}}"#
+                ),
+                (!self.body_suggestions.is_empty()).then(|| {
+                    iced::widget::container(
+                        widget::suggestion_list(&self.body_suggestions, Message::BodySuggestionPicked)
+                            .max_width(400)
+                    )
+                    .align_right(iced::Fill)
+                    .align_bottom(iced::Fill)
+                    .padding(10)
+                }),
+            ]
         )
     }
 
@@ -375,24 +1683,120 @@ impl View {
             ),
             GenericSearchDisplaySectionValue::Result{res, expanded_hits} => widget::section_with_header(
                 iced::widget::row![
-                    iced::widget::text(format!("Results")).align_y(iced::Center),
+                    iced::widget::text("Results".to_string()).align_y(iced::Center),
+                    self.display_mode_section(res),
                     iced::widget::space::horizontal(),
                     self.result_stats(res)
-                ],
-                iced::widget::scrollable(
-                    column(
-                    res.hits.hits.iter().zip(expanded_hits.iter()).enumerate().map(|(index, (item, expanded))|
-                        self.hit_item(item, *expanded, index)
-                            .width(iced::Fill)
-                            .into()
-                    ))
-                )
-                .width(iced::Fill)
-                .height(iced::Fill)
+                ]
+                .spacing(20)
+                .align_y(iced::Center),
+                iced::widget::column![
+                    self.histogram.as_ref().map(time_range::histogram_view),
+                    match self.display_mode {
+                        DisplayMode::Table => self.hits_table(res, expanded_hits),
+                        DisplayMode::Accordion => self.hits_accordion(res, expanded_hits),
+                        DisplayMode::Raw => match &self.raw_hits_tree {
+                            Some(tree) => iced::Element::from(tree.view_scrollable()).map(Message::RawTreeUpdated),
+                            None => iced::widget::space::vertical().into(),
+                        },
+                        DisplayMode::Aggregations => match &res.aggregations {
+                            Some(results) => aggregations::view(results, &self.searched_aggregations, &self.aggregation_presentations),
+                            None => iced::widget::space::vertical().into(),
+                        },
+                    }
+                ]
+                .spacing(10),
             ),
         }
     }
 
+    fn display_mode_section(&self, res: &es::OperationSearchResult) -> iced::widget::Row<'_, Message> {
+        let has_aggregations = res.aggregations.as_ref().is_some_and(|aggregations| !aggregations.is_empty());
+
+        iced::widget::row![
+            widget::RadioArea::new("Table", DisplayMode::Table, Some(self.display_mode), Message::DisplayModeSelected),
+            widget::RadioArea::new("Accordion", DisplayMode::Accordion, Some(self.display_mode), Message::DisplayModeSelected),
+            widget::RadioArea::new("JSON", DisplayMode::Raw, Some(self.display_mode), Message::DisplayModeSelected),
+            has_aggregations.then(|| {
+                widget::RadioArea::new("Aggregations", DisplayMode::Aggregations, Some(self.display_mode), Message::DisplayModeSelected)
+            }),
+        ]
+        .spacing(10)
+    }
+
+    fn hits_accordion<'a>(
+        &'a self,
+        res: &'a es::OperationSearchResult,
+        expanded_hits: &'a [Option<widget::JsonTree>]
+    ) -> iced::Element<'a, Message> {
+        widget::virtual_list::virtual_list(
+            &self.hits_list,
+            widget::virtual_list::Heights::Variable(
+                res.hits.hits.iter()
+                    .zip(expanded_hits)
+                    .map(|(hit, expanded)| self.hit_item_height(hit, expanded))
+                    .collect()
+            ),
+            |range| res.hits.hits[range.clone()].iter()
+                .zip(expanded_hits[range.clone()].iter())
+                .zip(range)
+                .map(|((item, expanded), index)|
+                    self.hit_item(item, expanded.as_ref(), index)
+                        .width(iced::Fill)
+                        .into()
+                )
+                .collect(),
+            Message::HitsScrolled
+        )
+        .into()
+    }
+
+    fn hits_table<'a>(
+        &'a self,
+        res: &'a es::OperationSearchResult,
+        expanded_hits: &'a [Option<widget::JsonTree>]
+    ) -> iced::Element<'a, Message> {
+        let heights = expanded_hits.iter()
+            .map(|expanded| match expanded {
+                // Row and the padding around the tree
+                Some(tree) => table::ROW_HEIGHT + 2.0 * ROW_TREE_PADDING + tree.height(),
+                None => table::ROW_HEIGHT,
+            })
+            .collect();
+
+        iced::widget::column![
+            table::column_picker(&self.columns, table::available_fields(&res.hits.hits, &self.field_mappings)),
+            table::header(&self.columns, self.sort.as_ref(), self.resizing_column),
+            iced::widget::rule::horizontal(1),
+            widget::virtual_list::virtual_list(
+                &self.hits_list,
+                widget::virtual_list::Heights::Variable(heights),
+                |range| res.hits.hits[range.clone()].iter()
+                    .zip(expanded_hits[range.clone()].iter())
+                    .zip(range)
+                    .map(|((hit, expanded), index)| {
+                        let row = table::row(hit, &self.columns, Self::expand_button(expanded.is_some(), index));
+                        match expanded {
+                            Some(tree) => iced::widget::column![
+                                row,
+                                iced::widget::container(
+                                    iced::Element::from(tree.view())
+                                        .map(move |message| Message::HitTreeUpdated(index, message))
+                                )
+                                .padding(ROW_TREE_PADDING),
+                            ]
+                            .into(),
+                            None => row.into(),
+                        }
+                    })
+                    .collect(),
+                Message::HitsScrolled
+            ),
+        ]
+        .spacing(5)
+        .into()
+    }
+
     // TODO: rename as search button. Should also submit query based on the search mode
     fn generic_search_search_button(&self) -> iced::widget::Button<'_, Message> {
         let produced_message = match self.generic_search_search_button_state {
@@ -429,10 +1833,83 @@ impl View {
         .padding(5)
     }
 
-    // TODO: consider allow the display of multiple fields based on selection
-    fn hit_item<'a>(&'a self, item: &'a serde_json::Value, expanded: bool, index: usize) -> iced::widget::Container<'a, Message> {
+    // Height of a hit as laid out by `hit_item`
+    fn hit_item_height(&self, hit: &serde_json::Value, expanded: &Option<widget::JsonTree>) -> f32 {
+        let header = match self.highlight_line_count(hit) {
+            0 => EXPAND_BUTTON_SIZE,
+            lines => EXPAND_BUTTON_SIZE + HIT_HEADER_SPACING + lines as f32 * HIGHLIGHT_LINE_HEIGHT,
+        };
+
+        match expanded {
+            Some(tree) => widget::section_with_header_height(header, tree.height()),
+            None => widget::section_height(header),
+        }
+    }
+
+    // Highlight fragments shown for a hit, counted without splitting them
+    fn highlight_line_count(&self, hit: &serde_json::Value) -> usize {
+        let Some(highlight) = &self.searched_highlight else {
+            return 0;
+        };
+
+        hit.get("highlight")
+            .and_then(serde_json::Value::as_object)
+            .map(|fields| fields.values().filter_map(serde_json::Value::as_array).map(Vec::len).sum::<usize>())
+            .unwrap_or_default()
+            .min(highlight_line_limit(highlight))
+    }
+
+    // Fragment of each highlighted field, with the text the query matched emphasized
+    fn highlight_lines<'a>(&'a self, hit: &'a serde_json::Value) -> Option<iced::widget::Column<'a, Message>> {
+        let fragments = self.searched_highlight.as_ref()?.hit_fragments(hit);
+        if fragments.is_empty() {
+            return None;
+        }
+
+        let matched_font = iced::Font {
+            weight: iced::font::Weight::Bold,
+            ..iced::Font::DEFAULT
+        };
+
+        let lines = fragments.into_iter()
+            .take(self.searched_highlight.as_ref().map_or(DEFAULT_HIGHLIGHT_LINES, highlight_line_limit))
+            .map(|fragment| {
+                // Each fragment is kept to a single line
+                let spans = fragment.segments.iter()
+                    .map(|segment| {
+                        let span = iced::widget::span(segment.text.replace('\n', " "));
+                        if segment.matched {
+                            span.font(matched_font)
+                                .background(iced::Color::from_rgba(1.0, 0.8, 0.0, 0.35))
+                        } else {
+                            span
+                        }
+                    })
+                    .collect::<Vec<iced::widget::text::Span<'a, (), iced::Font>>>();
+
+                iced::widget::container(
+                    iced::widget::row![
+                        iced::widget::text(fragment.field)
+                            .size(13)
+                            .font(iced::Font::MONOSPACE)
+                            .style(iced::widget::text::secondary),
+                        iced::widget::rich_text(spans)
+                            .size(13)
+                            .wrapping(iced::widget::text::Wrapping::None),
+                    ]
+                    .spacing(10)
+                )
+                .height(HIGHLIGHT_LINE_HEIGHT)
+                .clip(true)
+                .into()
+            });
+
+        Some(iced::widget::column(lines))
+    }
+
+    fn expand_button<'a>(expanded: bool, index: usize) -> iced::widget::Button<'a, Message> {
         // TODO make icon fill up more of the button
-        let expand_button = iced::widget::button(
+        iced::widget::button(
             if expanded {
                 assets::chevron_down_icon()
             } else {
@@ -441,29 +1918,31 @@ impl View {
             .width(iced::Fill)
             .height(iced::Fill)
         )
-        .width(30)
-        .height(30)
-        .on_press(Message::ResultsAccordianClicked(index));
-        
+        .width(EXPAND_BUTTON_SIZE)
+        .height(EXPAND_BUTTON_SIZE)
+        .on_press(Message::ResultsAccordianClicked(index))
+    }
+
+    fn hit_item<'a>(&'a self, item: &'a serde_json::Value, expanded: Option<&'a widget::JsonTree>, index: usize) -> iced::widget::Container<'a, Message> {
         // TODO: make this formatted with key hightlighted, kibana for reference
-        let header = iced::widget::row![
-            expand_button,
+        let id = iced::widget::row![
+            Self::expand_button(expanded.is_some(), index),
             iced_selection::text(
                 item.get("_id")
-                    .map(|val| val.as_str())
-                    .flatten()
+                    .and_then(|val| val.as_str())
                     .unwrap_or("_id field missing")
             )
             .align_y(iced::Center)
         ].spacing(10);
 
-        if expanded {
+        let header = iced::widget::column![id, self.highlight_lines(item)].spacing(HIT_HEADER_SPACING);
+
+        if let Some(tree) = expanded {
             widget::section_with_header(
                 header, 
-                iced_selection::text(
-                    serde_json::to_string_pretty(item)
-                        .unwrap_or(format!("Failed to display {:?}", item)))
-                )
+                iced::Element::from(tree.view())
+                    .map(move |message| Message::HitTreeUpdated(index, message))
+            )
         } else {
             widget::section(header)
         }
@@ -474,25 +1953,131 @@ impl View {
         context: Context
     ) -> iced::Task<Message> {
         match context {
-            Context::AllIndiciesAndAliases => iced::Task::perform(
-                    Self::get_all_indicies_and_aliases(client_res),
-                    Message::FilterRefreshResultsReturned
+            Context::AllDataStreamsIndiciesAndAliases(cluster, log) => iced::Task::perform(
+                    Self::refresh_filters(client_res, cluster, log),
+                    |(cluster, res)| Message::FilterRefreshResultsReturned(cluster, res)
+            ),
+            Context::FieldMappings(request, filters) => iced::Task::perform(
+                Self::get_field_mappings(client_res, filters),
+                move |res| Message::FieldMappingsReturned(request, res)
+            ),
+            Context::GenericSearch { request, body, indices, aliases, sort, time } => iced::Task::perform(
+                Self::generic_search(client_res, body, indices, aliases, sort, time),
+                move |res| Message::SearchResultsReturned(request, res)
+            ),
+            Context::QueryStringSearch { request, query_string, indices, aliases, sort, time, highlight } => iced::Task::perform(
+                Self::query_string_search(client_res, query_string, indices, aliases, sort, time, highlight),
+                move |res| Message::SearchResultsReturned(request, res)
             ),
-            Context::GenericSearch { body, indices, aliases } => iced::Task::perform(
-                Self::generic_search(client_res, body, indices, aliases),
-                Message::SearchResultsReturned
+            Context::Export(request) => iced::Task::run(
+                Self::export(client_res, request),
+                Message::ExportProgressed
+            ),
+            Context::Tail(generation, tail) => iced::Task::perform(
+                Self::poll_tail(client_res, tail),
+                move |res| Message::TailPolled(generation, res)
             ),
-            Context::QueryStringSearch { query_string, indices, aliases } => iced::Task::perform(
-                Self::query_string_search(client_res, query_string, indices, aliases), Message::SearchResultsReturned),
         }
     }
 
-    async fn get_all_indicies(client: &es::ElasticsearchClient) -> Result<Vec<String>, String> {
-        client.get_indicies().await
+    // Progress of each exported page. A cancelled export removes its partially written file
+    fn export(
+        client_res: Result<es::ElasticsearchClient, String>,
+        request: ExportRequest
+    ) -> impl iced::futures::Stream<Item = Result<export::Progress, String>> {
+        iced::stream::try_channel(1, async move |mut sender| {
+            use iced::futures::SinkExt;
+
+            let client = client_res?;
+
+            let mut file = tokio::fs::File::create(&request.path).await
+                .map_err(|err| format!("Unable to create {}, error kind: {}", request.path.to_string_lossy(), err.kind()))?;
+
+            // Pages are exported to memory then written to the file without blocking the executor
+            let mut export = export::Export::new(
+                client,
+                request.indices,
+                request.body,
+                request.format,
+                request.columns,
+                Vec::new()
+            )
+            .map_err(|err| err.to_string())?;
+
+            loop {
+                if request.cancel.load(Ordering::Relaxed) {
+                    export.close().await;
+                    drop(file);
+                    let _ = tokio::fs::remove_file(&request.path).await;
+                    return Err("Export cancelled".to_owned());
+                }
+
+                let res = match export.next_page().await.map_err(|err| err.to_string()) {
+                    Ok(progress) => Self::write_export_output(&mut export, &mut file, &request.path).await
+                        .map(|()| progress),
+                    Err(err) => Err(err),
+                };
+
+                match res {
+                    Ok(progress) => {
+                        let _ = sender.send(progress).await;
+                        if progress.finished {
+                            return Ok(());
+                        }
+                    },
+                    Err(err) => {
+                        export.close().await;
+                        return Err(err);
+                    },
+                }
+            }
+        })
+    }
+
+    async fn write_export_output(
+        export: &mut export::Export<Vec<u8>>,
+        file: &mut tokio::fs::File,
+        path: &std::path::Path
+    ) -> Result<(), String> {
+        use tokio::io::AsyncWriteExt;
+
+        let output = export.take_output().map_err(|err| err.to_string())?;
+        file.write_all(&output).await
+            .and(file.flush().await)
+            .map_err(|err| format!("Unable to write {}, error kind: {}", path.to_string_lossy(), err.kind()))
+    }
+
+    async fn poll_tail(
+        client_res: Result<es::ElasticsearchClient, String>,
+        mut tail: tail::Tail
+    ) -> Result<(tail::Tail, es::OperationSearchResult), String> {
+        let client = client_res?;
+        let res = tail.poll(&client, TAIL_BUFFER_SIZE).await
+            .map_err(|err| err.to_string())?;
+        Ok((tail, res))
+    }
+
+    async fn get_all_indicies(client: &es::ElasticsearchClient) -> Result<KnownIndicies, String> {
+        client.get_indicies_with_hidden().await
             .map(|indicies| 
                     indicies.into_iter()
-                        .map(|idx| idx.name)
-                        .collect::<Vec<String>>())
+                        .map(|idx| {
+                            let state = filters::IndexState { hidden: idx.hidden, closed: idx.is_closed() };
+                            (idx.name, state)
+                        })
+                        .collect::<KnownIndicies>())
+            .map_err(|err| err.to_string())
+    }
+
+    async fn get_all_data_streams(client: &es::ElasticsearchClient) -> Result<KnownIndicies, String> {
+        client.get_data_streams().await
+            .map(|data_streams|
+                    data_streams.into_iter()
+                        .map(|data_stream| {
+                            let state = filters::IndexState { hidden: data_stream.hidden, closed: false };
+                            (data_stream.name, state)
+                        })
+                        .collect::<KnownIndicies>())
             .map_err(|err| err.to_string())
     }
 
@@ -505,27 +2090,65 @@ impl View {
             .map_err(|err| err.to_string())
     }
 
-    async fn get_all_indicies_and_aliases(
+    async fn get_field_mappings(
+        client_res: Result<es::ElasticsearchClient, String>,
+        filters: Vec<String>
+    ) -> Result<Vec<es::FieldMapping>, String> {
+        let client = client_res?;
+        client.get_field_mappings(&filters).await
+            .map_err(|err| err.to_string())
+    }
+
+    // Lists are cached for the cluster once all of them were obtained
+    async fn refresh_filters(
+        client_res: Result<es::ElasticsearchClient, String>,
+        cluster: String,
+        log: logs::Log
+    ) -> (String, FilterRefreshResult) {
+        let res = Self::get_all_data_streams_indicies_and_aliases(client_res).await;
+
+        if let FilterRefreshResult { data_streams: Some(data_streams), indicies: Some(indicies), aliases: Some(aliases), error: None } = &res {
+            let cache = filters::Cache {
+                indicies: indicies.clone(),
+                aliases: aliases.clone(),
+                data_streams: data_streams.clone(),
+            };
+            if let Err(err) = filters::save_cache(cluster.clone(), cache).await {
+                log.error(format!("Failed to cache filters: {}", err));
+            }
+        }
+
+        (cluster, res)
+    }
+
+    async fn get_all_data_streams_indicies_and_aliases(
         client_res: Result<es::ElasticsearchClient, String>
-    ) -> Result<
-            (Vec<String>, Vec<String>), 
-            (String, Option<Vec<String>>, Option<Vec<String>>)>{
-        let client = client_res.map_err(|err| (err, None, None))?;
-        
-        let (indicies_res, aliases_res) = iced::futures::join!(
+    ) -> FilterRefreshResult {
+        let client = match client_res {
+            Ok(client) => client,
+            Err(err) => return FilterRefreshResult { data_streams: None, indicies: None, aliases: None, error: Some(err) },
+        };
+
+        let (data_streams_res, indicies_res, aliases_res) = iced::futures::join!(
+                Self::get_all_data_streams(&client),
                 Self::get_all_indicies(&client),
                 Self::get_all_aliases(&client)
         );
-        
-        match (indicies_res, aliases_res) {
-            (Ok(indices), Ok(aliases)) => 
-                Ok((indices, aliases)),
-            (Ok(indices), Err(alias_err)) => 
-                Err((format!("Failed to get aliases: {}", alias_err), Some(indices), None)),
-            (Err(index_err), Ok(aliases)) => 
-                Err((format!("Failed to get indicies: {}", index_err), None, Some(aliases))),
-            (Err(index_err), Err(alias_err)) => 
-                Err((format!("Failed to get indicies: {}\n Failed to get aliases: {}", index_err, alias_err), None, None)),
+
+        let errors = [
+            data_streams_res.as_ref().err().map(|err| format!("Failed to get data streams: {}", err)),
+            indicies_res.as_ref().err().map(|err| format!("Failed to get indicies: {}", err)),
+            aliases_res.as_ref().err().map(|err| format!("Failed to get aliases: {}", err)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<String>>();
+
+        FilterRefreshResult {
+            data_streams: data_streams_res.ok(),
+            indicies: indicies_res.ok(),
+            aliases: aliases_res.ok(),
+            error: (!errors.is_empty()).then(|| errors.join("\n ")),
         }
     }
 
@@ -533,16 +2156,41 @@ impl View {
         client_res: Result<es::ElasticsearchClient, String>,
         body: String,
         mut indices: Vec<String>,
-        mut aliases: Vec<String>
+        mut aliases: Vec<String>,
+        sort: Option<es::Sort>,
+        time: TimeSearch
     ) -> Result<es::OperationSearchResult, String> {
         let client = client_res?;
 
-        let search_body = (!body.is_empty()).then(|| {
+        let mut search_body = (!body.is_empty()).then(|| {
             serde_json::from_str::<serde_json::Value>(&body)
         })
         .transpose()
         .map_err(|err| err.to_string())?;
 
+        if sort.is_some() || time.filter.is_some() || time.histogram.is_some() {
+            let body = search_body.get_or_insert_with(|| serde_json::json!({}));
+            let Some(body) = body.as_object_mut() else {
+                return Err("Search body must be a JSON object".to_owned());
+            };
+
+            if let Some(sort) = sort {
+                body.insert("sort".to_owned(), serde_json::json!([sort]));
+            }
+            if let Some(filter) = time.filter {
+                add_query_filters(body, vec![serde_json::json!(filter)]);
+            }
+            if let Some(histogram) = time.histogram {
+                let aggs_key = if body.contains_key("aggregations") { "aggregations" } else { "aggs" };
+                match body.entry(aggs_key).or_insert_with(|| serde_json::json!({})).as_object_mut() {
+                    Some(aggs) => {
+                        aggs.insert(time_range::HISTOGRAM_NAME.to_owned(), serde_json::json!(histogram));
+                    },
+                    None => return Err("Aggregations of the search body must be a JSON object".to_owned()),
+                }
+            }
+        }
+
         indices.append(&mut aliases);
 
         client.search(&indices, search_body.as_ref()).await
@@ -553,11 +2201,33 @@ impl View {
         client_res: Result<es::ElasticsearchClient, String>,
         query_string: String,
         mut indices: Vec<String>,
-        mut aliases: Vec<String>
+        mut aliases: Vec<String>,
+        sort: Option<es::Sort>,
+        time: TimeSearch,
+        highlight: Option<Box<es::Highlight>>
     ) -> Result<es::OperationSearchResult, String> {
         let client = client_res?;
         indices.append(&mut aliases);
-        client.query_string(&indices, query_string).await
+
+        if time.filter.is_none() && time.histogram.is_none() && highlight.is_none() {
+            return client.query_string(&indices, query_string, sort.as_slice()).await
+                .map_err(|err| err.to_string());
+        }
+
+        let query = es::Query::query_string(query_string);
+        let mut body = es::SearchBody::new().query(match time.filter {
+            Some(filter) => es::BoolQuery::new().must(query).filter(*filter).into(),
+            None => query,
+        });
+        body.sort = sort.into_iter().collect();
+        if let Some(histogram) = time.histogram {
+            body = body.aggregation(time_range::HISTOGRAM_NAME, *histogram);
+        }
+        if let Some(highlight) = highlight {
+            body = body.highlight(*highlight);
+        }
+
+        client.search(&indices, Some(&body.to_json())).await
             .map_err(|err| err.to_string())
     }
 
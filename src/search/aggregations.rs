@@ -0,0 +1,376 @@
+// Aggregation results as nested tables, or charts for terms and date histograms.
+// Pressing a bucket narrows the search body to the documents of that bucket.
+use std::collections::{BTreeMap, HashMap};
+
+use elastic_ermine::es;
+
+use super::Message;
+use crate::widget::{self, chart};
+
+const INDENT: f32 = 20.0;
+const KEY_WIDTH: f32 = 260.0;
+const VALUE_WIDTH: f32 = 140.0;
+
+static EMPTY: Requested = BTreeMap::new();
+
+/**
+ * Aggregation names from the top level down to a bucket, each with the index of the bucket taken
+ * (None for single bucket aggregations such as filter)
+ */
+pub type BucketPath = Vec<(String, Option<usize>)>;
+
+// Aggregations requested in the search body, by name
+pub type Requested = BTreeMap<String, es::Aggregation>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Presentation {
+    Table,
+    Chart(chart::Kind),
+}
+
+impl Presentation {
+    // Charts for the aggregations they suit, tables for the rest
+    fn default_for(request: Option<&es::Aggregation>) -> Option<Presentation> {
+        match request.map(|request| &request.kind) {
+            Some(es::AggregationKind::Terms(_)) => Some(Presentation::Chart(chart::Kind::Bar)),
+            Some(es::AggregationKind::DateHistogram(_)) => Some(Presentation::Chart(chart::Kind::Line)),
+            _ => None,
+        }
+    }
+}
+
+// Chosen presentation of chartable aggregations by name, shared by aggregations of the same name in other buckets
+pub type Presentations = HashMap<String, Presentation>;
+
+/**
+ * Aggregations requested by a search body, empty when it has none.
+ */
+pub fn requested(body: &str) -> Result<Requested, String> {
+    let body = serde_json::from_str::<serde_json::Value>(body)
+        .map_err(|err| format!("Invalid search body: {}", err))?;
+
+    body.get("aggs")
+        .or_else(|| body.get("aggregations"))
+        .cloned()
+        .map(serde_json::from_value::<Requested>)
+        .transpose()
+        .map_err(|err| format!("Invalid aggregations: {}", err))
+        .map(Option::unwrap_or_default)
+}
+
+fn format_number(value: Option<f64>) -> String {
+    match value {
+        None => "-".to_owned(),
+        Some(value) if value.fract() == 0.0 && value.abs() < 1e15 => format!("{}", value as i64),
+        Some(value) => format!("{:.3}", value),
+    }
+}
+
+// Single text shown for results that fit in a cell
+fn metric_text(result: &es::AggregationResult) -> Option<String> {
+    match result {
+        es::AggregationResult::Value(value) => Some(
+            value.value_as_string.clone().unwrap_or_else(|| format_number(value.value))
+        ),
+        es::AggregationResult::Stats(stats) => Some(format!(
+            "min {} | max {} | avg {}", format_number(stats.min), format_number(stats.max), format_number(stats.avg)
+        )),
+        es::AggregationResult::Percentiles(percentiles) => Some(
+            percentiles.iter()
+                .map(|percentile| format!("p{} {}", percentile.percent.trim_end_matches(".0"), format_number(percentile.value)))
+                .collect::<Vec<String>>()
+                .join(" | ")
+        ),
+        _ => None,
+    }
+}
+
+fn cell<'a>(content: impl Into<iced::Element<'a, Message>>, width: f32) -> iced::widget::Container<'a, Message> {
+    iced::widget::container(content)
+        .width(width)
+        .padding([2, 5])
+        .clip(true)
+}
+
+fn small_text<'a>(text: impl iced::widget::text::IntoFragment<'a>) -> iced::widget::Text<'a> {
+    iced::widget::text(text).size(14).wrapping(iced::widget::text::Wrapping::None)
+}
+
+fn bucket_button<'a>(label: String, path: BucketPath) -> iced::widget::Button<'a, Message> {
+    iced::widget::button(small_text(label))
+        .on_press(Message::AggregationBucketPressed(path))
+        .padding(0)
+        .style(iced::widget::button::text)
+}
+
+fn presentation_picker<'a>(name: &'a str, presentation: Presentation) -> iced::widget::Row<'a, Message> {
+    let options = std::iter::once(("Table".to_owned(), Presentation::Table))
+        .chain(chart::Kind::ALL.into_iter().map(|kind| (kind.to_string(), Presentation::Chart(kind))));
+
+    iced::widget::row(options.map(|(label, option)| {
+        widget::RadioArea::new(label, option, Some(presentation), |option| {
+            Message::AggregationPresentationSelected(name.to_owned(), option)
+        })
+        .into()
+    }))
+    .spacing(5)
+}
+
+fn buckets_chart<'a>(
+    name: &'a str,
+    result: &'a es::BucketsResult,
+    kind: chart::Kind,
+    path: &BucketPath,
+) -> iced::Element<'a, Message> {
+    let entries = result.buckets.iter()
+        .map(|bucket| chart::Entry::new(bucket.label(), bucket.doc_count as f64))
+        .collect();
+
+    let path = path.clone();
+    chart::Chart::new(kind, entries)
+        .on_press(move |idx| {
+            let mut bucket_path = path.clone();
+            bucket_path.push((name.to_owned(), Some(idx)));
+            Message::AggregationBucketPressed(bucket_path)
+        })
+        .into()
+}
+
+fn buckets_table<'a>(
+    name: &'a str,
+    result: &'a es::BucketsResult,
+    requested: &'a Requested,
+    presentations: &Presentations,
+    path: &BucketPath,
+) -> iced::widget::Column<'a, Message> {
+    // Metrics shared by the buckets are shown as columns, bucket aggregations are nested under their bucket
+    let metric_names = result.buckets.first()
+        .map(|bucket| {
+            bucket.aggregations.iter()
+                .filter(|(_, result)| metric_text(result).is_some())
+                .map(|(name, _)| name.as_str())
+                .collect::<Vec<&str>>()
+        })
+        .unwrap_or_default();
+
+    let header = iced::widget::row![
+        cell(small_text("Key"), KEY_WIDTH),
+        cell(small_text("Documents"), VALUE_WIDTH),
+    ]
+    .extend(metric_names.iter().map(|name| cell(small_text(*name), VALUE_WIDTH).into()));
+
+    let mut table = iced::widget::column![header, iced::widget::rule::horizontal(1)];
+
+    for (idx, bucket) in result.buckets.iter().enumerate() {
+        let mut bucket_path = path.clone();
+        bucket_path.push((name.to_owned(), Some(idx)));
+
+        let metrics = metric_names.iter().map(|name| {
+            let text = bucket.aggregations.get(*name).and_then(metric_text).unwrap_or_else(|| "-".to_owned());
+            cell(small_text(text), VALUE_WIDTH).into()
+        });
+
+        table = table.push(
+            iced::widget::row![
+                cell(bucket_button(bucket.label(), bucket_path.clone()), KEY_WIDTH),
+                cell(small_text(bucket.doc_count.to_string()), VALUE_WIDTH),
+            ]
+            .extend(metrics)
+        );
+
+        let nested = bucket.aggregations.iter()
+            .filter(|(name, result)| !metric_names.contains(&name.as_str()) || metric_text(result).is_none())
+            .map(|(name, result)| aggregation(name, result, requested, presentations, &bucket_path).into())
+            .collect::<Vec<iced::Element<'a, Message>>>();
+
+        if !nested.is_empty() {
+            table = table.push(
+                iced::widget::container(iced::widget::column(nested).spacing(5))
+                    .padding(iced::Padding::default().left(INDENT).bottom(5))
+            );
+        }
+    }
+
+    if let Some(others) = result.sum_other_doc_count.filter(|others| *others > 0) {
+        table = table.push(
+            iced::widget::row![
+                cell(small_text("Other"), KEY_WIDTH),
+                cell(small_text(others.to_string()), VALUE_WIDTH),
+            ]
+        );
+    }
+
+    table
+}
+
+/**
+ * Name of the aggregation followed by its result. Path leads to the bucket the aggregation is nested in.
+ * Requested holds the aggregations requested at the level of this one.
+ */
+pub fn aggregation<'a>(
+    name: &'a str,
+    result: &'a es::AggregationResult,
+    requested: &'a Requested,
+    presentations: &Presentations,
+    path: &BucketPath,
+) -> iced::widget::Column<'a, Message> {
+    let title = iced::widget::text(name).size(14).font(iced::Font::MONOSPACE);
+
+    // Aggregations nested in this one
+    let request = requested.get(name);
+    let nested_requested = request.map(|request| &request.aggs).unwrap_or(&EMPTY);
+
+    match result {
+        es::AggregationResult::Buckets(buckets) => {
+            let presentation = Presentation::default_for(request)
+                .map(|default| presentations.get(name).copied().unwrap_or(default));
+
+            match presentation {
+                Some(presentation) => iced::widget::column![
+                    iced::widget::row![title, presentation_picker(name, presentation)]
+                        .spacing(10)
+                        .align_y(iced::Center),
+                    match presentation {
+                        Presentation::Chart(kind) => buckets_chart(name, buckets, kind, path),
+                        Presentation::Table => buckets_table(name, buckets, nested_requested, presentations, path).into(),
+                    },
+                ],
+                None => iced::widget::column![
+                    title,
+                    buckets_table(name, buckets, nested_requested, presentations, path),
+                ],
+            }
+        },
+        es::AggregationResult::SingleBucket(bucket) => {
+            let mut bucket_path = path.clone();
+            bucket_path.push((name.to_owned(), None));
+
+            let nested = bucket.aggregations.iter()
+                .map(|(name, result)| aggregation(name, result, nested_requested, presentations, &bucket_path).into())
+                .collect::<Vec<iced::Element<'a, Message>>>();
+
+            iced::widget::column![
+                iced::widget::row![
+                    title,
+                    bucket_button(format!("{} documents", bucket.doc_count), bucket_path.clone()),
+                ]
+                .spacing(10),
+                iced::widget::container(iced::widget::column(nested).spacing(5))
+                    .padding(iced::Padding::default().left(INDENT)),
+            ]
+        },
+        es::AggregationResult::Percentiles(percentiles) => iced::widget::column![title].extend(
+            percentiles.iter().map(|percentile| {
+                iced::widget::row![
+                    cell(small_text(format!("{}%", percentile.percent)), KEY_WIDTH),
+                    cell(small_text(format_number(percentile.value)), VALUE_WIDTH),
+                ]
+                .into()
+            })
+        ),
+        es::AggregationResult::Stats(stats) => iced::widget::column![title].extend(
+            [
+                ("count", Some(stats.count as f64)),
+                ("min", stats.min),
+                ("max", stats.max),
+                ("avg", stats.avg),
+                ("sum", stats.sum),
+            ]
+            .into_iter()
+            .map(|(label, value)| {
+                iced::widget::row![
+                    cell(small_text(label), KEY_WIDTH),
+                    cell(small_text(format_number(value)), VALUE_WIDTH),
+                ]
+                .into()
+            })
+        ),
+        es::AggregationResult::Value(_) => iced::widget::column![
+            iced::widget::row![
+                title,
+                small_text(metric_text(result).unwrap_or_default()),
+            ]
+            .spacing(10)
+        ],
+        es::AggregationResult::Other(value) => iced::widget::column![
+            title,
+            iced_selection::text(serde_json::to_string_pretty(value).unwrap_or_default())
+                .size(12)
+                .font(iced::Font::MONOSPACE),
+        ],
+    }
+    .spacing(5)
+}
+
+pub fn view<'a>(
+    aggregations: &'a es::Aggregations,
+    requested: &'a Requested,
+    presentations: &Presentations,
+) -> iced::Element<'a, Message> {
+    iced::widget::scrollable(
+        iced::widget::column(
+            aggregations.iter().map(|(name, result)| aggregation(name, result, requested, presentations, &Vec::new()).into())
+        )
+        .spacing(15)
+        .padding(iced::Padding::default().right(15))
+    )
+    .height(iced::Fill)
+    .into()
+}
+
+// Filters of each bucket along the path, from the aggregations requested in the body and their results
+fn bucket_filters(
+    requested: &Requested,
+    results: &es::Aggregations,
+    path: &[(String, Option<usize>)],
+) -> Result<Vec<es::Query>, String> {
+    let Some(((name, bucket_idx), rest)) = path.split_first() else {
+        return Ok(Vec::new());
+    };
+
+    let request = requested.get(name)
+        .ok_or_else(|| format!("Aggregation {} is no longer in the search body", name))?;
+
+    let (bucket, filter) = match (results.get(name), bucket_idx) {
+        (Some(es::AggregationResult::Buckets(result)), Some(idx)) => {
+            let bucket = result.buckets.get(*idx).ok_or("Bucket not found")?;
+            (bucket, request.bucket_filter(bucket))
+        },
+        (Some(es::AggregationResult::SingleBucket(bucket)), None) => (bucket, request.bucket_filter(bucket)),
+        _ => return Err(format!("Aggregation {} has no such bucket", name)),
+    };
+
+    let mut filters = bucket_filters(&request.aggs, &bucket.aggregations, rest)?;
+    if let Some(filter) = filter {
+        filters.insert(0, filter);
+    }
+    Ok(filters)
+}
+
+/**
+ * Search body with the filters of the bucket at the path added to its query
+ */
+pub fn drill_down(body: &str, results: &es::Aggregations, path: &BucketPath) -> Result<String, String> {
+    let requested = requested(body)?;
+
+    let mut body = serde_json::from_str::<serde_json::Value>(body)
+        .map_err(|err| format!("Invalid search body: {}", err))?;
+
+    let Some(body_object) = body.as_object_mut() else {
+        return Err("Search body must be a JSON object".to_owned());
+    };
+
+    let filters = bucket_filters(&requested, results, path)?;
+    if filters.is_empty() {
+        return Err("This bucket can not be turned into a filter".to_owned());
+    }
+
+    let filters = filters.into_iter()
+        .map(serde_json::to_value)
+        .collect::<Result<Vec<serde_json::Value>, serde_json::Error>>()
+        .map_err(|err| err.to_string())?;
+
+    super::add_query_filters(body_object, filters);
+
+    serde_json::to_string_pretty(&body).map_err(|err| err.to_string())
+}
@@ -0,0 +1,79 @@
+use crate::util;
+
+pub const DEFAULT_WIDTH: f32 = 200.0;
+pub const MIN_WIDTH: f32 = 60.0;
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Column {
+    // Path of the field within _source, e.g. "user.name"
+    pub field: String,
+    pub width: f32,
+}
+
+impl Column {
+    pub fn new(field: String) -> Self {
+        Self {
+            field,
+            width: DEFAULT_WIDTH,
+        }
+    }
+}
+
+fn columns_file() -> Result<std::path::PathBuf, String> {
+    util::app_data_dir()
+        .map(|dir| dir.join("columns.json"))
+        .ok_or("Unable to determine data directory".to_owned())
+}
+
+/**
+ * Key the columns of a search over the given indicies and aliases are stored under.
+ * No filters searches every index.
+ */
+pub fn index_pattern(filters: &[String]) -> String {
+    if filters.is_empty() {
+        return "*".to_owned();
+    }
+
+    let mut filters = filters.to_vec();
+    filters.sort();
+    filters.join(",")
+}
+
+async fn load_all() -> Result<std::collections::HashMap<String, Vec<Column>>, String> {
+    let path = columns_file()?;
+
+    match tokio::fs::read_to_string(&path).await {
+        Ok(contents) => serde_json::from_str(&contents)
+            .map_err(|err| format!("Unable to parse {}: {}", path.to_string_lossy(), err)),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Default::default()),
+        Err(err) => Err(format!("Unable to read {}, error kind: {}", path.to_string_lossy(), err.kind())),
+    }
+}
+
+// An index pattern without saved columns yields an empty list
+pub async fn load(pattern: String) -> Result<Vec<Column>, String> {
+    Ok(load_all().await?
+        .remove(&pattern)
+        .unwrap_or_default())
+}
+
+pub async fn save(pattern: String, columns: Vec<Column>) -> Result<(), String> {
+    let path = columns_file()?;
+
+    let mut all_columns = load_all().await?;
+    if columns.is_empty() {
+        all_columns.remove(&pattern);
+    } else {
+        all_columns.insert(pattern, columns);
+    }
+
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await
+            .map_err(|err| format!("Unable to create {}, error kind: {}", parent.to_string_lossy(), err.kind()))?;
+    }
+
+    let contents = serde_json::to_string(&all_columns).map_err(|err| err.to_string())?;
+
+    tokio::fs::write(&path, contents).await
+        .map_err(|err| format!("Unable to write {}, error kind: {}", path.to_string_lossy(), err.kind()))
+}
@@ -0,0 +1,532 @@
+// Data stream, index and alias filters of the search sidebar, narrowed down by name and state and with indicies grouped into families
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::util;
+
+use super::{FiltersUpdate, Message};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct IndexState {
+    pub hidden: bool,
+    pub closed: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct Options {
+    // Case insensitive part of the names listed
+    pub search: String,
+    // Comma separated index patterns searched along with the selected indicies and aliases, e.g. logs-*
+    pub pattern: String,
+    pub show_hidden: bool,
+    pub show_closed: bool,
+    // Families listed with their indicies
+    pub expanded: HashSet<String>,
+}
+
+impl Options {
+    pub fn matches(&self, name: &str) -> bool {
+        let search = self.search.trim().to_lowercase();
+        search.is_empty() || name.to_lowercase().contains(&search)
+    }
+
+    pub fn shows_state(&self, state: Option<&IndexState>) -> bool {
+        let state = state.copied().unwrap_or_default();
+        (self.show_hidden || !state.hidden) && (self.show_closed || !state.closed)
+    }
+
+    pub fn shows_index(&self, name: &str, state: Option<&IndexState>) -> bool {
+        self.shows_state(state) && self.matches(name)
+    }
+
+    pub fn patterns(&self) -> Vec<String> {
+        self.pattern.split(',')
+            .map(str::trim)
+            .filter(|pattern| !pattern.is_empty())
+            .map(str::to_owned)
+            .collect()
+    }
+}
+
+// Data streams, indicies and aliases last listed for a cluster, shown until they are refreshed
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct Cache {
+    pub indicies: Vec<(String, IndexState)>,
+    pub aliases: Vec<String>,
+    // Missing from caches written before data streams were listed
+    #[serde(default)]
+    pub data_streams: Vec<(String, IndexState)>,
+}
+
+fn data_dir() -> Result<std::path::PathBuf, String> {
+    util::app_data_dir().ok_or("Unable to determine data directory".to_owned())
+}
+
+fn cache_file(dir: &std::path::Path, cluster: &str) -> std::path::PathBuf {
+    dir.join("filters").join(format!("{}.json", util::cluster_file_name(cluster)))
+}
+
+// None when the cluster's lists were never refreshed
+pub async fn load_cache(cluster: String) -> Result<Option<Cache>, String> {
+    load_cache_in(&data_dir()?, &cluster).await
+}
+
+pub async fn load_cache_in(dir: &std::path::Path, cluster: &str) -> Result<Option<Cache>, String> {
+    let path = cache_file(dir, cluster);
+
+    match tokio::fs::read_to_string(&path).await {
+        Ok(contents) => serde_json::from_str(&contents)
+            .map(Some)
+            .map_err(|err| format!("Unable to parse {}: {}", path.to_string_lossy(), err)),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(format!("Unable to read {}, error kind: {}", path.to_string_lossy(), err.kind())),
+    }
+}
+
+pub async fn save_cache(cluster: String, cache: Cache) -> Result<(), String> {
+    save_cache_in(&data_dir()?, &cluster, &cache).await
+}
+
+pub async fn save_cache_in(dir: &std::path::Path, cluster: &str, cache: &Cache) -> Result<(), String> {
+    let path = cache_file(dir, cluster);
+
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await
+            .map_err(|err| format!("Unable to create {}, error kind: {}", parent.to_string_lossy(), err.kind()))?;
+    }
+
+    let contents = serde_json::to_string(cache).map_err(|err| err.to_string())?;
+
+    tokio::fs::write(&path, contents).await
+        .map_err(|err| format!("Unable to write {}, error kind: {}", path.to_string_lossy(), err.kind()))
+}
+
+/**
+ * Replaces the known names with those listed, keeping the selection of names still listed.
+ * Selected names no longer listed are kept selected and marked missing, the others are dropped.
+ */
+pub fn merge(known: &mut HashMap<String, bool>, listed: impl IntoIterator<Item = String>, missing: &mut HashSet<String>) {
+    let previous = std::mem::take(known);
+
+    for name in listed {
+        missing.remove(&name);
+        let selected = previous.get(&name).copied().unwrap_or_default();
+        known.insert(name, selected);
+    }
+
+    for (name, selected) in previous {
+        if known.contains_key(&name) {
+            continue;
+        }
+
+        if selected {
+            missing.insert(name.clone());
+            known.insert(name, true);
+        } else {
+            missing.remove(&name);
+        }
+    }
+}
+
+// Selected names to search, leaving out the missing ones that would fail the whole search
+pub fn searched<'a>(known: &'a HashMap<String, bool>, missing: &'a HashSet<String>) -> impl Iterator<Item = String> + 'a {
+    known.iter()
+        .filter(|(name, selected)| **selected && !missing.contains(*name))
+        .map(|(name, _)| name.to_owned())
+}
+
+/**
+ * Unselects the names whose state the options no longer show, such as hidden indicies once hidden ones are not shown.
+ * Names only left out by the search stay selected. Returns whether any was unselected.
+ */
+pub fn unselect_not_shown(known: &mut HashMap<String, bool>, states: &HashMap<String, IndexState>, options: &Options) -> bool {
+    let mut unselected = false;
+    for (name, selected) in known.iter_mut() {
+        if *selected && !options.shows_state(states.get(name)) {
+            *selected = false;
+            unselected = true;
+        }
+    }
+    unselected
+}
+
+// Name without a rollover generation suffix such as -000001
+fn strip_generation(name: &str) -> Option<&str> {
+    let (head, generation) = name.rsplit_once('-')?;
+    (generation.len() >= 6 && generation.chars().all(|c| c.is_ascii_digit())).then_some(head)
+}
+
+// Name without a date suffix such as -2025.01.31, -2025-01-31 or -2025.01
+fn strip_date(name: &str) -> Option<&str> {
+    ["dddd.dd.dd", "dddd-dd-dd", "dddd.dd", "dddd-dd"].iter().find_map(|format| {
+        let (head, suffix) = name.split_at_checked(name.len().checked_sub(format.len() + 1)?)?;
+        let date = suffix.strip_prefix('-')?;
+        let matches = date.is_ascii() && date.chars()
+            .zip(format.chars())
+            .all(|(c, f)| if f == 'd' { c.is_ascii_digit() } else { c == f });
+        matches.then_some(head)
+    })
+}
+
+/**
+ * Name shared by the indicies of a rollover family or the backing indicies of a data stream,
+ * e.g. logs for logs-000001, logs-2025.01.31 and .ds-logs-2025.01.31-000001.
+ * None when the name has neither a generation nor a date suffix.
+ */
+pub fn family(name: &str) -> Option<&str> {
+    let base = name.strip_prefix(".ds-").unwrap_or(name);
+    let without_generation = strip_generation(base);
+    let family = strip_date(without_generation.unwrap_or(base)).or(without_generation)?;
+    (!family.is_empty()).then_some(family)
+}
+
+// Row of the data stream, index and alias filter list
+pub enum Row<'a> {
+    Header(&'static str),
+    DataStream {
+        name: &'a str,
+        selected: bool,
+        // Data streams are never closed
+        state: IndexState,
+        missing: bool,
+    },
+    Family {
+        name: &'a str,
+        count: usize,
+        selected: usize,
+        expanded: bool,
+    },
+    Index {
+        name: &'a str,
+        selected: bool,
+        state: IndexState,
+        // Listed under its family
+        nested: bool,
+        // Selected but no longer listed by the cluster
+        missing: bool,
+    },
+    Alias {
+        name: &'a str,
+        selected: bool,
+        missing: bool,
+    },
+}
+
+/**
+ * Data streams followed by indicies grouped by family and aliases, sorted by name.
+ * Families of a single index are listed as that index, all families are expanded while searching.
+ * The data streams group is left out for clusters without any.
+ */
+pub fn rows<'a>(
+    data_streams: &'a HashMap<String, bool>,
+    indicies: &'a HashMap<String, bool>,
+    states: &HashMap<String, IndexState>,
+    aliases: &'a HashMap<String, bool>,
+    missing: &HashSet<String>,
+    options: &'a Options,
+) -> Vec<Row<'a>> {
+    let mut families = BTreeMap::<&str, Vec<(&str, bool)>>::new();
+    for (index, selected) in indicies.iter().filter(|(index, _)| options.shows_index(index, states.get(*index))) {
+        families.entry(family(index).unwrap_or(index))
+            .or_default()
+            .push((index, *selected));
+    }
+
+    let mut aliases = aliases.iter()
+        .filter(|(alias, _)| options.matches(alias))
+        .map(|(alias, selected)| Row::Alias { name: alias, selected: *selected, missing: missing.contains(alias) })
+        .collect::<Vec<Row>>();
+    aliases.sort_by_key(|row| match row {
+        Row::Alias { name, .. } => *name,
+        _ => "",
+    });
+
+    let mut data_streams = data_streams.iter()
+        .filter(|(data_stream, _)| options.shows_index(data_stream, states.get(*data_stream)))
+        .map(|(data_stream, selected)| Row::DataStream {
+            name: data_stream,
+            selected: *selected,
+            state: states.get(data_stream).copied().unwrap_or_default(),
+            missing: missing.contains(data_stream),
+        })
+        .collect::<Vec<Row>>();
+    data_streams.sort_by_key(|row| match row {
+        Row::DataStream { name, .. } => *name,
+        _ => "",
+    });
+
+    let mut rows = Vec::new();
+    if !data_streams.is_empty() {
+        rows.push(Row::Header("Data streams"));
+        rows.extend(data_streams);
+    }
+
+    rows.push(Row::Header("Indicies"));
+    for (name, mut members) in families {
+        members.sort();
+        let index_row = |(index, selected): (&'a str, bool), nested: bool| Row::Index {
+            name: index,
+            selected,
+            state: states.get(index).copied().unwrap_or_default(),
+            nested,
+            missing: missing.contains(index),
+        };
+
+        if members.len() == 1 {
+            rows.push(index_row(members[0], false));
+            continue;
+        }
+
+        let expanded = options.expanded.contains(name) || !options.search.trim().is_empty();
+        rows.push(Row::Family {
+            name,
+            count: members.len(),
+            selected: members.iter().filter(|(_, selected)| *selected).count(),
+            expanded,
+        });
+        if expanded {
+            rows.extend(members.into_iter().map(|member| index_row(member, true)));
+        }
+    }
+
+    rows.push(Row::Header("Aliases"));
+    rows.extend(aliases);
+    rows
+}
+
+fn state_label<'a>(state: IndexState) -> Option<iced::widget::Text<'a>> {
+    let label = match (state.closed, state.hidden) {
+        (true, true) => "closed, hidden",
+        (true, false) => "closed",
+        (false, true) => "hidden",
+        (false, false) => return None,
+    };
+
+    Some(
+        iced::widget::text(label)
+            .size(12)
+            .style(iced::widget::text::secondary)
+    )
+}
+
+fn missing_label<'a>(missing: bool) -> Option<iced::widget::Text<'a>> {
+    missing.then(|| {
+        iced::widget::text("missing")
+            .size(12)
+            .style(iced::widget::text::danger)
+    })
+}
+
+pub fn row_view<'a>(row: &Row<'a>) -> iced::Element<'a, Message> {
+    let element: iced::Element<'a, Message> = match *row {
+        Row::Header(title) => iced::widget::text(title).into(),
+        Row::DataStream { name, selected, state, missing } => iced::widget::row![
+            iced::widget::checkbox(selected)
+                .label(name)
+                .on_toggle(|toggled| {
+                    if toggled {
+                        Message::SelectedFiltersUpdated(FiltersUpdate::AddDataStream(name.to_owned()))
+                    } else {
+                        Message::SelectedFiltersUpdated(FiltersUpdate::RemoveDataStream(name.to_owned()))
+                    }
+                }),
+            state_label(state),
+            missing_label(missing),
+        ]
+        .spacing(5)
+        .align_y(iced::Center)
+        .into(),
+        Row::Family { name, count, selected, expanded } => iced::widget::row![
+            iced::widget::button(iced::widget::text(if expanded { "▾" } else { "▸" }).size(12))
+                .on_press(Message::FilterFamilyToggled(name.to_owned()))
+                .padding([0, 4])
+                .style(iced::widget::button::text),
+            iced::widget::checkbox(selected == count)
+                .label(format!("{} ({})", name, count))
+                .on_toggle(|toggled| Message::SelectedFiltersUpdated(FiltersUpdate::SelectFamily(name.to_owned(), toggled))),
+            (selected > 0 && selected < count).then(|| {
+                iced::widget::text(format!("{} selected", selected))
+                    .size(12)
+                    .style(iced::widget::text::secondary)
+            }),
+        ]
+        .spacing(5)
+        .align_y(iced::Center)
+        .into(),
+        Row::Index { name, selected, state, nested, missing } => iced::widget::row![
+            nested.then(|| iced::widget::space().width(20)),
+            iced::widget::checkbox(selected)
+                .label(name)
+                .on_toggle(|toggled| {
+                    if toggled {
+                        Message::SelectedFiltersUpdated(FiltersUpdate::AddIndex(name.to_owned()))
+                    } else {
+                        Message::SelectedFiltersUpdated(FiltersUpdate::RemoveIndex(name.to_owned()))
+                    }
+                }),
+            state_label(state),
+            missing_label(missing),
+        ]
+        .spacing(5)
+        .align_y(iced::Center)
+        .into(),
+        Row::Alias { name, selected, missing } => iced::widget::row![
+            iced::widget::checkbox(selected)
+                .label(name)
+                .on_toggle(|toggled| {
+                    if toggled {
+                        Message::SelectedFiltersUpdated(FiltersUpdate::AddAlias(name.to_owned()))
+                    } else {
+                        Message::SelectedFiltersUpdated(FiltersUpdate::RemoveAlias(name.to_owned()))
+                    }
+                }),
+            missing_label(missing),
+        ]
+        .spacing(5)
+        .align_y(iced::Center)
+        .into(),
+    };
+
+    iced::widget::container(element)
+        .height(iced::Fill)
+        .align_y(iced::Center)
+        .into()
+}
+
+// Name, state toggles, selection shortcuts and index pattern, shown above the lists
+pub fn controls(options: &Options) -> iced::widget::Column<'_, Message> {
+    iced::widget::column![
+        iced::widget::text_input("Filter by name", &options.search)
+            .on_input(Message::FilterSearchUpdated)
+            .size(14),
+        iced::widget::row![
+            iced::widget::checkbox(options.show_hidden)
+                .label("Hidden")
+                .on_toggle(Message::FilterShowHiddenToggled)
+                .text_size(14),
+            iced::widget::checkbox(options.show_closed)
+                .label("Closed")
+                .on_toggle(Message::FilterShowClosedToggled)
+                .text_size(14),
+            iced::widget::space::horizontal(),
+            iced::widget::button(iced::widget::text("All").size(12))
+                .on_press(Message::SelectedFiltersUpdated(FiltersUpdate::SelectAll(true))),
+            iced::widget::button(iced::widget::text("None").size(12))
+                .on_press(Message::SelectedFiltersUpdated(FiltersUpdate::SelectAll(false))),
+        ]
+        .spacing(5)
+        .align_y(iced::Center),
+        iced::widget::text_input("Index pattern, e.g. logs-*", &options.pattern)
+            .on_input(Message::FilterPatternUpdated)
+            .on_submit(Message::FilterPatternSubmitted)
+            .size(14),
+    ]
+    .spacing(5)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn families_of_rollover_and_dated_indicies() {
+        assert_eq!(family("logs-000001"), Some("logs"));
+        assert_eq!(family("logs-2025.01.31"), Some("logs"));
+        assert_eq!(family("logs-2025-01-31"), Some("logs"));
+        assert_eq!(family("logs-2025.01"), Some("logs"));
+        assert_eq!(family("logs-2025.01.31-000002"), Some("logs"));
+        assert_eq!(family(".ds-logs-app-2025.01.31-000001"), Some("logs-app"));
+    }
+
+    #[test]
+    fn no_family_without_a_suffix() {
+        assert_eq!(family("logs"), None);
+        assert_eq!(family("logs-v2"), None);
+        // Too short for a rollover generation
+        assert_eq!(family("logs-01"), None);
+        assert_eq!(family("-000001"), None);
+        assert_eq!(family("logs-2025.1x.31"), None);
+    }
+
+    fn known(entries: &[(&str, bool)]) -> HashMap<String, bool> {
+        entries.iter().map(|(name, selected)| (name.to_string(), *selected)).collect()
+    }
+
+    #[test]
+    fn merge_keeps_the_selection_of_listed_names() {
+        let mut known = known(&[("a", true), ("b", false)]);
+        let mut missing = HashSet::new();
+
+        merge(&mut known, ["a".to_owned(), "b".to_owned(), "c".to_owned()], &mut missing);
+
+        assert_eq!(known, self::known(&[("a", true), ("b", false), ("c", false)]));
+        assert!(missing.is_empty());
+    }
+
+    #[test]
+    fn merge_keeps_selected_names_no_longer_listed_as_missing() {
+        let mut known = known(&[("a", true), ("b", false)]);
+        let mut missing = HashSet::new();
+
+        merge(&mut known, Vec::new(), &mut missing);
+
+        assert_eq!(known, self::known(&[("a", true)]));
+        assert_eq!(missing, HashSet::from(["a".to_owned()]));
+
+        // Listed again
+        merge(&mut known, ["a".to_owned()], &mut missing);
+
+        assert_eq!(known, self::known(&[("a", true)]));
+        assert!(missing.is_empty());
+    }
+
+    #[test]
+    fn searches_selected_names_that_are_not_missing() {
+        let mut known = known(&[("a", true), ("b", true), ("c", false)]);
+        let mut missing = HashSet::new();
+
+        merge(&mut known, ["b".to_owned(), "c".to_owned()], &mut missing);
+
+        assert_eq!(searched(&known, &missing).collect::<Vec<String>>(), vec!["b"]);
+        assert_eq!(known.get("a"), Some(&true));
+    }
+
+    #[test]
+    fn unselects_names_whose_state_is_not_shown() {
+        let mut known = known(&[("hidden", true), ("closed", true), ("open", true)]);
+        let states = HashMap::from([
+            ("hidden".to_owned(), IndexState { hidden: true, closed: false }),
+            ("closed".to_owned(), IndexState { hidden: false, closed: true }),
+        ]);
+        let options = Options { show_closed: true, search: "nothing matches".to_owned(), ..Default::default() };
+
+        assert!(unselect_not_shown(&mut known, &states, &options));
+        assert_eq!(known, self::known(&[("hidden", false), ("closed", true), ("open", true)]));
+        assert!(!unselect_not_shown(&mut known, &states, &options));
+    }
+
+    #[test]
+    fn cache_saved_for_the_cluster_is_loaded_for_the_restored_connection_at_startup() {
+        let dir = std::env::temp_dir().join(format!("elastic_ermine_startup_{}", std::process::id()));
+        let rt = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+        let cache = Cache {
+            indicies: vec![("logs-000001".to_owned(), IndexState::default())],
+            aliases: vec!["logs".to_owned()],
+            data_streams: vec![],
+        };
+        let connection = crate::settings::LastConnection { url: "https://localhost:9200".to_owned(), ..Default::default() };
+
+        let loaded = rt.block_on(async {
+            crate::settings::save_connection(dir.clone(), connection).await?;
+            save_cache_in(&dir, "https://localhost:9200", &cache).await?;
+            save_cache_in(&dir, "https://other:9200", &Cache::default()).await?;
+
+            let settings = crate::settings::View::restored(crate::logs::Log::default(), &dir);
+            load_cache_in(&dir, &settings.cluster_key()).await
+        });
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let loaded = loaded.unwrap().unwrap();
+        assert_eq!(loaded.indicies, cache.indicies);
+        assert_eq!(loaded.aliases, cache.aliases);
+    }
+}
@@ -0,0 +1,332 @@
+// Searches saved under a name to be run again, stored locally and shared as JSON files
+use crate::{util, widget};
+
+use super::{columns, Message, SearchType};
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct SavedQuery {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    pub search_type: SearchType,
+    // Query string or search payload, depending on the search type
+    pub query: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub data_streams: Vec<String>,
+    #[serde(default)]
+    pub indices: Vec<String>,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    // Index patterns entered along with the selected indicies and aliases
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub pattern: String,
+    #[serde(default)]
+    pub columns: Vec<columns::Column>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl SavedQuery {
+    // Case insensitive match of the search on the name, description or tags
+    pub fn matches(&self, search: &str, tag: Option<&String>) -> bool {
+        if tag.is_some_and(|tag| !self.tags.contains(tag)) {
+            return false;
+        }
+
+        let search = search.trim().to_lowercase();
+        search.is_empty()
+            || self.name.to_lowercase().contains(&search)
+            || self.description.to_lowercase().contains(&search)
+            || self.tags.iter().any(|tag| tag.to_lowercase().contains(&search))
+    }
+}
+
+// Comma separated tags as entered, without blanks or duplicates
+pub fn parse_tags(tags: &str) -> Vec<String> {
+    let mut tags = tags.split(',')
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+        .map(str::to_owned)
+        .collect::<Vec<String>>();
+    tags.sort();
+    tags.dedup();
+    tags
+}
+
+fn saved_queries_file() -> Result<std::path::PathBuf, String> {
+    util::app_data_dir()
+        .map(|dir| dir.join("saved_queries.json"))
+        .ok_or("Unable to determine data directory".to_owned())
+}
+
+async fn read(path: &std::path::Path) -> Result<Vec<SavedQuery>, String> {
+    let contents = tokio::fs::read_to_string(path).await
+        .map_err(|err| format!("Unable to read {}, error kind: {}", path.to_string_lossy(), err.kind()))?;
+
+    serde_json::from_str(&contents)
+        .map_err(|err| format!("Unable to parse {}: {}", path.to_string_lossy(), err))
+}
+
+async fn write(path: &std::path::Path, queries: &[SavedQuery]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await
+            .map_err(|err| format!("Unable to create {}, error kind: {}", parent.to_string_lossy(), err.kind()))?;
+    }
+
+    // Pretty printed so that shared files diff well
+    let contents = serde_json::to_string_pretty(queries).map_err(|err| err.to_string())?;
+
+    tokio::fs::write(path, contents).await
+        .map_err(|err| format!("Unable to write {}, error kind: {}", path.to_string_lossy(), err.kind()))
+}
+
+// Case insensitive order of the names
+fn sort_by_name(queries: &mut [SavedQuery]) {
+    queries.sort_by_key(|query| query.name.to_lowercase());
+}
+
+/**
+ * Saved queries ordered by name.
+ * Nothing saved yet yields an empty list.
+ */
+pub async fn load() -> Result<Vec<SavedQuery>, String> {
+    let path = saved_queries_file()?;
+
+    if matches!(tokio::fs::try_exists(&path).await, Ok(false)) {
+        return Ok(Vec::new());
+    }
+
+    let mut queries = read(&path).await?;
+    sort_by_name(&mut queries);
+    Ok(queries)
+}
+
+// Queries of the same name are replaced. Returns all saved queries.
+async fn merge(new_queries: Vec<SavedQuery>) -> Result<Vec<SavedQuery>, String> {
+    let mut queries = load().await?;
+    queries.retain(|query| !new_queries.iter().any(|new_query| new_query.name == query.name));
+    queries.extend(new_queries);
+    sort_by_name(&mut queries);
+
+    write(&saved_queries_file()?, &queries).await?;
+    Ok(queries)
+}
+
+/**
+ * Saves the query, replacing any of the same name.
+ * Changes the queries stored on disk rather than those held in memory, as history does.
+ */
+pub async fn save(query: SavedQuery) -> Result<Vec<SavedQuery>, String> {
+    merge(vec![query]).await
+}
+
+pub async fn delete(name: String) -> Result<Vec<SavedQuery>, String> {
+    let mut queries = load().await?;
+    queries.retain(|query| query.name != name);
+
+    write(&saved_queries_file()?, &queries).await?;
+    Ok(queries)
+}
+
+/**
+ * Adds the queries of an exported file, those named as a saved query replace it.
+ * Returns how many were imported along with all saved queries.
+ */
+pub async fn import(path: std::path::PathBuf) -> Result<(usize, Vec<SavedQuery>), String> {
+    let imported = read(&path).await?;
+    let count = imported.len();
+    Ok((count, merge(imported).await?))
+}
+
+pub async fn export(path: std::path::PathBuf, queries: Vec<SavedQuery>) -> Result<usize, String> {
+    write(&path, &queries).await?;
+    Ok(queries.len())
+}
+
+// State of the saved queries panel
+#[derive(Debug, Default)]
+pub struct Library {
+    pub open: bool,
+    // None until loaded from disk, the first time the panel is opened
+    pub queries: Option<Vec<SavedQuery>>,
+    pub error: Option<String>,
+    pub search: String,
+    pub tag: Option<String>,
+    // Entered to save the current search, filled from the last query loaded
+    pub name: String,
+    pub description: String,
+    pub tags: String,
+}
+
+impl Library {
+    fn all_tags(&self) -> Vec<String> {
+        let mut tags = self.queries.iter()
+            .flatten()
+            .flat_map(|query| query.tags.iter().cloned())
+            .collect::<Vec<String>>();
+        tags.sort();
+        tags.dedup();
+        tags
+    }
+
+    pub fn view(&self) -> iced::widget::Container<'_, Message> {
+        let queries = self.queries.iter()
+            .flatten()
+            .enumerate()
+            .filter(|(_, query)| query.matches(&self.search, self.tag.as_ref()))
+            .map(|(idx, query)| Self::item(query, idx).into());
+
+        widget::section_with_header(
+            iced::widget::row![
+                iced::widget::text("Saved queries"),
+                iced::widget::space::horizontal(),
+                iced::widget::button(iced::widget::text("Import").size(12))
+                    .on_press(Message::SavedQueriesImportPressed),
+                iced::widget::button(iced::widget::text("Export").size(12))
+                    .on_press_maybe(
+                        self.queries.as_ref().is_some_and(|queries| !queries.is_empty())
+                            .then_some(Message::SavedQueriesExportPressed)
+                    ),
+            ]
+            .spacing(5)
+            .align_y(iced::Center),
+            iced::widget::column![
+                self.save_form(),
+                iced::widget::rule::horizontal(1),
+                iced::widget::row![
+                    iced::widget::text_input("Search saved queries", &self.search)
+                        .on_input(Message::SavedQuerySearchUpdated),
+                    iced::widget::pick_list(self.all_tags(), self.tag.clone(), |tag| Message::SavedQueryTagSelected(Some(tag)))
+                        .placeholder("All tags"),
+                    self.tag.is_some().then(|| {
+                        iced::widget::button(iced::widget::text("Clear").size(12))
+                            .on_press(Message::SavedQueryTagSelected(None))
+                    }),
+                ]
+                .spacing(5)
+                .align_y(iced::Center),
+                self.error.as_ref()
+                    .map(iced::widget::text),
+                iced::widget::scrollable(
+                    iced::widget::column(queries)
+                        .spacing(5)
+                )
+                .width(iced::Fill)
+                .height(iced::Fill),
+            ]
+            .spacing(10)
+        )
+    }
+
+    // Saves the current search under the name entered
+    fn save_form(&self) -> iced::widget::Column<'_, Message> {
+        iced::widget::column![
+            iced::widget::text_input("Name", &self.name)
+                .on_input(Message::SavedQueryNameUpdated),
+            iced::widget::text_input("Description", &self.description)
+                .on_input(Message::SavedQueryDescriptionUpdated),
+            iced::widget::row![
+                iced::widget::text_input("Tags, comma separated", &self.tags)
+                    .on_input(Message::SavedQueryTagsUpdated)
+                    .on_submit(Message::SavedQuerySavePressed),
+                iced::widget::button("Save")
+                    .on_press_maybe((!self.name.trim().is_empty()).then_some(Message::SavedQuerySavePressed)),
+            ]
+            .spacing(5),
+        ]
+        .spacing(5)
+    }
+
+    fn item(query: &SavedQuery, idx: usize) -> iced::widget::Container<'_, Message> {
+        let search_type = match query.search_type {
+            SearchType::StringSearch => "Query string",
+            SearchType::GenericSearch => "Search payload",
+        };
+        let filters = query.data_streams.iter()
+            .chain(query.indices.iter())
+            .chain(query.aliases.iter())
+            .map(String::as_str)
+            .chain((!query.pattern.is_empty()).then_some(query.pattern.as_str()))
+            .collect::<Vec<&str>>();
+        let filters = if filters.is_empty() { "all indicies".to_owned() } else { filters.join(", ") };
+
+        iced::widget::container(
+            iced::widget::column![
+                iced::widget::text(&query.name),
+                (!query.description.is_empty()).then(|| iced::widget::text(&query.description).size(12)),
+                iced::widget::text(format!("{} | {}", search_type, filters)).size(12),
+                (!query.tags.is_empty()).then(|| {
+                    iced::widget::text(query.tags.iter().map(|tag| format!("#{}", tag)).collect::<Vec<String>>().join(" "))
+                        .size(12)
+                        .style(iced::widget::text::primary)
+                }),
+                iced::widget::row![
+                    iced::widget::button(iced::widget::text("Load").size(12))
+                        .on_press(Message::SavedQueryLoadPressed(idx)),
+                    iced::widget::button(iced::widget::text("Delete").size(12))
+                        .on_press(Message::SavedQueryDeletePressed(idx))
+                        .style(iced::widget::button::danger),
+                ]
+                .spacing(5),
+            ]
+            .spacing(5)
+        )
+        .width(iced::Fill)
+        .padding(5)
+        .style(iced::widget::container::bordered_box)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(name: &str, description: &str, tags: &[&str]) -> SavedQuery {
+        SavedQuery {
+            name: name.to_owned(),
+            description: description.to_owned(),
+            search_type: SearchType::StringSearch,
+            query: "*".to_owned(),
+            data_streams: Vec::new(),
+            indices: Vec::new(),
+            aliases: Vec::new(),
+            pattern: String::new(),
+            columns: Vec::new(),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn parses_tags_without_blanks_or_duplicates() {
+        assert_eq!(parse_tags(" prod, errors,,prod , "), vec!["errors", "prod"]);
+        assert!(parse_tags(" , ").is_empty());
+    }
+
+    #[test]
+    fn matches_the_name_description_or_tags_ignoring_case() {
+        let query = query("Checkout errors", "Failed payments", &["prod"]);
+
+        assert!(query.matches("", None));
+        assert!(query.matches("  checkout ", None));
+        assert!(query.matches("PAYMENTS", None));
+        assert!(query.matches("pro", None));
+        assert!(!query.matches("staging", None));
+    }
+
+    #[test]
+    fn matches_only_queries_with_the_tag() {
+        let query = query("Checkout errors", "", &["prod"]);
+
+        assert!(query.matches("checkout", Some(&"prod".to_owned())));
+        assert!(!query.matches("checkout", Some(&"staging".to_owned())));
+    }
+
+    #[test]
+    fn sorts_names_ignoring_case() {
+        let mut queries = vec![query("beta", "", &[]), query("Gamma", "", &[]), query("Alpha", "", &[])];
+
+        sort_by_name(&mut queries);
+
+        assert_eq!(queries.iter().map(|query| query.name.as_str()).collect::<Vec<&str>>(), vec!["Alpha", "beta", "Gamma"]);
+    }
+}
@@ -0,0 +1,212 @@
+// Discover style table of hits, one column per selected _source field
+use std::collections::BTreeSet;
+
+use elastic_ermine::{es, export};
+
+use super::columns::{self, Column};
+use super::Message;
+
+pub const ROW_HEIGHT: f32 = 36.0;
+// Width of the expand button column at the start of each row
+pub const EXPAND_WIDTH: f32 = 40.0;
+const RESIZE_HANDLE_WIDTH: f32 = 6.0;
+// Longer cells are cut short, the full value is in the expanded hit
+const MAX_CELL_CHARS: usize = 300;
+
+pub fn cell_text(value: Option<&serde_json::Value>) -> String {
+    let text = match value {
+        None | Some(serde_json::Value::Null) => "-".to_owned(),
+        Some(serde_json::Value::String(string)) => string.to_owned(),
+        Some(serde_json::Value::Array(values)) => values.iter()
+            .map(|value| cell_text(Some(value)))
+            .collect::<Vec<String>>()
+            .join(", "),
+        Some(other) => other.to_string(),
+    };
+
+    if text.chars().count() > MAX_CELL_CHARS {
+        text.chars().take(MAX_CELL_CHARS).collect::<String>() + "…"
+    } else {
+        text
+    }
+}
+
+// Field paths that can be shown as columns: those found in the _source of the hits along with the mapped fields
+pub fn available_fields(hits: &[serde_json::Value], mappings: &[es::FieldMapping]) -> Vec<String> {
+    let mut paths = BTreeSet::new();
+
+    for hit in hits {
+        if let Some(source) = hit.get("_source") {
+            paths.extend(export::leaf_paths(source));
+        }
+    }
+
+    paths.extend(export::source_fields(mappings));
+    paths.into_iter().collect()
+}
+
+// One line summary of a hit, used when no columns are selected
+pub fn document_summary(hit: &serde_json::Value) -> String {
+    let source = hit.get("_source").unwrap_or(&serde_json::Value::Null);
+    let paths = export::leaf_paths(source);
+
+    let id = hit.get("_id").and_then(serde_json::Value::as_str).unwrap_or("-");
+
+    let fields = paths.iter()
+        .map(|path| format!("{}: {}", path, cell_text(export::field_value(source, path).as_ref())))
+        .collect::<Vec<String>>()
+        .join("  ");
+
+    cell_text(Some(&serde_json::Value::from(format!("_id: {}  {}", id, fields))))
+}
+
+// Column index being resized given the x position of the cursor within the header
+pub fn resized_width(columns: &[Column], idx: usize, cursor_x: f32) -> f32 {
+    let start = EXPAND_WIDTH + columns[..idx].iter().map(|column| column.width).sum::<f32>();
+    (cursor_x - start).max(columns::MIN_WIDTH)
+}
+
+fn cell<'a>(content: impl Into<iced::Element<'a, Message>>, width: f32) -> iced::widget::Container<'a, Message> {
+    iced::widget::container(content)
+        .width(width)
+        .height(iced::Fill)
+        .padding([0, 5])
+        .align_y(iced::Center)
+        .clip(true)
+}
+
+pub fn header<'a>(
+    columns: &'a [Column],
+    sort: Option<&'a (String, es::SortOrder)>,
+    resizing: Option<usize>,
+) -> iced::Element<'a, Message> {
+    let header_cells = columns.iter().enumerate().map(|(idx, column)| {
+        let indicator = match sort {
+            Some((field, es::SortOrder::Asc)) if *field == column.field => " ▲",
+            Some((field, es::SortOrder::Desc)) if *field == column.field => " ▼",
+            _ => "",
+        };
+
+        iced::widget::row![
+            iced::widget::button(
+                iced::widget::text(format!("{}{}", column.field, indicator))
+                    .size(14)
+                    .wrapping(iced::widget::text::Wrapping::None)
+            )
+            .on_press(Message::ColumnSortPressed(column.field.clone()))
+            .padding([0, 5])
+            .style(iced::widget::button::text)
+            .width(column.width - RESIZE_HANDLE_WIDTH)
+            .clip(true),
+            iced::widget::mouse_area(
+                iced::widget::container(iced::widget::rule::vertical(1))
+                    .width(RESIZE_HANDLE_WIDTH)
+                    .height(iced::Fill)
+                    .align_x(iced::Center)
+            )
+            .on_press(Message::ColumnResizeStarted(idx))
+            .interaction(iced::mouse::Interaction::ResizingHorizontally),
+        ]
+        .width(column.width)
+        .height(iced::Fill)
+        .align_y(iced::Center)
+        .into()
+    });
+
+    let header = iced::widget::row![
+        iced::widget::space::horizontal().width(EXPAND_WIDTH),
+        (columns.is_empty()).then(|| cell(iced::widget::text("Document").size(14), 0.0).width(iced::Fill)),
+    ]
+    .extend(header_cells)
+    .height(ROW_HEIGHT);
+
+    let header = iced::widget::mouse_area(header);
+
+    // Only follows the cursor while a column is being resized
+    match resizing {
+        Some(_) => header
+            .on_move(|point| Message::ColumnResizeMoved(point.x))
+            .on_release(Message::ColumnResizeEnded)
+            .on_exit(Message::ColumnResizeEnded)
+            .into(),
+        None => header.into(),
+    }
+}
+
+pub fn row<'a>(
+    hit: &'a serde_json::Value,
+    columns: &'a [Column],
+    expand_button: iced::widget::Button<'a, Message>,
+) -> iced::widget::Row<'a, Message> {
+    let source = hit.get("_source").unwrap_or(&serde_json::Value::Null);
+
+    let cells = columns.iter().map(|column| {
+        cell(
+            iced_selection::text(cell_text(export::field_value(source, &column.field).as_ref()))
+                .size(14)
+                .wrapping(iced::widget::text::Wrapping::None),
+            column.width
+        )
+        .into()
+    });
+
+    iced::widget::row![
+        iced::widget::container(expand_button)
+            .width(EXPAND_WIDTH)
+            .height(iced::Fill)
+            .align_y(iced::Center),
+        (columns.is_empty()).then(|| {
+            cell(
+                iced::widget::text(document_summary(hit))
+                    .size(14)
+                    .wrapping(iced::widget::text::Wrapping::None),
+                0.0
+            )
+            .width(iced::Fill)
+        }),
+    ]
+    .extend(cells)
+    .height(ROW_HEIGHT)
+}
+
+// Selected columns with controls to reorder and remove them, followed by a picker to add one
+pub fn column_picker<'a>(columns: &'a [Column], available_fields: Vec<String>) -> iced::Element<'a, Message> {
+    let last = columns.len().saturating_sub(1);
+
+    let chips = columns.iter().enumerate().map(|(idx, column)| {
+        let small_button = |label: &'a str, message: Option<Message>| {
+            iced::widget::button(iced::widget::text(label).size(12))
+                .on_press_maybe(message)
+                .padding([0, 4])
+                .style(iced::widget::button::text)
+        };
+
+        iced::widget::container(
+            iced::widget::row![
+                small_button("◀", (idx > 0).then_some(Message::ColumnMoved(idx, idx - 1))),
+                iced::widget::text(&column.field).size(12),
+                small_button("▶", (idx < last).then_some(Message::ColumnMoved(idx, idx + 1))),
+                small_button("×", Some(Message::ColumnRemoved(idx))),
+            ]
+            .align_y(iced::Center)
+        )
+        .padding([2, 4])
+        .style(iced::widget::container::bordered_box)
+        .into()
+    });
+
+    let unselected = available_fields.into_iter()
+        .filter(|field| !columns.iter().any(|column| column.field == *field))
+        .collect::<Vec<String>>();
+
+    iced::widget::row(chips)
+        .push(
+            iced::widget::pick_list(unselected, None::<String>, Message::ColumnAdded)
+                .placeholder("Add column")
+                .text_size(12)
+        )
+        .spacing(5)
+        .align_y(iced::Center)
+        .wrap()
+        .into()
+}
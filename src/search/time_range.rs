@@ -0,0 +1,305 @@
+// Time range of searches over a date field, and the histogram of document counts over it
+use elastic_ermine::es;
+
+use super::Message;
+use crate::widget::chart;
+
+// Name the histogram aggregation is added to searches under, taken out of the results before they are shown
+pub const HISTOGRAM_NAME: &str = "ermine_time_histogram";
+// Buckets asked of the auto date histogram
+const HISTOGRAM_BUCKETS: u64 = 60;
+const HISTOGRAM_HEIGHT: f32 = 140.0;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Preset {
+    #[default]
+    AnyTime,
+    Last15Minutes,
+    LastHour,
+    Last4Hours,
+    Last24Hours,
+    Last7Days,
+    Last30Days,
+    Last90Days,
+    LastYear,
+    // From and to as entered
+    Absolute,
+}
+
+impl Preset {
+    pub const ALL: [Preset; 10] = [
+        Preset::AnyTime,
+        Preset::Last15Minutes,
+        Preset::LastHour,
+        Preset::Last4Hours,
+        Preset::Last24Hours,
+        Preset::Last7Days,
+        Preset::Last30Days,
+        Preset::Last90Days,
+        Preset::LastYear,
+        Preset::Absolute,
+    ];
+
+    // Start of relative ranges in date math, see https://www.elastic.co/docs/reference/elasticsearch/rest-apis/common-options#date-math
+    fn start(&self) -> Option<&'static str> {
+        match self {
+            Preset::Last15Minutes => Some("now-15m"),
+            Preset::LastHour => Some("now-1h"),
+            Preset::Last4Hours => Some("now-4h"),
+            Preset::Last24Hours => Some("now-24h"),
+            Preset::Last7Days => Some("now-7d"),
+            Preset::Last30Days => Some("now-30d"),
+            Preset::Last90Days => Some("now-90d"),
+            Preset::LastYear => Some("now-1y"),
+            Preset::AnyTime | Preset::Absolute => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Preset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Preset::AnyTime => "Any time",
+            Preset::Last15Minutes => "Last 15 minutes",
+            Preset::LastHour => "Last hour",
+            Preset::Last4Hours => "Last 4 hours",
+            Preset::Last24Hours => "Last 24 hours",
+            Preset::Last7Days => "Last 7 days",
+            Preset::Last30Days => "Last 30 days",
+            Preset::Last90Days => "Last 90 days",
+            Preset::LastYear => "Last year",
+            Preset::Absolute => "Absolute",
+        })
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct TimeRange {
+    pub preset: Preset,
+    // Bounds of absolute ranges as dates or date math, an empty bound leaves that end open
+    pub from: String,
+    pub to: String,
+}
+
+impl TimeRange {
+    /**
+     * Absolute range between two instants in epoch millis, written as local times
+     */
+    pub fn between(from_millis: i64, to_millis: i64) -> Self {
+        let format = |millis: i64| {
+            chrono::DateTime::from_timestamp_millis(millis)
+                .map(|time| time.with_timezone(&chrono::Local).to_rfc3339_opts(chrono::SecondsFormat::Millis, false))
+                .unwrap_or_else(|| millis.to_string())
+        };
+
+        Self {
+            preset: Preset::Absolute,
+            from: format(from_millis),
+            to: format(to_millis),
+        }
+    }
+
+    /**
+     * Range query on the field, None when the range is not limited.
+     * Absolute dates without an offset are taken as local time.
+     */
+    pub fn filter(&self, field: &str) -> Option<es::Query> {
+        let params = match self.preset {
+            Preset::AnyTime => return None,
+            Preset::Absolute => {
+                let (from, to) = (self.from.trim(), self.to.trim());
+                if from.is_empty() && to.is_empty() {
+                    return None;
+                }
+
+                let mut params = es::RangeParams::default().time_zone(local_time_zone());
+                if !from.is_empty() {
+                    params = params.gte(from);
+                }
+                if !to.is_empty() {
+                    params = params.lte(to);
+                }
+                params
+            },
+            preset => es::RangeParams::default().gte(preset.start()?).lte("now"),
+        };
+
+        Some(es::Query::range(field, params))
+    }
+}
+
+/**
+ * IANA name of the local time zone, e.g. Europe/Paris, for dates on either side of a daylight saving change
+ * to be taken at their own offset. The current offset when the name is unknown.
+ */
+fn local_time_zone() -> String {
+    iana_time_zone::get_timezone().unwrap_or_else(|_| chrono::Local::now().format("%:z").to_string())
+}
+
+// Fields mapped as dates, those a time range can be applied to
+pub fn date_fields(mappings: &[es::FieldMapping]) -> Vec<String> {
+    let mut fields = mappings.iter()
+        .filter(|mapping| mapping.field_type == "date" || mapping.field_type == "date_nanos")
+        .map(|mapping| mapping.name.to_owned())
+        .collect::<Vec<String>>();
+    fields.sort();
+    fields.dedup();
+    fields
+}
+
+// @timestamp when there is one, as used by data streams and most log shippers
+pub fn default_field(date_fields: &[String]) -> Option<String> {
+    date_fields.iter()
+        .find(|field| *field == "@timestamp")
+        .or_else(|| date_fields.first())
+        .cloned()
+}
+
+/**
+ * Document counts over the field, with the interval chosen by Elasticsearch to fit the range searched.
+ * See https://www.elastic.co/docs/reference/aggregations/search-aggregations-bucket-autodatehistogram-aggregation
+ */
+pub fn histogram(field: &str) -> es::Aggregation {
+    let mut kind = serde_json::Map::new();
+    kind.insert(
+        "auto_date_histogram".to_owned(),
+        serde_json::json!({ "field": field, "buckets": HISTOGRAM_BUCKETS })
+    );
+    es::Aggregation::new(es::AggregationKind::Other(kind))
+}
+
+/**
+ * End of the bucket starting at the epoch millis, going by the interval of an auto date histogram such as "12h" or "3M".
+ * Buckets are in UTC as no time zone is given to the aggregation.
+ */
+fn interval_end(start: i64, interval: &str) -> Option<i64> {
+    let unit_start = interval.find(|c: char| !c.is_ascii_digit())?;
+    let count = interval[..unit_start].parse::<u32>().ok()?;
+
+    let unit_millis = match &interval[unit_start..] {
+        "s" => 1_000,
+        "m" => 60_000,
+        "h" => 3_600_000,
+        "d" => 86_400_000,
+        unit @ ("M" | "y") => {
+            let months = if unit == "y" { count.checked_mul(12)? } else { count };
+            return chrono::DateTime::from_timestamp_millis(start)?
+                .checked_add_months(chrono::Months::new(months))
+                .map(|end| end.timestamp_millis());
+        },
+        _ => return None,
+    };
+    Some(start + i64::from(count) * unit_millis)
+}
+
+/**
+ * Instants in epoch millis from the start of the first bucket to the end of the last one.
+ * Without an interval, a bucket ends where the next one starts and the last one is taken to be as long as the one before it.
+ */
+pub fn buckets_range(histogram: &es::BucketsResult, first: usize, last: usize) -> Option<(i64, i64)> {
+    let start = |idx: usize| histogram.buckets.get(idx).and_then(|bucket| bucket.key.as_i64());
+
+    let from = start(first)?;
+    let last_start = start(last)?;
+    let to = match histogram.interval.as_deref().and_then(|interval| interval_end(last_start, interval)) {
+        Some(end) => end,
+        None => start(last + 1)
+            .or_else(|| last.checked_sub(1).and_then(start).map(|previous| 2 * last_start - previous))?,
+    };
+    Some((from, to - 1))
+}
+
+// Time field, preset and the bounds of absolute ranges
+pub fn picker<'a>(date_fields: &'a [String], field: Option<&'a String>, range: &'a TimeRange) -> iced::Element<'a, Message> {
+    iced::widget::row![
+        iced::widget::text("Time").size(14),
+        iced::widget::pick_list(date_fields, field, Message::TimeFieldSelected)
+            .placeholder("Date field")
+            .text_size(14),
+        iced::widget::pick_list(Preset::ALL, Some(range.preset), Message::TimePresetSelected)
+            .text_size(14),
+        (range.preset == Preset::Absolute).then(|| {
+            iced::widget::row![
+                iced::widget::text_input("From, e.g. 2025-01-31T08:00 or now-2d", &range.from)
+                    .on_input(Message::TimeFromUpdated)
+                    .on_submit(Message::SearchPressed)
+                    .size(14)
+                    .width(240),
+                iced::widget::text("to").size(14),
+                iced::widget::text_input("To, open when empty", &range.to)
+                    .on_input(Message::TimeToUpdated)
+                    .on_submit(Message::SearchPressed)
+                    .size(14)
+                    .width(240),
+            ]
+            .spacing(5)
+            .align_y(iced::Center)
+        }),
+    ]
+    .spacing(10)
+    .align_y(iced::Center)
+    .into()
+}
+
+// Bar chart of the document counts, brushing over buckets zooms into them
+pub fn histogram_view(histogram: &es::BucketsResult) -> iced::Element<'_, Message> {
+    let entries = histogram.buckets.iter()
+        .map(|bucket| {
+            let label = bucket.key.as_i64()
+                .and_then(chrono::DateTime::from_timestamp_millis)
+                .map(|time| time.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_else(|| bucket.label());
+            chart::Entry::new(label, bucket.doc_count as f64)
+        })
+        .collect();
+
+    chart::Chart::new(chart::Kind::Bar, entries)
+        .on_brush(Message::HistogramBrushed)
+        .height(HISTOGRAM_HEIGHT)
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn histogram(keys: &[i64], interval: Option<&str>) -> es::BucketsResult {
+        let buckets = keys.iter().map(|key| serde_json::json!({ "key": key, "doc_count": 1 })).collect::<Vec<serde_json::Value>>();
+        match es::AggregationResult::from(serde_json::json!({ "buckets": buckets, "interval": interval })) {
+            es::AggregationResult::Buckets(result) => result,
+            other => panic!("expected buckets, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn ends_buckets_after_the_interval() {
+        let histogram = histogram(&[0, 1_800_000, 3_600_000], Some("30m"));
+
+        assert_eq!(buckets_range(&histogram, 0, 1), Some((0, 3_599_999)));
+        assert_eq!(buckets_range(&histogram, 2, 2), Some((3_600_000, 5_399_999)));
+    }
+
+    #[test]
+    fn ends_a_single_bucket_after_the_interval() {
+        let histogram = histogram(&[1_000], Some("1h"));
+
+        assert_eq!(buckets_range(&histogram, 0, 0), Some((1_000, 3_600_999)));
+    }
+
+    #[test]
+    fn ends_month_buckets_on_the_next_month() {
+        // 2024-02-01 to 2024-03-01, February having 29 days that year
+        let histogram = histogram(&[1_706_745_600_000], Some("1M"));
+
+        assert_eq!(buckets_range(&histogram, 0, 0), Some((1_706_745_600_000, 1_709_251_199_999)));
+    }
+
+    #[test]
+    fn falls_back_to_the_bucket_starts() {
+        let histogram = histogram(&[0, 10, 20], None);
+
+        assert_eq!(buckets_range(&histogram, 0, 1), Some((0, 19)));
+        assert_eq!(buckets_range(&histogram, 2, 2), Some((20, 29)));
+        assert_eq!(buckets_range(&histogram, 3, 3), None);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{assets, widget, es, util};
+use crate::{assets, logs, widget, es, util};
 use iced::widget::{column, row};
 
 #[derive(Debug, Clone)]
@@ -14,15 +14,19 @@ pub enum Message {
     CertObtained(Result<Option<(std::path::PathBuf, reqwest::Certificate)>, String>), // might need to rc certificate to avoid clone
     TestConnectionButtonPressed,
     TestConnectionButtonResultReturned(Result<(), String>),
+    ConnectionSaved(Result<(), String>),
 }
 
 pub enum Action {
     Run(iced::Task<Message>),
+    // Testing the connection succeeded
+    Connected,
     None,
 }
 
 #[derive(Debug)]
 pub struct View {
+    log: logs::Log,
     es_url: String,
     auth_choice_type: Option<AuthChoice>, // TODO remove Option and place the option in the radio button widget
     basic_auth_data: es::BasicAuth,
@@ -36,6 +40,7 @@ pub struct View {
 impl Default for View {
     fn default() -> Self {
         Self { 
+            log: Default::default(),
             es_url: Default::default(), 
             auth_choice_type: Some(Default::default()),
             basic_auth_data: Default::default(),
@@ -51,7 +56,7 @@ impl Default for View {
     }
 }
 
-#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum AuthChoice {
     Basic,
     AWSSigV4,
@@ -66,7 +71,118 @@ enum TestConnectionButtonState {
     Waiting,
 }
 
+/**
+ * Connection last tested successfully, stored as connection.json in the application data directory and
+ * restored on startup so that the data persisted for its cluster is found. The password is not stored.
+ */
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct LastConnection {
+    pub url: String,
+    pub auth: AuthChoice,
+    pub username: String,
+    pub aws_region: Option<String>,
+    pub aws_profile: Option<String>,
+    pub cacert: Option<std::path::PathBuf>,
+}
+
+fn connection_file(dir: &std::path::Path) -> std::path::PathBuf {
+    dir.join("connection.json")
+}
+
+// Read on startup, before any task runs. None when no connection succeeded yet
+fn load_connection(dir: &std::path::Path) -> Result<Option<LastConnection>, String> {
+    let path = connection_file(dir);
+
+    match std::fs::read_to_string(&path) {
+        Ok(contents) => serde_json::from_str(&contents)
+            .map(Some)
+            .map_err(|err| format!("Unable to parse {}: {}", path.to_string_lossy(), err)),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(format!("Unable to read {}, error kind: {}", path.to_string_lossy(), err.kind())),
+    }
+}
+
+pub async fn save_connection(dir: std::path::PathBuf, connection: LastConnection) -> Result<(), String> {
+    tokio::fs::create_dir_all(&dir).await
+        .map_err(|err| format!("Unable to create {}, error kind: {}", dir.to_string_lossy(), err.kind()))?;
+
+    let path = connection_file(&dir);
+    let contents = serde_json::to_string_pretty(&connection).map_err(|err| err.to_string())?;
+
+    tokio::fs::write(&path, contents).await
+        .map_err(|err| format!("Unable to write {}, error kind: {}", path.to_string_lossy(), err.kind()))
+}
+
+fn read_certificate(path: &std::path::Path, contents: &[u8]) -> Result<reqwest::Certificate, String> {
+    if util::path_has_extension(path, "pem") {
+        reqwest::Certificate::from_pem(contents)
+            .map_err(|err| format!("Unable to interpret {} as pem, error kind: {}", path.to_string_lossy(), err))
+    } else if util::path_has_extension(path, "der") {
+        reqwest::Certificate::from_der(contents)
+            .map_err(|err| format!("Unable to interpret {} as der, error kind: {}", path.to_string_lossy(), err))
+    } else {
+        Err(format!("{} is unsupported file type", path.to_string_lossy()))
+    }
+}
+
 impl View {
+    // Restores the last connection
+    pub fn new(log: logs::Log) -> Self {
+        match util::app_data_dir() {
+            Some(dir) => Self::restored(log, &dir),
+            None => Self { log, ..Default::default() },
+        }
+    }
+
+    // Restores the last connection saved in the directory
+    pub fn restored(log: logs::Log, dir: &std::path::Path) -> Self {
+        let mut view = Self { log, ..Default::default() };
+
+        match load_connection(dir) {
+            Ok(Some(connection)) => view.restore(connection),
+            Ok(None) => (),
+            Err(err) => view.log.error(format!("Failed to restore the last connection: {}", err)),
+        }
+        view
+    }
+
+    fn restore(&mut self, connection: LastConnection) {
+        self.es_url = connection.url;
+        self.auth_choice_type = Some(connection.auth);
+        self.basic_auth_data.username = connection.username;
+        if let Some(region) = connection.aws_region {
+            self.aws_sigv4_data.region = region;
+        }
+        self.aws_sigv4_data.profile = connection.aws_profile;
+        self.selected_cert = match connection.cacert {
+            Some(path) => std::fs::read(&path)
+                .map_err(|err| format!("Unable to read {}, error kind: {}", path.to_string_lossy(), err.kind()))
+                .and_then(|contents| read_certificate(&path, &contents))
+                .map(|certificate| Some((path, certificate))),
+            None => Ok(None),
+        };
+    }
+
+    pub fn last_connection(&self) -> LastConnection {
+        LastConnection {
+            url: self.es_url.clone(),
+            auth: self.auth_choice_type.unwrap_or_default(),
+            username: self.basic_auth_data.username.clone(),
+            aws_region: Some(self.aws_sigv4_data.region.clone()),
+            aws_profile: self.aws_sigv4_data.profile.clone(),
+            cacert: self.selected_cert.as_ref().ok().and_then(|cert| cert.as_ref()).map(|(path, _)| path.clone()),
+        }
+    }
+
+    // Saves the connection to be restored on the next startup
+    pub fn save_connection(&self) -> iced::Task<Message> {
+        let Some(dir) = util::app_data_dir() else {
+            return iced::Task::done(Message::ConnectionSaved(Err("Unable to determine data directory".to_owned())));
+        };
+        iced::Task::perform(save_connection(dir, self.last_connection()), Message::ConnectionSaved)
+    }
+
     #[must_use]
     pub fn update(&mut self, message: Message) -> Action {
         match message {
@@ -134,7 +250,23 @@ impl View {
             },
             Message::TestConnectionButtonResultReturned(res) => {
                 self.test_connection_button_state = TestConnectionButtonState::Ready;
+                let action = match &res {
+                    Ok(()) => {
+                        self.log.info(format!("Connected to {}", self.es_url));
+                        Action::Connected
+                    },
+                    Err(err) => {
+                        self.log.error(format!("Failed to connect to {}: {}", self.es_url, err));
+                        Action::None
+                    },
+                };
                 self.test_connection_result = Some(res);
+                action
+            },
+            Message::ConnectionSaved(res) => {
+                if let Err(err) = res {
+                    self.log.error(format!("Failed to save the connection: {}", err));
+                }
                 Action::None
             },
         }
@@ -187,7 +319,7 @@ impl View {
                     widget::RadioArea::new("No Auth", AuthChoice::None, self.auth_choice_type, Message::AuthChoiceSelected)
                         .width(iced::FillPortion(1)),
                 ].width(iced::Fill).spacing(10),
-                self.auth_choice_type.map(|choice| {
+                self.auth_choice_type.and_then(|choice| {
                     match choice {
                         AuthChoice::Basic => Some(column![
                             iced::widget::text("Username"),
@@ -196,7 +328,7 @@ impl View {
                             iced::widget::text("Password (Optional)"),
                             iced::widget::text_input(
                                     "password", 
-                                    self.basic_auth_data.password.as_ref().map(String::as_str).unwrap_or("")
+                                    self.basic_auth_data.password.as_deref().unwrap_or("")
                                 )
                                 .on_input(Message::BasicAuthPasswordChanged),
                         ]),
@@ -207,13 +339,13 @@ impl View {
                             iced::widget::text("AWS Profile"),
                             iced::widget::text_input(
                                     "default", 
-                                    self.aws_sigv4_data.profile.as_ref().map(String::as_str).unwrap_or("")
+                                    self.aws_sigv4_data.profile.as_deref().unwrap_or("")
                                 )
                                 .on_input(Message::AwsAuthProfileChanged)
                         ]),
                         AuthChoice::None => None,
                     }
-                }).flatten()
+                })
             ]
         )
     }
@@ -294,6 +426,14 @@ impl View {
         .spacing(15)
     }
 
+    /**
+     * Identifies the cluster for data persisted per cluster (e.g. request history, cached index lists).
+     * Unrelated to the named connection profiles of the command line (profiles.json).
+     */
+    pub fn cluster_key(&self) -> String {
+        self.es_url.clone()
+    }
+
     pub fn get_client(&self) -> Result<es::ElasticsearchClient, String> {
         let mut client = es::ElasticsearchClient::new(self.es_url.clone())
             .map_err(|err| err.to_string())?;
@@ -302,13 +442,13 @@ impl View {
             client.use_custom_certificate(cert.clone()).map_err(|err| err.to_string())?
         }
 
-        let auth = self.auth_choice_type.map(|auth_type| {
+        let auth = self.auth_choice_type.and_then(|auth_type| {
             match auth_type {
                 AuthChoice::Basic => Some(es::Auth::BASIC(self.basic_auth_data.clone())),
                 AuthChoice::AWSSigV4 => Some(es::Auth::AWS(self.aws_sigv4_data.clone())),
                 AuthChoice::None => None,
             }
-        }).flatten(); 
+        }); 
 
         if let Some(auth_choice) = auth {
             client.use_auth(auth_choice);
@@ -340,19 +480,10 @@ impl View {
                 Some(handle) => 
                     iced::Task::perform(async move {
                         let path: std::path::PathBuf = handle.into();
-                        let contents = tokio::fs::read_to_string(&path).await
+                        let contents = tokio::fs::read(&path).await
                             .map_err(|err| format!("Unable to read {}, error kind: {}", path.to_string_lossy(), err.kind()))?;
 
-                        let certificate = if util::path_has_extension(&path,"pem") {
-                            reqwest::Certificate::from_pem(contents.as_bytes())
-                                .map_err(|err| format!("Unable to interpret {} as pem, error kind: {}", path.to_string_lossy(), err.to_string()))
-                        }
-                        else if util::path_has_extension(&path,"der") {
-                            reqwest::Certificate::from_der(contents.as_bytes())
-                                .map_err(|err| format!("Unable to interpret {} as der, error kind: {}", path.to_string_lossy(), err.to_string()))
-                        } else {
-                            Err( format!("{} is unsupported file type", path.to_string_lossy()))
-                        }?;
+                        let certificate = read_certificate(&path, &contents)?;
 
                         Ok(Some((path, certificate)))
                     }, Message::CertObtained),
@@ -4,8 +4,10 @@ use crate::{assets};
 pub enum Page {
     #[default]
     Search,
+    Indices,
     DevConsole,
     Connection,
+    Import,
     Logs
 }
 
@@ -17,6 +19,7 @@ pub enum Message {
 
 pub enum Action {
     None,
+    PageOpened(Page),
 }
 
 #[derive(Debug, Default)]
@@ -32,8 +35,8 @@ impl View {
     pub fn update(&mut self, message: Message) -> Action {
         match message {
             Message::PageChanged(page) => {
-                self.current_page = page;
-                Action::None
+                self.current_page = page.clone();
+                Action::PageOpened(page)
             },
             Message::ExpandedClicked =>  {
                 self.toolbar_expanded = !self.toolbar_expanded;
@@ -73,7 +76,9 @@ impl View {
         // Entries in the form of (icon, expanded text, message)
         let buttons = [
             (assets::search_icon(), "Search", Message::PageChanged(Page::Search)),
+            (assets::database_icon(), "Indices", Message::PageChanged(Page::Indices)),
             (assets::terminal_icon(), "Dev Tools", Message::PageChanged(Page::DevConsole)),
+            (assets::upload_icon(), "Import", Message::PageChanged(Page::Import)),
             (assets::settings_icon(), "Settings", Message::PageChanged(Page::Connection)),
             (assets::file_icon(), "Logs", Message::PageChanged(Page::Logs)),
         ];
@@ -0,0 +1,209 @@
+// Follows the documents matching a search as they are indexed.
+// The first poll returns the newest documents, later polls those sorted after the last one returned,
+// using search_after on the timestamp followed by a tiebreaker.
+use crate::es;
+
+// Documents requested per search
+const PAGE_SIZE: usize = 1000;
+// Used when no tiebreaker field is given. Only ordered within a shard, so documents sharing a timestamp
+// across shards can be missed; a unique field such as an event id avoids that.
+const DEFAULT_TIEBREAKER: &str = "_doc";
+
+// Search body keys the tail sets itself or that make no sense when polling
+const IGNORED_BODY_KEYS: [&str; 10] = [
+    "from", "size", "sort", "pit", "search_after", "track_total_hits", "aggs", "aggregations", "scroll", "highlight",
+];
+
+#[derive(Debug, Clone)]
+pub struct Tail {
+    indicies: Vec<String>,
+    body: serde_json::Map<String, serde_json::Value>,
+    timestamp_field: String,
+    tiebreaker: String,
+    // Sort values of the newest document returned, None until a poll returned one
+    search_after: Option<serde_json::Value>,
+}
+
+impl Tail {
+    /**
+     * indicies as an empty list implies all indicies.
+     * The query and other keys of the body are kept, its sort and paging are replaced.
+     */
+    pub fn new(
+        indicies: Vec<String>,
+        body: Option<serde_json::Value>,
+        timestamp_field: impl Into<String>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut body = match body {
+            Some(serde_json::Value::Object(body)) => body,
+            Some(_) => return Err("Search body must be a JSON object".into()),
+            None => Default::default(),
+        };
+
+        for key in IGNORED_BODY_KEYS {
+            body.remove(key);
+        }
+
+        Ok(Self {
+            indicies,
+            body,
+            timestamp_field: timestamp_field.into(),
+            tiebreaker: DEFAULT_TIEBREAKER.to_owned(),
+            search_after: None,
+        })
+    }
+
+    // Field ordering documents sharing a timestamp, ideally unique per document
+    pub fn tiebreaker(mut self, field: impl Into<String>) -> Self {
+        self.tiebreaker = field.into();
+        self
+    }
+
+    fn sort(&self, order: es::SortOrder) -> serde_json::Value {
+        let sort = |field: &str| match order {
+            es::SortOrder::Asc => es::Sort::asc(field),
+            es::SortOrder::Desc => es::Sort::desc(field),
+        };
+        serde_json::json!([sort(&self.timestamp_field), sort(&self.tiebreaker)])
+    }
+
+    // The first poll asks for the newest documents, later ones for those sorted after the cursor
+    fn order(&self) -> es::SortOrder {
+        match self.search_after {
+            Some(_) => es::SortOrder::Asc,
+            None => es::SortOrder::Desc,
+        }
+    }
+
+    fn body(&self, size: usize, order: es::SortOrder) -> serde_json::Value {
+        let mut body = self.body.clone();
+        body.insert("size".to_owned(), size.into());
+        body.insert("sort".to_owned(), self.sort(order));
+        body.insert("track_total_hits".to_owned(), false.into());
+        if order == es::SortOrder::Asc && let Some(search_after) = &self.search_after {
+            body.insert("search_after".to_owned(), search_after.clone());
+        }
+        serde_json::Value::Object(body)
+    }
+
+    // Puts the hits oldest first and moves the cursor to the last of them, kept when there are none
+    fn received(&mut self, hits: &mut [serde_json::Value], order: es::SortOrder) {
+        if order == es::SortOrder::Desc {
+            hits.reverse();
+        }
+        if let Some(sort) = hits.last().and_then(|hit| hit.get("sort")) {
+            self.search_after = Some(sort.clone());
+        }
+    }
+
+    async fn search(
+        &mut self,
+        client: &es::ElasticsearchClient,
+        size: usize,
+    ) -> Result<es::OperationSearchResult, Box<dyn std::error::Error>> {
+        let order = self.order();
+        let mut res = client.search(&self.indicies, Some(&self.body(size, order))).await?;
+        self.received(&mut res.hits.hits, order);
+        Ok(res)
+    }
+
+    /**
+     * Search result whose hits are the documents indexed since the last poll, oldest first and at most max of them.
+     * The first poll returns the newest max documents. When more than max arrived, the rest are returned by the next polls.
+     */
+    pub async fn poll(
+        &mut self,
+        client: &es::ElasticsearchClient,
+        max: usize,
+    ) -> Result<es::OperationSearchResult, Box<dyn std::error::Error>> {
+        if self.search_after.is_none() {
+            return self.search(client, max).await;
+        }
+
+        let size = PAGE_SIZE.min(max);
+        let mut res = self.search(client, size).await?;
+        let mut full = res.hits.hits.len() == size;
+
+        loop {
+            let remaining = max - res.hits.hits.len();
+            if !full || remaining == 0 {
+                return Ok(res);
+            }
+
+            let size = PAGE_SIZE.min(remaining);
+            let page = self.search(client, size).await?;
+            full = page.hits.hits.len() == size;
+            res.hits.hits.extend(page.hits.hits);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn hit(timestamp: &str, doc: u64) -> serde_json::Value {
+        json!({ "_id": doc.to_string(), "sort": [timestamp, doc] })
+    }
+
+    #[test]
+    fn keeps_the_query_and_drops_the_paging_of_the_body() {
+        let body = json!({ "query": { "match_all": {} }, "from": 10, "size": 5, "aggs": {}, "highlight": {} });
+        let tail = Tail::new(vec!["logs".to_owned()], Some(body), "@timestamp").unwrap();
+
+        assert_eq!(tail.body(20, es::SortOrder::Desc), json!({
+            "query": { "match_all": {} },
+            "size": 20,
+            "sort": [{ "@timestamp": { "order": "desc" } }, { "_doc": { "order": "desc" } }],
+            "track_total_hits": false,
+        }));
+    }
+
+    #[test]
+    fn rejects_a_body_that_is_not_an_object() {
+        assert!(Tail::new(Vec::new(), Some(json!([])), "@timestamp").is_err());
+    }
+
+    #[test]
+    fn first_poll_sorts_the_newest_documents_first_and_returns_them_oldest_first() {
+        let mut tail = Tail::new(Vec::new(), None, "@timestamp").unwrap();
+        assert_eq!(tail.order(), es::SortOrder::Desc);
+        assert_eq!(tail.body(3, tail.order()).get("search_after"), None);
+
+        let mut hits = vec![hit("2025-01-03", 3), hit("2025-01-02", 2), hit("2025-01-01", 1)];
+        tail.received(&mut hits, es::SortOrder::Desc);
+
+        assert_eq!(hits, vec![hit("2025-01-01", 1), hit("2025-01-02", 2), hit("2025-01-03", 3)]);
+        assert_eq!(tail.search_after, Some(json!(["2025-01-03", 3])));
+    }
+
+    #[test]
+    fn later_polls_search_ascending_after_the_last_hit() {
+        let mut tail = Tail::new(Vec::new(), None, "@timestamp").unwrap().tiebreaker("event.id");
+        tail.received(&mut [hit("2025-01-01", 1)], es::SortOrder::Desc);
+        assert_eq!(tail.order(), es::SortOrder::Asc);
+
+        assert_eq!(tail.body(1000, tail.order()), json!({
+            "size": 1000,
+            "sort": [{ "@timestamp": { "order": "asc" } }, { "event.id": { "order": "asc" } }],
+            "track_total_hits": false,
+            "search_after": ["2025-01-01", 1],
+        }));
+
+        tail.received(&mut [hit("2025-01-02", 2), hit("2025-01-04", 4)], es::SortOrder::Asc);
+        assert_eq!(tail.body(1000, tail.order())["search_after"], json!(["2025-01-04", 4]));
+    }
+
+    #[test]
+    fn empty_poll_keeps_the_cursor() {
+        let mut tail = Tail::new(Vec::new(), None, "@timestamp").unwrap();
+        tail.received(&mut [], es::SortOrder::Desc);
+        assert_eq!(tail.search_after, None);
+        assert_eq!(tail.order(), es::SortOrder::Desc);
+
+        tail.received(&mut [hit("2025-01-01", 1)], es::SortOrder::Desc);
+        tail.received(&mut [], es::SortOrder::Asc);
+        assert_eq!(tail.search_after, Some(json!(["2025-01-01", 1])));
+    }
+}
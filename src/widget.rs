@@ -1,4 +1,12 @@
+pub mod chart;
 pub mod circle;
+pub mod json_editor;
+pub mod json_highlighter;
+pub mod json_tree;
+pub mod virtual_list;
+
+pub use json_editor::JsonEditor;
+pub use json_tree::JsonTree;
 
 pub struct RadioArea<Message, V> {
     produced_message: Message,
@@ -60,7 +68,8 @@ where
         .on_press(radio_area.produced_message)
         .style(|t: &iced::Theme, _s| {
             let palette = t.extended_palette();
-            let base = iced::widget::button::Style {
+            
+            iced::widget::button::Style {
                 background: Some(iced::Color::TRANSPARENT.into()),
                 border: iced::Border {
                     width: 1.0,
@@ -69,14 +78,18 @@ where
                 },
                 text_color: t.palette().text,
                 ..Default::default()
-            };
-            base
+            }
         })
         .width(radio_area.width)
         .into()
     }
 }
 
+// Around the header and body of sections
+pub const SECTION_PADDING: f32 = 10.0;
+// Of the rule between the header and body of sections
+pub const SECTION_RULE_WIDTH: f32 = 1.0;
+
 pub fn section_with_header<'a, Message: 'a>(
     header: impl Into<iced::Element<'a, Message>>, 
     body: impl Into<iced::Element<'a, Message>>
@@ -85,20 +98,67 @@ pub fn section_with_header<'a, Message: 'a>(
         iced::widget::column![
             iced::widget::container(
                 header
-            ).padding(10),
-            iced::widget::rule::horizontal(1),
+            ).padding(SECTION_PADDING),
+            iced::widget::rule::horizontal(SECTION_RULE_WIDTH),
             iced::widget::container(
                 body
-            ).padding(10)
+            ).padding(SECTION_PADDING)
         ]
     )
     .style(iced::widget::container::bordered_box)
 }
 
+// Height of a `section_with_header` whose header and body are the given heights, for virtual lists
+pub fn section_with_header_height(header: f32, body: f32) -> f32 {
+    section_height(header) + SECTION_RULE_WIDTH + section_height(body)
+}
+
 pub fn section<'a, Message: 'a>(
     body: impl Into<iced::Element<'a, Message>>
 ) -> iced::widget::Container<'a, Message> {
     iced::widget::container(body)
-        .padding(10)
+        .padding(SECTION_PADDING)
         .style(iced::widget::container::bordered_box)
 }
+
+// Height of a `section` whose body is the given height
+pub fn section_height(body: f32) -> f32 {
+    body + 2.0 * SECTION_PADDING
+}
+
+/**
+ * List of autocomplete suggestions, each showing its label and kind
+ */
+pub fn suggestion_list<'a, Message: 'a + Clone>(
+    suggestions: &'a [elastic_ermine::autocomplete::Suggestion],
+    on_pick: impl Fn(usize) -> Message
+) -> iced::widget::Container<'a, Message> {
+    let items = suggestions.iter()
+        .enumerate()
+        .map(|(idx, suggestion)| {
+            iced::widget::button(
+                iced::widget::row![
+                    iced::widget::text(&suggestion.label)
+                        .size(14)
+                        .font(iced::Font::MONOSPACE),
+                    iced::widget::space::horizontal(),
+                    iced::widget::text(suggestion.kind.as_str())
+                        .size(12),
+                ]
+                .align_y(iced::Center)
+            )
+            .on_press(on_pick(idx))
+            .width(iced::Fill)
+            .padding([2, 8])
+            .style(iced::widget::button::text)
+            .into()
+        });
+
+    iced::widget::container(
+        iced::widget::scrollable(iced::widget::column(items))
+    )
+    .max_height(200)
+    .width(iced::Fill)
+    .padding(2)
+    .style(iced::widget::container::bordered_box)
+}
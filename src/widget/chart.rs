@@ -0,0 +1,537 @@
+// Bar, line and pie charts of labelled values drawn on a canvas.
+// Hovering an entry shows its label and value, pressing it produces a message with the index of the entry.
+// Bar and line charts can also be brushed, dragging across entries to select a range of them.
+use iced::widget::canvas;
+use iced::{Color, Point, Rectangle, Size};
+
+const MARGIN: f32 = 10.0;
+// Room left of the plot for the value axis labels
+const AXIS_WIDTH: f32 = 60.0;
+// Room under the plot for the entry labels
+const LABEL_HEIGHT: f32 = 22.0;
+const TEXT_SIZE: f32 = 12.0;
+// Rough width of a character of TEXT_SIZE, used to keep labels apart
+const CHAR_WIDTH: f32 = 7.0;
+const MAX_LABEL_CHARS: usize = 16;
+const GRID_LINES: usize = 4;
+const LEGEND_WIDTH: f32 = 220.0;
+const LEGEND_ROW_HEIGHT: f32 = 18.0;
+const POINT_RADIUS: f32 = 3.0;
+
+// Slice colors of pie charts, repeated when there are more slices
+const PALETTE: [Color; 8] = [
+    Color::from_rgb(0.33, 0.53, 0.85),
+    Color::from_rgb(0.90, 0.55, 0.20),
+    Color::from_rgb(0.35, 0.70, 0.40),
+    Color::from_rgb(0.85, 0.33, 0.35),
+    Color::from_rgb(0.60, 0.45, 0.80),
+    Color::from_rgb(0.55, 0.40, 0.30),
+    Color::from_rgb(0.85, 0.45, 0.70),
+    Color::from_rgb(0.50, 0.50, 0.50),
+];
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    #[default]
+    Bar,
+    Line,
+    Pie,
+}
+
+impl Kind {
+    pub const ALL: [Kind; 3] = [Kind::Bar, Kind::Line, Kind::Pie];
+}
+
+impl std::fmt::Display for Kind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Kind::Bar => "Bar",
+            Kind::Line => "Line",
+            Kind::Pie => "Pie",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub label: String,
+    // Negative values are drawn as zero
+    pub value: f64,
+}
+
+impl Entry {
+    pub fn new(label: impl Into<String>, value: f64) -> Self {
+        Self { label: label.into(), value }
+    }
+}
+
+pub struct Chart<'a, Message> {
+    kind: Kind,
+    entries: Vec<Entry>,
+    on_press: Option<Box<dyn Fn(usize) -> Message + 'a>>,
+    on_brush: Option<Box<dyn Fn(usize, usize) -> Message + 'a>>,
+    height: f32,
+}
+
+#[derive(Debug, Default)]
+pub struct State {
+    // Entry under the cursor, kept to redraw only when it changes
+    hovered: Option<usize>,
+    // Entry the brush started at while the button is held
+    brush_start: Option<usize>,
+}
+
+impl<'a, Message> Chart<'a, Message> {
+    const DEFAULT_HEIGHT: f32 = 260.0;
+
+    pub fn new(kind: Kind, entries: Vec<Entry>) -> Self {
+        Self {
+            kind,
+            entries,
+            on_press: None,
+            on_brush: None,
+            height: Self::DEFAULT_HEIGHT,
+        }
+    }
+
+    // Message produced with the index of the entry pressed
+    pub fn on_press(mut self, on_press: impl Fn(usize) -> Message + 'a) -> Self {
+        self.on_press = Some(Box::new(on_press));
+        self
+    }
+
+    /**
+     * Message produced with the first and last index of the entries brushed over, in that order.
+     * Only bar and line charts can be brushed. Releasing on the entry the brush started at is a press.
+     */
+    pub fn on_brush(mut self, on_brush: impl Fn(usize, usize) -> Message + 'a) -> Self {
+        self.on_brush = Some(Box::new(on_brush));
+        self
+    }
+
+    fn can_brush(&self) -> bool {
+        self.on_brush.is_some() && self.kind != Kind::Pie
+    }
+
+    pub fn height(mut self, height: f32) -> Self {
+        self.height = height;
+        self
+    }
+
+    fn value(&self, idx: usize) -> f32 {
+        self.entries[idx].value.max(0.0) as f32
+    }
+
+    fn total(&self) -> f32 {
+        (0..self.entries.len()).map(|idx| self.value(idx)).sum()
+    }
+
+    // Width of the slot of each entry within the plot of bar and line charts
+    fn slot_width(&self, plot: Rectangle) -> f32 {
+        plot.width / self.entries.len().max(1) as f32
+    }
+
+    // Entry at the position, relative to the top left of the chart
+    fn entry_at(&self, size: Size, position: Point) -> Option<usize> {
+        if self.entries.is_empty() {
+            return None;
+        }
+
+        match self.kind {
+            Kind::Bar | Kind::Line => {
+                let plot = plot_area(size);
+                if !plot.contains(position) {
+                    return None;
+                }
+                let idx = ((position.x - plot.x) / self.slot_width(plot)).floor() as usize;
+                Some(idx.min(self.entries.len() - 1))
+            },
+            Kind::Pie => {
+                let (center, radius) = pie_area(size);
+                let offset = position - center;
+                if offset.x.hypot(offset.y) > radius || self.total() <= 0.0 {
+                    return None;
+                }
+
+                // Slices start at the top and go clockwise
+                let angle = (offset.y.atan2(offset.x) + std::f32::consts::FRAC_PI_2).rem_euclid(std::f32::consts::TAU);
+                let target = angle / std::f32::consts::TAU * self.total();
+
+                let mut end = 0.0;
+                (0..self.entries.len()).find(|idx| {
+                    end += self.value(*idx);
+                    target < end
+                })
+            },
+        }
+    }
+
+    fn draw_axes(&self, frame: &mut canvas::Frame, theme: &iced::Theme, plot: Rectangle, max: f64) {
+        let palette = theme.extended_palette();
+
+        for line in 0..=GRID_LINES {
+            let fraction = line as f32 / GRID_LINES as f32;
+            let y = plot.y + plot.height * (1.0 - fraction);
+
+            frame.stroke(
+                &canvas::Path::line(Point::new(plot.x, y), Point::new(plot.x + plot.width, y)),
+                canvas::Stroke::default().with_width(1.0).with_color(palette.background.weak.color),
+            );
+            frame.fill_text(canvas::Text {
+                content: format_value(max * fraction as f64),
+                position: Point::new(plot.x - 6.0, y),
+                color: palette.background.base.text,
+                size: TEXT_SIZE.into(),
+                align_x: iced::widget::text::Alignment::Right,
+                align_y: iced::alignment::Vertical::Center,
+                ..canvas::Text::default()
+            });
+        }
+
+        // Labels are skipped so that the shown ones do not overlap
+        let slot_width = self.slot_width(plot);
+        let label_width = (MAX_LABEL_CHARS as f32 + 1.0) * CHAR_WIDTH;
+        let step = (label_width / slot_width).ceil().max(1.0) as usize;
+
+        for (idx, entry) in self.entries.iter().enumerate().step_by(step) {
+            frame.fill_text(canvas::Text {
+                content: truncate(&entry.label, MAX_LABEL_CHARS),
+                position: Point::new(plot.x + slot_width * (idx as f32 + 0.5), plot.y + plot.height + 4.0),
+                color: palette.background.base.text,
+                size: TEXT_SIZE.into(),
+                align_x: iced::widget::text::Alignment::Center,
+                ..canvas::Text::default()
+            });
+        }
+    }
+
+    fn draw_bars(&self, frame: &mut canvas::Frame, theme: &iced::Theme, hovered: Option<usize>) {
+        let palette = theme.extended_palette();
+        let plot = plot_area(frame.size());
+        let max = nice_max(self.entries.iter().map(|entry| entry.value).fold(0.0, f64::max));
+        self.draw_axes(frame, theme, plot, max);
+
+        let slot_width = self.slot_width(plot);
+        let bar_width = (slot_width * 0.8).max(1.0);
+
+        for idx in 0..self.entries.len() {
+            let height = plot.height * (self.value(idx) as f64 / max) as f32;
+            let color = if hovered == Some(idx) { palette.primary.strong.color } else { palette.primary.base.color };
+
+            frame.fill_rectangle(
+                Point::new(plot.x + slot_width * idx as f32 + (slot_width - bar_width) / 2.0, plot.y + plot.height - height),
+                Size::new(bar_width, height),
+                color,
+            );
+        }
+    }
+
+    fn draw_line(&self, frame: &mut canvas::Frame, theme: &iced::Theme, hovered: Option<usize>) {
+        let palette = theme.extended_palette();
+        let plot = plot_area(frame.size());
+        let max = nice_max(self.entries.iter().map(|entry| entry.value).fold(0.0, f64::max));
+        self.draw_axes(frame, theme, plot, max);
+
+        let slot_width = self.slot_width(plot);
+        let points = (0..self.entries.len())
+            .map(|idx| Point::new(
+                plot.x + slot_width * (idx as f32 + 0.5),
+                plot.y + plot.height * (1.0 - (self.value(idx) as f64 / max) as f32),
+            ))
+            .collect::<Vec<Point>>();
+
+        let line = canvas::Path::new(|builder| {
+            for (idx, point) in points.iter().enumerate() {
+                if idx == 0 {
+                    builder.move_to(*point);
+                } else {
+                    builder.line_to(*point);
+                }
+            }
+        });
+        frame.stroke(&line, canvas::Stroke::default().with_width(2.0).with_color(palette.primary.base.color));
+
+        if let Some(idx) = hovered {
+            frame.stroke(
+                &canvas::Path::line(Point::new(points[idx].x, plot.y), Point::new(points[idx].x, plot.y + plot.height)),
+                canvas::Stroke::default().with_width(1.0).with_color(palette.background.strong.color),
+            );
+        }
+
+        for (idx, point) in points.iter().enumerate() {
+            let radius = if hovered == Some(idx) { POINT_RADIUS * 1.5 } else { POINT_RADIUS };
+            frame.fill(&canvas::Path::circle(*point, radius), palette.primary.strong.color);
+        }
+    }
+
+    fn draw_pie(&self, frame: &mut canvas::Frame, theme: &iced::Theme, hovered: Option<usize>) {
+        let palette = theme.extended_palette();
+        let size = frame.size();
+        let (center, radius) = pie_area(size);
+        let total = self.total();
+
+        if total > 0.0 && radius > 0.0 {
+            let mut start = -std::f32::consts::FRAC_PI_2;
+
+            for idx in 0..self.entries.len() {
+                let sweep = self.value(idx) / total * std::f32::consts::TAU;
+                // The hovered slice is pulled out a little
+                let offset = if hovered == Some(idx) {
+                    let middle = start + sweep / 2.0;
+                    iced::Vector::new(middle.cos(), middle.sin()) * 6.0
+                } else {
+                    iced::Vector::new(0.0, 0.0)
+                };
+
+                let slice = canvas::Path::new(|builder| {
+                    builder.move_to(center + offset);
+                    builder.arc(canvas::path::Arc {
+                        center: center + offset,
+                        radius,
+                        start_angle: iced::Radians(start),
+                        end_angle: iced::Radians(start + sweep),
+                    });
+                    builder.close();
+                });
+                frame.fill(&slice, PALETTE[idx % PALETTE.len()]);
+                frame.stroke(&slice, canvas::Stroke::default().with_width(1.0).with_color(palette.background.base.color));
+
+                start += sweep;
+            }
+        }
+
+        // Legend for as many entries as fit to the right of the pie
+        if size.width >= LEGEND_WIDTH * 2.0 {
+            let x = size.width - LEGEND_WIDTH;
+            let rows = ((size.height - MARGIN * 2.0) / LEGEND_ROW_HEIGHT).max(0.0) as usize;
+
+            for (idx, entry) in self.entries.iter().enumerate().take(rows) {
+                let y = MARGIN + LEGEND_ROW_HEIGHT * idx as f32;
+                frame.fill_rectangle(Point::new(x, y + 3.0), Size::new(12.0, 12.0), PALETTE[idx % PALETTE.len()]);
+                frame.fill_text(canvas::Text {
+                    content: format!("{} ({})", truncate(&entry.label, MAX_LABEL_CHARS), format_value(entry.value)),
+                    position: Point::new(x + 18.0, y + LEGEND_ROW_HEIGHT / 2.0),
+                    color: palette.background.base.text,
+                    size: TEXT_SIZE.into(),
+                    align_y: iced::alignment::Vertical::Center,
+                    ..canvas::Text::default()
+                });
+            }
+        }
+    }
+
+    fn draw_brush(&self, frame: &mut canvas::Frame, theme: &iced::Theme, start: usize, end: usize) {
+        let plot = plot_area(frame.size());
+        let slot_width = self.slot_width(plot);
+        let (first, last) = (start.min(end), start.max(end));
+
+        let mut color = theme.extended_palette().primary.weak.color;
+        color.a = 0.3;
+        frame.fill_rectangle(
+            Point::new(plot.x + slot_width * first as f32, plot.y),
+            Size::new(slot_width * (last - first + 1) as f32, plot.height),
+            color,
+        );
+    }
+
+    // Label and value of the hovered entry next to the cursor, kept within the chart
+    fn draw_tooltip(&self, frame: &mut canvas::Frame, theme: &iced::Theme, idx: usize, cursor: Point) {
+        let palette = theme.extended_palette();
+        let entry = &self.entries[idx];
+
+        let content = match self.kind {
+            Kind::Pie => format!(
+                "{}: {} ({:.1}%)", entry.label, format_value(entry.value), self.value(idx) / self.total() * 100.0
+            ),
+            Kind::Bar | Kind::Line => format!("{}: {}", entry.label, format_value(entry.value)),
+        };
+
+        let size = Size::new(content.chars().count() as f32 * CHAR_WIDTH + 12.0, TEXT_SIZE + 12.0);
+        let position = Point::new(
+            (cursor.x + 12.0).min(frame.width() - size.width).max(0.0),
+            (cursor.y - size.height - 4.0).max(0.0),
+        );
+
+        let background = canvas::Path::rounded_rectangle(position, size, 4.0.into());
+        frame.fill(&background, palette.background.strong.color);
+        frame.fill_text(canvas::Text {
+            content,
+            position: Point::new(position.x + 6.0, position.y + size.height / 2.0),
+            color: palette.background.strong.text,
+            size: TEXT_SIZE.into(),
+            align_y: iced::alignment::Vertical::Center,
+            ..canvas::Text::default()
+        });
+    }
+}
+
+impl<Message> canvas::Program<Message> for Chart<'_, Message> {
+    type State = State;
+
+    fn update(
+        &self,
+        state: &mut Self::State,
+        event: &iced::Event,
+        bounds: Rectangle,
+        cursor: iced::mouse::Cursor,
+    ) -> Option<canvas::Action<Message>> {
+        let hovered = cursor.position_in(bounds).and_then(|position| self.entry_at(bounds.size(), position));
+
+        match event {
+            iced::Event::Mouse(iced::mouse::Event::ButtonPressed(iced::mouse::Button::Left)) => {
+                let idx = hovered?;
+                if self.can_brush() {
+                    state.brush_start = Some(idx);
+                    return Some(canvas::Action::request_redraw().and_capture());
+                }
+
+                let on_press = self.on_press.as_ref()?;
+                Some(canvas::Action::publish(on_press(idx)).and_capture())
+            },
+            iced::Event::Mouse(iced::mouse::Event::ButtonReleased(iced::mouse::Button::Left)) => {
+                let start = state.brush_start.take()?;
+                // Released outside of the plot, the brush ends at the last entry hovered
+                let end = hovered.or(state.hovered).unwrap_or(start);
+
+                let message = match (&self.on_press, &self.on_brush) {
+                    (Some(on_press), _) if start == end => Some(on_press(start)),
+                    (_, Some(on_brush)) => Some(on_brush(start.min(end), start.max(end))),
+                    _ => None,
+                };
+
+                Some(match message {
+                    Some(message) => canvas::Action::publish(message).and_capture(),
+                    None => canvas::Action::request_redraw(),
+                })
+            },
+            iced::Event::Mouse(iced::mouse::Event::CursorMoved { .. } | iced::mouse::Event::CursorLeft) => {
+                // The tooltip follows the cursor while an entry is hovered
+                let redraw = state.hovered.is_some() || hovered.is_some() || state.brush_start.is_some();
+                if hovered.is_some() || state.brush_start.is_none() {
+                    state.hovered = hovered;
+                }
+                redraw.then(canvas::Action::request_redraw)
+            },
+            _ => None,
+        }
+    }
+
+    fn draw(
+        &self,
+        state: &Self::State,
+        renderer: &iced::Renderer,
+        theme: &iced::Theme,
+        bounds: Rectangle,
+        cursor: iced::mouse::Cursor,
+    ) -> Vec<canvas::Geometry> {
+        let mut frame = canvas::Frame::new(renderer, bounds.size());
+
+        if self.entries.is_empty() {
+            frame.fill_text(canvas::Text {
+                content: "Nothing to chart".to_owned(),
+                position: frame.center(),
+                color: theme.extended_palette().background.base.text,
+                size: TEXT_SIZE.into(),
+                align_x: iced::widget::text::Alignment::Center,
+                align_y: iced::alignment::Vertical::Center,
+                ..canvas::Text::default()
+            });
+            return vec![frame.into_geometry()];
+        }
+
+        let cursor_position = cursor.position_in(bounds);
+        let hovered = cursor_position.and_then(|position| self.entry_at(bounds.size(), position));
+
+        match self.kind {
+            Kind::Bar => self.draw_bars(&mut frame, theme, hovered),
+            Kind::Line => self.draw_line(&mut frame, theme, hovered),
+            Kind::Pie => self.draw_pie(&mut frame, theme, hovered),
+        }
+
+        if let Some(start) = state.brush_start {
+            self.draw_brush(&mut frame, theme, start, hovered.or(state.hovered).unwrap_or(start));
+        }
+
+        if let (Some(idx), Some(position)) = (hovered, cursor_position) {
+            self.draw_tooltip(&mut frame, theme, idx, position);
+        }
+
+        vec![frame.into_geometry()]
+    }
+
+    fn mouse_interaction(
+        &self,
+        _state: &Self::State,
+        bounds: Rectangle,
+        cursor: iced::mouse::Cursor,
+    ) -> iced::mouse::Interaction {
+        let hovered = cursor.position_in(bounds).and_then(|position| self.entry_at(bounds.size(), position));
+
+        if hovered.is_some() && self.can_brush() {
+            iced::mouse::Interaction::Crosshair
+        } else if hovered.is_some() && self.on_press.is_some() {
+            iced::mouse::Interaction::Pointer
+        } else {
+            iced::mouse::Interaction::default()
+        }
+    }
+}
+
+impl<'a, Message: 'a> From<Chart<'a, Message>> for iced::Element<'a, Message> {
+    fn from(chart: Chart<'a, Message>) -> Self {
+        let height = chart.height;
+        iced::widget::canvas(chart)
+            .width(iced::Fill)
+            .height(height)
+            .into()
+    }
+}
+
+// Area of bar and line charts within the axes
+fn plot_area(size: Size) -> Rectangle {
+    Rectangle {
+        x: AXIS_WIDTH,
+        y: MARGIN,
+        width: (size.width - AXIS_WIDTH - MARGIN).max(0.0),
+        height: (size.height - MARGIN - LABEL_HEIGHT).max(0.0),
+    }
+}
+
+// Center and radius of pie charts, leaving room for the legend when it is shown
+fn pie_area(size: Size) -> (Point, f32) {
+    let width = if size.width >= LEGEND_WIDTH * 2.0 { size.width - LEGEND_WIDTH } else { size.width };
+    let radius = (width.min(size.height) / 2.0 - MARGIN).max(0.0);
+    (Point::new(width / 2.0, size.height / 2.0), radius)
+}
+
+// Top of the value axis: the max rounded up to 1, 2 or 5 times a power of ten
+fn nice_max(max: f64) -> f64 {
+    if max <= 0.0 || !max.is_finite() {
+        return 1.0;
+    }
+
+    let magnitude = 10f64.powf(max.log10().floor());
+    let nice = match max / magnitude {
+        fraction if fraction <= 1.0 => 1.0,
+        fraction if fraction <= 2.0 => 2.0,
+        fraction if fraction <= 5.0 => 5.0,
+        _ => 10.0,
+    };
+    nice * magnitude
+}
+
+fn format_value(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        format!("{}", value as i64)
+    } else {
+        format!("{:.2}", value)
+    }
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() > max_chars {
+        text.chars().take(max_chars - 1).collect::<String>() + "…"
+    } else {
+        text.to_owned()
+    }
+}
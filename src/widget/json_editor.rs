@@ -0,0 +1,182 @@
+use elastic_ermine::console;
+
+use super::json_highlighter;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Syntax {
+    #[default]
+    Json,
+    // Kibana Console requests, see `console`
+    Console,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    // Zero based
+    pub line: usize,
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.column {
+            Some(column) => write!(f, "Line {}, column {}: {}", self.line + 1, column + 1, self.message),
+            None => write!(f, "Line {}: {}", self.line + 1, self.message),
+        }
+    }
+}
+
+/**
+ * Text editor content that is kept validated, with the bracket at the cursor matched, as it is edited.
+ */
+#[derive(Debug, Default)]
+pub struct JsonEditor {
+    content: iced::widget::text_editor::Content,
+    syntax: Syntax,
+    error: Option<ParseError>,
+    matched_brackets: Vec<(usize, usize)>,
+}
+
+impl JsonEditor {
+    pub fn new(syntax: Syntax) -> Self {
+        Self {
+            syntax,
+            ..Default::default()
+        }
+    }
+
+    pub fn perform(&mut self, action: iced::widget::text_editor::Action) {
+        let is_edit = action.is_edit();
+        self.content.perform(action);
+
+        if is_edit {
+            self.validate();
+        }
+        self.match_brackets();
+    }
+
+    pub fn text(&self) -> String {
+        self.content.text()
+    }
+
+    pub fn set_text(&mut self, text: &str) {
+        self.content = iced::widget::text_editor::Content::with_text(text);
+        self.validate();
+        self.match_brackets();
+    }
+
+    pub fn clear(&mut self) {
+        self.set_text("");
+    }
+
+    pub fn cursor(&self) -> iced::widget::text_editor::Cursor {
+        self.content.cursor()
+    }
+
+    // Text from the start of the content up to the cursor
+    pub fn text_before_cursor(&self) -> String {
+        let position = self.content.cursor().position;
+        let text = self.text();
+        let mut before = text.lines()
+            .take(position.line)
+            .fold(String::new(), |acc, line| acc + line + "\n");
+
+        if let Some(line) = text.lines().nth(position.line) {
+            // The cursor column is a byte index into the line
+            let mut column = position.column.min(line.len());
+            while !line.is_char_boundary(column) {
+                column -= 1;
+            }
+            before.push_str(&line[..column]);
+        }
+
+        before
+    }
+
+    // Replaces the given number of characters before the cursor with text, e.g. to accept a suggestion
+    pub fn replace_before_cursor(&mut self, len: usize, text: &str) {
+        use iced::widget::text_editor::{Action, Edit};
+
+        for _ in 0..len {
+            self.content.perform(Action::Edit(Edit::Backspace));
+        }
+        self.perform(Action::Edit(Edit::Paste(std::sync::Arc::new(text.to_owned()))));
+    }
+
+    // Pretty prints the JSON, leaving the content untouched if it is not valid JSON
+    pub fn format(&mut self) -> Result<(), String> {
+        let text = self.text();
+        if text.trim().is_empty() {
+            return Ok(());
+        }
+
+        let json = serde_json::from_str::<serde_json::Value>(&text)
+            .map_err(|err| format!("Unable to format invalid JSON: {}", err))?;
+
+        let formatted = serde_json::to_string_pretty(&json).map_err(|err| err.to_string())?;
+        self.set_text(&formatted);
+        Ok(())
+    }
+
+    fn validate(&mut self) {
+        let text = self.text();
+
+        self.error = match self.syntax {
+            Syntax::Json => {
+                if text.trim().is_empty() {
+                    None
+                } else {
+                    serde_json::from_str::<serde_json::Value>(&text).err().map(|err| {
+                        // serde_json lines and columns are one based, a column of 0 means the end of the previous line
+                        let line = err.line().saturating_sub(1);
+                        let column = err.column().saturating_sub(1);
+                        ParseError {
+                            line,
+                            column: Some(text.lines().nth(line).map(|text| json_highlighter::char_column(text, column)).unwrap_or(column)),
+                            message: err.to_string(),
+                        }
+                    })
+                }
+            },
+            Syntax::Console => console::parse(&text).into_iter()
+                .find_map(Result::err)
+                .map(|err| ParseError {
+                    line: err.line,
+                    column: None,
+                    message: err.message,
+                }),
+        };
+    }
+
+    fn match_brackets(&mut self) {
+        let position = self.content.cursor().position;
+        self.matched_brackets = json_highlighter::matching_brackets(&self.text(), position.line, position.column);
+    }
+
+    pub fn view<'a, Message: 'a + Clone>(
+        &'a self,
+        on_action: impl Fn(iced::widget::text_editor::Action) -> Message + 'a,
+        placeholder: &'a str,
+    ) -> iced::widget::Column<'a, Message> {
+        let settings = json_highlighter::Settings {
+            matched_brackets: self.matched_brackets.clone(),
+            error: self.error.as_ref().map(|err| (err.line, err.column)),
+        };
+
+        iced::widget::column![
+            iced::widget::text_editor(&self.content)
+                .on_action(on_action)
+                .height(iced::Fill)
+                .font(iced::Font::MONOSPACE)
+                .placeholder(placeholder)
+                .highlight_with::<json_highlighter::JsonHighlighter>(settings, json_highlighter::Highlight::text_format),
+            self.error.as_ref().map(|err| {
+                iced::widget::text(err.to_string())
+                    .size(12)
+                    .style(iced::widget::text::danger)
+            }),
+        ]
+        .spacing(5)
+    }
+}
@@ -0,0 +1,355 @@
+// Syntax highlighting for JSON, also understanding the comments and triple quoted strings of the console syntax
+use std::ops::Range;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Settings {
+    // (line, column) of the bracket at the cursor and of its match
+    pub matched_brackets: Vec<(usize, usize)>,
+    // (line, column) of a parse error. A missing column marks the whole line.
+    pub error: Option<(usize, Option<usize>)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Highlight {
+    Key,
+    String,
+    Number,
+    // true, false, null and console methods
+    Keyword,
+    Comment,
+    MatchedBracket,
+    Error,
+}
+
+impl Highlight {
+    pub fn text_format(&self, theme: &iced::Theme) -> iced::advanced::text::highlighter::Format<iced::Font> {
+        let palette = theme.extended_palette();
+
+        let color = match self {
+            Highlight::Key => palette.primary.strong.color,
+            Highlight::String => palette.success.base.color,
+            Highlight::Number => palette.warning.base.color,
+            Highlight::Keyword => palette.secondary.strong.color,
+            Highlight::Comment => palette.background.strongest.color,
+            Highlight::MatchedBracket => palette.primary.base.color,
+            Highlight::Error => palette.danger.base.color,
+        };
+
+        iced::advanced::text::highlighter::Format {
+            color: Some(color),
+            font: matches!(self, Highlight::MatchedBracket).then_some(iced::Font {
+                weight: iced::font::Weight::Bold,
+                ..iced::Font::MONOSPACE
+            }),
+        }
+    }
+}
+
+pub struct JsonHighlighter {
+    settings: Settings,
+    current_line: usize,
+    // Whether each highlighted line ended inside a triple quoted string
+    line_ends_in_triple_quote: Vec<bool>,
+}
+
+impl iced::advanced::text::Highlighter for JsonHighlighter {
+    type Settings = Settings;
+    type Highlight = Highlight;
+    type Iterator<'a> = std::vec::IntoIter<(Range<usize>, Highlight)>;
+
+    fn new(settings: &Self::Settings) -> Self {
+        Self {
+            settings: settings.clone(),
+            current_line: 0,
+            line_ends_in_triple_quote: Vec::new(),
+        }
+    }
+
+    fn update(&mut self, new_settings: &Self::Settings) {
+        self.settings = new_settings.clone();
+        self.change_line(0);
+    }
+
+    fn change_line(&mut self, line: usize) {
+        self.current_line = line.min(self.current_line);
+        self.line_ends_in_triple_quote.truncate(self.current_line);
+    }
+
+    fn highlight_line(&mut self, line: &str) -> Self::Iterator<'_> {
+        let line_idx = self.current_line;
+        let starts_in_triple_quote = line_idx
+            .checked_sub(1)
+            .and_then(|prev| self.line_ends_in_triple_quote.get(prev).copied())
+            .unwrap_or(false);
+
+        let (mut highlights, ends_in_triple_quote) = highlight_tokens(line, starts_in_triple_quote);
+
+        for (bracket_line, column) in &self.settings.matched_brackets {
+            if *bracket_line == line_idx && let Some(range) = char_range(line, *column) {
+                highlights.retain(|(existing, _)| existing.end <= range.start || existing.start >= range.end);
+                highlights.push((range, Highlight::MatchedBracket));
+            }
+        }
+
+        if let Some((error_line, column)) = self.settings.error && error_line == line_idx {
+            let range = match column {
+                Some(column) => char_range(line, column).unwrap_or(line.len().saturating_sub(1)..line.len()),
+                None => 0..line.len(),
+            };
+            highlights.retain(|(existing, _)| existing.end <= range.start || existing.start >= range.end);
+            highlights.push((range, Highlight::Error));
+        }
+
+        highlights.sort_by_key(|(range, _)| range.start);
+
+        self.line_ends_in_triple_quote.push(ends_in_triple_quote);
+        self.current_line += 1;
+
+        highlights.into_iter()
+    }
+
+    fn current_line(&self) -> usize {
+        self.current_line
+    }
+}
+
+// Byte range of the character at the given (zero based) char column
+fn char_range(line: &str, column: usize) -> Option<Range<usize>> {
+    line.char_indices()
+        .nth(column)
+        .map(|(start, c)| start..start + c.len_utf8())
+}
+
+// Char column of the given byte column, which is how the editor's cursor and serde_json count columns
+pub fn char_column(line: &str, byte_column: usize) -> usize {
+    line.char_indices()
+        .take_while(|(start, _)| *start < byte_column)
+        .count()
+}
+
+fn highlight_tokens(line: &str, starts_in_triple_quote: bool) -> (Vec<(Range<usize>, Highlight)>, bool) {
+    let bytes = line.as_bytes();
+    let mut highlights = Vec::new();
+    let mut idx = 0;
+
+    if starts_in_triple_quote {
+        match line.find(r#"""""#) {
+            Some(end) => {
+                highlights.push((0..end + 3, Highlight::String));
+                idx = end + 3;
+            },
+            None => return (vec![(0..line.len(), Highlight::String)], true),
+        }
+    }
+
+    // Console request lines, e.g. "GET /_search"
+    if idx == 0 {
+        let trimmed = line.trim_start();
+        let method = trimmed.split(char::is_whitespace).next().unwrap_or("");
+        if method.parse::<elastic_ermine::es::ElasticSearchMethodType>().is_ok() && method.chars().all(|c| c.is_ascii_uppercase()) {
+            let start = line.len() - trimmed.len();
+            return (vec![(start..start + method.len(), Highlight::Keyword)], false);
+        }
+    }
+
+    while idx < bytes.len() {
+        let c = bytes[idx];
+
+        if line[idx..].starts_with(r#"""""#) {
+            match line[idx + 3..].find(r#"""""#) {
+                Some(end) => {
+                    let end = idx + 3 + end + 3;
+                    highlights.push((idx..end, Highlight::String));
+                    idx = end;
+                    continue;
+                },
+                None => {
+                    highlights.push((idx..line.len(), Highlight::String));
+                    return (highlights, true);
+                },
+            }
+        }
+
+        match c {
+            b'"' => {
+                let mut end = idx + 1;
+                let mut escaped = false;
+                while end < bytes.len() {
+                    if escaped {
+                        escaped = false;
+                    } else if bytes[end] == b'\\' {
+                        escaped = true;
+                    } else if bytes[end] == b'"' {
+                        break;
+                    }
+                    end += 1;
+                }
+                let end = (end + 1).min(bytes.len());
+
+                let is_key = line[end..].trim_start().starts_with(':');
+                highlights.push((idx..end, if is_key { Highlight::Key } else { Highlight::String }));
+                idx = end;
+            },
+            b'#' => {
+                highlights.push((idx..line.len(), Highlight::Comment));
+                break;
+            },
+            b'/' if bytes.get(idx + 1) == Some(&b'/') => {
+                highlights.push((idx..line.len(), Highlight::Comment));
+                break;
+            },
+            b'-' | b'0'..=b'9' => {
+                let end = line[idx..]
+                    .find(|c: char| !(c.is_ascii_digit() || "+-.eE".contains(c)))
+                    .map(|len| idx + len)
+                    .unwrap_or(line.len());
+                highlights.push((idx..end, Highlight::Number));
+                idx = end;
+            },
+            b't' | b'f' | b'n' => {
+                let keyword = ["true", "false", "null"].into_iter()
+                    .find(|keyword| line[idx..].starts_with(keyword));
+                match keyword {
+                    Some(keyword) => {
+                        highlights.push((idx..idx + keyword.len(), Highlight::Keyword));
+                        idx += keyword.len();
+                    },
+                    None => idx += 1,
+                }
+            },
+            // Steps over whole characters so idx always stays on a char boundary
+            _ => idx += line[idx..].chars().next().map(char::len_utf8).unwrap_or(1),
+        }
+    }
+
+    (highlights, false)
+}
+
+/**
+ * Finds the bracket right before or at the cursor and its matching bracket.
+ * The cursor column is in bytes, as reported by the editor, while the returned (line, column) of
+ * both brackets are in chars, as taken by the highlighter. Nothing is returned if the cursor is not next to a bracket.
+ */
+pub fn matching_brackets(text: &str, cursor_line: usize, cursor_column: usize) -> Vec<(usize, usize)> {
+    let cursor_column = text.lines()
+        .nth(cursor_line)
+        .map(|line| char_column(line, cursor_column))
+        .unwrap_or(cursor_column);
+
+    // (line, column, char) of every bracket outside of strings
+    let mut brackets = Vec::new();
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (line_idx, line) in text.lines().enumerate() {
+        for (column, c) in line.chars().enumerate() {
+            if in_string {
+                if escaped {
+                    escaped = false;
+                } else if c == '\\' {
+                    escaped = true;
+                } else if c == '"' {
+                    in_string = false;
+                }
+            } else if c == '"' {
+                in_string = true;
+            } else if "{}[]".contains(c) {
+                brackets.push((line_idx, column, c));
+            }
+        }
+        // JSON strings cannot span lines, this stops a missing quote from affecting the rest of the buffer
+        in_string = false;
+    }
+
+    let at_cursor = brackets.iter()
+        .position(|(line, column, _)| *line == cursor_line && *column + 1 == cursor_column)
+        .or_else(|| brackets.iter().position(|(line, column, _)| *line == cursor_line && *column == cursor_column));
+
+    let Some(at_cursor) = at_cursor else {
+        return Vec::new();
+    };
+
+    let (line, column, bracket) = brackets[at_cursor];
+    let (open, close) = match bracket {
+        '{' | '}' => ('{', '}'),
+        _ => ('[', ']'),
+    };
+
+    let mut depth = 0;
+
+    let partner = if bracket == open {
+        brackets[at_cursor..].iter()
+            .find(|(_, _, c)| {
+                if *c == open {
+                    depth += 1;
+                } else if *c == close {
+                    depth -= 1;
+                }
+                depth == 0
+            })
+    } else {
+        brackets[..=at_cursor].iter()
+            .rev()
+            .find(|(_, _, c)| {
+                if *c == close {
+                    depth += 1;
+                } else if *c == open {
+                    depth -= 1;
+                }
+                depth == 0
+            })
+    };
+
+    match partner {
+        Some((partner_line, partner_column, _)) => vec![(line, column), (*partner_line, *partner_column)],
+        None => vec![(line, column)],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn highlights_tokens() {
+        let (highlights, ends_in_triple_quote) = highlight_tokens(r#"  "size": 10, "a": true # comment"#, false);
+
+        assert_eq!(highlights, vec![
+            (2..8, Highlight::Key),
+            (10..12, Highlight::Number),
+            (14..17, Highlight::Key),
+            (19..23, Highlight::Keyword),
+            (24..33, Highlight::Comment),
+        ]);
+        assert!(!ends_in_triple_quote);
+    }
+
+    #[test]
+    fn carries_triple_quoted_strings_over_lines() {
+        assert_eq!(highlight_tokens(r#"  "source": """a"#, false), (vec![(2..10, Highlight::Key), (12..16, Highlight::String)], true));
+        assert_eq!(highlight_tokens(r#"b""", "x": 1"#, true).0[0], (0..4, Highlight::String));
+    }
+
+    #[test]
+    fn highlights_console_methods() {
+        assert_eq!(highlight_tokens("GET /_search", false).0, vec![(0..3, Highlight::Keyword)]);
+    }
+
+    #[test]
+    fn matches_brackets_around_the_cursor() {
+        let text = "{\n  \"a\": [1, \"]\"]\n}";
+
+        assert_eq!(matching_brackets(text, 0, 1), vec![(0, 0), (2, 0)]);
+        assert_eq!(matching_brackets(text, 1, 7), vec![(1, 7), (1, 14)]);
+        assert_eq!(matching_brackets(text, 1, 3), Vec::<(usize, usize)>::new());
+    }
+
+    #[test]
+    fn matches_brackets_after_multibyte_chars() {
+        // "é" is two bytes, the cursor right after the [ is at byte 8 but char 7
+        let text = "{\"é\": [1]}";
+
+        assert_eq!(matching_brackets(text, 0, 8), vec![(0, 6), (0, 8)]);
+        assert_eq!(char_range(text, 6), Some(7..8));
+    }
+}
@@ -0,0 +1,299 @@
+// Collapsible tree view of a JSON value.
+// The tree is laid out as a flat list of single line rows of fixed height, with only expanded nodes producing rows
+// and large arrays and objects revealing their children a page at a time.
+use std::collections::{HashMap, HashSet};
+
+use super::json_highlighter::Highlight;
+use super::virtual_list;
+
+pub const ROW_HEIGHT: f32 = 22.0;
+
+// Children shown when a node is expanded, and added each time more are requested
+const PAGE_SIZE: usize = 100;
+// Longer values are cut short in the tree, copying them gives the full value
+const MAX_VALUE_CHARS: usize = 200;
+const INDENT: f32 = 16.0;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    // JSON pointer of the toggled node
+    Toggled(String),
+    ShowMorePressed(String),
+    // Path of the node, e.g. "hits.hits[0]._source"
+    CopyPathPressed(String),
+    // JSON pointer of the node
+    CopyValuePressed(String),
+    Scrolled(iced::widget::scrollable::Viewport),
+}
+
+#[derive(Debug)]
+pub struct JsonTree {
+    value: serde_json::Value,
+    // JSON pointers of the expanded nodes
+    expanded: HashSet<String>,
+    // Number of children shown of expanded nodes, PAGE_SIZE when missing
+    shown: HashMap<String, usize>,
+    list: virtual_list::State,
+}
+
+#[derive(Debug)]
+enum RowContent<'a> {
+    Container {
+        value: &'a serde_json::Value,
+        expanded: bool,
+    },
+    Leaf(&'a serde_json::Value),
+    ShowMore {
+        shown: usize,
+        total: usize,
+    },
+}
+
+// A single line of the laid out tree
+#[derive(Debug)]
+pub struct Row<'a> {
+    depth: usize,
+    // Object key or array index, missing for the root
+    label: Option<String>,
+    pointer: String,
+    path: String,
+    content: RowContent<'a>,
+}
+
+// Escapes a key for use as a JSON pointer token, see RFC 6901
+fn pointer_token(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+fn path_with_key(path: &str, key: &str) -> String {
+    let is_identifier = !key.is_empty() && key.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '@');
+
+    match (path.is_empty(), is_identifier) {
+        (true, true) => key.to_owned(),
+        (false, true) => format!("{}.{}", path, key),
+        (_, false) => format!("{}[{}]", path, serde_json::Value::from(key)),
+    }
+}
+
+fn container_summary(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Object(map) => format!("{{{}}}", map.len()),
+        serde_json::Value::Array(values) => format!("[{}]", values.len()),
+        _ => String::new(),
+    }
+}
+
+fn leaf_text(value: &serde_json::Value) -> (String, Highlight) {
+    let (text, highlight) = match value {
+        serde_json::Value::String(string) => (serde_json::Value::from(string.as_str()).to_string(), Highlight::String),
+        serde_json::Value::Number(number) => (number.to_string(), Highlight::Number),
+        other => (other.to_string(), Highlight::Keyword),
+    };
+
+    if text.chars().count() > MAX_VALUE_CHARS {
+        (text.chars().take(MAX_VALUE_CHARS).collect::<String>() + "…", highlight)
+    } else {
+        (text, highlight)
+    }
+}
+
+fn highlighted<'a>(content: String, highlight: Highlight) -> iced::widget::Text<'a> {
+    iced::widget::text(content)
+        .size(14)
+        .font(iced::Font::MONOSPACE)
+        .wrapping(iced::widget::text::Wrapping::None)
+        .style(move |theme: &iced::Theme| iced::widget::text::Style {
+            color: highlight.text_format(theme).color,
+        })
+}
+
+// Borderless button that only shows its content
+fn plain_button<'a, Message: 'a + Clone>(
+    content: impl Into<iced::Element<'a, Message>>,
+    on_press: Message
+) -> iced::widget::Button<'a, Message> {
+    iced::widget::button(content)
+        .on_press(on_press)
+        .padding(0)
+        .style(iced::widget::button::text)
+}
+
+impl JsonTree {
+    pub fn new(value: serde_json::Value) -> Self {
+        Self {
+            value,
+            expanded: HashSet::from([String::new()]),
+            shown: HashMap::new(),
+            list: Default::default(),
+        }
+    }
+
+    pub fn update<T>(&mut self, message: Message) -> iced::Task<T> {
+        match message {
+            Message::Toggled(pointer) => {
+                if !self.expanded.remove(&pointer) {
+                    self.expanded.insert(pointer);
+                }
+                iced::Task::none()
+            },
+            Message::ShowMorePressed(pointer) => {
+                let shown = self.shown.entry(pointer).or_insert(PAGE_SIZE);
+                *shown += PAGE_SIZE;
+                iced::Task::none()
+            },
+            Message::CopyPathPressed(path) => iced::clipboard::write(path),
+            Message::CopyValuePressed(pointer) => {
+                let contents = match self.value.pointer(&pointer) {
+                    Some(serde_json::Value::String(string)) => string.clone(),
+                    Some(value) => serde_json::to_string_pretty(value).unwrap_or_default(),
+                    None => return iced::Task::none(),
+                };
+                iced::clipboard::write(contents)
+            },
+            Message::Scrolled(viewport) => {
+                self.list.scrolled(viewport);
+                iced::Task::none()
+            },
+        }
+    }
+
+    // Rows of the currently expanded nodes, in display order
+    pub fn rows(&self) -> Vec<Row<'_>> {
+        let mut rows = Vec::new();
+        self.push_rows(&mut rows, &self.value, None, String::new(), String::new(), 0);
+        rows
+    }
+
+    fn push_rows<'a>(
+        &'a self,
+        rows: &mut Vec<Row<'a>>,
+        value: &'a serde_json::Value,
+        label: Option<String>,
+        pointer: String,
+        path: String,
+        depth: usize,
+    ) {
+        // Lazy so that only the shown children of large nodes are visited
+        let (children, total): (Box<dyn Iterator<Item = (String, &serde_json::Value)>>, usize) = match value {
+            serde_json::Value::Object(map) => (Box::new(map.iter().map(|(key, child)| (key.clone(), child))), map.len()),
+            serde_json::Value::Array(values) => (Box::new(values.iter().enumerate().map(|(idx, child)| (idx.to_string(), child))), values.len()),
+            leaf => {
+                rows.push(Row { depth, label, pointer, path, content: RowContent::Leaf(leaf) });
+                return;
+            },
+        };
+
+        let expanded = self.expanded.contains(&pointer);
+        let is_array = value.is_array();
+
+        rows.push(Row {
+            depth,
+            label,
+            pointer: pointer.clone(),
+            path: path.clone(),
+            content: RowContent::Container { value, expanded },
+        });
+
+        if !expanded {
+            return;
+        }
+
+        let shown = self.shown.get(&pointer).copied().unwrap_or(PAGE_SIZE);
+
+        for (key, child) in children.take(shown) {
+            let child_path = if is_array {
+                format!("{}[{}]", path, key)
+            } else {
+                path_with_key(&path, &key)
+            };
+            let child_pointer = format!("{}/{}", pointer, pointer_token(&key));
+
+            self.push_rows(rows, child, Some(key), child_pointer, child_path, depth + 1);
+        }
+
+        if shown < total {
+            rows.push(Row {
+                depth: depth + 1,
+                label: None,
+                pointer,
+                path,
+                content: RowContent::ShowMore { shown, total },
+            });
+        }
+    }
+
+    pub fn view_row<'a>(row: Row<'a>) -> iced::Element<'a, Message> {
+        let label = row.label.map(|label| {
+            plain_button(highlighted(format!("{}:", label), Highlight::Key), Message::CopyPathPressed(row.path.clone()))
+        });
+
+        let line: iced::Element<'a, Message> = match row.content {
+            RowContent::Container { value, expanded } => iced::widget::row![
+                plain_button(
+                    iced::widget::text(if expanded { "▾" } else { "▸" }).size(14),
+                    Message::Toggled(row.pointer.clone())
+                )
+                .width(INDENT),
+                label,
+                plain_button(highlighted(container_summary(value), Highlight::Comment), Message::CopyValuePressed(row.pointer)),
+            ]
+            .spacing(5)
+            .into(),
+            RowContent::Leaf(value) => {
+                let (text, highlight) = leaf_text(value);
+                iced::widget::row![
+                    iced::widget::space::horizontal().width(INDENT),
+                    label,
+                    plain_button(highlighted(text, highlight), Message::CopyValuePressed(row.pointer)),
+                ]
+                .spacing(5)
+                .into()
+            },
+            RowContent::ShowMore { shown, total } => iced::widget::row![
+                iced::widget::space::horizontal().width(INDENT),
+                iced::widget::button(
+                    iced::widget::text(format!("Show {} more ({} of {} shown)", (total - shown).min(PAGE_SIZE), shown, total))
+                        .size(12)
+                )
+                .on_press(Message::ShowMorePressed(row.pointer))
+                .padding([0, 5]),
+            ]
+            .into(),
+        };
+
+        iced::widget::container(line)
+            .padding(iced::Padding::ZERO.left(row.depth as f32 * INDENT))
+            .height(ROW_HEIGHT)
+            .align_y(iced::Center)
+            .into()
+    }
+
+    // Height of the tree as laid out by `view`
+    pub fn height(&self) -> f32 {
+        self.rows().len() as f32 * ROW_HEIGHT
+    }
+
+    /**
+     * Lays out every row, clicking a key copies its path and clicking a value copies the value.
+     * Meant for trees shown within another scrollable, see `view_scrollable` otherwise.
+     */
+    pub fn view(&self) -> iced::widget::Column<'_, Message> {
+        iced::widget::column(self.rows().into_iter().map(Self::view_row))
+    }
+
+    // Scrollable tree that only lays out the rows in view
+    pub fn view_scrollable(&self) -> iced::widget::Scrollable<'_, Message> {
+        let rows = self.rows();
+
+        virtual_list::virtual_list(
+            &self.list,
+            virtual_list::Heights::Fixed { count: rows.len(), height: ROW_HEIGHT },
+            |range| rows.into_iter()
+                .skip(range.start)
+                .take(range.len())
+                .map(Self::view_row)
+                .collect(),
+            Message::Scrolled
+        )
+    }
+}
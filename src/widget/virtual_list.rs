@@ -0,0 +1,124 @@
+// Scrollable list that only lays out the rows inside the viewport.
+// Rows have heights known up front, rows outside of the viewport are replaced by empty space of the same height.
+use std::ops::Range;
+
+use iced::widget::scrollable::Viewport;
+
+// Used until the list has been laid out and reported its real viewport
+const DEFAULT_VIEWPORT_HEIGHT: f32 = 1080.0;
+// Rows laid out above and below the viewport, so fast scrolling does not show gaps
+const OVERSCAN: usize = 5;
+
+pub enum Heights {
+    Fixed {
+        count: usize,
+        height: f32,
+    },
+    // Height of each row
+    Variable(Vec<f32>),
+}
+
+impl Heights {
+    fn count(&self) -> usize {
+        match self {
+            Heights::Fixed { count, .. } => *count,
+            Heights::Variable(heights) => heights.len(),
+        }
+    }
+
+    fn height(&self, idx: usize) -> f32 {
+        match self {
+            Heights::Fixed { height, .. } => *height,
+            Heights::Variable(heights) => heights[idx],
+        }
+    }
+
+    // Offset of the top of every row, followed by the total height
+    fn offsets(&self) -> Vec<f32> {
+        let mut offsets = Vec::with_capacity(self.count() + 1);
+        let mut offset = 0.0;
+        offsets.push(offset);
+        for idx in 0..self.count() {
+            offset += self.height(idx);
+            offsets.push(offset);
+        }
+        offsets
+    }
+}
+
+/**
+ * Scroll position of a list, owned by the view showing the list and kept up to date from its scroll messages
+ */
+#[derive(Debug, Clone, Copy)]
+pub struct State {
+    offset_y: f32,
+    viewport_height: f32,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self {
+            offset_y: 0.0,
+            viewport_height: DEFAULT_VIEWPORT_HEIGHT,
+        }
+    }
+}
+
+impl State {
+    pub fn scrolled(&mut self, viewport: Viewport) {
+        self.offset_y = viewport.absolute_offset().y;
+        self.viewport_height = viewport.bounds().height;
+    }
+
+    // Rows intersecting the viewport, including the overscan
+    fn visible_range(&self, offsets: &[f32]) -> Range<usize> {
+        let count = offsets.len().saturating_sub(1);
+        let top = self.offset_y;
+        let bottom = self.offset_y + self.viewport_height;
+
+        // First row whose bottom is below the top of the viewport, first row whose top is below the bottom of the viewport
+        let start = offsets[1..].partition_point(|row_bottom| *row_bottom <= top);
+        let end = offsets[..count].partition_point(|row_top| *row_top < bottom);
+
+        start.saturating_sub(OVERSCAN)..(end + OVERSCAN).min(count)
+    }
+}
+
+/**
+ * view_rows is given the range of rows to lay out and must return exactly one element per row
+ */
+pub fn virtual_list<'a, Message: 'a>(
+    state: &State,
+    heights: Heights,
+    view_rows: impl FnOnce(Range<usize>) -> Vec<iced::Element<'a, Message>>,
+    on_scroll: impl Fn(Viewport) -> Message + 'a,
+) -> iced::widget::Scrollable<'a, Message> {
+    let offsets = heights.offsets();
+    let range = state.visible_range(&offsets);
+    let total_height = offsets.last().copied().unwrap_or(0.0);
+
+    let above = offsets[range.start];
+    let below = total_height - offsets[range.end];
+
+    let rows = view_rows(range.clone())
+        .into_iter()
+        .zip(range)
+        .map(|(row, idx)| {
+            iced::widget::container(row)
+                .height(heights.height(idx))
+                .clip(true)
+                .into()
+        });
+
+    iced::widget::scrollable(
+        iced::widget::column![
+            iced::widget::space::vertical().height(above),
+            iced::widget::column(rows),
+            iced::widget::space::vertical().height(below),
+        ]
+        .width(iced::Fill)
+    )
+    .on_scroll(on_scroll)
+    .width(iced::Fill)
+    .height(iced::Fill)
+}